@@ -40,11 +40,12 @@ pub mod multisig;
 use exonum::{
     api::ServiceApiBuilder,
     blockchain::{self, Transaction, TransactionSet},
-    crypto::Hash,
+    crypto::{Hash, PublicKey},
     helpers::fabric::{self, Context},
     messages::RawTransaction,
-    storage::Snapshot,
+    storage::{Fork, Snapshot},
 };
+use serde_json::Value;
 
 use crate::transactions::WalletTransactions;
 
@@ -55,6 +56,20 @@ const SERVICE_NAME: &str = "cryptocurrency";
 /// Initial balance of the wallet.
 const INITIAL_BALANCE: u64 = 100;
 
+/// Wallet created in the genesis block.
+///
+/// The genesis data of the service is a list of such wallets, for example,
+/// `[{ "pub_key": "<hex>", "name": "Alice", "balance": 1000 }]`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GenesisWallet {
+    /// `PublicKey` of the wallet.
+    pub pub_key: PublicKey,
+    /// Name of the wallet.
+    pub name: String,
+    /// Initial balance of the wallet.
+    pub balance: u64,
+}
+
 /// Exonum `Service` implementation.
 #[derive(Default, Debug)]
 pub struct Service;
@@ -77,6 +92,20 @@ impl blockchain::Service for Service {
         WalletTransactions::tx_from_raw(raw).map(Into::into)
     }
 
+    fn initialize_with(&self, fork: &mut Fork, params: Value) -> Value {
+        let wallets: Vec<GenesisWallet> =
+            serde_json::from_value(params).expect("Invalid genesis data of wallets");
+        let mut schema = Schema::new(fork);
+        for wallet in &wallets {
+            schema.create_genesis_wallet(&wallet.pub_key, &wallet.name, wallet.balance);
+        }
+        Value::Null
+    }
+
+    fn error_name(&self, code: u16) -> Option<&'static str> {
+        transactions::Error::name(code)
+    }
+
     fn wire_api(&self, builder: &mut ServiceApiBuilder) {
         api::PublicApi::wire(builder);
     }
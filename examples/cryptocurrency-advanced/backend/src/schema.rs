@@ -195,6 +195,14 @@ impl<'a> Schema<&'a mut Fork> {
     }
 
 
+    /// Create new wallet with the given balance from the genesis data.
+    /// The history of the wallet is empty.
+    pub fn create_genesis_wallet(&mut self, key: &PublicKey, name: &str, balance: u64) {
+        let history_hash = self.wallet_history_mut(key).merkle_root();
+        let wallet = Wallet::new(key, name, balance, balance, 0, &history_hash, Vec::new());
+        self.wallets_mut().put(key, wallet);
+    }
+
     /// Create new wallet and append first record to its history.
     pub fn create_wallet(&mut self, key: &PublicKey, name: &str, transaction: &Hash) {
         let wallet = {
@@ -32,8 +32,6 @@ multisig::{
 },
 CRYPTOCURRENCY_SERVICE_ID};
 
-const ERROR_SENDER_SAME_AS_RECEIVER: u8 = 0;
-
 /// Error codes emitted by wallet transactions during execution.
 #[derive(Debug, Fail)]
 #[repr(u8)]
@@ -73,13 +71,35 @@ pub enum Error {
     /// Can be emitted by `TxSign`.
     #[fail(display = "Signer is not authorised")]
     MultisigWrongApprover = 5,
+
+    /// Sender is the same as receiver.
+    ///
+    /// Can be emitted by `Transfer`.
+    #[fail(display = "Sender is the same as receiver")]
+    SenderSameAsReceiver = 6,
 }
 
+impl Error {
+    /// Returns the name of the error with the given code.
+    pub fn name(code: u16) -> Option<&'static str> {
+        let name = match code {
+            c if c == Error::WalletAlreadyExists as u16 => "WalletAlreadyExists",
+            c if c == Error::SenderNotFound as u16 => "SenderNotFound",
+            c if c == Error::ReceiverNotFound as u16 => "ReceiverNotFound",
+            c if c == Error::InsufficientCurrencyAmount as u16 => "InsufficientCurrencyAmount",
+            c if c == Error::MultisigNotFound as u16 => "MultisigNotFound",
+            c if c == Error::MultisigWrongApprover as u16 => "MultisigWrongApprover",
+            c if c == Error::SenderSameAsReceiver as u16 => "SenderSameAsReceiver",
+            _ => return None,
+        };
+        Some(name)
+    }
+}
 
 impl From<Error> for ExecutionError {
     fn from(value: Error) -> ExecutionError {
         let description = format!("{}", value);
-        ExecutionError::with_description(value as u8, description)
+        ExecutionError::with_description(value as u16, description)
     }
 }
 
@@ -175,7 +195,7 @@ impl Transaction for Transfer {
         let amount = self.amount;
 
         if from == to {
-            return Err(ExecutionError::new(ERROR_SENDER_SAME_AS_RECEIVER));
+            Err(Error::SenderSameAsReceiver)?
         }
 
         let sender = schema.wallet(from).ok_or(Error::SenderNotFound)?;
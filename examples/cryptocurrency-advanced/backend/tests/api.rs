@@ -120,7 +120,13 @@ fn test_transfer_from_nonexisting_wallet() {
     testkit.create_block_with_tx_hashes(&[tx.hash()]);
     api.assert_tx_status(
         tx.hash(),
-        &json!({ "type": "error", "code": 1, "description": "Sender doesn't exist" }),
+        &json!({
+            "type": "error",
+            "code": 1,
+            "description": "Sender doesn't exist",
+            "service_id": 128,
+            "name": "SenderNotFound",
+        }),
     );
 
     // Check that Bob's balance doesn't change.
@@ -154,7 +160,13 @@ fn test_transfer_to_nonexisting_wallet() {
     testkit.create_block_with_tx_hashes(&[tx.hash()]);
     api.assert_tx_status(
         tx.hash(),
-        &json!({ "type": "error", "code": 2, "description": "Receiver doesn't exist" }),
+        &json!({
+            "type": "error",
+            "code": 2,
+            "description": "Receiver doesn't exist",
+            "service_id": 128,
+            "name": "ReceiverNotFound",
+        }),
     );
 
     // Check that Alice's balance doesn't change.
@@ -183,7 +195,13 @@ fn test_transfer_overcharge() {
     testkit.create_block();
     api.assert_tx_status(
         tx.hash(),
-        &json!({ "type": "error", "code": 3, "description": "Insufficient currency amount" }),
+        &json!({
+            "type": "error",
+            "code": 3,
+            "description": "Insufficient currency amount",
+            "service_id": 128,
+            "name": "InsufficientCurrencyAmount",
+        }),
     );
 
     let wallet = api.get_wallet(tx_alice.author()).unwrap();
@@ -192,6 +210,59 @@ fn test_transfer_overcharge() {
     assert_eq!(wallet.balance, 100);
 }
 
+/// Check that a transfer to the sender's own wallet is rejected with its own error code.
+#[test]
+fn test_transfer_to_self() {
+    let (mut testkit, api) = create_testkit();
+
+    let (tx_alice, key_alice) = api.create_wallet(ALICE_NAME);
+    testkit.create_block();
+
+    let tx = Transfer::sign(
+        &tx_alice.author(),
+        &tx_alice.author(),
+        10, // transfer amount
+        0,  // seed
+        &key_alice,
+    );
+    api.transfer(&tx);
+    testkit.create_block();
+    api.assert_tx_status(
+        tx.hash(),
+        &json!({
+            "type": "error",
+            "code": 6,
+            "description": "Sender is the same as receiver",
+            "service_id": 128,
+            "name": "SenderSameAsReceiver",
+        }),
+    );
+
+    let wallet = api.get_wallet(tx_alice.author()).unwrap();
+    assert_eq!(wallet.balance, 100);
+}
+
+/// Check that the wallets from the genesis data are created with the given balances.
+#[test]
+fn test_genesis_wallets() {
+    let (alice, _) = crypto::gen_keypair();
+    let testkit = TestKitBuilder::validator()
+        .with_service(Service)
+        .with_service_data(
+            "cryptocurrency",
+            json!([{ "pub_key": alice, "name": ALICE_NAME, "balance": 1000 }]),
+        )
+        .create();
+    let api = CryptocurrencyApi {
+        inner: testkit.api(),
+    };
+
+    let wallet = api.get_wallet(alice).unwrap();
+    assert_eq!(wallet.name, ALICE_NAME);
+    assert_eq!(wallet.balance, 1000);
+    assert_eq!(wallet.history_len, 0);
+}
+
 #[test]
 fn test_unknown_wallet_request() {
     let (_testkit, api) = create_testkit();
@@ -26,6 +26,7 @@ fn node_config() -> NodeConfig {
     let validator_keys = ValidatorKeys {
         consensus_key: consensus_public_key,
         service_key: service_public_key,
+        voting_power: None,
     };
     let genesis = GenesisConfig::new(vec![validator_keys].into_iter());
 
@@ -52,6 +53,10 @@ fn node_config() -> NodeConfig {
         services_configs: Default::default(),
         database: Default::default(),
         thread_pool_size: Default::default(),
+        execution_threads: Default::default(),
+        undo_depth: Default::default(),
+        rotated_keys: Default::default(),
+        follower: None,
     }
 }
 
@@ -95,6 +95,9 @@ pub mod schema {
         view: T,
     }
 
+    /// Name of the index keeping the wallets.
+    pub const WALLETS_INDEX: &str = "cryptocurrency.wallets";
+
     /// Declare the layout of data managed by the service. An instance of [`MapIndex`] is used
     /// to keep wallets in the storage. Index values are serialized [`Wallet`] structs.
     ///
@@ -108,7 +111,7 @@ pub mod schema {
 
         /// Returns an immutable version of the wallets table.
         pub fn wallets(&self) -> MapIndex<&dyn Snapshot, PublicKey, Wallet> {
-            MapIndex::new("cryptocurrency.wallets", self.view.as_ref())
+            MapIndex::new(WALLETS_INDEX, self.view.as_ref())
         }
 
         /// Gets a specific wallet from the storage.
@@ -122,7 +125,7 @@ pub mod schema {
     impl<'a> CurrencySchema<&'a mut Fork> {
         /// Returns a mutable version of the wallets table.
         pub fn wallets_mut(&mut self) -> MapIndex<&mut Fork, PublicKey, Wallet> {
-            MapIndex::new("cryptocurrency.wallets", &mut self.view)
+            MapIndex::new(WALLETS_INDEX, &mut self.view)
         }
     }
 }
@@ -255,18 +258,21 @@ pub mod errors {
     impl From<Error> for ExecutionError {
         fn from(value: Error) -> ExecutionError {
             let description = format!("{}", value);
-            ExecutionError::with_description(value as u8, description)
+            ExecutionError::with_description(value as u16, description)
         }
     }
 }
 
 /// Contracts.
 pub mod contracts {
-    use exonum::blockchain::{ExecutionResult, Transaction, TransactionContext};
+    use exonum::{
+        blockchain::{AccessSet, ExecutionResult, Transaction, TransactionContext},
+        crypto::PublicKey,
+    };
 
     use crate::{
         errors::Error,
-        schema::{CurrencySchema, Wallet},
+        schema::{CurrencySchema, Wallet, WALLETS_INDEX},
         transactions::{TxCreateWallet, TxTransfer},
     };
 
@@ -332,6 +338,16 @@ pub mod contracts {
                 Err(Error::InsufficientCurrencyAmount)?
             }
         }
+
+        /// Declares writing of the wallets of the sender and the receiver, so transfers
+        /// between different wallets can be executed concurrently.
+        fn access_set(&self, author: &PublicKey) -> Option<AccessSet> {
+            Some(
+                AccessSet::new()
+                    .write_key(WALLETS_INDEX, author)
+                    .write_key(WALLETS_INDEX, &self.to),
+            )
+        }
     }
 }
 
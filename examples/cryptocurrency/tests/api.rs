@@ -122,7 +122,12 @@ fn test_transfer_from_nonexisting_wallet() {
     testkit.create_block_with_tx_hashes(&[tx.hash()]);
     api.assert_tx_status(
         tx.hash(),
-        &json!({ "type": "error", "code": 1, "description": "Sender doesn't exist" }),
+        &json!({
+            "type": "error",
+            "code": 1,
+            "description": "Sender doesn't exist",
+            "service_id": 1,
+        }),
     );
 
     // Check that Bob's balance doesn't change.
@@ -156,7 +161,12 @@ fn test_transfer_to_nonexisting_wallet() {
     testkit.create_block_with_tx_hashes(&[tx.hash()]);
     api.assert_tx_status(
         tx.hash(),
-        &json!({ "type": "error", "code": 2, "description": "Receiver doesn't exist" }),
+        &json!({
+            "type": "error",
+            "code": 2,
+            "description": "Receiver doesn't exist",
+            "service_id": 1,
+        }),
     );
 
     // Check that Alice's balance doesn't change.
@@ -185,7 +195,12 @@ fn test_transfer_overcharge() {
     testkit.create_block();
     api.assert_tx_status(
         tx.hash(),
-        &json!({ "type": "error", "code": 3, "description": "Insufficient currency amount" }),
+        &json!({
+            "type": "error",
+            "code": 3,
+            "description": "Insufficient currency amount",
+            "service_id": 1,
+        }),
     );
 
     let wallet = api.get_wallet(tx_alice.author());
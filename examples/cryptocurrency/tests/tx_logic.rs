@@ -22,6 +22,7 @@
 extern crate exonum_testkit;
 
 use exonum::{
+    blockchain::Transaction,
     crypto::{self, PublicKey, SecretKey},
     messages::{RawTransaction, Signed},
 };
@@ -192,6 +193,30 @@ fn test_transfers_in_single_block() {
     assert_eq!(bob_wallet.balance, 70);
 }
 
+#[test]
+fn test_transfer_access_sets() {
+    let (alice_pubkey, bob_pubkey, carol_pubkey, dave_pubkey) = (
+        crypto::gen_keypair().0,
+        crypto::gen_keypair().0,
+        crypto::gen_keypair().0,
+        crypto::gen_keypair().0,
+    );
+    let transfer = |to: &PublicKey| TxTransfer {
+        to: *to,
+        amount: 10,
+        seed: 0,
+    };
+
+    let a_to_b = transfer(&bob_pubkey).access_set(&alice_pubkey).unwrap();
+    let c_to_d = transfer(&dave_pubkey).access_set(&carol_pubkey).unwrap();
+    let d_to_a = transfer(&alice_pubkey).access_set(&dave_pubkey).unwrap();
+    // Transfers between different wallets can be executed concurrently.
+    assert!(!a_to_b.conflicts_with(&c_to_d));
+    // Transfers sharing a wallet are executed sequentially.
+    assert!(a_to_b.conflicts_with(&d_to_a));
+    assert!(c_to_d.conflicts_with(&d_to_a));
+}
+
 /// Generate random transactions to perform [fuzz testing][fuzz] of the service. The service
 /// should maintain invariants under all circumstances; e.g., the total amount of tokens
 /// in existence should depend only on the number of registered wallets.
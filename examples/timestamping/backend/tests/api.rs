@@ -162,6 +162,11 @@ fn test_api_can_not_add_same_content_hash() {
     assert_status(
         &api,
         &tx_err,
-        &json!({ "type": "error", "code": 0, "description": "Content hash already exists" }),
+        &json!({
+            "type": "error",
+            "code": 0,
+            "description": "Content hash already exists",
+            "service_id": 130,
+        }),
     );
 }
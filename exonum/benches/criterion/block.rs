@@ -36,10 +36,13 @@
 //!   Accounts are stored in a `MapIndex`.
 //! - `block_cryptocurrency_rollback`: Transferring cryptocurrency among random accounts.
 //!   Accounts are stored in a `MapIndex`. Transactions are rolled back 50% of the time.
+//! - `block_cryptocurrency_cached`: Same as `block_cryptocurrency`, but the first half
+//!   of the transactions has been executed before as a part of another propose
+//!   (`Blockchain::create_patch_cached` method).
 
 use criterion::{Criterion, ParameterizedBenchmark, Throughput};
 use exonum::{
-    blockchain::{Blockchain, Schema, Service, Transaction},
+    blockchain::{Blockchain, ExecutionCache, Schema, Service, Transaction},
     crypto::{Hash, PublicKey, SecretKey},
     helpers::{Height, ValidatorId},
     messages::{RawTransaction, Signed},
@@ -51,7 +54,7 @@ use rand::{Rng, SeedableRng};
 use rand_xorshift::XorShiftRng;
 use tempdir::TempDir;
 
-use std::iter;
+use std::{iter, time::UNIX_EPOCH};
 
 /// Number of transactions added to the blockchain before the bench begins.
 const PREPARE_TRANSACTIONS: usize = 10_000;
@@ -98,6 +101,7 @@ fn create_blockchain(db: impl Database, services: Vec<Box<dyn Service>>) -> Bloc
     let config = GenesisConfig::new(iter::once(ValidatorKeys {
         consensus_key: consensus_keypair.0,
         service_key: service_keypair.0,
+        voting_power: None,
     }));
     blockchain.initialize(config).unwrap();
 
@@ -105,7 +109,22 @@ fn create_blockchain(db: impl Database, services: Vec<Box<dyn Service>>) -> Bloc
 }
 
 fn execute_block(blockchain: &Blockchain, height: u64, txs: &[Hash]) -> (Hash, Patch) {
-    blockchain.create_patch(ValidatorId::zero(), Height(height), txs)
+    blockchain.create_patch(ValidatorId::zero(), Height(height), UNIX_EPOCH.into(), txs)
+}
+
+fn execute_block_cached(
+    blockchain: &Blockchain,
+    cache: &mut ExecutionCache,
+    height: u64,
+    txs: &[Hash],
+) -> (Hash, Patch) {
+    blockchain.create_patch_cached(
+        cache,
+        ValidatorId::zero(),
+        Height(height),
+        UNIX_EPOCH.into(),
+        txs,
+    )
 }
 
 mod timestamping {
@@ -484,14 +503,14 @@ fn prepare_blockchain(
     }
 }
 
-fn execute_block_rocksdb(
-    criterion: &mut Criterion,
-    bench_name: &'static str,
+/// Creates a blockchain with the given service and adds transactions for the benchmarked
+/// block to the pool. Returns the blockchain and the hashes of the added transactions.
+fn prepare_bench_blockchain(
+    tempdir: &TempDir,
     service: Box<dyn Service>,
     mut tx_generator: impl Iterator<Item = Signed<RawTransaction>>,
-) {
-    let tempdir = TempDir::new("exonum").unwrap();
-    let db = create_rocksdb(&tempdir);
+) -> (Blockchain, Vec<Hash>) {
+    let db = create_rocksdb(tempdir);
     let mut blockchain = create_blockchain(db, vec![service]);
 
     // We don't particularly care how transactions are distributed in the blockchain
@@ -510,6 +529,17 @@ fn execute_block_rocksdb(
 
     let tx_hashes = prepare_txs(&mut blockchain, txs);
     assert_transactions_in_pool(&blockchain, &tx_hashes);
+    (blockchain, tx_hashes)
+}
+
+fn execute_block_rocksdb(
+    criterion: &mut Criterion,
+    bench_name: &'static str,
+    service: Box<dyn Service>,
+    tx_generator: impl Iterator<Item = Signed<RawTransaction>>,
+) {
+    let tempdir = TempDir::new("exonum").unwrap();
+    let (blockchain, tx_hashes) = prepare_bench_blockchain(&tempdir, service, tx_generator);
 
     // Because execute_block is not really "micro benchmark"
     // executing it as regular benches, with 100 samples,
@@ -532,6 +562,43 @@ fn execute_block_rocksdb(
     );
 }
 
+fn execute_block_cached_rocksdb(
+    criterion: &mut Criterion,
+    bench_name: &'static str,
+    service: Box<dyn Service>,
+    tx_generator: impl Iterator<Item = Signed<RawTransaction>>,
+) {
+    let tempdir = TempDir::new("exonum").unwrap();
+    let (blockchain, tx_hashes) = prepare_bench_blockchain(&tempdir, service, tx_generator);
+
+    criterion.bench(
+        bench_name,
+        ParameterizedBenchmark::new(
+            "transactions",
+            move |bencher, &&txs_in_block| {
+                let height: u64 = blockchain.last_block().height().next().into();
+                let tx_hashes = &tx_hashes[..txs_in_block];
+                // Execute the propose of the previous round sharing the first half
+                // of the transactions with the benchmarked one.
+                let mut cache = ExecutionCache::new();
+                execute_block_cached(
+                    &blockchain,
+                    &mut cache,
+                    height,
+                    &tx_hashes[..txs_in_block / 2],
+                );
+                bencher.iter_with_setup(
+                    || cache.clone(),
+                    |mut cache| execute_block_cached(&blockchain, &mut cache, height, tx_hashes),
+                );
+            },
+            TXS_IN_BLOCK,
+        )
+        .sample_size(50)
+        .throughput(|&&txs_in_block| Throughput::Elements(txs_in_block as u32)),
+    );
+}
+
 pub fn bench_block(criterion: &mut Criterion) {
     use log::LevelFilter;
     use std::panic;
@@ -576,4 +643,11 @@ pub fn bench_block(criterion: &mut Criterion) {
         cryptocurrency::Cryptocurrency.into(),
         cryptocurrency::rollback_transactions(XorShiftRng::from_seed([4; 16])),
     );
+
+    execute_block_cached_rocksdb(
+        criterion,
+        "block/cryptocurrency_cached",
+        cryptocurrency::Cryptocurrency.into(),
+        cryptocurrency::provable_transactions(XorShiftRng::from_seed([3; 16])),
+    );
 }
@@ -172,6 +172,8 @@ fn main() {
                 "type": "error",
                 "code": 1,
                 "description": "Not allowed",
+                "service_id": 0,
+                "name": "NotAllowed",
             },
             // Other fields...
             "content": serde_json::to_value(erroneous_tx.content()).unwrap(),
@@ -185,7 +187,7 @@ fn main() {
     assert_eq!(
         serde_json::to_value(&panicked_tx).unwrap(),
         json!({
-            "status": { "type": "panic", "description": "oops" },
+            "status": { "type": "panic", "description": "oops", "service_id": 0 },
             // Other fields...
             "content": serde_json::to_value(panicked_tx.content()).unwrap(),
             "location": panicked_tx.location(),
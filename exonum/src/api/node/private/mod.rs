@@ -22,6 +22,7 @@ use std::{collections::HashMap, net::SocketAddr};
 use crate::api::{Error as ApiError, ServiceApiScope, ServiceApiState};
 use crate::blockchain::{Service, SharedNodeState};
 use crate::crypto::PublicKey;
+use crate::helpers::Height;
 use crate::messages::PROTOCOL_MAJOR_VERSION;
 use crate::node::{ConnectInfo, ExternalMessage};
 
@@ -101,6 +102,11 @@ struct ConsensusEnabledQuery {
     enabled: bool,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct MaintenanceQuery {
+    until: Height,
+}
+
 /// Private system API.
 #[derive(Clone, Debug)]
 pub struct SystemApi {
@@ -124,6 +130,7 @@ impl SystemApi {
             .handle_network_info("v1/network", api_scope)
             .handle_is_consensus_enabled("v1/consensus_enabled", api_scope)
             .handle_set_consensus_enabled("v1/consensus_enabled", api_scope)
+            .handle_maintenance("v1/maintenance", api_scope)
             .handle_shutdown("v1/shutdown", api_scope)
             .handle_rebroadcast("v1/rebroadcast", api_scope);
         api_scope
@@ -210,6 +217,19 @@ impl SystemApi {
         self_
     }
 
+    fn handle_maintenance(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
+        api_scope.endpoint_mut(
+            name,
+            move |state: &ServiceApiState, query: MaintenanceQuery| {
+                state
+                    .sender()
+                    .send_external_message(ExternalMessage::Maintenance(query.until))
+                    .map_err(ApiError::from)
+            },
+        );
+        self
+    }
+
     fn handle_shutdown(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
         api_scope.endpoint_mut(name, move |state: &ServiceApiState, _query: ()| {
             state
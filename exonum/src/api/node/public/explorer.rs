@@ -20,21 +20,23 @@ use chrono::{DateTime, Utc};
 use futures::IntoFuture;
 
 use std::ops::Range;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 
 use crate::{
     api::{
         backends::actix::{
             self as actix_backend, FutureResponse, HttpRequest, RawHandler, RequestHandler,
         },
-        websocket::{Server, Session},
+        websocket::{Server, Session, Subscription},
         Error as ApiError, ServiceApiBackend, ServiceApiScope, ServiceApiState,
     },
-    blockchain::{Block, SharedNodeState},
-    crypto::Hash,
-    explorer::{self, BlockchainExplorer, TransactionInfo},
+    blockchain::{Block, Schema, SharedNodeState},
+    crypto::{Hash, PublicKey},
+    explorer::{
+        self, BlockchainExplorer, EventInfo, EventProof, SimulatedTransaction, TransactionInfo,
+    },
     helpers::Height,
-    messages::{Message, Precommit, RawTransaction, Signed, SignedMessage},
+    messages::{Evidence, Message, Precommit, RawTransaction, Signed},
 };
 
 /// The maximum number of blocks to return per blocks request, in this way
@@ -125,6 +127,79 @@ impl TransactionQuery {
     }
 }
 
+/// Account nonce query parameters.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct NonceQuery {
+    /// Public key of the transactions author.
+    pub public_key: PublicKey,
+}
+
+impl NonceQuery {
+    /// Creates a new nonce query with the given public key.
+    pub fn new(public_key: PublicKey) -> Self {
+        Self { public_key }
+    }
+}
+
+/// Information about the account nonce.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct NonceInfo {
+    /// Public key of the transactions author.
+    pub public_key: PublicKey,
+    /// Nonce that the next transaction of the author should have.
+    pub nonce: u64,
+}
+
+/// Events query parameters.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct EventsQuery {
+    /// Identifier of the service which has emitted the events.
+    /// Events of all services are returned by default.
+    pub service_id: Option<u16>,
+    /// Type of the events. Events of all types are returned by default.
+    pub event_type: Option<String>,
+    /// The minimum height of the blocks to search events in.
+    /// The default value is the height of the latest block.
+    pub from: Option<Height>,
+    /// The maximum height of the blocks to search events in.
+    /// The default value is the height of the latest block.
+    pub to: Option<Height>,
+}
+
+/// Event proof query parameters.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct EventProofQuery {
+    /// The height of the block in which the event has been emitted.
+    pub height: Height,
+    /// The index of the event in the block event log.
+    pub index: u64,
+}
+
+impl EventProofQuery {
+    /// Creates a new event proof query with the given height and index.
+    pub fn new(height: Height, index: u64) -> Self {
+        Self { height, index }
+    }
+}
+
+/// Evidence query parameters.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub struct EvidenceQuery {
+    /// Consensus key of the validator which has signed the conflicting messages.
+    /// Evidence for all validators is returned by default.
+    pub public_key: Option<PublicKey>,
+}
+
+/// Evidence of equivocation of a validator recorded in the blockchain.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EvidenceInfo {
+    /// Key of the evidence in the `evidence` index of the core schema,
+    /// see `Evidence::key`.
+    pub hash: Hash,
+    /// Conflicting messages signed by the validator.
+    pub evidence: Evidence,
+}
+
 /// Exonum blockchain explorer API.
 #[derive(Debug, Clone, Copy)]
 pub struct ExplorerApi;
@@ -196,14 +271,94 @@ impl ExplorerApi {
         state: &ServiceApiState,
         query: TransactionQuery,
     ) -> Result<TransactionInfo, ApiError> {
-        BlockchainExplorer::new(state.blockchain())
-            .transaction(&query.hash)
-            .ok_or_else(|| {
-                let description = serde_json::to_string(&json!({ "type": "unknown" })).unwrap();
-                debug!("{}", description);
-                ApiError::NotFound(description)
+        let explorer = BlockchainExplorer::new(state.blockchain());
+        explorer.transaction(&query.hash).ok_or_else(|| {
+            let info = match explorer.transaction_eviction_height(&query.hash) {
+                Some(height) => json!({ "type": "evicted", "evicted_at": height }),
+                None => json!({ "type": "unknown" }),
+            };
+            let description = serde_json::to_string(&info).unwrap();
+            debug!("{}", description);
+            ApiError::NotFound(description)
+        })
+    }
+
+    /// Returns the next expected nonce of the transactions author.
+    pub fn account_nonce(
+        state: &ServiceApiState,
+        query: NonceQuery,
+    ) -> Result<NonceInfo, ApiError> {
+        let nonce = BlockchainExplorer::new(state.blockchain()).account_nonce(&query.public_key);
+        Ok(NonceInfo {
+            public_key: query.public_key,
+            nonce,
+        })
+    }
+
+    /// Returns the evidence of equivocation of validators recorded in the blockchain.
+    pub fn evidence(
+        state: &ServiceApiState,
+        query: EvidenceQuery,
+    ) -> Result<Vec<EvidenceInfo>, ApiError> {
+        let snapshot = state.snapshot();
+        let schema = Schema::new(&snapshot);
+        let evidence = schema
+            .evidence()
+            .iter()
+            .filter(|(_, evidence)| {
+                query
+                    .public_key
+                    .map_or(true, |key| evidence.author() == key)
             })
+            .map(|(hash, evidence)| EvidenceInfo { hash, evidence })
+            .collect();
+        Ok(evidence)
     }
+
+    /// Returns events emitted in the blocks of the specified range which match the query.
+    /// The range should not contain more than `MAX_BLOCKS_PER_REQUEST` blocks.
+    pub fn events(state: &ServiceApiState, query: EventsQuery) -> Result<Vec<EventInfo>, ApiError> {
+        let explorer = BlockchainExplorer::new(state.blockchain());
+        let to = query.to.unwrap_or_else(|| explorer.height());
+        let from = query.from.unwrap_or(to);
+        if from > to {
+            return Err(ApiError::BadRequest(
+                "`from` height should not be greater than `to` height".to_owned(),
+            ));
+        }
+        if to.0 - from.0 >= MAX_BLOCKS_PER_REQUEST as u64 {
+            return Err(ApiError::BadRequest(format!(
+                "Max block count per request exceeded ({})",
+                MAX_BLOCKS_PER_REQUEST
+            )));
+        }
+
+        let events = explorer
+            .blocks(from..to.next())
+            .flat_map(|block| explorer.block_events(block.height()))
+            .filter(|info| {
+                query
+                    .service_id
+                    .map_or(true, |id| info.event.service_id() == id)
+                    && query
+                        .event_type
+                        .as_ref()
+                        .map_or(true, |event_type| info.event.event_type() == event_type)
+            })
+            .collect();
+        Ok(events)
+    }
+
+    /// Returns the proof of inclusion of the event into the blockchain.
+    pub fn event_proof(
+        state: &ServiceApiState,
+        query: EventProofQuery,
+    ) -> Result<EventProof, ApiError> {
+        BlockchainExplorer::new(state.blockchain())
+            .event_proof(query.height, query.index)
+            .ok_or_else(|| ApiError::NotFound("Event not found".to_owned()))
+    }
+
     /// Adds transaction into unconfirmed tx pool, and broadcast transaction to other nodes.
     pub fn add_transaction(
         state: &ServiceApiState,
@@ -213,10 +368,9 @@ impl ExplorerApi {
         use crate::messages::ProtocolMessage;
 
         let buf: Vec<u8> = ::hex::decode(query.tx_body).map_err(into_failure)?;
-        let signed = SignedMessage::from_raw_buffer(buf)?;
-        let tx_hash = signed.hash();
-        let signed = RawTransaction::try_from(Message::deserialize(signed)?)
+        let signed = RawTransaction::try_from(Message::from_raw_buffer(buf)?)
             .map_err(|_| format_err!("Couldn't deserialize transaction message."))?;
+        let tx_hash = signed.hash();
         let _ = state
             .sender()
             .broadcast_transaction(signed)
@@ -224,6 +378,23 @@ impl ExplorerApi {
         Ok(TransactionResponse { tx_hash })
     }
 
+    /// Executes the transaction against the latest blockchain state without adding it
+    /// to the pool and returns the execution status and the names of the touched indexes.
+    pub fn simulate_transaction(
+        state: &ServiceApiState,
+        query: TransactionHex,
+    ) -> Result<SimulatedTransaction, ApiError> {
+        use crate::events::error::into_failure;
+        use crate::messages::ProtocolMessage;
+
+        let buf: Vec<u8> = ::hex::decode(query.tx_body).map_err(into_failure)?;
+        let signed = RawTransaction::try_from(Message::from_raw_buffer(buf)?)
+            .map_err(|_| format_err!("Couldn't deserialize transaction message."))?;
+        BlockchainExplorer::new(state.blockchain())
+            .simulate(&signed)
+            .map_err(|e| ApiError::BadRequest(e.to_string()))
+    }
+
     /// Subscribes to block commits events.
     pub fn handle_subscribe(
         name: &'static str,
@@ -231,20 +402,47 @@ impl ExplorerApi {
         service_api_state: ServiceApiState,
         shared_node_state: SharedNodeState,
     ) {
-        let server = Arc::new(Mutex::new(None));
+        Self::handle_ws(
+            name,
+            Subscription::Blocks,
+            backend,
+            service_api_state,
+            shared_node_state,
+        );
+    }
+
+    /// Subscribes to events emitted in committed blocks.
+    pub fn handle_events_subscribe(
+        name: &'static str,
+        backend: &mut actix_backend::ApiBuilder,
+        service_api_state: ServiceApiState,
+        shared_node_state: SharedNodeState,
+    ) {
+        Self::handle_ws(
+            name,
+            Subscription::Events,
+            backend,
+            service_api_state,
+            shared_node_state,
+        );
+    }
+
+    fn handle_ws(
+        name: &'static str,
+        subscription: Subscription,
+        backend: &mut actix_backend::ApiBuilder,
+        service_api_state: ServiceApiState,
+        shared_node_state: SharedNodeState,
+    ) {
         let service_api_state = Arc::new(service_api_state);
 
         let index = move |req: HttpRequest| -> FutureResponse {
-            let server = server.clone();
             let service_api_state = service_api_state.clone();
-            let mut address = server.lock().expect("Expected mutex lock");
-            if address.is_none() {
-                *address = Some(Arbiter::start(|_| Server::new(service_api_state)));
-
-                shared_node_state.set_broadcast_server_address(address.to_owned().unwrap());
-            }
+            let address = shared_node_state.broadcast_server_address_or_insert_with(|| {
+                Arbiter::start(|_| Server::new(service_api_state))
+            });
 
-            Box::new(ws::start(&req, Session::new(address.to_owned().unwrap())).into_future())
+            Box::new(ws::start(&req, Session::new(address, subscription)).into_future())
         };
 
         backend.raw_handler(RequestHandler {
@@ -263,6 +461,12 @@ impl ExplorerApi {
         Self::handle_subscribe(
             "v1/blocks/subscribe",
             api_scope.web_backend(),
+            service_api_state.clone(),
+            shared_node_state.clone(),
+        );
+        Self::handle_events_subscribe(
+            "v1/events/subscribe",
+            api_scope.web_backend(),
             service_api_state,
             shared_node_state,
         );
@@ -271,6 +475,11 @@ impl ExplorerApi {
             .endpoint("v1/block", Self::block)
             .endpoint("v1/transactions", Self::transaction_info)
             .endpoint_mut("v1/transactions", Self::add_transaction)
+            .endpoint_mut("v1/transactions/simulate", Self::simulate_transaction)
+            .endpoint("v1/accounts/nonce", Self::account_nonce)
+            .endpoint("v1/events", Self::events)
+            .endpoint("v1/events/proof", Self::event_proof)
+            .endpoint("v1/evidence", Self::evidence)
     }
 }
 
@@ -16,7 +16,7 @@
 
 use crate::api::{ServiceApiScope, ServiceApiState};
 use crate::blockchain::{Schema, SharedNodeState};
-use crate::helpers::user_agent;
+use crate::helpers::{user_agent, Height, ValidatorId};
 
 /// Information about the current state of the node memory pool.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -47,6 +47,15 @@ pub struct HealthCheckInfo {
     pub connected_peers: usize,
 }
 
+/// Information about a validator which has announced the maintenance.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct MaintenanceInfo {
+    /// Identifier of the validator in the actual configuration.
+    pub validator: ValidatorId,
+    /// Height at which the maintenance ends.
+    pub until: Height,
+}
+
 /// Public system API.
 #[derive(Clone, Debug)]
 pub struct SystemApi {
@@ -70,6 +79,19 @@ impl SystemApi {
         self
     }
 
+    fn handle_maintenance_info(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
+        api_scope.endpoint(name, move |state: &ServiceApiState, _query: ()| {
+            let snapshot = state.snapshot();
+            let schema = Schema::new(&snapshot);
+            Ok(schema
+                .validators_in_maintenance()
+                .into_iter()
+                .map(|(validator, until)| MaintenanceInfo { validator, until })
+                .collect::<Vec<_>>())
+        });
+        self
+    }
+
     fn handle_user_agent_info(self, name: &'static str, api_scope: &mut ServiceApiScope) -> Self {
         api_scope.endpoint(name, move |_state: &ServiceApiState, _query: ()| {
             Ok(user_agent::get())
@@ -112,6 +134,7 @@ impl SystemApi {
     pub fn wire(self, api_scope: &mut ServiceApiScope) -> &mut ServiceApiScope {
         self.handle_mempool_info("v1/mempool", api_scope)
             .handle_healthcheck_info("v1/healthcheck", api_scope)
+            .handle_maintenance_info("v1/maintenance", api_scope)
             .handle_user_agent_info("v1/user_agent", api_scope);
         api_scope
     }
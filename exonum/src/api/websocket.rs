@@ -24,15 +24,26 @@ use std::{cell::RefCell, collections::HashMap, sync::Arc};
 use crate::api::ServiceApiState;
 use crate::blockchain::Schema;
 use crate::crypto::Hash;
+use crate::explorer::BlockchainExplorer;
 
 /// WebSocket message for communication between clients(`Session`) and server(`Server`).
 #[derive(Message, Debug)]
 pub(crate) struct Message(pub String);
 
+/// Type of data streamed to a WebSocket client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Subscription {
+    /// Headers of committed blocks.
+    Blocks,
+    /// Events emitted in committed blocks, one message per event.
+    Events,
+}
+
 #[derive(Message)]
 #[rtype(usize)]
 pub(crate) struct Subscribe {
     pub address: Recipient<Message>,
+    pub subscription: Subscription,
 }
 
 #[derive(Message)]
@@ -46,7 +57,7 @@ pub(crate) struct Broadcast {
 }
 
 pub(crate) struct Server {
-    pub subscribers: HashMap<usize, Recipient<Message>>,
+    pub subscribers: HashMap<usize, (Subscription, Recipient<Message>)>,
     service_api_state: Arc<ServiceApiState>,
     rng: RefCell<ThreadRng>,
 }
@@ -68,9 +79,16 @@ impl Actor for Server {
 impl Handler<Subscribe> for Server {
     type Result = usize;
 
-    fn handle(&mut self, Subscribe { address }: Subscribe, _ctx: &mut Self::Context) -> usize {
+    fn handle(
+        &mut self,
+        Subscribe {
+            address,
+            subscription,
+        }: Subscribe,
+        _ctx: &mut Self::Context,
+    ) -> usize {
         let id = self.rng.borrow_mut().gen::<usize>();
-        self.subscribers.insert(id, address);
+        self.subscribers.insert(id, (subscription, address));
 
         id
     }
@@ -91,9 +109,35 @@ impl Handler<Broadcast> for Server {
         let snapshot = self.service_api_state.snapshot();
         let schema = Schema::new(snapshot);
         let block_header = schema.blocks().get(&block_hash);
+        let has_events_subscribers = self
+            .subscribers
+            .values()
+            .any(|(subscription, _)| *subscription == Subscription::Events);
+        let events = block_header
+            .as_ref()
+            .filter(|_| has_events_subscribers)
+            .map(|block| {
+                BlockchainExplorer::new(self.service_api_state.blockchain())
+                    .block_events(block.height())
+            })
+            .unwrap_or_default();
         let block_header_json = serde_json::to_value(block_header).unwrap().to_string();
-        for address in self.subscribers.values() {
-            let _ = address.do_send(Message(block_header_json.clone()));
+        let events_json = events
+            .iter()
+            .map(|event| serde_json::to_value(event).unwrap().to_string())
+            .collect::<Vec<_>>();
+
+        for (subscription, address) in self.subscribers.values() {
+            match subscription {
+                Subscription::Blocks => {
+                    let _ = address.do_send(Message(block_header_json.clone()));
+                }
+                Subscription::Events => {
+                    for event_json in &events_json {
+                        let _ = address.do_send(Message(event_json.clone()));
+                    }
+                }
+            }
         }
     }
 }
@@ -101,13 +145,15 @@ impl Handler<Broadcast> for Server {
 pub(crate) struct Session {
     pub id: usize,
     pub server_address: Addr<Server>,
+    pub subscription: Subscription,
 }
 
 impl Session {
-    pub fn new(server_address: Addr<Server>) -> Self {
+    pub fn new(server_address: Addr<Server>, subscription: Subscription) -> Self {
         Self {
             id: 0,
             server_address,
+            subscription,
         }
     }
 }
@@ -117,8 +163,12 @@ impl Actor for Session {
 
     fn started(&mut self, ctx: &mut Self::Context) {
         let address: Recipient<_> = ctx.address().recipient();
+        let subscription = self.subscription;
         self.server_address
-            .send(Subscribe { address })
+            .send(Subscribe {
+                address,
+                subscription,
+            })
             .into_actor(self)
             .then(|response, actor, context| {
                 match response {
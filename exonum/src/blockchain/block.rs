@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use chrono::{DateTime, Utc};
+
 use crate::crypto::Hash;
 use crate::helpers::{Height, ValidatorId};
 use crate::messages::{Precommit, Signed};
@@ -41,10 +43,16 @@ pub struct Block {
     tx_hash: Hash,
     /// Hash of the blockchain state after applying transactions in the block.
     state_hash: Hash,
+    /// Root hash of the Merkle tree of events emitted by transactions in this block.
+    event_hash: Hash,
+    /// Time of the block set by its proposer. The time of a block is never earlier
+    /// than the time of the previous block.
+    time: DateTime<Utc>,
 }
 
 impl Block {
     /// Create new `Block`.
+    #[cfg_attr(feature = "cargo-clippy", allow(clippy::too_many_arguments))]
     pub fn new(
         proposer_id: ValidatorId,
         height: Height,
@@ -52,6 +60,8 @@ impl Block {
         prev_hash: &Hash,
         tx_hash: &Hash,
         state_hash: &Hash,
+        event_hash: &Hash,
+        time: DateTime<Utc>,
     ) -> Self {
         Self {
             proposer_id,
@@ -60,6 +70,8 @@ impl Block {
             prev_hash: *prev_hash,
             tx_hash: *tx_hash,
             state_hash: *state_hash,
+            event_hash: *event_hash,
+            time,
         }
     }
     /// Identifier of the leader node which has proposed the block.
@@ -87,6 +99,14 @@ impl Block {
     pub fn state_hash(&self) -> &Hash {
         &self.state_hash
     }
+    /// Root hash of the Merkle tree of events emitted by transactions in this block.
+    pub fn event_hash(&self) -> &Hash {
+        &self.event_hash
+    }
+    /// Time of the block set by its proposer.
+    pub fn time(&self) -> DateTime<Utc> {
+        self.time
+    }
 }
 
 /// Block with its `Precommit` messages.
@@ -118,6 +138,8 @@ mod tests {
         let tx_hash = hash(&txs);
         let tx_count = txs.len() as u32;
         let state_hash = hash(&[7, 8, 9]);
+        let event_hash = hash(&[10, 11]);
+        let time = Utc::now();
         let block = Block::new(
             proposer_id,
             height,
@@ -125,6 +147,8 @@ mod tests {
             &prev_hash,
             &tx_hash,
             &state_hash,
+            &event_hash,
+            time,
         );
 
         assert_eq!(block.proposer_id(), proposer_id);
@@ -133,6 +157,8 @@ mod tests {
         assert_eq!(block.prev_hash(), &prev_hash);
         assert_eq!(block.tx_hash(), &tx_hash);
         assert_eq!(block.state_hash(), &state_hash);
+        assert_eq!(block.event_hash(), &event_hash);
+        assert_eq!(block.time(), time);
         let json_str = ::serde_json::to_string(&block).unwrap();
         let block1: Block = ::serde_json::from_str(&json_str).unwrap();
         assert_eq!(block1, block);
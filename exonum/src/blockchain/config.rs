@@ -24,12 +24,17 @@
 use serde::de::Error;
 use serde_json::Error as JsonError;
 
-use std::collections::{BTreeMap, HashSet};
+use std::{
+    cmp,
+    collections::{BTreeMap, HashSet},
+};
 
 use crate::crypto::{hash, CryptoHash, Hash, PublicKey};
 use crate::helpers::{Height, Milliseconds};
-use crate::messages::EMPTY_SIGNED_MESSAGE_SIZE;
-use crate::storage::StorageValue;
+use crate::messages::{
+    EMPTY_SIGNED_MESSAGE_SIZE, RAW_TRANSACTION_HEADER, TRANSACTION_RESPONSE_EMPTY_SIZE,
+};
+use crate::storage::{AccessLimits, StorageValue};
 
 /// Public keys of a validator. Each validator has two public keys: the
 /// `consensus_key` is used for internal operations in the consensus process,
@@ -41,6 +46,20 @@ pub struct ValidatorKeys {
     /// Service key is used for services, for example, the configuration
     /// updater service, the anchoring service, etc.
     pub service_key: PublicKey,
+    /// Voting power of the validator. Prevotes and precommits need validators
+    /// with more than 2/3 of the total voting power to reach the consensus.
+    ///
+    /// If the voting power is not specified, it is equal to 1, so each validator has
+    /// a single vote.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voting_power: Option<u64>,
+}
+
+impl ValidatorKeys {
+    /// Returns the voting power of the validator, which is 1 if it is not specified.
+    pub fn voting_power(&self) -> u64 {
+        self.voting_power.unwrap_or(1)
+    }
 }
 
 /// Exonum blockchain global configuration. Services
@@ -121,6 +140,163 @@ pub struct ConsensusConfig {
     /// in a block if the transaction pool is almost empty, and create blocks faster when there are
     /// enough transactions in the pool.
     pub propose_timeout_threshold: u32,
+    /// Limits of the storage access performed by transactions.
+    ///
+    /// By default, the storage access is not limited.
+    #[serde(default, skip_serializing_if = "ExecutionLimits::is_unlimited")]
+    pub execution_limits: ExecutionLimits,
+    /// Strategy of choosing the leader of each consensus round.
+    ///
+    /// By default, the leaders are chosen in the round-robin order.
+    #[serde(
+        default,
+        skip_serializing_if = "LeaderElectionStrategy::is_round_robin"
+    )]
+    pub leader_election: LeaderElectionStrategy,
+    /// Maximal interval between blocks if there are no transactions to commit.
+    ///
+    /// If set, the leader does not propose empty blocks: it waits for transactions to appear
+    /// in the pool instead. An empty block is still proposed if no block has been committed
+    /// during this interval, so services relying on regular blocks (e.g., `exonum-time`)
+    /// keep working. By default, empty blocks are proposed as usual.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_idle_timeout: Option<Milliseconds>,
+    /// Bounds of the round timeouts adapted to the network latency.
+    ///
+    /// If set, `first_round_timeout` is used only until the node observes the latency
+    /// of the consensus. By default, the round timeouts are static.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adaptive_timeouts: Option<AdaptiveTimeouts>,
+    /// Maximum total size (in bytes) of the transactions per block.
+    ///
+    /// The leader does not include transactions exceeding this limit into its `Propose`,
+    /// and validators do not vote for such proposes. By default, only the number of
+    /// transactions is limited by `txs_block_limit`.
+    ///
+    /// The transactions of a block are sent to the lagging nodes in a `TransactionsResponse`,
+    /// so the limit with the overhead of the response must not exceed `max_message_len`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub txs_block_size_limit: Option<u64>,
+    /// Maximum total execution cost of the transactions per block.
+    ///
+    /// The cost of a transaction is returned by `Transaction::execution_cost`. The limit
+    /// is applied in the same way as `txs_block_size_limit`. By default, the execution cost
+    /// is not limited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub txs_block_cost_limit: Option<u64>,
+    /// Maximum interval by which the time of a `Propose` may be ahead of the local clock
+    /// of a validator.
+    ///
+    /// Validators do not vote for proposes with the time further in the future, so a leader
+    /// cannot move the time of the blockchain forward arbitrarily. The interval should cover
+    /// the clock skew between the validators and must be at least `max_propose_timeout`.
+    /// The default value is `DEFAULT_MAX_PROPOSE_TIME_DRIFT`.
+    #[serde(default = "ConsensusConfig::default_max_propose_time_drift")]
+    pub max_propose_time_drift: Milliseconds,
+}
+
+/// Parameters of the round timeouts adapted to the observed network latency.
+///
+/// Each node measures the consensus latency, that is, the time passed since the `Propose`
+/// of the block was received till the majority of precommits for the block. The time
+/// the leader waits for transactions (see `max_idle_timeout`) is thus not counted as
+/// the latency. The timeout of the first round of a height is `LATENCY_FACTOR` times
+/// the average latency over the last `window` heights committed by the node, bounded by
+/// `min_round_timeout` and `max_round_timeout`. Timeouts of the following rounds are
+/// increased by `ConsensusConfig::TIMEOUT_LINEAR_INCREASE_PERCENT` of the first round
+/// timeout as usual.
+///
+/// Latencies are measured locally, so the round timeouts of the nodes may slightly differ.
+/// This does not affect the safety of the consensus.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveTimeouts {
+    /// Minimal timeout of the first round.
+    pub min_round_timeout: Milliseconds,
+    /// Maximal timeout of the first round.
+    pub max_round_timeout: Milliseconds,
+    /// Number of the recent heights the consensus latency is averaged over.
+    pub window: u32,
+}
+
+impl AdaptiveTimeouts {
+    /// Ratio of the first round timeout to the average consensus latency.
+    pub const LATENCY_FACTOR: u64 = 2;
+
+    /// Returns the timeout of the first round for the given average consensus latency.
+    pub fn round_timeout(&self, average_latency: Milliseconds) -> Milliseconds {
+        self.bound(average_latency.saturating_mul(Self::LATENCY_FACTOR))
+    }
+
+    /// Bounds the given timeout by `min_round_timeout` and `max_round_timeout`.
+    pub fn bound(&self, timeout: Milliseconds) -> Milliseconds {
+        cmp::min(
+            cmp::max(timeout, self.min_round_timeout),
+            self.max_round_timeout,
+        )
+    }
+}
+
+/// Strategy of choosing the leader of a consensus round, i.e., the validator which
+/// is expected to create a `Propose` in this round. The leader is chosen deterministically
+/// from the committed blocks, so all the nodes agree on it.
+///
+/// See the [`leader_election`] module for the implementations of the strategies.
+///
+/// [`leader_election`]: ../node/leader_election/index.html
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LeaderElectionStrategy {
+    /// Validators become leaders in turn, one after another.
+    RoundRobin,
+    /// Leaders are chosen pseudo-randomly, with the probability proportional to the voting
+    /// power of the validator.
+    Weighted,
+    /// Validators become leaders in turn, but the validators which have not proposed
+    /// the block in each of their last `missed_proposes` turns are moved to the end
+    /// of the order. Such validators get the first round back in every
+    /// `missed_proposes + 1`-th turn, so they can restore their reputation.
+    Reputation {
+        /// Number of consecutive missed proposes after which the validator is skipped.
+        missed_proposes: u32,
+    },
+}
+
+impl LeaderElectionStrategy {
+    /// Returns `true` if the strategy is round-robin.
+    pub fn is_round_robin(&self) -> bool {
+        *self == LeaderElectionStrategy::RoundRobin
+    }
+}
+
+impl Default for LeaderElectionStrategy {
+    fn default() -> Self {
+        LeaderElectionStrategy::RoundRobin
+    }
+}
+
+/// Limits of the storage access performed during transaction execution.
+///
+/// A transaction exceeding any of the limits is aborted with the
+/// [`LimitExceeded`] error type, and all its changes are rolled back.
+/// Note that the limits only account for the storage access, so they cannot
+/// stop a transaction which loops without touching the storage.
+///
+/// [`LimitExceeded`]: enum.TransactionErrorType.html#variant.LimitExceeded
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExecutionLimits {
+    /// Limits for a single transaction.
+    #[serde(default)]
+    pub per_transaction: AccessLimits,
+    /// Limits for all transactions in a block.
+    #[serde(default)]
+    pub per_block: AccessLimits,
+}
+
+impl ExecutionLimits {
+    /// Returns `true` if neither the transaction nor the block limits are set.
+    pub fn is_unlimited(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 impl ConsensusConfig {
@@ -130,6 +306,13 @@ impl ConsensusConfig {
     /// Time that will be added to round timeout for each next round in terms of percent of first_round_timeout.
     pub const TIMEOUT_LINEAR_INCREASE_PERCENT: u64 = 10; //default value 10%
 
+    /// Default value for max_propose_time_drift, five times the default max_propose_timeout.
+    pub const DEFAULT_MAX_PROPOSE_TIME_DRIFT: Milliseconds = 1000;
+
+    fn default_max_propose_time_drift() -> Milliseconds {
+        Self::DEFAULT_MAX_PROPOSE_TIME_DRIFT
+    }
+
     /// Produces warnings if configuration contains non-optimal values.
     ///
     /// Validation for logical correctness is performed in the `StoredConfiguration::try_deserialize`
@@ -176,6 +359,13 @@ impl Default for ConsensusConfig {
             min_propose_timeout: 10,
             max_propose_timeout: 200,
             propose_timeout_threshold: 500,
+            execution_limits: ExecutionLimits::default(),
+            leader_election: LeaderElectionStrategy::default(),
+            max_idle_timeout: None,
+            adaptive_timeouts: None,
+            txs_block_size_limit: None,
+            txs_block_cost_limit: None,
+            max_propose_time_drift: Self::DEFAULT_MAX_PROPOSE_TIME_DRIFT,
         }
     }
 }
@@ -187,6 +377,21 @@ impl StoredConfiguration {
         serde_json::to_vec(&self)
     }
 
+    /// Returns the total voting power of the validators.
+    pub fn total_voting_power(&self) -> u64 {
+        self.validator_keys
+            .iter()
+            .map(ValidatorKeys::voting_power)
+            .sum()
+    }
+
+    /// Returns the voting power sufficient to reach the consensus, that is,
+    /// more than 2/3 of the total voting power of the validators.
+    pub fn majority_voting_power(&self) -> u64 {
+        let total = u128::from(self.total_voting_power());
+        (total * 2 / 3) as u64 + 1
+    }
+
     /// Tries to deserialize `StorageConfiguration` from the given UTF-8 encoded
     /// JSON. Additionally, this method performs a logic validation of the
     /// configuration. The method returns either the result of execution or an error.
@@ -210,6 +415,22 @@ impl StoredConfiguration {
             }
         }
 
+        // Check voting power.
+        {
+            let mut total: u64 = 0;
+            for k in &config.validator_keys {
+                if k.voting_power == Some(0) {
+                    return Err(JsonError::custom(format!(
+                        "Voting power of the validator {:?} should not be equal to zero",
+                        k.consensus_key
+                    )));
+                }
+                total = total
+                    .checked_add(k.voting_power())
+                    .ok_or_else(|| JsonError::custom("Total voting power is too large"))?;
+            }
+        }
+
         // Check timeouts.
         if config.consensus.min_propose_timeout > config.consensus.max_propose_timeout {
             return Err(JsonError::custom(format!(
@@ -226,12 +447,68 @@ impl StoredConfiguration {
             )));
         }
 
+        if let Some(max_idle_timeout) = config.consensus.max_idle_timeout {
+            if max_idle_timeout < config.consensus.max_propose_timeout {
+                return Err(JsonError::custom(format!(
+                    "max_idle_timeout({}) must be at least max_propose_timeout({})",
+                    max_idle_timeout, config.consensus.max_propose_timeout
+                )));
+            }
+        }
+
+        if let Some(adaptive) = config.consensus.adaptive_timeouts {
+            if adaptive.min_round_timeout <= config.consensus.max_propose_timeout {
+                return Err(JsonError::custom(format!(
+                    "min_round_timeout({}) must be strictly larger than max_propose_timeout({})",
+                    adaptive.min_round_timeout, config.consensus.max_propose_timeout
+                )));
+            }
+            if adaptive.min_round_timeout > adaptive.max_round_timeout {
+                return Err(JsonError::custom(format!(
+                    "min_round_timeout({}) must be less or equal than max_round_timeout({})",
+                    adaptive.min_round_timeout, adaptive.max_round_timeout
+                )));
+            }
+            if adaptive.window == 0 {
+                return Err(JsonError::custom(
+                    "window of the adaptive timeouts should not be equal to zero",
+                ));
+            }
+        }
+
         // Check transactions limit.
         if config.consensus.txs_block_limit == 0 {
             return Err(JsonError::custom(
                 "txs_block_limit should not be equal to zero",
             ));
         }
+        if config.consensus.txs_block_size_limit == Some(0) {
+            return Err(JsonError::custom(
+                "txs_block_size_limit should not be equal to zero",
+            ));
+        }
+        if config.consensus.txs_block_cost_limit == Some(0) {
+            return Err(JsonError::custom(
+                "txs_block_cost_limit should not be equal to zero",
+            ));
+        }
+
+        // Check propose time drift.
+        if config.consensus.max_propose_time_drift < config.consensus.max_propose_timeout {
+            return Err(JsonError::custom(format!(
+                "max_propose_time_drift({}) must be at least max_propose_timeout({})",
+                config.consensus.max_propose_time_drift, config.consensus.max_propose_timeout
+            )));
+        }
+
+        // Check leader election.
+        if config.consensus.leader_election
+            == (LeaderElectionStrategy::Reputation { missed_proposes: 0 })
+        {
+            return Err(JsonError::custom(
+                "missed_proposes of the reputation leader election should not be equal to zero",
+            ));
+        }
 
         // Check maximum message length for sanity.
         if config.consensus.max_message_len < MINIMAL_MESSAGE_LENGTH {
@@ -241,6 +518,19 @@ impl StoredConfiguration {
             )));
         }
 
+        // Check that the transactions of a full block fit into a single response.
+        if let Some(size_limit) = config.consensus.txs_block_size_limit {
+            let overhead = TRANSACTION_RESPONSE_EMPTY_SIZE as u64
+                + u64::from(config.consensus.txs_block_limit) * RAW_TRANSACTION_HEADER as u64;
+            if size_limit.saturating_add(overhead) > u64::from(config.consensus.max_message_len) {
+                return Err(JsonError::custom(format!(
+                    "txs_block_size_limit ({}) with the response overhead ({}) must not exceed \
+                     max_message_len ({})",
+                    size_limit, overhead, config.consensus.max_message_len
+                )));
+            }
+        }
+
         Ok(config)
     }
 }
@@ -316,6 +606,109 @@ mod tests {
         assert_eq!(configuration, serialize_deserialize(&configuration));
     }
 
+    #[test]
+    fn execution_limits_parse_from_toml() {
+        let toml_content = r#"
+            [per_transaction]
+            writes = 10
+            bytes = 1024
+
+            [per_block]
+            reads = 1000
+            "#;
+
+        let limits: ExecutionLimits = toml::from_str(toml_content).unwrap();
+        assert_eq!(limits.per_transaction.reads, None);
+        assert_eq!(limits.per_transaction.writes, Some(10));
+        assert_eq!(limits.per_transaction.bytes, Some(1024));
+        assert_eq!(limits.per_block.reads, Some(1000));
+        assert!(!limits.is_unlimited());
+
+        let mut configuration = create_test_configuration();
+        configuration.consensus.execution_limits = limits;
+        assert_eq!(configuration, serialize_deserialize(&configuration));
+    }
+
+    #[test]
+    fn leader_election_parse_from_toml() {
+        let toml_content = r#"
+            type = "reputation"
+            missed_proposes = 3
+            "#;
+
+        let strategy: LeaderElectionStrategy = toml::from_str(toml_content).unwrap();
+        assert_eq!(
+            strategy,
+            LeaderElectionStrategy::Reputation { missed_proposes: 3 }
+        );
+
+        let mut configuration = create_test_configuration();
+        let serialized = String::from_utf8(configuration.try_serialize().unwrap()).unwrap();
+        assert!(!serialized.contains("leader_election"));
+        configuration.consensus.leader_election = strategy;
+        assert_eq!(configuration, serialize_deserialize(&configuration));
+        configuration.consensus.leader_election = LeaderElectionStrategy::Weighted;
+        assert_eq!(configuration, serialize_deserialize(&configuration));
+    }
+
+    #[test]
+    #[should_panic(expected = "missed_proposes of the reputation leader election")]
+    fn invalid_leader_election() {
+        let mut configuration = create_test_configuration();
+        configuration.consensus.leader_election =
+            LeaderElectionStrategy::Reputation { missed_proposes: 0 };
+        serialize_deserialize(&configuration);
+    }
+
+    #[test]
+    fn max_idle_timeout() {
+        let mut configuration = create_test_configuration();
+        let serialized = String::from_utf8(configuration.try_serialize().unwrap()).unwrap();
+        assert!(!serialized.contains("max_idle_timeout"));
+        configuration.consensus.max_idle_timeout = Some(60_000);
+        assert_eq!(configuration, serialize_deserialize(&configuration));
+    }
+
+    #[test]
+    #[should_panic(expected = "must be at least max_propose_timeout")]
+    fn invalid_max_idle_timeout() {
+        let mut configuration = create_test_configuration();
+        configuration.consensus.max_idle_timeout =
+            Some(configuration.consensus.max_propose_timeout - 1);
+        serialize_deserialize(&configuration);
+    }
+
+    #[test]
+    fn adaptive_timeouts() {
+        let mut configuration = create_test_configuration();
+        let serialized = String::from_utf8(configuration.try_serialize().unwrap()).unwrap();
+        assert!(!serialized.contains("adaptive_timeouts"));
+
+        let adaptive = AdaptiveTimeouts {
+            min_round_timeout: 500,
+            max_round_timeout: 10_000,
+            window: 10,
+        };
+        assert_eq!(adaptive.round_timeout(100), 500);
+        assert_eq!(adaptive.round_timeout(1_000), 2_000);
+        assert_eq!(adaptive.round_timeout(8_000), 10_000);
+
+        configuration.consensus.adaptive_timeouts = Some(adaptive);
+        assert_eq!(configuration, serialize_deserialize(&configuration));
+    }
+
+    #[test]
+    #[should_panic(expected = "must be less or equal than max_round_timeout")]
+    fn invalid_adaptive_timeouts() {
+        let mut configuration = create_test_configuration();
+        configuration.consensus.adaptive_timeouts = Some(AdaptiveTimeouts {
+            min_round_timeout: 5_000,
+            max_round_timeout: 1_000,
+            window: 10,
+        });
+        serialize_deserialize(&configuration);
+    }
+
     #[test]
     #[should_panic(expected = "Duplicated keys are found")]
     fn duplicated_validators_keys() {
@@ -323,10 +716,42 @@ mod tests {
         configuration.validator_keys.push(ValidatorKeys {
             consensus_key: PublicKey::zero(),
             service_key: PublicKey::zero(),
+            voting_power: None,
         });
         serialize_deserialize(&configuration);
     }
 
+    #[test]
+    fn voting_power() {
+        let mut configuration = create_test_configuration();
+        assert_eq!(configuration.total_voting_power(), 3);
+        assert_eq!(configuration.majority_voting_power(), 3);
+        let serialized = String::from_utf8(configuration.try_serialize().unwrap()).unwrap();
+        assert!(!serialized.contains("voting_power"));
+
+        configuration.validator_keys[0].voting_power = Some(5);
+        configuration.validator_keys[1].voting_power = Some(3);
+        assert_eq!(configuration.total_voting_power(), 9);
+        assert_eq!(configuration.majority_voting_power(), 7);
+        assert_eq!(configuration, serialize_deserialize(&configuration));
+    }
+
+    #[test]
+    #[should_panic(expected = "should not be equal to zero")]
+    fn zero_voting_power() {
+        let mut configuration = create_test_configuration();
+        configuration.validator_keys[0].voting_power = Some(0);
+        serialize_deserialize(&configuration);
+    }
+
+    #[test]
+    #[should_panic(expected = "Total voting power is too large")]
+    fn too_large_voting_power() {
+        let mut configuration = create_test_configuration();
+        configuration.validator_keys[0].voting_power = Some(u64::max_value());
+        serialize_deserialize(&configuration);
+    }
+
     #[test]
     #[should_panic(expected = "Invalid propose timeouts: min_propose_timeout should be less or")]
     fn min_max_propose_timeouts() {
@@ -355,6 +780,80 @@ mod tests {
         serialize_deserialize(&configuration);
     }
 
+    #[test]
+    fn txs_block_size_and_cost_limits() {
+        let mut configuration = create_test_configuration();
+        let serialized = String::from_utf8(configuration.try_serialize().unwrap()).unwrap();
+        assert!(!serialized.contains("txs_block_size_limit"));
+        assert!(!serialized.contains("txs_block_cost_limit"));
+
+        configuration.consensus.txs_block_size_limit = Some(512 * 1024);
+        configuration.consensus.txs_block_cost_limit = Some(10_000);
+        assert_eq!(configuration, serialize_deserialize(&configuration));
+    }
+
+    #[test]
+    #[should_panic(expected = "txs_block_size_limit should not be equal to zero")]
+    fn invalid_txs_block_size_limit() {
+        let mut configuration = create_test_configuration();
+        configuration.consensus.txs_block_size_limit = Some(0);
+        serialize_deserialize(&configuration);
+    }
+
+    #[test]
+    #[should_panic(expected = "with the response overhead")]
+    fn too_large_txs_block_size_limit() {
+        let mut configuration = create_test_configuration();
+        configuration.consensus.txs_block_size_limit =
+            Some(u64::from(configuration.consensus.max_message_len));
+        serialize_deserialize(&configuration);
+    }
+
+    #[test]
+    #[should_panic(expected = "txs_block_cost_limit should not be equal to zero")]
+    fn invalid_txs_block_cost_limit() {
+        let mut configuration = create_test_configuration();
+        configuration.consensus.txs_block_cost_limit = Some(0);
+        serialize_deserialize(&configuration);
+    }
+
+    #[test]
+    fn max_propose_time_drift() {
+        let mut configuration = create_test_configuration();
+        assert_eq!(
+            configuration.consensus.max_propose_time_drift,
+            ConsensusConfig::DEFAULT_MAX_PROPOSE_TIME_DRIFT
+        );
+
+        configuration.consensus.max_propose_time_drift = 10_000;
+        assert_eq!(configuration, serialize_deserialize(&configuration));
+    }
+
+    #[test]
+    fn max_propose_time_drift_default() {
+        let configuration = create_test_configuration();
+        let mut value = serde_json::to_value(&configuration).unwrap();
+        value["consensus"]
+            .as_object_mut()
+            .unwrap()
+            .remove("max_propose_time_drift");
+        let serialized = serde_json::to_vec(&value).unwrap();
+        let deserialized = StoredConfiguration::try_deserialize(&serialized).unwrap();
+        assert_eq!(
+            deserialized.consensus.max_propose_time_drift,
+            ConsensusConfig::DEFAULT_MAX_PROPOSE_TIME_DRIFT
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "must be at least max_propose_timeout")]
+    fn invalid_max_propose_time_drift() {
+        let mut configuration = create_test_configuration();
+        configuration.consensus.max_propose_time_drift =
+            configuration.consensus.max_propose_timeout - 1;
+        serialize_deserialize(&configuration);
+    }
+
     #[test]
     #[should_panic(expected = "max_message_len (128) must be at least")]
     fn too_small_max_message_len() {
@@ -368,6 +867,7 @@ mod tests {
             .map(|i| ValidatorKeys {
                 consensus_key: gen_keypair_from_seed(&Seed::new([i; SEED_LENGTH])).0,
                 service_key: gen_keypair_from_seed(&Seed::new([i * 10; SEED_LENGTH])).0,
+                voting_power: None,
             })
             .collect();
 
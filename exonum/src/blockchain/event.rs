@@ -0,0 +1,114 @@
+// Copyright 2019 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Events emitted by services during transaction execution.
+
+use crate::crypto::Hash;
+use crate::messages::{BinaryForm, HexStringRepresentation};
+use crate::proto;
+
+/// Typed event which can be emitted by a transaction via [`TransactionContext::emit`].
+///
+/// # Examples
+///
+/// ```
+/// # extern crate exonum;
+/// # #[macro_use] extern crate exonum_derive;
+/// # #[macro_use] extern crate serde_derive;
+/// #
+/// use exonum::blockchain::ServiceEvent;
+/// use exonum::crypto::PublicKey;
+///
+/// #[derive(Debug, Clone, Serialize, Deserialize, ProtobufConvert)]
+/// #[exonum(pb = "exonum::proto::schema::doc_tests::MyTransaction")]
+/// struct WalletCreated {
+///     public_key: PublicKey,
+/// }
+///
+/// impl ServiceEvent for WalletCreated {
+///     const EVENT_TYPE: &'static str = "wallet_created";
+/// }
+/// # fn main() {}
+/// ```
+///
+/// [`TransactionContext::emit`]: struct.TransactionContext.html#method.emit
+pub trait ServiceEvent: BinaryForm {
+    /// Type of the event. Events can be filtered by their type in the explorer API,
+    /// so the type should be unique within the service.
+    const EVENT_TYPE: &'static str;
+}
+
+/// Event stored in the event log of the block.
+///
+/// Events emitted by transactions of the block are kept in the [`block_events`] index,
+/// and the root hash of this index is committed in the block header as [`event_hash`].
+///
+/// [`block_events`]: struct.Schema.html#method.block_events
+/// [`event_hash`]: struct.Block.html#method.event_hash
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize, ProtobufConvert)]
+#[exonum(pb = "proto::Event", crate = "crate")]
+pub struct Event {
+    /// Identifier of the service which has emitted the event.
+    service_id: u16,
+    /// Type of the event.
+    event_type: String,
+    /// Hash of the transaction which has emitted the event.
+    tx_hash: Hash,
+    /// Serialized event.
+    #[serde(with = "HexStringRepresentation")]
+    payload: Vec<u8>,
+}
+
+impl Event {
+    /// Creates a new event.
+    pub fn new(service_id: u16, event_type: &str, tx_hash: Hash, payload: Vec<u8>) -> Self {
+        Self {
+            service_id,
+            event_type: event_type.to_owned(),
+            tx_hash,
+            payload,
+        }
+    }
+
+    /// Identifier of the service which has emitted the event.
+    pub fn service_id(&self) -> u16 {
+        self.service_id
+    }
+
+    /// Type of the event.
+    pub fn event_type(&self) -> &str {
+        &self.event_type
+    }
+
+    /// Hash of the transaction which has emitted the event.
+    pub fn tx_hash(&self) -> &Hash {
+        &self.tx_hash
+    }
+
+    /// Serialized event.
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Deserializes the event payload into the given event type.
+    ///
+    /// Returns `None` if the type of the event does not match `E::EVENT_TYPE`.
+    pub fn decode<E: ServiceEvent>(&self) -> Option<Result<E, failure::Error>> {
+        if self.event_type == E::EVENT_TYPE {
+            Some(E::decode(&self.payload))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright 2019 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cache of the transactions executed at the current height.
+//!
+//! Proposes of different rounds usually share most of their transactions. The cache
+//! stores the changes made by each executed transaction in a prefix tree keyed
+//! by the transaction hashes, so that the execution of a propose sharing a prefix
+//! with an already executed one starts from the state after the shared prefix.
+
+use chrono::{DateTime, Utc};
+
+use std::collections::HashMap;
+
+use crate::crypto::Hash;
+use crate::storage::{AccessCounters, Fork, KeyChanges};
+
+/// Cache of the transactions executed on top of a certain block.
+///
+/// The cache is used by [`Blockchain::create_patch_cached`]. It is cleared automatically
+/// once it is used on top of another block.
+///
+/// [`Blockchain::create_patch_cached`]: struct.Blockchain.html#method.create_patch_cached
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionCache {
+    /// Hash of the block preceding the cached transactions.
+    last_hash: Option<Hash>,
+    /// Time of the block being executed.
+    time: Option<DateTime<Utc>>,
+    entries: Vec<CacheEntry>,
+    /// Indexes of the entries by the preceding entry and the transaction hash.
+    children: HashMap<(Option<usize>, Hash), usize>,
+    /// Entry preceding the next executed transaction.
+    cursor: Option<usize>,
+}
+
+/// Executed transaction stored in the cache.
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    /// Changes made by the transaction, including its result and location.
+    changes: KeyChanges,
+    /// Storage access of the block after the transaction.
+    block_counters: AccessCounters,
+    /// Time of the block if the transaction has read it.
+    time: Option<DateTime<Utc>>,
+}
+
+impl ExecutionCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of cached transactions.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the cache contains no transactions.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all cached transactions.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Applies the changes of the longest cached prefix of the given transactions
+    /// to the fork. Returns the length of the prefix and the storage access
+    /// of the block after the prefix.
+    ///
+    /// Transactions which have read the time of the block are a part of the prefix
+    /// only if they have been executed with the same time.
+    pub(crate) fn restore(
+        &mut self,
+        last_hash: Hash,
+        time: DateTime<Utc>,
+        tx_hashes: &[Hash],
+        fork: &mut Fork,
+    ) -> (usize, AccessCounters) {
+        if self.last_hash != Some(last_hash) {
+            self.clear();
+            self.last_hash = Some(last_hash);
+        }
+
+        self.time = Some(time);
+        self.cursor = None;
+        let mut block_counters = AccessCounters::default();
+        for (len, tx_hash) in tx_hashes.iter().enumerate() {
+            let entry = self
+                .children
+                .get(&(self.cursor, *tx_hash))
+                .map(|&index| (index, &self.entries[index]))
+                .filter(|(_, entry)| entry.time.map_or(true, |entry_time| entry_time == time));
+            let (index, entry) = match entry {
+                Some(entry) => entry,
+                None => return (len, block_counters),
+            };
+            fork.apply_changes(entry.changes.clone());
+            block_counters = entry.block_counters;
+            self.cursor = Some(index);
+        }
+        (tx_hashes.len(), block_counters)
+    }
+
+    /// Adds the transaction executed after the restored prefix or the previously
+    /// added transaction. A transaction which has read the time of the block replaces
+    /// the one executed with another time.
+    pub(crate) fn push(
+        &mut self,
+        tx_hash: Hash,
+        changes: KeyChanges,
+        block_counters: AccessCounters,
+        reads_time: bool,
+    ) {
+        let index = self.entries.len();
+        self.entries.push(CacheEntry {
+            changes,
+            block_counters,
+            time: if reads_time { self.time } else { None },
+        });
+        self.children.insert((self.cursor, tx_hash), index);
+        self.cursor = Some(index);
+    }
+}
@@ -12,6 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use serde_json::Value;
+
+use std::collections::BTreeMap;
+
 use super::config::{ConsensusConfig, ValidatorKeys};
 
 /// The initial configuration which is committed into the genesis block.
@@ -20,13 +24,24 @@ use super::config::{ConsensusConfig, ValidatorKeys};
 /// when the blockchain is initially launched. This block can contain some service
 /// data, but does not include transactions.
 ///
-/// `GenesisConfig` includes consensus related configuration and the public keys of validators.
+/// `GenesisConfig` includes consensus related configuration, the public keys of validators
+/// and the initial data of services.
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct GenesisConfig {
     /// Consensus configuration.
     pub consensus: ConsensusConfig,
     /// List of public keys of validators.
     pub validator_keys: Vec<ValidatorKeys>,
+    /// Initial data of services keyed by the service name. The data of a service
+    /// is passed to [`Service::initialize_with`] during the creation of the genesis block.
+    ///
+    /// Binary payloads, such as serialized Protobuf messages, can be passed as hex strings.
+    /// Note that the configuration is saved in the TOML format, which does not support
+    /// `null` values.
+    ///
+    /// [`Service::initialize_with`]: trait.Service.html#method.initialize_with
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub services_data: BTreeMap<String, Value>,
 }
 
 impl GenesisConfig {
@@ -44,6 +59,13 @@ impl GenesisConfig {
         Self {
             consensus,
             validator_keys: validator_keys.collect(),
+            services_data: BTreeMap::new(),
         }
     }
+
+    /// Sets the initial data of the service with the given name.
+    pub fn with_service_data(mut self, service_name: &str, data: Value) -> Self {
+        self.services_data.insert(service_name.to_owned(), data);
+        self
+    }
 }
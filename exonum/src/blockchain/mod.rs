@@ -33,44 +33,125 @@
 
 pub use self::{
     block::{Block, BlockProof},
-    config::{ConsensusConfig, StoredConfiguration, ValidatorKeys},
+    config::{
+        AdaptiveTimeouts, ConsensusConfig, ExecutionLimits, LeaderElectionStrategy,
+        StoredConfiguration, ValidatorKeys,
+    },
+    event::{Event, ServiceEvent},
+    execution_cache::ExecutionCache,
     genesis::GenesisConfig,
+    parallel::AccessSet,
     schema::{Schema, TxLocation},
-    service::{Service, ServiceContext, SharedNodeState},
+    service::{BlockContext, Service, ServiceContext, SharedNodeState},
     transaction::{
         ExecutionError, ExecutionResult, Transaction, TransactionContext, TransactionError,
         TransactionErrorType, TransactionMessage, TransactionResult, TransactionSet,
     },
 };
 
+pub(crate) use self::undo::rollback;
+
 pub mod config;
 
 use byteorder::{ByteOrder, LittleEndian};
+use chrono::{DateTime, Utc};
+use tokio_threadpool::{Builder as ThreadPoolBuilder, ThreadPool};
 
 use std::{
     collections::{BTreeMap, HashMap},
     fmt, iter, mem, panic,
     sync::Arc,
+    time::UNIX_EPOCH,
 };
 
 use crate::crypto::{self, CryptoHash, Hash, PublicKey, SecretKey};
 use crate::helpers::{Height, Round, ValidatorId};
-use crate::messages::{Connect, Message, Precommit, ProtocolMessage, RawTransaction, Signed};
+use crate::messages::{
+    Batch, Connect, Evidence, KeyRotation, MaintenanceNotice, Message, MultiSigned, Precommit,
+    ProtocolMessage, RawTransaction, Signed, BATCH_SERVICE_ID, EVIDENCE_SERVICE_ID,
+    KEY_ROTATION_SERVICE_ID, MAINTENANCE_SERVICE_ID, MULTISIG_SERVICE_ID,
+    RAW_TRANSACTION_NONCE_MARKER,
+};
 use crate::node::ApiSender;
-use crate::storage::{self, Database, Error, Fork, Patch, Snapshot};
+use crate::storage::{
+    self, AccessCounters, AccessLimits, AccessLog, Database, Error, Fork, LimitExceeded, Patch,
+    PatchSummary, Snapshot,
+};
+
+use self::transaction::{BatchTransaction, MultiSignedTransaction};
+use self::undo::UndoData;
 
 mod block;
+mod event;
+mod execution_cache;
 mod genesis;
+mod parallel;
 mod schema;
 mod service;
 #[macro_use]
 mod transaction;
 #[cfg(test)]
 mod tests;
+mod undo;
 
 /// Id of core service table family.
 pub const CORE_SERVICE: u16 = 0;
 
+/// Default number of the latest blocks which can be rolled back.
+const DEFAULT_UNDO_DEPTH: u64 = 1_000;
+
+/// Transaction parsed for execution.
+enum ParsedTransaction {
+    /// Transaction of a single service together with the service identifier.
+    Single(u16, Box<dyn Transaction>),
+    /// Batch of service transactions.
+    Batch(BatchTransaction),
+}
+
+/// Transaction of a block prepared for execution.
+struct PendingTransaction {
+    /// Position of the transaction in the block.
+    index: usize,
+    tx_hash: Hash,
+    raw: Signed<RawTransaction>,
+    /// Name of the service used in logs.
+    service_name: &'static str,
+    tx: ParsedTransaction,
+}
+
+/// State of the block being executed.
+struct BlockExecution<'a> {
+    height: Height,
+    limits: ExecutionLimits,
+    /// Storage access of the transactions executed so far.
+    counters: AccessCounters,
+    /// Cache receiving the changes of the executed transactions.
+    cache: Option<&'a mut ExecutionCache>,
+}
+
+impl<'a> BlockExecution<'a> {
+    /// Returns the limits of the next transaction. The transaction cannot use more
+    /// storage access than is left from the block limits.
+    fn tx_limits(&self) -> AccessLimits {
+        self.limits
+            .per_transaction
+            .min(&self.limits.per_block.remaining(&self.counters))
+    }
+
+    /// Adds the changes of the executed transaction to the cache, if any.
+    fn cache_transaction(&mut self, tx_hash: Hash, log: &AccessLog, fork: &Fork) {
+        if let Some(ref mut cache) = self.cache {
+            let reads_time = log.reads.contains_index(schema::BLOCK_TIME);
+            cache.push(
+                tx_hash,
+                fork.changes_of(&log.writes),
+                self.counters,
+                reads_time,
+            );
+        }
+    }
+}
+
 /// Exonum blockchain instance with a certain services set and data storage.
 ///
 /// Only nodes with an identical set of services and genesis block can be combined
@@ -81,6 +162,9 @@ pub struct Blockchain {
     #[doc(hidden)]
     pub service_keypair: (PublicKey, SecretKey),
     pub(crate) api_sender: ApiSender,
+    execution_threads: usize,
+    execution_pool: Option<Arc<ThreadPool>>,
+    undo_depth: u64,
 }
 
 impl Blockchain {
@@ -95,6 +179,15 @@ impl Blockchain {
         let mut service_map = HashMap::new();
         for service in services {
             let id = service.service_id();
+            if id == RAW_TRANSACTION_NONCE_MARKER
+                || id == BATCH_SERVICE_ID
+                || id == MULTISIG_SERVICE_ID
+                || id == EVIDENCE_SERVICE_ID
+                || id == KEY_ROTATION_SERVICE_ID
+                || id == MAINTENANCE_SERVICE_ID
+            {
+                panic!("Service id={} is reserved, please change it.", id);
+            }
             if service_map.contains_key(&id) {
                 panic!(
                     "Services have already contain service with id={}, please change it.",
@@ -109,9 +202,42 @@ impl Blockchain {
             service_map: Arc::new(service_map),
             service_keypair: (service_public_key, service_secret_key),
             api_sender,
+            execution_threads: 1,
+            execution_pool: None,
+            undo_depth: DEFAULT_UNDO_DEPTH,
         }
     }
 
+    /// Sets the number of threads used to execute transactions of a block.
+    ///
+    /// If more than one thread is set, transactions declaring non-conflicting
+    /// [access sets] are executed concurrently. The resulting state is the same
+    /// as for the sequential execution, so this setting is local to the node.
+    /// The threads are kept in a pool shared by the clones of the blockchain.
+    ///
+    /// [access sets]: trait.Transaction.html#method.access_set
+    pub fn set_execution_threads(&mut self, threads: usize) {
+        self.execution_threads = threads;
+        self.execution_pool = if threads > 1 {
+            let pool = ThreadPoolBuilder::new()
+                .pool_size(threads)
+                .name_prefix("execution-")
+                .build();
+            Some(Arc::new(pool))
+        } else {
+            None
+        };
+    }
+
+    /// Sets the number of the latest blocks which can be rolled back with [`rollback`].
+    /// The undo data of older blocks is removed from the storage. The default value is 1000;
+    /// zero disables saving of the undo data.
+    ///
+    /// [`rollback`]: #method.rollback
+    pub fn set_undo_depth(&mut self, depth: u64) {
+        self.undo_depth = depth;
+    }
+
     /// Recreates the blockchain to reuse with a sandbox.
     #[doc(hidden)]
     pub fn clone_with_api_sender(&self, api_sender: ApiSender) -> Self {
@@ -144,7 +270,36 @@ impl Blockchain {
     ///
     /// - Blockchain has a service with the `service_id` of the given raw message.
     /// - Service can deserialize the given raw message.
+    ///
+    /// For a [`Batch`](../messages/struct.Batch.html) these conditions are checked
+    /// for every transaction of the batch. For a [`MultiSigned`] envelope these
+    /// conditions are checked for the wrapped transaction. An [`Evidence`] and
+    /// a [`KeyRotation`] are parsed by the core, and their signatures are verified.
+    /// A [`MaintenanceNotice`] is parsed by the core as well.
+    ///
+    /// Signatures of a [`MultiSigned`] envelope are not verified by this method.
+    ///
+    /// [`MultiSigned`]: ../messages/struct.MultiSigned.html
+    /// [`Evidence`]: ../messages/enum.Evidence.html
+    /// [`KeyRotation`]: ../messages/struct.KeyRotation.html
+    /// [`MaintenanceNotice`]: ../messages/struct.MaintenanceNotice.html
     pub fn tx_from_raw(&self, raw: RawTransaction) -> Result<Box<dyn Transaction>, failure::Error> {
+        if raw.service_id() == BATCH_SERVICE_ID {
+            return Ok(Box::new(self.batch_from_raw(&raw)?));
+        }
+        if raw.service_id() == MULTISIG_SERVICE_ID {
+            return Ok(Box::new(self.multisigned_from_raw(&raw)?));
+        }
+        if raw.service_id() == EVIDENCE_SERVICE_ID {
+            return Ok(Box::new(Evidence::from_raw(&raw)?));
+        }
+        if raw.service_id() == KEY_ROTATION_SERVICE_ID {
+            return Ok(Box::new(KeyRotation::from_raw(&raw)?));
+        }
+        if raw.service_id() == MAINTENANCE_SERVICE_ID {
+            return Ok(Box::new(MaintenanceNotice::from_raw(&raw)?));
+        }
+
         let service = self
             .service_map
             .get(&raw.service_id())
@@ -152,6 +307,40 @@ impl Blockchain {
         service.tx_from_raw(raw)
     }
 
+    /// Parses transactions of the batch contained in the given raw message.
+    fn batch_from_raw(&self, raw: &RawTransaction) -> Result<BatchTransaction, failure::Error> {
+        let transactions = Batch::from_raw(raw)?
+            .transactions()
+            .iter()
+            .map(|raw| Ok((raw.service_id(), self.tx_from_raw(raw.clone())?)))
+            .collect::<Result<_, failure::Error>>()?;
+        Ok(BatchTransaction { transactions })
+    }
+
+    /// Parses the transaction wrapped into the multisignature envelope contained
+    /// in the given raw message.
+    fn multisigned_from_raw(
+        &self,
+        raw: &RawTransaction,
+    ) -> Result<MultiSignedTransaction, failure::Error> {
+        let envelope = MultiSigned::from_raw(raw)?;
+        let signers = envelope.signers();
+        let raw = envelope.into_payload();
+        Ok(MultiSignedTransaction {
+            service_id: raw.service_id(),
+            signers,
+            transaction: self.tx_from_raw(raw)?,
+        })
+    }
+
+    /// Returns the name of the error code registered by the service with the given
+    /// identifier, see [`Service::error_name`] for details.
+    ///
+    /// [`Service::error_name`]: trait.Service.html#method.error_name
+    pub fn error_name(&self, service_id: u16, code: u16) -> Option<&'static str> {
+        self.service_map.get(&service_id)?.error_name(code)
+    }
+
     /// Commits changes from the patch to the blockchain storage.
     /// See [`Fork`](../storage/struct.Fork.html) for details.
     pub fn merge(&mut self, patch: Patch) -> Result<(), Error> {
@@ -182,6 +371,7 @@ impl Blockchain {
     ///
     /// * If the genesis block was not committed.
     /// * If storage version is not specified or not supported.
+    /// * If the genesis configuration contains data for an unknown service.
     pub fn initialize(&mut self, cfg: GenesisConfig) -> Result<(), Error> {
         let has_genesis_block = !Schema::new(&self.snapshot())
             .block_hashes_by_height()
@@ -222,7 +412,13 @@ impl Blockchain {
     }
 
     /// Creates and commits the genesis block with the given genesis configuration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the genesis configuration contains data for an unknown service
+    /// or for a service which does not accept genesis data.
     fn create_genesis_block(&mut self, cfg: GenesisConfig) -> Result<(), Error> {
+        let mut services_data = cfg.services_data;
         let mut config_propose = StoredConfiguration {
             previous_cfg_hash: Hash::zero(),
             actual_from: Height::zero(),
@@ -235,8 +431,11 @@ impl Blockchain {
             let mut fork = self.fork();
             // Update service tables
             for (_, service) in self.service_map.iter() {
-                let cfg = service.initialize(&mut fork);
                 let name = service.service_name();
+                let cfg = match services_data.remove(name) {
+                    Some(params) => service.initialize_with(&mut fork, params),
+                    None => service.initialize(&mut fork),
+                };
                 if config_propose.services.contains_key(name) {
                     panic!(
                         "Services already contain service with '{}' name, please change it",
@@ -245,6 +444,12 @@ impl Blockchain {
                 }
                 config_propose.services.insert(name.into(), cfg);
             }
+            if let Some(name) = services_data.keys().next() {
+                panic!(
+                    "Genesis configuration contains data for unknown service '{}'",
+                    name
+                );
+            }
             // Commit actual configuration
             {
                 let mut schema = Schema::new(&mut fork);
@@ -255,7 +460,8 @@ impl Blockchain {
                 schema.commit_configuration(config_propose);
             };
             self.merge(fork.into_patch())?;
-            self.create_patch(ValidatorId::zero(), Height::zero(), &[])
+            // The genesis block has a fixed time, so it is the same on every node.
+            self.create_patch(ValidatorId::zero(), Height::zero(), UNIX_EPOCH.into(), &[])
                 .1
         };
         self.merge(patch)?;
@@ -304,36 +510,125 @@ impl Blockchain {
     /// Executes the given transactions from the pool.
     /// Then collects the resulting changes from the current storage state and returns them
     /// with the hash of the resulting block.
+    ///
+    /// The `time` of the block is available to the transactions and services
+    /// via `TransactionContext::time` and `BlockContext::time`.
     pub fn create_patch(
         &self,
         proposer_id: ValidatorId,
         height: Height,
+        time: DateTime<Utc>,
+        tx_hashes: &[Hash],
+    ) -> (Hash, Patch) {
+        self.execute_block(proposer_id, height, time, tx_hashes, None)
+    }
+
+    /// Executes the given transactions from the pool like [`create_patch`], reusing
+    /// the transactions executed by the previous calls with the same cache.
+    ///
+    /// The state after the longest prefix of `tx_hashes` found in the cache is restored
+    /// without re-execution, and the remaining transactions are executed and added
+    /// to the cache. Transactions which have read the block time are reused only
+    /// for blocks with the same time. The cache is meant to be shared by the proposes
+    /// of a single height.
+    ///
+    /// [`create_patch`]: #method.create_patch
+    pub fn create_patch_cached(
+        &self,
+        cache: &mut ExecutionCache,
+        proposer_id: ValidatorId,
+        height: Height,
+        time: DateTime<Utc>,
         tx_hashes: &[Hash],
+    ) -> (Hash, Patch) {
+        self.execute_block(proposer_id, height, time, tx_hashes, Some(cache))
+    }
+
+    fn execute_block(
+        &self,
+        proposer_id: ValidatorId,
+        height: Height,
+        time: DateTime<Utc>,
+        tx_hashes: &[Hash],
+        cache: Option<&mut ExecutionCache>,
     ) -> (Hash, Patch) {
         // Create fork
         let mut fork = self.fork();
+        Schema::new(&mut fork).set_block_time(time);
 
         let block_hash = {
             // Get last hash.
             let last_hash = self.last_hash();
+            let mut block = BlockExecution {
+                height,
+                limits: Self::execution_limits(&fork),
+                counters: AccessCounters::default(),
+                cache,
+            };
+            // Restore transactions executed in the previous proposes.
+            let executed = match block.cache {
+                Some(ref mut cache) => {
+                    let (executed, counters) = cache.restore(last_hash, time, tx_hashes, &mut fork);
+                    block.counters = counters;
+                    executed
+                }
+                None => 0,
+            };
             // Save & execute transactions.
-            for (index, hash) in tx_hashes.iter().enumerate() {
-                self.execute_transaction(*hash, height, index, &mut fork)
-                    // Execution could fail if the transaction
-                    // cannot be deserialized or it isn't in the pool.
+            if self.execution_threads > 1 {
+                self.execute_parallel(tx_hashes, executed, &mut block, &mut fork)
                     .expect("Transaction execution error.");
+            } else {
+                for (index, hash) in tx_hashes.iter().enumerate().skip(executed) {
+                    let tx = self
+                        .prepare_transaction(index, *hash, &fork)
+                        // Execution could fail if the transaction
+                        // cannot be deserialized or it isn't in the pool.
+                        .expect("Transaction execution error.");
+                    Self::execute_cached(&tx, &mut block, &mut fork);
+                }
             }
 
             // Invoke execute method for all services.
-            for service in self.service_map.values() {
-                // Skip execution for genesis block.
-                if height > Height(0) {
-                    before_commit(service.as_ref(), &mut fork);
+            // Skip execution for genesis block.
+            if height > Height(0) {
+                let transactions = {
+                    let schema = Schema::new(&fork);
+                    let results = schema.transaction_results();
+                    tx_hashes
+                        .iter()
+                        .map(|hash| {
+                            let result = results
+                                .get(hash)
+                                .expect("BUG: Cannot find result of the executed transaction.");
+                            (*hash, result)
+                        })
+                        .collect::<Vec<_>>()
+                };
+
+                let evidence = {
+                    let schema = Schema::new(&fork);
+                    let evidence = schema.evidence();
+                    schema
+                        .block_evidence(height)
+                        .iter()
+                        .map(|hash| {
+                            evidence
+                                .get(&hash)
+                                .expect("BUG: Cannot find the recorded evidence.")
+                        })
+                        .collect::<Vec<_>>()
+                };
+
+                for service in self.service_map.values() {
+                    let context =
+                        BlockContext::new(&mut fork, height, proposer_id, &transactions, &evidence);
+                    before_commit(service.as_ref(), context);
                 }
             }
 
             // Get tx & state hash.
-            let (tx_hash, state_hash) = {
+            let (tx_hash, state_hash, event_hash) = {
                 let state_hashes = {
                     let schema = Schema::new(&fork);
 
@@ -368,8 +663,9 @@ impl Blockchain {
                 };
 
                 let tx_hash = schema.block_transactions(height).merkle_root();
+                let event_hash = schema.block_events(height).merkle_root();
 
-                (tx_hash, state_hash)
+                (tx_hash, state_hash, event_hash)
             };
 
             // Create block.
@@ -380,6 +676,8 @@ impl Blockchain {
                 &last_hash,
                 &tx_hash,
                 &state_hash,
+                &event_hash,
+                time,
             );
             trace!("execute block = {:?}", block);
             // Calculate block hash.
@@ -396,23 +694,165 @@ impl Blockchain {
         (block_hash, fork.into_patch())
     }
 
-    fn execute_transaction(
+    /// Takes the transaction from the pool and parses it for execution.
+    fn prepare_transaction(
         &self,
-        tx_hash: Hash,
-        height: Height,
         index: usize,
-        fork: &mut Fork,
-    ) -> Result<(), failure::Error> {
-        let (tx, raw, service_name) = {
-            let schema = Schema::new(&fork);
-
-            let raw = schema.transactions().get(&tx_hash).ok_or_else(|| {
+        tx_hash: Hash,
+        fork: &Fork,
+    ) -> Result<PendingTransaction, failure::Error> {
+        let raw = Schema::new(fork)
+            .transactions()
+            .get(&tx_hash)
+            .ok_or_else(|| {
                 failure::err_msg(format!(
                     "BUG: Cannot find transaction in database. tx: {:?}",
                     tx_hash
                 ))
             })?;
+        let (service_name, tx) = self.parse_transaction(&raw, tx_hash)?;
+        Ok(PendingTransaction {
+            index,
+            tx_hash,
+            raw,
+            service_name,
+            tx,
+        })
+    }
+
+    /// Executes the transaction of the block and records its results.
+    fn execute_pending(tx: &PendingTransaction, block: &mut BlockExecution, fork: &mut Fork) {
+        let (tx_result, item_results, counters) = Self::execute_parsed(
+            &tx.raw,
+            tx.tx_hash,
+            tx.service_name,
+            &tx.tx,
+            block.tx_limits(),
+            fork,
+        );
+        Self::record_transaction(tx, block.height, tx_result, item_results, fork);
+        block.counters.add(&counters);
+    }
+
+    /// Executes the transaction of the block like `execute_pending` and adds its changes
+    /// to the cache, if any.
+    fn execute_cached(tx: &PendingTransaction, block: &mut BlockExecution, fork: &mut Fork) {
+        if block.cache.is_some() {
+            fork.start_access_log();
+            Self::execute_pending(tx, block, fork);
+            let log = fork.stop_access_log();
+            block.cache_transaction(tx.tx_hash, &log, fork);
+        } else {
+            Self::execute_pending(tx, block, fork);
+        }
+    }
+
+    /// Records the results and the location of the executed transaction
+    /// and removes it from the pool.
+    fn record_transaction(
+        tx: &PendingTransaction,
+        height: Height,
+        tx_result: TransactionResult,
+        item_results: Vec<TransactionResult>,
+        fork: &mut Fork,
+    ) {
+        let tx_hash = tx.tx_hash;
+        let failed = tx_result.0.is_err();
+        let mut schema = Schema::new(fork);
+        schema.transaction_results_mut().put(&tx_hash, tx_result);
+        for (index, item_result) in item_results.into_iter().enumerate() {
+            // Changes of the successful transactions of a failed batch are rolled back,
+            // so only the result of the failed transaction is recorded.
+            if failed && item_result.0.is_ok() {
+                continue;
+            }
+            let item_hash = Batch::transaction_hash(&tx_hash, index);
+            schema
+                .transaction_results_mut()
+                .put(&item_hash, item_result);
+        }
+        schema.commit_transaction(&tx_hash);
+        schema.block_transactions_mut(height).push(tx_hash);
+        let location = TxLocation::new(height, tx.index as u64);
+        schema.transactions_locations_mut().put(&tx_hash, location);
+    }
 
+    /// Returns the execution limits of the actual configuration. The execution is not limited
+    /// if the blockchain has no configuration yet.
+    fn execution_limits<T: AsRef<dyn Snapshot>>(snapshot: T) -> ExecutionLimits {
+        let schema = Schema::new(snapshot);
+        if schema.configs_actual_from().is_empty() {
+            return ExecutionLimits::default();
+        }
+        schema.actual_configuration().consensus.execution_limits
+    }
+
+    /// Parses and executes the transaction without recording its result and location.
+    /// The storage access of the transaction is metered against the given limits.
+    ///
+    /// Returns the result of the transaction, results of the batch items
+    /// (empty if the transaction is not a batch) and the storage access performed
+    /// by the transaction.
+    fn execute_raw(
+        &self,
+        raw: &Signed<RawTransaction>,
+        tx_hash: Hash,
+        limits: AccessLimits,
+        fork: &mut Fork,
+    ) -> Result<(TransactionResult, Vec<TransactionResult>, AccessCounters), failure::Error> {
+        let (service_name, tx) = self.parse_transaction(raw, tx_hash)?;
+        Ok(Self::execute_parsed(
+            raw,
+            tx_hash,
+            service_name,
+            &tx,
+            limits,
+            fork,
+        ))
+    }
+
+    /// Parses the transaction by the corresponding service. Returns the name of the service
+    /// and the parsed transaction.
+    fn parse_transaction(
+        &self,
+        raw: &Signed<RawTransaction>,
+        tx_hash: Hash,
+    ) -> Result<(&'static str, ParsedTransaction), failure::Error> {
+        let parsed = if raw.service_id() == BATCH_SERVICE_ID {
+            let batch = self
+                .batch_from_raw(raw.payload())
+                .map_err(|error| format_err!("Batch: {}, tx: {:?}", error, tx_hash))?;
+            ("batch", ParsedTransaction::Batch(batch))
+        } else if raw.service_id() == MULTISIG_SERVICE_ID {
+            let tx = self
+                .multisigned_from_raw(raw.payload())
+                .map_err(|error| format_err!("Multisig: {}, tx: {:?}", error, tx_hash))?;
+            (
+                "multisig",
+                ParsedTransaction::Single(tx.service_id, Box::new(tx)),
+            )
+        } else if raw.service_id() == EVIDENCE_SERVICE_ID {
+            let evidence = Evidence::from_raw(raw.payload())
+                .map_err(|error| format_err!("Evidence: {}, tx: {:?}", error, tx_hash))?;
+            (
+                "evidence",
+                ParsedTransaction::Single(EVIDENCE_SERVICE_ID, Box::new(evidence)),
+            )
+        } else if raw.service_id() == KEY_ROTATION_SERVICE_ID {
+            let rotation = KeyRotation::from_raw(raw.payload())
+                .map_err(|error| format_err!("Key rotation: {}, tx: {:?}", error, tx_hash))?;
+            (
+                "key_rotation",
+                ParsedTransaction::Single(KEY_ROTATION_SERVICE_ID, Box::new(rotation)),
+            )
+        } else if raw.service_id() == MAINTENANCE_SERVICE_ID {
+            let notice = MaintenanceNotice::from_raw(raw.payload())
+                .map_err(|error| format_err!("Maintenance: {}, tx: {:?}", error, tx_hash))?;
+            (
+                "maintenance",
+                ParsedTransaction::Single(MAINTENANCE_SERVICE_ID, Box::new(notice)),
+            )
+        } else {
             let service_name = self
                 .service_map
                 .get(&raw.service_id())
@@ -427,55 +867,217 @@ impl Blockchain {
             let tx = self.tx_from_raw(raw.payload().clone()).map_err(|error| {
                 format_err!("Service <{}>: {}, tx: {:?}", service_name, error, tx_hash)
             })?;
-            (tx, raw, service_name)
+            (
+                service_name,
+                ParsedTransaction::Single(raw.service_id(), tx),
+            )
         };
+        Ok(parsed)
+    }
 
-        fork.checkpoint();
+    /// Executes the parsed transaction without recording its result and location.
+    /// The storage access of the transaction is metered against the given limits.
+    fn execute_parsed(
+        raw: &Signed<RawTransaction>,
+        tx_hash: Hash,
+        service_name: &str,
+        tx: &ParsedTransaction,
+        limits: AccessLimits,
+        fork: &mut Fork,
+    ) -> (TransactionResult, Vec<TransactionResult>, AccessCounters) {
+        let mut item_results = Vec::new();
+        let mut counters = AccessCounters::default();
+        let tx_result = match Self::consume_nonce(raw, fork) {
+            Err(error) => {
+                info!(
+                    "Service <{}>: {:?} transaction was not executed: {}",
+                    service_name, tx_hash, error
+                );
+                TransactionResult(Err(error))
+            }
+            Ok(()) => {
+                fork.start_metering(limits);
+                let result = match *tx {
+                    ParsedTransaction::Single(service_id, ref tx) => Self::execute_in_checkpoint(
+                        &**tx,
+                        raw,
+                        tx_hash,
+                        service_id,
+                        service_name,
+                        fork,
+                    ),
+                    ParsedTransaction::Batch(ref batch) => {
+                        let (result, results) =
+                            Self::execute_batch(batch, raw.author(), tx_hash, fork);
+                        item_results = results;
+                        result
+                    }
+                };
+                counters = fork.stop_metering();
+                result
+            }
+        };
+        (tx_result, item_results, counters)
+    }
 
-        let catch_result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
-            let context = TransactionContext::new(&mut *fork, &raw);
-            tx.execute(context)
-        }));
+    /// Executes the transaction against a throwaway fork of the latest blockchain state
+    /// and returns its result together with the summary of the changes made by
+    /// the transaction and the names of the indexes read by the transaction.
+    /// Neither the blockchain state nor the transaction pool is modified.
+    ///
+    /// The signature of the transaction is not verified by this method. The transaction
+    /// is subject to the per-transaction execution limits of the actual configuration.
+    ///
+    /// Returns an error if the transaction cannot be parsed by the corresponding service.
+    pub fn simulate(
+        &self,
+        tx: &Signed<RawTransaction>,
+    ) -> Result<(TransactionResult, PatchSummary, Vec<String>), failure::Error> {
+        let mut fork = self.fork();
+        let limits = Self::execution_limits(&fork).per_transaction;
+        fork.start_access_log();
+        let (result, ..) = self.execute_raw(tx, tx.hash(), limits, &mut fork)?;
+        let read_indexes = fork
+            .stop_access_log()
+            .reads
+            .iter()
+            .map(|(name, _)| name.to_owned())
+            .collect();
+        Ok((result, fork.into_patch().summary(), read_indexes))
+    }
 
-        let tx_result = TransactionResult(match catch_result {
-            Ok(execution_result) => {
-                match execution_result {
-                    Ok(()) => {
-                        fork.commit();
-                    }
-                    Err(ref e) => {
-                        // Unlike panic, transaction failure isn't that rare, so logging the
-                        // whole transaction body is an overkill: it can be relatively big.
-                        info!(
-                            "Service <{}>: {:?} transaction execution failed: {:?}",
-                            service_name, tx_hash, e
-                        );
-                        fork.rollback();
-                    }
+    /// Checks the author nonce of the transaction, if any, and increments the next expected
+    /// nonce of the author. Transactions without nonces are always accepted.
+    fn consume_nonce(
+        raw: &Signed<RawTransaction>,
+        fork: &mut Fork,
+    ) -> Result<(), TransactionError> {
+        let nonce = match raw.nonce() {
+            Some(nonce) => nonce,
+            None => return Ok(()),
+        };
+
+        let author = raw.author();
+        let mut schema = Schema::new(fork);
+        let expected = schema.account_nonce(&author);
+        if nonce != expected {
+            return Err(TransactionError::invalid_nonce(Some(format!(
+                "Expected nonce {}, got {}",
+                expected, nonce
+            ))));
+        }
+        schema.account_nonces_mut().put(&author, nonce + 1);
+        Ok(())
+    }
+
+    /// Executes the transaction inside a fork checkpoint. Changes made by the transaction are
+    /// rolled back if the execution fails or panics.
+    fn execute_in_checkpoint(
+        tx: &dyn Transaction,
+        raw: &Signed<RawTransaction>,
+        tx_hash: Hash,
+        service_id: u16,
+        service_name: &str,
+        fork: &mut Fork,
+    ) -> TransactionResult {
+        fork.checkpoint();
+
+        let result = Self::catch_execution(tx, tx_hash, service_name, || {
+            tx.execute(TransactionContext::new(&mut *fork, raw))
+        });
+        match result {
+            Ok(()) => fork.commit(),
+            Err(ref e) => {
+                if let TransactionErrorType::Code(_) = e.error_type() {
+                    // Unlike panic, transaction failure isn't that rare, so logging the
+                    // whole transaction body is an overkill: it can be relatively big.
+                    info!(
+                        "Service <{}>: {:?} transaction execution failed: {:?}",
+                        service_name, tx_hash, e
+                    );
                 }
-                execution_result.map_err(TransactionError::from)
+                fork.rollback();
             }
+        }
+        TransactionResult(result.map_err(|error| error.with_service_id(service_id)))
+    }
+
+    /// Executes transactions of the batch inside a single fork checkpoint. Execution stops
+    /// on the first failed transaction, and changes made by all transactions of the batch
+    /// are rolled back in this case.
+    ///
+    /// Returns the result of the whole batch, which is the result of the failed transaction
+    /// if there is one, and results of the executed transactions of the batch.
+    pub(crate) fn execute_batch(
+        batch: &BatchTransaction,
+        author: PublicKey,
+        tx_hash: Hash,
+        fork: &mut Fork,
+    ) -> (TransactionResult, Vec<TransactionResult>) {
+        fork.checkpoint();
+
+        let mut results = Vec::new();
+        for (index, (service_id, tx)) in batch.transactions.iter().enumerate() {
+            let item_hash = Batch::transaction_hash(&tx_hash, index);
+            let result = Self::catch_execution(&**tx, item_hash, "batch", || {
+                tx.execute(TransactionContext::for_batch_item(
+                    &mut *fork,
+                    *service_id,
+                    item_hash,
+                    author,
+                ))
+            })
+            .map_err(|error| error.with_service_id(*service_id));
+
+            results.push(TransactionResult(result.clone()));
+            if let Err(ref e) = result {
+                info!(
+                    "Batch {:?}: transaction #{} execution failed: {:?}",
+                    tx_hash, index, e
+                );
+                fork.rollback();
+                return (TransactionResult(result), results);
+            }
+        }
+
+        fork.commit();
+        (TransactionResult(Ok(())), results)
+    }
+
+    /// Runs the transaction execution catching the panics raised by the transaction.
+    /// Panics caused by storage errors are propagated, and the ones caused by exceeding
+    /// the storage access limits are reported as `LimitExceeded` errors.
+    fn catch_execution<F>(
+        tx: &dyn Transaction,
+        tx_hash: Hash,
+        service_name: &str,
+        execute: F,
+    ) -> Result<(), TransactionError>
+    where
+        F: FnOnce() -> ExecutionResult,
+    {
+        match panic::catch_unwind(panic::AssertUnwindSafe(execute)) {
+            Ok(execution_result) => execution_result.map_err(TransactionError::from),
             Err(err) => {
                 if err.is::<Error>() {
                     // Continue panic unwind if the reason is StorageError.
                     panic::resume_unwind(err);
                 }
-                fork.rollback();
-                error!(
-                    "Service <{}>: {:?} transaction execution panicked: {:?}",
-                    service_name, tx, err
-                );
-                Err(TransactionError::from_panic(&err))
+                if let Some(e) = err.downcast_ref::<LimitExceeded>() {
+                    info!(
+                        "Service <{}>: {:?} transaction execution aborted: {}",
+                        service_name, tx_hash, e
+                    );
+                    Err(TransactionError::limit_exceeded(Some(e.to_string())))
+                } else {
+                    error!(
+                        "Service <{}>: {:?} transaction execution panicked: {:?}",
+                        service_name, tx, err
+                    );
+                    Err(TransactionError::from_panic(&err))
+                }
             }
-        });
-
-        let mut schema = Schema::new(fork);
-        schema.transaction_results_mut().put(&tx_hash, tx_result);
-        schema.commit_transaction(&tx_hash);
-        schema.block_transactions_mut(height).push(tx_hash);
-        let location = TxLocation::new(height, index as u64);
-        schema.transactions_locations_mut().put(&tx_hash, location);
-        Ok(())
+        }
     }
 
     /// Commits to the blockchain a new block with the indicated changes (patch),
@@ -510,6 +1112,7 @@ impl Blockchain {
             }
             fork.into_patch()
         };
+        let patch = self.add_undo_data(patch);
         self.merge(patch)?;
 
         // Invokes `after_commit` for each service in order of their identifiers
@@ -526,6 +1129,70 @@ impl Blockchain {
         Ok(())
     }
 
+    /// Adds the undo data of the block committed by the patch and removes the undo data
+    /// of the block which cannot be rolled back anymore.
+    fn add_undo_data(&self, patch: Patch) -> Patch {
+        if self.undo_depth == 0 {
+            return patch;
+        }
+
+        let undo_data = UndoData::new(&patch, &*self.snapshot());
+        let mut fork = self.fork();
+        fork.merge(patch);
+        {
+            let mut schema = Schema::new(&mut fork);
+            let height = schema.height().0;
+            let mut index = schema.undo_data_mut();
+            index.put(&height, undo_data);
+            if let Some(stale_height) = height.checked_sub(self.undo_depth) {
+                index.remove(&stale_height);
+            }
+        }
+        fork.into_patch()
+    }
+
+    /// Reverts the blocks committed after the given height using the saved undo data,
+    /// so that the node can resync with the network. The node must not be running.
+    ///
+    /// The transactions of the reverted blocks are returned to the pool.
+    /// The consensus messages cache is cleared.
+    pub fn rollback(&mut self, height: Height) -> Result<(), failure::Error> {
+        rollback(&*self.db, height)
+    }
+
+    /// Evicts stale transactions from the persistent pool.
+    ///
+    /// See [`Schema::evict_transactions`] for the eviction rules. Records about
+    /// transactions evicted `evicted_retention` blocks ago or earlier are pruned.
+    /// Returns hashes of the evicted transactions.
+    ///
+    /// [`Schema::evict_transactions`]: struct.Schema.html#method.evict_transactions
+    pub(crate) fn evict_pool_transactions(
+        &mut self,
+        max_age: Option<u64>,
+        max_size: Option<u64>,
+        evicted_retention: Option<u64>,
+    ) -> Vec<Hash> {
+        if max_age.is_none() && max_size.is_none() && evicted_retention.is_none() {
+            return Vec::new();
+        }
+
+        let mut fork = self.fork();
+        let (evicted, pruned) = {
+            let mut schema = Schema::new(&mut fork);
+            let evicted = schema.evict_transactions(max_age, max_size);
+            let pruned = evicted_retention.map_or(false, |retention| {
+                schema.prune_evicted_transactions(retention)
+            });
+            (evicted, pruned)
+        };
+        if !evicted.is_empty() || pruned {
+            self.merge(fork.into_patch())
+                .expect("Unable to evict transactions from the persistent pool");
+        }
+        evicted
+    }
+
     /// Saves the `Connect` message from a peer to the cache.
     pub(crate) fn save_peer(&mut self, pubkey: &PublicKey, peer: Signed<Connect>) {
         let mut fork = self.fork();
@@ -585,16 +1252,19 @@ impl Blockchain {
     }
 }
 
-fn before_commit(service: &dyn Service, fork: &mut Fork) {
-    fork.checkpoint();
-    match panic::catch_unwind(panic::AssertUnwindSafe(|| service.before_commit(fork))) {
-        Ok(..) => fork.commit(),
+fn before_commit(service: &dyn Service, mut context: BlockContext) {
+    context.fork().checkpoint();
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        service.before_commit(context.reborrow())
+    }));
+    match result {
+        Ok(..) => context.fork().commit(),
         Err(err) => {
             if err.is::<Error>() {
                 // Continue panic unwind if the reason is StorageError.
                 panic::resume_unwind(err);
             }
-            fork.rollback();
+            context.fork().rollback();
             error!(
                 "{} service before_commit failed with error: {:?}",
                 service.service_name(),
@@ -617,6 +1287,9 @@ impl Clone for Blockchain {
             service_map: Arc::clone(&self.service_map),
             api_sender: self.api_sender.clone(),
             service_keypair: self.service_keypair.clone(),
+            execution_threads: self.execution_threads,
+            execution_pool: self.execution_pool.clone(),
+            undo_depth: self.undo_depth,
         }
     }
 }
@@ -0,0 +1,330 @@
+// Copyright 2019 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parallel execution of transactions declaring their storage access.
+//!
+//! Transactions of a block are split into groups of consecutive transactions with
+//! non-conflicting access sets. Transactions of a group are executed concurrently
+//! by the execution thread pool on separate forks of the state preceding the group.
+//! Then their changes are merged in the block order. A transaction which has actually
+//! read a key written by the preceding transactions of the group is re-executed,
+//! so the resulting state is always the same as for the sequential execution.
+
+use futures::{future, Future};
+
+use std::{mem, rc::Rc, sync::Arc};
+
+use crate::crypto::{Hash, PublicKey};
+use crate::storage::{
+    AccessCounters, AccessLimits, Database, Fork, IndexKeys, Iter, Patch, Snapshot, StorageKey,
+};
+
+use super::{
+    BlockExecution, Blockchain, ParsedTransaction, PendingTransaction, TransactionErrorType,
+    TransactionResult,
+};
+
+/// Storage access declared by a transaction.
+///
+/// Keys are specified in the same form as they are passed to the index methods, for example,
+/// `MapIndex::get`. Note that all keys of a Merkelized index depend on each other,
+/// so such indexes should be declared as a whole. All indexes of a family share
+/// the name of the family, so they are declared as a whole by the family name too.
+///
+/// See [`Transaction::access_set`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use exonum::blockchain::AccessSet;
+/// # use exonum::crypto::gen_keypair;
+///
+/// # let (from, to) = (gen_keypair().0, gen_keypair().0);
+/// let transfer = AccessSet::new()
+///     .write_key("wallets", &from)
+///     .write_key("wallets", &to)
+///     .read_index("config");
+/// let other = AccessSet::new().write_key("wallets", &gen_keypair().0);
+/// assert!(!transfer.conflicts_with(&other));
+/// let audit = AccessSet::new().read_index("wallets");
+/// assert!(transfer.conflicts_with(&audit));
+/// ```
+///
+/// [`Transaction::access_set`]: trait.Transaction.html#method.access_set
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessSet {
+    reads: IndexKeys,
+    writes: IndexKeys,
+}
+
+impl AccessSet {
+    /// Creates an empty access set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares reading of all keys of the index.
+    pub fn read_index(mut self, name: &str) -> Self {
+        self.reads.insert_index(name);
+        self
+    }
+
+    /// Declares reading of the key of the index.
+    pub fn read_key<K: StorageKey + ?Sized>(mut self, name: &str, key: &K) -> Self {
+        self.reads.insert_key(name, &key_bytes(key));
+        self
+    }
+
+    /// Declares writing of all keys of the index. Writing implies reading.
+    pub fn write_index(mut self, name: &str) -> Self {
+        self.writes.insert_index(name);
+        self
+    }
+
+    /// Declares writing of the key of the index. Writing implies reading.
+    pub fn write_key<K: StorageKey + ?Sized>(mut self, name: &str, key: &K) -> Self {
+        self.writes.insert_key(name, &key_bytes(key));
+        self
+    }
+
+    /// Returns `true` if one of the access sets writes a key accessed by the other.
+    pub fn conflicts_with(&self, other: &Self) -> bool {
+        self.writes.intersects(&other.writes)
+            || self.writes.intersects(&other.reads)
+            || self.reads.intersects(&other.writes)
+    }
+
+    fn union(&mut self, other: &Self) {
+        self.reads.union(&other.reads);
+        self.writes.union(&other.writes);
+    }
+}
+
+fn key_bytes<K: StorageKey + ?Sized>(key: &K) -> Vec<u8> {
+    let mut buffer = vec![0; key.size()];
+    key.write(&mut buffer);
+    buffer
+}
+
+impl ParsedTransaction {
+    /// Returns the access set declared by the transaction. Batches are always
+    /// executed sequentially.
+    fn access_set(&self, author: &PublicKey) -> Option<AccessSet> {
+        match *self {
+            ParsedTransaction::Single(_, ref tx) => tx.access_set(author),
+            ParsedTransaction::Batch(_) => None,
+        }
+    }
+}
+
+/// Transaction executed by a worker thread.
+struct ExecutedTransaction {
+    tx: PendingTransaction,
+    result: TransactionResult,
+    item_results: Vec<TransactionResult>,
+    counters: AccessCounters,
+    /// Keys actually read by the transaction.
+    reads: IndexKeys,
+    /// Changes made by the transaction.
+    patch: Patch,
+}
+
+/// Snapshot shared by the forks of a worker thread.
+struct SharedSnapshot(Rc<Fork>);
+
+impl Snapshot for SharedSnapshot {
+    fn get(&self, name: &str, key: &[u8]) -> Option<Vec<u8>> {
+        self.0.get(name, key)
+    }
+
+    fn contains(&self, name: &str, key: &[u8]) -> bool {
+        self.0.contains(name, key)
+    }
+
+    fn iter<'a>(&'a self, name: &str, from: &[u8]) -> Iter<'a> {
+        self.0.iter(name, from)
+    }
+}
+
+impl Blockchain {
+    /// Executes the given transactions from the pool starting from `start`, running groups
+    /// of transactions with non-conflicting access sets concurrently.
+    pub(super) fn execute_parallel(
+        &self,
+        tx_hashes: &[Hash],
+        start: usize,
+        block: &mut BlockExecution,
+        fork: &mut Fork,
+    ) -> Result<(), failure::Error> {
+        let mut group = Vec::new();
+        let mut group_access = AccessSet::new();
+
+        for (index, hash) in tx_hashes.iter().enumerate().skip(start) {
+            let tx = self.prepare_transaction(index, *hash, fork)?;
+            match tx.tx.access_set(&tx.raw.author()) {
+                Some(ref access) if !access.conflicts_with(&group_access) => {
+                    group_access.union(access);
+                    group.push(tx);
+                }
+                access => {
+                    let ready = mem::replace(&mut group, Vec::new());
+                    self.execute_group(ready, block, fork);
+                    match access {
+                        Some(access) => {
+                            group_access = access;
+                            group.push(tx);
+                        }
+                        None => {
+                            group_access = AccessSet::new();
+                            Self::execute_cached(&tx, block, fork);
+                        }
+                    }
+                }
+            }
+        }
+        self.execute_group(group, block, fork);
+        Ok(())
+    }
+
+    /// Executes the group of transactions concurrently and merges their changes
+    /// in the block order.
+    fn execute_group(
+        &self,
+        group: Vec<PendingTransaction>,
+        block: &mut BlockExecution,
+        fork: &mut Fork,
+    ) {
+        if group.len() < 2 {
+            for tx in &group {
+                Self::execute_cached(tx, block, fork);
+            }
+            return;
+        }
+
+        // Transactions of the group cannot use more than is left from the block limits
+        // before the group. The actual limits are checked during the merge.
+        let group_limits = block.tx_limits();
+        // The database is not modified during the block execution, so the worker threads
+        // see the same state as the fork.
+        let base = Arc::new(fork.patch().clone());
+
+        let pool = self
+            .execution_pool
+            .as_ref()
+            .expect("Execution thread pool is not initialized");
+        let threads = self.execution_threads.min(group.len());
+        let mut chunks: Vec<_> = (0..threads).map(|_| Vec::new()).collect();
+        for (index, tx) in group.into_iter().enumerate() {
+            chunks[index % threads].push(tx);
+        }
+        let workers: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| {
+                let db = Arc::clone(&self.db);
+                let base = Arc::clone(&base);
+                pool.spawn_handle(future::lazy(move || {
+                    Ok::<_, ()>(execute_chunk(&*db, &base, chunk, group_limits))
+                }))
+            })
+            .collect();
+
+        let mut executed = Vec::new();
+        for worker in workers {
+            // Panics of the workers, such as storage errors, are resumed by `wait`,
+            // so they are propagated as in the sequential execution.
+            let chunk = worker
+                .wait()
+                .expect("Execution of transactions cannot fail");
+            executed.extend(chunk);
+        }
+        executed.sort_by_key(|executed| executed.tx.index);
+
+        // Keys written by the merged transactions of the group.
+        let mut written = IndexKeys::default();
+        for executed in executed {
+            let tx_hash = executed.tx.tx_hash;
+            let conflicts = written.intersects(&executed.reads);
+            let limit_exceeded = match executed.result.0 {
+                Err(ref e) => e.error_type() == TransactionErrorType::LimitExceeded,
+                Ok(()) => false,
+            };
+
+            fork.start_access_log();
+            let reads =
+                if conflicts || limit_exceeded || !block.tx_limits().allows(&executed.counters) {
+                    trace!("Re-executing transaction {:?}", tx_hash);
+                    Self::execute_pending(&executed.tx, block, fork);
+                    None
+                } else {
+                    fork.merge(executed.patch);
+                    Self::record_transaction(
+                        &executed.tx,
+                        block.height,
+                        executed.result,
+                        executed.item_results,
+                        fork,
+                    );
+                    block.counters.add(&executed.counters);
+                    Some(executed.reads)
+                };
+            let mut log = fork.stop_access_log();
+            // Reads of the merged transaction are logged by the worker thread.
+            if let Some(reads) = reads {
+                log.reads.union(&reads);
+            }
+            block.cache_transaction(tx_hash, &log, fork);
+            written.union(&log.writes);
+        }
+    }
+}
+
+/// Executes transactions of the worker thread, each one on a separate fork
+/// of the given state.
+fn execute_chunk(
+    db: &dyn Database,
+    base: &Patch,
+    chunk: Vec<PendingTransaction>,
+    limits: AccessLimits,
+) -> Vec<ExecutedTransaction> {
+    let snapshot = {
+        let mut fork = db.fork();
+        fork.merge(base.clone());
+        Rc::new(fork)
+    };
+
+    chunk
+        .into_iter()
+        .map(|tx| {
+            let mut fork = Fork::new(Box::new(SharedSnapshot(Rc::clone(&snapshot))));
+            fork.start_access_log();
+            let (result, item_results, counters) = Blockchain::execute_parsed(
+                &tx.raw,
+                tx.tx_hash,
+                tx.service_name,
+                &tx.tx,
+                limits,
+                &mut fork,
+            );
+            let reads = fork.stop_access_log().reads;
+            ExecutedTransaction {
+                tx,
+                result,
+                item_results,
+                counters,
+                reads,
+                patch: fork.into_patch(),
+            }
+        })
+        .collect()
+}
@@ -12,11 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::{config::StoredConfiguration, Block, BlockProof, Blockchain, TransactionResult};
+use chrono::{DateTime, Utc};
+
+use std::{collections::BTreeMap, time::UNIX_EPOCH};
+
+use super::{
+    config::StoredConfiguration, undo::UndoData, Block, BlockProof, Blockchain, Event,
+    TransactionResult,
+};
 use crate::{
     crypto::{CryptoHash, Hash, PublicKey},
-    helpers::{Height, Round},
-    messages::{Connect, Message, Precommit, RawTransaction, Signed},
+    helpers::{Height, Round, ValidatorId},
+    messages::{Connect, Evidence, Message, Precommit, RawTransaction, Signed},
     proto,
     storage::{
         Entry, Fork, KeySetIndex, ListIndex, MapIndex, MapProof, ProofListIndex, ProofMapIndex,
@@ -40,6 +47,10 @@ define_names!(
     TRANSACTION_RESULTS => "transaction_results";
     TRANSACTIONS_POOL => "transactions_pool";
     TRANSACTIONS_POOL_LEN => "transactions_pool_len";
+    TRANSACTIONS_POOL_ARRIVALS => "transactions_pool_arrivals";
+    TRANSACTIONS_EVICTED => "transactions_evicted";
+    TRANSACTIONS_EVICTED_AT => "transactions_evicted_at";
+    TRANSACTIONS_EVICTION_HEIGHTS => "transactions_eviction_heights";
     TRANSACTIONS_LOCATIONS => "transactions_locations";
     BLOCKS => "blocks";
     BLOCK_HASHES_BY_HEIGHT => "block_hashes_by_height";
@@ -51,8 +62,18 @@ define_names!(
     PEERS_CACHE => "peers_cache";
     CONSENSUS_MESSAGES_CACHE => "consensus_messages_cache";
     CONSENSUS_ROUND => "consensus_round";
+    ACCOUNT_NONCES => "account_nonces";
+    BLOCK_EVENTS => "block_events";
+    UNDO_DATA => "undo_data";
+    EVIDENCE => "evidence";
+    BLOCK_EVIDENCE => "block_evidence";
+    VALIDATORS_MAINTENANCE => "validators_maintenance";
 );
 
+/// Name of the entry with the time of the block. Transactions reading this entry
+/// depend on the time of the block.
+pub(super) const BLOCK_TIME: &str = "core.block_time";
+
 /// Configuration index.
 #[derive(Debug, Serialize, Deserialize, ProtobufConvert)]
 #[exonum(pb = "proto::ConfigReference", crate = "crate")]
@@ -164,6 +185,22 @@ where
         pool.get().unwrap_or(0)
     }
 
+    /// Returns a table that keeps the height at which every uncommitted transaction
+    /// has been added into the pool.
+    pub(crate) fn transactions_pool_arrivals(&self) -> MapIndex<&T, Hash, Height> {
+        MapIndex::new(TRANSACTIONS_POOL_ARRIVALS, &self.view)
+    }
+
+    /// Returns a table that keeps hashes of transactions evicted from the pool without
+    /// being committed, along with the height at which the eviction took place.
+    ///
+    /// Contents of evicted transactions are not kept, so the same transaction can be
+    /// submitted again; in this case it is removed from this table. Entries may also
+    /// be pruned after the retention period set in the memory pool configuration.
+    pub fn transactions_evicted(&self) -> MapIndex<&T, Hash, Height> {
+        MapIndex::new(TRANSACTIONS_EVICTED, &self.view)
+    }
+
     /// Returns a table that keeps the block height and transaction position inside the block for every
     /// transaction hash.
     pub fn transactions_locations(&self) -> MapIndex<&T, Hash, TxLocation> {
@@ -186,6 +223,14 @@ where
         ProofListIndex::new_in_family(BLOCK_TRANSACTIONS, &height, &self.view)
     }
 
+    /// Returns a table that keeps a list of events emitted by transactions of the block
+    /// with the given height. The root hash of this table is stored in the block header
+    /// as the `event_hash`.
+    pub fn block_events(&self, height: Height) -> ProofListIndex<&T, Event> {
+        let height: u64 = height.into();
+        ProofListIndex::new_in_family(BLOCK_EVENTS, &height, &self.view)
+    }
+
     /// Returns a table that keeps a list of precommits for the block with the given hash.
     pub fn precommits(&self, hash: &Hash) -> ListIndex<&T, Signed<Precommit>> {
         ListIndex::new_in_family(PRECOMMITS, hash, &self.view)
@@ -223,12 +268,86 @@ where
         ProofMapIndex::new(STATE_HASH_AGGREGATOR, &self.view)
     }
 
+    /// Returns a table that keeps the next expected nonce for every author that has
+    /// sent transactions with nonces.
+    ///
+    /// A transaction with a nonce is executed only if its nonce equals the value stored
+    /// for its author (or zero if there is no value). After that the stored value is
+    /// incremented regardless of the execution result. Transactions without nonces
+    /// are not affected.
+    pub fn account_nonces(&self) -> ProofMapIndex<&T, PublicKey, u64> {
+        ProofMapIndex::new(ACCOUNT_NONCES, &self.view)
+    }
+
+    /// Returns a table that keeps the evidence of equivocation of validators
+    /// indexed by the [key] of the evidence, so only the first evidence is recorded
+    /// for each kind of messages, validator, height and round.
+    ///
+    /// Evidence is recorded by the transactions with the reserved `EVIDENCE_SERVICE_ID`
+    /// service identifier, see [`Evidence`] for details.
+    ///
+    /// [key]: ../messages/enum.Evidence.html#method.key
+    /// [`Evidence`]: ../messages/enum.Evidence.html
+    pub fn evidence(&self) -> ProofMapIndex<&T, Hash, Evidence> {
+        ProofMapIndex::new(EVIDENCE, &self.view)
+    }
+
+    /// Returns a table that keeps keys of the evidence recorded in the block
+    /// with the given height.
+    pub fn block_evidence(&self, height: Height) -> ListIndex<&T, Hash> {
+        let height: u64 = height.into();
+        ListIndex::new_in_family(BLOCK_EVIDENCE, &height, &self.view)
+    }
+
+    /// Returns a table that keeps the heights until which the validators are
+    /// in maintenance, indexed by the service keys of the validators.
+    ///
+    /// Maintenance is announced by the transactions with the reserved `MAINTENANCE_SERVICE_ID`
+    /// service identifier, see [`MaintenanceNotice`] for details. The table may contain
+    /// outdated entries; use [`validators_in_maintenance`] to get the validators which
+    /// are in maintenance at the next height.
+    ///
+    /// [`MaintenanceNotice`]: ../messages/struct.MaintenanceNotice.html
+    /// [`validators_in_maintenance`]: #method.validators_in_maintenance
+    pub fn validators_maintenance(&self) -> ProofMapIndex<&T, PublicKey, Height> {
+        ProofMapIndex::new(VALIDATORS_MAINTENANCE, &self.view)
+    }
+
+    /// Returns the validators of the actual configuration which are in maintenance
+    /// at the next height, along with the heights at which their maintenance ends.
+    pub fn validators_in_maintenance(&self) -> BTreeMap<ValidatorId, Height> {
+        let next_height = self.next_height();
+        let maintenance = self.validators_maintenance();
+        self.actual_configuration()
+            .validator_keys
+            .iter()
+            .enumerate()
+            .filter_map(|(id, keys)| {
+                maintenance
+                    .get(&keys.service_key)
+                    .filter(|&until| until > next_height)
+                    .map(|until| (ValidatorId(id as u16), until))
+            })
+            .collect()
+    }
+
+    /// Returns the next expected nonce of the given author.
+    pub fn account_nonce(&self, author: &PublicKey) -> u64 {
+        self.account_nonces().get(author).unwrap_or(0)
+    }
+
     /// Returns peers that have to be recovered in case of process restart
     /// after abnormal termination.
     pub(crate) fn peers_cache(&self) -> MapIndex<&T, PublicKey, Signed<Connect>> {
         MapIndex::new(PEERS_CACHE, &self.view)
     }
 
+    /// Returns a table that keeps the undo data of the latest committed blocks
+    /// by their heights. The data is used to roll back the storage.
+    pub(crate) fn undo_data(&self) -> MapIndex<&T, u64, UndoData> {
+        MapIndex::new(UNDO_DATA, &self.view)
+    }
+
     /// Returns consensus messages that have to be recovered in case of process restart
     /// after abnormal termination.
     pub(crate) fn consensus_messages_cache(&self) -> ListIndex<&T, Message> {
@@ -243,6 +362,14 @@ where
             .unwrap_or_else(Round::first)
     }
 
+    /// Returns the time of the block being executed. Outside of the block execution,
+    /// returns the time of the latest committed block.
+    pub fn block_time(&self) -> DateTime<Utc> {
+        Entry::new(BLOCK_TIME, &self.view)
+            .get()
+            .unwrap_or_else(|| UNIX_EPOCH.into())
+    }
+
     /// Returns the block hash for the given height.
     pub fn block_hash_by_height(&self, height: Height) -> Option<Hash> {
         self.block_hashes_by_height().get(height.into())
@@ -358,6 +485,9 @@ where
         vec![
             self.configs().merkle_root(),
             self.transaction_results().merkle_root(),
+            self.account_nonces().merkle_root(),
+            self.evidence().merkle_root(),
+            self.validators_maintenance().merkle_root(),
         ]
     }
 
@@ -410,7 +540,7 @@ where
 
     /// Returns the next height of the blockchain.
     /// Its value is equal to "height of the latest committed block" + 1.
-    fn next_height(&self) -> Height {
+    pub(crate) fn next_height(&self) -> Height {
         Height(self.block_hashes_by_height().len())
     }
 }
@@ -446,6 +576,32 @@ impl<'a> Schema<&'a mut Fork> {
         Entry::new(TRANSACTIONS_POOL_LEN, self.view)
     }
 
+    /// Mutable reference to the [`transactions_pool_arrivals`][1] index.
+    ///
+    /// [1]: struct.Schema.html#method.transactions_pool_arrivals
+    fn transactions_pool_arrivals_mut(&mut self) -> MapIndex<&mut Fork, Hash, Height> {
+        MapIndex::new(TRANSACTIONS_POOL_ARRIVALS, self.view)
+    }
+
+    /// Mutable reference to the [`transactions_evicted`][1] index.
+    ///
+    /// [1]: struct.Schema.html#method.transactions_evicted
+    fn transactions_evicted_mut(&mut self) -> MapIndex<&mut Fork, Hash, Height> {
+        MapIndex::new(TRANSACTIONS_EVICTED, self.view)
+    }
+
+    /// Returns a table that keeps hashes of transactions evicted at the given height.
+    fn transactions_evicted_at_mut(&mut self, height: Height) -> ListIndex<&mut Fork, Hash> {
+        let height: u64 = height.into();
+        ListIndex::new_in_family(TRANSACTIONS_EVICTED_AT, &height, self.view)
+    }
+
+    /// Returns a set of heights at which transactions have been evicted and
+    /// which are not pruned yet.
+    fn transactions_eviction_heights_mut(&mut self) -> KeySetIndex<&mut Fork, u64> {
+        KeySetIndex::new(TRANSACTIONS_EVICTION_HEIGHTS, self.view)
+    }
+
     /// Mutable reference to the [`transactions_locations`][1] index.
     ///
     /// [1]: struct.Schema.html#method.transactions_locations
@@ -478,6 +634,14 @@ impl<'a> Schema<&'a mut Fork> {
         ProofListIndex::new_in_family(BLOCK_TRANSACTIONS, &height, self.view)
     }
 
+    /// Mutable reference to the [`block_events`][1] index.
+    ///
+    /// [1]: struct.Schema.html#method.block_events
+    pub(crate) fn block_events_mut(&mut self, height: Height) -> ProofListIndex<&mut Fork, Event> {
+        let height: u64 = height.into();
+        ProofListIndex::new_in_family(BLOCK_EVENTS, &height, self.view)
+    }
+
     /// Mutable reference to the [`precommits`][1] index.
     ///
     /// [1]: struct.Schema.html#method.precommits
@@ -509,6 +673,37 @@ impl<'a> Schema<&'a mut Fork> {
         ProofMapIndex::new(STATE_HASH_AGGREGATOR, self.view)
     }
 
+    /// Mutable reference to the [`account_nonces`][1] index.
+    ///
+    /// [1]: struct.Schema.html#method.account_nonces
+    pub(crate) fn account_nonces_mut(&mut self) -> ProofMapIndex<&mut Fork, PublicKey, u64> {
+        ProofMapIndex::new(ACCOUNT_NONCES, self.view)
+    }
+
+    /// Mutable reference to the [`evidence`][1] index.
+    ///
+    /// [1]: struct.Schema.html#method.evidence
+    pub(crate) fn evidence_mut(&mut self) -> ProofMapIndex<&mut Fork, Hash, Evidence> {
+        ProofMapIndex::new(EVIDENCE, self.view)
+    }
+
+    /// Mutable reference to the [`validators_maintenance`][1] index.
+    ///
+    /// [1]: struct.Schema.html#method.validators_maintenance
+    pub(crate) fn validators_maintenance_mut(
+        &mut self,
+    ) -> ProofMapIndex<&mut Fork, PublicKey, Height> {
+        ProofMapIndex::new(VALIDATORS_MAINTENANCE, self.view)
+    }
+
+    /// Mutable reference to the [`block_evidence`][1] index.
+    ///
+    /// [1]: struct.Schema.html#method.block_evidence
+    pub(crate) fn block_evidence_mut(&mut self, height: Height) -> ListIndex<&mut Fork, Hash> {
+        let height: u64 = height.into();
+        ListIndex::new_in_family(BLOCK_EVIDENCE, &height, self.view)
+    }
+
     /// Mutable reference to the [`peers_cache`][1] index.
     ///
     /// [1]: struct.Schema.html#method.peers_cache
@@ -516,6 +711,13 @@ impl<'a> Schema<&'a mut Fork> {
         MapIndex::new(PEERS_CACHE, self.view)
     }
 
+    /// Mutable reference to the [`undo_data`][1] index.
+    ///
+    /// [1]: struct.Schema.html#method.undo_data
+    pub(crate) fn undo_data_mut(&mut self) -> MapIndex<&mut Fork, u64, UndoData> {
+        MapIndex::new(UNDO_DATA, self.view)
+    }
+
     /// Mutable reference to the [`consensus_messages_cache`][1] index.
     ///
     /// [1]: struct.Schema.html#method.consensus_messages
@@ -529,6 +731,12 @@ impl<'a> Schema<&'a mut Fork> {
         entry.set(round);
     }
 
+    /// Saves the time of the block being executed into the storage.
+    pub(crate) fn set_block_time(&mut self, time: DateTime<Utc>) {
+        let mut entry: Entry<&mut Fork, _> = Entry::new(BLOCK_TIME, self.view);
+        entry.set(time);
+    }
+
     /// Adds a new configuration to the blockchain, which will become actual at
     /// the `actual_from` height in `config_data`.
     pub fn commit_configuration(&mut self, config_data: StoredConfiguration) {
@@ -571,15 +779,107 @@ impl<'a> Schema<&'a mut Fork> {
     /// be sure to decrement it when transaction committed.
     #[doc(hidden)]
     pub fn add_transaction_into_pool(&mut self, tx: Signed<RawTransaction>) {
-        self.transactions_pool_mut().insert(tx.hash());
+        let hash = tx.hash();
+        let height = self.next_height();
+        self.transactions_pool_mut().insert(hash);
+        self.transactions_pool_arrivals_mut().put(&hash, height);
+        self.transactions_evicted_mut().remove(&hash);
         let x = self.transactions_pool_len_index().get().unwrap_or(0);
         self.transactions_pool_len_index_mut().set(x + 1);
-        self.transactions_mut().put(&tx.hash(), tx);
+        self.transactions_mut().put(&hash, tx);
     }
 
     /// Changes the transaction status from `in_pool`, to `committed`.
     pub(crate) fn commit_transaction(&mut self, hash: &Hash) {
         self.transactions_pool_mut().remove(hash);
+        self.transactions_pool_arrivals_mut().remove(hash);
+    }
+
+    /// Evicts uncommitted transactions from the persistent pool.
+    ///
+    /// Transactions that have been waiting in the pool for `max_age` blocks or more
+    /// are evicted first. Then, if the pool still holds more than `max_size`
+    /// transactions, the oldest ones are evicted until the pool fits the limit.
+    /// Transactions that arrived at the same height are ordered by their hashes,
+    /// so eviction does not depend on the order of pool iteration.
+    ///
+    /// Returns hashes of the evicted transactions.
+    pub(crate) fn evict_transactions(
+        &mut self,
+        max_age: Option<u64>,
+        max_size: Option<u64>,
+    ) -> Vec<Hash> {
+        let height = self.next_height();
+        let mut pool: Vec<(Height, Hash)> = {
+            let arrivals = self.transactions_pool_arrivals();
+            self.transactions_pool()
+                .iter()
+                .map(|hash| (arrivals.get(&hash).unwrap_or(height), hash))
+                .collect()
+        };
+        pool.sort();
+
+        let expired = max_age.map_or(0, |max_age| {
+            pool.iter()
+                .take_while(|(arrived, _)| height.0.saturating_sub(arrived.0) >= max_age)
+                .count()
+        });
+        let overflow = max_size.map_or(0, |max_size| pool.len().saturating_sub(max_size as usize));
+        let evicted: Vec<Hash> = pool
+            .into_iter()
+            .take(expired.max(overflow))
+            .map(|(_, hash)| hash)
+            .collect();
+
+        for hash in &evicted {
+            self.transactions_pool_mut().remove(hash);
+            self.transactions_pool_arrivals_mut().remove(hash);
+            self.transactions_mut().remove(hash);
+            self.transactions_evicted_mut().put(hash, height);
+        }
+        if !evicted.is_empty() {
+            let x = self.transactions_pool_len_index().get().unwrap_or(0);
+            self.transactions_pool_len_index_mut()
+                .set(x.saturating_sub(evicted.len() as u64));
+            self.transactions_evicted_at_mut(height)
+                .extend(evicted.iter().cloned());
+            self.transactions_eviction_heights_mut()
+                .insert(height.into());
+        }
+        evicted
+    }
+
+    /// Removes records about transactions evicted `retention` blocks ago or earlier
+    /// from the [`transactions_evicted`][1] index. Returns `true` if any records
+    /// have been pruned.
+    ///
+    /// [1]: struct.Schema.html#method.transactions_evicted
+    pub(crate) fn prune_evicted_transactions(&mut self, retention: u64) -> bool {
+        let threshold = match self.next_height().0.checked_sub(retention) {
+            Some(threshold) => threshold,
+            None => return false,
+        };
+        let heights: Vec<u64> = self
+            .transactions_eviction_heights_mut()
+            .iter()
+            .take_while(|height| *height <= threshold)
+            .collect();
+
+        for &height in &heights {
+            let hashes: Vec<Hash> = self
+                .transactions_evicted_at_mut(Height(height))
+                .iter()
+                .collect();
+            for hash in &hashes {
+                // The transaction may have been resubmitted and evicted again later.
+                if self.transactions_evicted_mut().get(hash) == Some(Height(height)) {
+                    self.transactions_evicted_mut().remove(hash);
+                }
+            }
+            self.transactions_evicted_at_mut(Height(height)).clear();
+            self.transactions_eviction_heights_mut().remove(&height);
+        }
+        !heights.is_empty()
     }
 
     /// Removes transaction from the persistent pool.
@@ -587,6 +887,7 @@ impl<'a> Schema<&'a mut Fork> {
     pub(crate) fn reject_transaction(&mut self, hash: &Hash) -> Result<(), ()> {
         let contains = self.transactions_pool_mut().contains(hash);
         self.transactions_pool_mut().remove(hash);
+        self.transactions_pool_arrivals_mut().remove(hash);
         self.transactions_mut().remove(hash);
 
         if contains {
@@ -16,6 +16,7 @@
 //! blockchain platforms, Exonum services encapsulate business logic of the blockchain application.
 
 use actix::Addr;
+use chrono::{DateTime, Utc};
 use serde_json::Value;
 
 use std::{
@@ -25,13 +26,13 @@ use std::{
     sync::{Arc, RwLock},
 };
 
-use super::transaction::Transaction;
+use super::transaction::{Transaction, TransactionResult};
 use crate::api::{websocket, ServiceApiBuilder};
 use crate::blockchain::{ConsensusConfig, Schema, StoredConfiguration, ValidatorKeys};
 use crate::crypto::{Hash, PublicKey, SecretKey};
 use crate::events::network::ConnectedPeerAddr;
 use crate::helpers::{Height, Milliseconds, ValidatorId};
-use crate::messages::{Message, RawTransaction, ServiceTransaction, Signed};
+use crate::messages::{Evidence, Message, RawTransaction, ServiceTransaction, Signed};
 use crate::node::{ApiSender, ConnectInfo, NodeRole, State};
 use crate::storage::{Fork, Snapshot};
 
@@ -176,6 +177,16 @@ pub trait Service: Send + Sync + 'static {
     /// [the `Service` example above](#examples).
     fn tx_from_raw(&self, raw: RawTransaction) -> Result<Box<dyn Transaction>, failure::Error>;
 
+    /// Returns the name of the error code returned by transactions of the service,
+    /// e.g., `"InsufficientCurrencyAmount"`. This is the service error registry
+    /// used by the explorer to render transaction errors, so that clients can match
+    /// on names instead of numeric codes.
+    ///
+    /// *Default implementation returns `None` for all codes*
+    fn error_name(&self, code: u16) -> Option<&'static str> {
+        None
+    }
+
     /// Invoked for all deployed services during the blockchain initialization
     /// on genesis block creation each time a node is started.
     /// During the handling of the method the service is able to perform the following activities:
@@ -190,15 +201,47 @@ pub trait Service: Send + Sync + 'static {
         Value::Null
     }
 
+    /// Invoked instead of [`initialize`] if the genesis configuration contains
+    /// the initial data of the service, for example, pre-funded accounts.
+    /// The `params` are taken from [`GenesisConfig::services_data`] by the service name.
+    /// The returned value is the initial global configuration of the service,
+    /// as for [`initialize`].
+    ///
+    /// *Default implementation calls [`initialize`] if `params` are `null`.*
+    ///
+    /// # Panics
+    ///
+    /// The default implementation panics if `params` are not `null`, so the genesis data
+    /// of a service which does not support it is not lost silently.
+    ///
+    /// [`initialize`]: #method.initialize
+    /// [`GenesisConfig::services_data`]: struct.GenesisConfig.html#structfield.services_data
+    fn initialize_with(&self, fork: &mut Fork, params: Value) -> Value {
+        if !params.is_null() {
+            panic!(
+                "Service '{}' does not accept genesis data",
+                self.service_name()
+            );
+        }
+        self.initialize(fork)
+    }
+
     /// A service execution. This method is invoked for each service after execution
     /// of all transactions in the block but before `after_commit` handler.
     ///
+    /// The [`BlockContext`] provides the fork of the blockchain state and the metadata
+    /// of the block being created, such as its height, proposer, results of
+    /// the executed transactions and the evidence of equivocation of validators
+    /// recorded by the block.
+    ///
     /// The order of invoking `before_commit` method for every service depends on the
     /// service ID. `before_commit` for the service with the smallest ID is invoked
     /// first up to the largest one.
     /// Effectively, this means that services should not rely on a particular ordering of
     /// Service::execute invocations.
-    fn before_commit(&self, fork: &mut Fork) {}
+    ///
+    /// [`BlockContext`]: struct.BlockContext.html
+    fn before_commit(&self, context: BlockContext) {}
 
     /// Handles block commit. This handler is invoked for each service after commit of the block.
     /// For example, a service can create one or more transactions if a specific condition
@@ -215,6 +258,88 @@ pub trait Service: Send + Sync + 'static {
     fn wire_api(&self, _builder: &mut ServiceApiBuilder) {}
 }
 
+/// Metadata of the block being created and the fork of the blockchain state
+/// after execution of the block transactions. This structure is passed to the
+/// `before_commit` method of the `Service` trait.
+///
+/// All data provided by the context is the same on every node, so services can use
+/// it to implement deterministic end-of-block logic.
+#[derive(Debug)]
+pub struct BlockContext<'a> {
+    fork: &'a mut Fork,
+    height: Height,
+    proposer_id: ValidatorId,
+    transactions: &'a [(Hash, TransactionResult)],
+    evidence: &'a [Evidence],
+}
+
+impl<'a> BlockContext<'a> {
+    /// Creates a block context for the given fork and block metadata.
+    pub(crate) fn new(
+        fork: &'a mut Fork,
+        height: Height,
+        proposer_id: ValidatorId,
+        transactions: &'a [(Hash, TransactionResult)],
+        evidence: &'a [Evidence],
+    ) -> Self {
+        BlockContext {
+            fork,
+            height,
+            proposer_id,
+            transactions,
+            evidence,
+        }
+    }
+
+    /// Creates a context with the same data which borrows the fork from this context.
+    pub(crate) fn reborrow(&mut self) -> BlockContext {
+        BlockContext {
+            fork: &mut *self.fork,
+            height: self.height,
+            proposer_id: self.proposer_id,
+            transactions: self.transactions,
+            evidence: self.evidence,
+        }
+    }
+
+    /// Returns fork of the blockchain state with changes made by the block transactions.
+    pub fn fork(&mut self) -> &mut Fork {
+        self.fork
+    }
+
+    /// Returns the height of the block being created.
+    pub fn height(&self) -> Height {
+        self.height
+    }
+
+    /// Returns the identifier of the validator which has proposed the block.
+    pub fn proposer_id(&self) -> ValidatorId {
+        self.proposer_id
+    }
+
+    /// Returns hashes of the block transactions together with the results of their
+    /// execution, in the order of execution.
+    pub fn transactions(&self) -> &[(Hash, TransactionResult)] {
+        self.transactions
+    }
+
+    /// Returns the evidence of equivocation of validators recorded by the block
+    /// transactions. Services can use it to penalise the validators.
+    pub fn evidence(&self) -> &[Evidence] {
+        self.evidence
+    }
+
+    /// Returns the time of the block being created.
+    pub fn time(&self) -> DateTime<Utc> {
+        Schema::new(&*self.fork).block_time()
+    }
+
+    /// Returns the configuration which is actual for the block being created.
+    pub fn actual_configuration(&self) -> StoredConfiguration {
+        Schema::new(&*self.fork).actual_configuration()
+    }
+}
+
 /// The current node state on which the blockchain is running, or in other words
 /// execution context. This structure is passed to the `after_commit` method
 /// of the `Service` trait and is used for the interaction between service
@@ -347,7 +472,7 @@ pub struct ApiNodeState {
     reconnects_timeout: HashMap<SocketAddr, Milliseconds>,
     is_enabled: bool,
     node_role: NodeRole,
-    majority_count: usize,
+    majority_count: u64,
     validators: Vec<ValidatorKeys>,
     broadcast_server_address: Option<Addr<websocket::Server>>,
 }
@@ -460,21 +585,25 @@ impl SharedNodeState {
     /// Returns a boolean value which indicates whether the consensus is achieved.
     pub fn consensus_status(&self) -> bool {
         let lock = self.state.read().expect("Expected read lock.");
-        let mut active_validators = lock
+        let mut active_validators: u64 = lock
             .incoming_connections
             .iter()
             .chain(lock.outgoing_connections.iter())
-            .filter(|ci| {
+            .filter_map(|ci| {
                 lock.validators
                     .iter()
-                    .any(|v| v.consensus_key == ci.public_key)
+                    .find(|v| v.consensus_key == ci.public_key)
             })
-            .count();
+            .map(ValidatorKeys::voting_power)
+            .sum();
 
-        if lock.node_role.is_validator() {
-            // Peers list doesn't include current node address, so we have to increment its length.
+        if let NodeRole::Validator(id) = lock.node_role {
+            // Peers list doesn't include current node address, so we have to add its voting power.
             // E.g. if we have 3 items in peers list, it means that we have 4 nodes overall.
-            active_validators += 1;
+            active_validators += lock
+                .validators
+                .get(id.0 as usize)
+                .map_or(0, ValidatorKeys::voting_power);
         }
 
         // Just after Node is started (node status isn't updated) majority_count = 0,
@@ -528,9 +657,20 @@ impl SharedNodeState {
             .remove(addr)
     }
 
-    pub(crate) fn set_broadcast_server_address(&self, address: Addr<websocket::Server>) {
+    /// Returns the address of the WebSocket server broadcasting committed blocks,
+    /// starting the server with the given function if it is not started yet.
+    pub(crate) fn broadcast_server_address_or_insert_with<F>(
+        &self,
+        start_server: F,
+    ) -> Addr<websocket::Server>
+    where
+        F: FnOnce() -> Addr<websocket::Server>,
+    {
         let mut state = self.state.write().expect("Expected write lock");
-        state.broadcast_server_address = Some(address);
+        state
+            .broadcast_server_address
+            .get_or_insert_with(start_server)
+            .clone()
     }
 
     /// Broadcast message to all subscribers.
@@ -14,18 +14,29 @@
 
 #![allow(dead_code, unsafe_code)]
 
+use chrono::{DateTime, TimeZone, Utc};
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use serde_json::Value;
+
+use std::iter;
 
 use crate::blockchain::{
-    Blockchain, ExecutionResult, Schema, Service, Transaction, TransactionContext, TransactionSet,
+    AccessSet, BlockContext, Blockchain, ConsensusConfig, ExecutionCache, ExecutionResult,
+    GenesisConfig, Schema, Service, ServiceEvent, Transaction, TransactionContext,
+    TransactionErrorType, TransactionResult, TransactionSet, ValidatorKeys,
+};
+use crate::crypto::{gen_keypair, hash, CryptoHash, Hash, PublicKey, SecretKey};
+use crate::helpers::{Height, Round, ValidatorId};
+use crate::messages::{
+    Batch, Evidence, KeyRotation, Message, MultiSigned, Prevote, RawTransaction, Signed,
+    ValidatorKeyKind, BATCH_SERVICE_ID, EVIDENCE_SERVICE_ID, KEY_ROTATION_SERVICE_ID,
+    MULTISIG_SERVICE_ID,
 };
-use crate::crypto::{gen_keypair, Hash};
-use crate::helpers::{Height, ValidatorId};
-use crate::messages::{Message, RawTransaction};
 use crate::proto;
-use crate::storage::{Database, Error, Fork, ListIndex, Snapshot};
+use crate::storage::{AccessLimits, Database, Error, Fork, ListIndex, MapIndex, Snapshot};
 
 const IDX_NAME: &'static str = "idx_name";
+const MAP_IDX_NAME: &'static str = "map_idx_name";
 const TEST_SERVICE_ID: u16 = 255;
 
 struct TestService;
@@ -64,6 +75,12 @@ impl Tx {
 #[exonum(crate = "crate")]
 enum TestServiceTxs {
     Tx(Tx),
+    MapTx(MapTx),
+    TimeTx(TimeTx),
+}
+
+impl ServiceEvent for Tx {
+    const EVENT_TYPE: &'static str = "tx";
 }
 
 impl Transaction for Tx {
@@ -71,6 +88,7 @@ impl Transaction for Tx {
         if self.value == 42 {
             panic!(Error::new("42"))
         }
+        tc.emit(self);
         let mut index = ListIndex::new(IDX_NAME, tc.fork());
         index.push(self.value);
         index.push(42 / self.value);
@@ -78,6 +96,62 @@ impl Transaction for Tx {
     }
 }
 
+/// Adds the value to the bucket `value % 10` of the map index.
+#[derive(Serialize, Deserialize, ProtobufConvert, Debug, Clone)]
+#[exonum(pb = "proto::schema::tests::TestServiceTx", crate = "crate")]
+struct MapTx {
+    value: u64,
+}
+
+impl MapTx {
+    /// Transaction with this value declares an incorrect access set.
+    const MISDECLARED_VALUE: u64 = 15;
+
+    fn new(value: u64) -> Self {
+        Self { value }
+    }
+}
+
+impl Transaction for MapTx {
+    fn execute(&self, tc: TransactionContext) -> ExecutionResult {
+        let mut index = MapIndex::new(MAP_IDX_NAME, tc.fork());
+        let bucket = self.value % 10;
+        let sum = index.get(&bucket).unwrap_or(0) + self.value;
+        index.put(&bucket, sum);
+        Ok(())
+    }
+
+    fn access_set(&self, _author: &PublicKey) -> Option<AccessSet> {
+        if self.value == Self::MISDECLARED_VALUE {
+            return Some(AccessSet::new());
+        }
+        let bucket = self.value % 10;
+        Some(AccessSet::new().write_key(MAP_IDX_NAME, &bucket))
+    }
+}
+
+/// Saves the time of the block into the list index.
+#[derive(Serialize, Deserialize, ProtobufConvert, Debug, Clone)]
+#[exonum(pb = "proto::schema::tests::TestServiceTx", crate = "crate")]
+struct TimeTx {
+    value: u64,
+}
+
+impl TimeTx {
+    fn new(value: u64) -> Self {
+        Self { value }
+    }
+}
+
+impl Transaction for TimeTx {
+    fn execute(&self, tc: TransactionContext) -> ExecutionResult {
+        let timestamp = tc.time().timestamp() as u64;
+        let mut index = ListIndex::new(IDX_NAME, tc.fork());
+        index.push(timestamp + self.value);
+        Ok(())
+    }
+}
+
 fn gen_tempdir_name() -> String {
     thread_rng().sample_iter(&Alphanumeric).take(10).collect()
 }
@@ -106,6 +180,7 @@ fn handling_tx_panic(blockchain: &mut Blockchain) {
     let (_, patch) = blockchain.create_patch(
         ValidatorId::zero(),
         Height::zero(),
+        Utc::now(),
         &[tx_ok1.hash(), tx_failed.hash(), tx_ok2.hash()],
     );
 
@@ -135,6 +210,367 @@ fn handling_tx_panic(blockchain: &mut Blockchain) {
     assert_eq!(index.get(3), Some(10));
 }
 
+fn handling_tx_events(blockchain: &mut Blockchain) {
+    let (pk, sec_key) = gen_keypair();
+    let tx_ok1 = Message::sign_transaction(Tx::new(3), TEST_SERVICE_ID, pk, &sec_key);
+    let tx_failed = Message::sign_transaction(Tx::new(0), TEST_SERVICE_ID, pk, &sec_key);
+    let tx_ok2 = Message::sign_transaction(Tx::new(5), TEST_SERVICE_ID, pk, &sec_key);
+
+    let mut fork = blockchain.fork();
+    {
+        let mut schema = Schema::new(&mut fork);
+        schema.add_transaction_into_pool(tx_ok1.clone());
+        schema.add_transaction_into_pool(tx_failed.clone());
+        schema.add_transaction_into_pool(tx_ok2.clone());
+    }
+    blockchain.merge(fork.into_patch()).unwrap();
+
+    let (block_hash, patch) = blockchain.create_patch(
+        ValidatorId::zero(),
+        Height::zero(),
+        Utc::now(),
+        &[tx_ok1.hash(), tx_failed.hash(), tx_ok2.hash()],
+    );
+    blockchain.merge(patch).unwrap();
+
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+    let events = schema.block_events(Height::zero());
+    // The event of the failed transaction is rolled back.
+    let expected = vec![(tx_ok1.hash(), 3), (tx_ok2.hash(), 5)];
+    let actual = events
+        .iter()
+        .map(|event| {
+            assert_eq!(event.service_id(), TEST_SERVICE_ID);
+            assert_eq!(event.event_type(), "tx");
+            let tx: Tx = event.decode().unwrap().unwrap();
+            (*event.tx_hash(), tx.value)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(actual, expected);
+
+    let block = schema.blocks().get(&block_hash).unwrap();
+    assert_eq!(block.event_hash(), &events.merkle_root());
+}
+
+fn handling_tx_simulation(blockchain: &mut Blockchain) {
+    let (pk, sec_key) = gen_keypair();
+    let tx_ok = Message::sign_transaction(Tx::new(3), TEST_SERVICE_ID, pk, &sec_key);
+    let tx_failed = Message::sign_transaction(Tx::new(0), TEST_SERVICE_ID, pk, &sec_key);
+
+    let (result, summary, read_indexes) = blockchain.simulate(&tx_ok).unwrap();
+    assert_eq!(result, TransactionResult(Ok(())));
+    assert_eq!(
+        summary.touched_indexes().collect::<Vec<_>>(),
+        vec!["core.block_events", IDX_NAME]
+    );
+    assert_eq!(summary.changes_count(IDX_NAME), 2);
+    // The transaction reads the current height and the lengths of the lists it appends to.
+    for name in &["core.block_hashes_by_height", "core.block_events", IDX_NAME] {
+        assert!(read_indexes.iter().any(|index| index == name));
+    }
+
+    let (result, summary, _) = blockchain.simulate(&tx_failed).unwrap();
+    let error = result.0.unwrap_err();
+    assert_eq!(error.error_type(), TransactionErrorType::Panic);
+    assert_eq!(error.service_id(), Some(TEST_SERVICE_ID));
+    assert!(summary.is_empty());
+
+    // Simulation does not change the blockchain state and the pool.
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+    assert!(!schema.transactions().contains(&tx_ok.hash()));
+    assert_eq!(schema.transactions_pool_len(), 0);
+    let index: ListIndex<_, u64> = ListIndex::new(IDX_NAME, &snapshot);
+    assert!(index.is_empty());
+}
+
+fn handling_tx_execution_limits(blockchain: &mut Blockchain) {
+    let (pk, sec_key) = gen_keypair();
+    let tx1 = Message::sign_transaction(Tx::new(3), TEST_SERVICE_ID, pk, &sec_key);
+    let tx2 = Message::sign_transaction(Tx::new(4), TEST_SERVICE_ID, pk, &sec_key);
+
+    // Measure the storage access of a single transaction.
+    let counters = {
+        let mut fork = blockchain.fork();
+        let (result, _, counters) = blockchain
+            .execute_raw(&tx1, tx1.hash(), AccessLimits::default(), &mut fork)
+            .unwrap();
+        assert_eq!(result, TransactionResult(Ok(())));
+        counters
+    };
+    assert!(counters.writes > 0);
+
+    // The block limits allow to execute only the first transaction.
+    let mut consensus = ConsensusConfig::default();
+    consensus.execution_limits.per_block.writes = Some(counters.writes);
+    let (consensus_key, _) = gen_keypair();
+    let genesis = GenesisConfig::new_with_consensus(
+        consensus,
+        iter::once(ValidatorKeys {
+            consensus_key,
+            service_key: blockchain.service_keypair.0,
+            voting_power: None,
+        }),
+    );
+    blockchain.initialize(genesis).unwrap();
+
+    let mut fork = blockchain.fork();
+    {
+        let mut schema = Schema::new(&mut fork);
+        schema.add_transaction_into_pool(tx1.clone());
+        schema.add_transaction_into_pool(tx2.clone());
+    }
+    blockchain.merge(fork.into_patch()).unwrap();
+
+    let (_, patch) = blockchain.create_patch(
+        ValidatorId::zero(),
+        Height(1),
+        Utc::now(),
+        &[tx1.hash(), tx2.hash()],
+    );
+    blockchain.merge(patch).unwrap();
+
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+    let results = schema.transaction_results();
+    assert_eq!(results.get(&tx1.hash()), Some(TransactionResult(Ok(()))));
+    let error = results.get(&tx2.hash()).unwrap().0.unwrap_err();
+    assert_eq!(error.error_type(), TransactionErrorType::LimitExceeded);
+    assert_eq!(error.service_id(), Some(TEST_SERVICE_ID));
+
+    // Changes of the aborted transaction are rolled back.
+    let index = ListIndex::new(IDX_NAME, &snapshot);
+    assert_eq!(index.iter().collect::<Vec<u64>>(), vec![3, 14]);
+}
+
+fn handling_parallel_execution(sequential: &mut Blockchain, parallel: &mut Blockchain) {
+    parallel.set_execution_threads(4);
+
+    let (consensus_key, _) = gen_keypair();
+    let genesis = GenesisConfig::new(iter::once(ValidatorKeys {
+        consensus_key,
+        service_key: sequential.service_keypair.0,
+        voting_power: None,
+    }));
+    sequential.initialize(genesis.clone()).unwrap();
+    parallel.initialize(genesis).unwrap();
+
+    let (pk, sec_key) = gen_keypair();
+    let map_tx =
+        |value| Message::sign_transaction(MapTx::new(value), TEST_SERVICE_ID, pk, &sec_key);
+    let txs = vec![
+        map_tx(1),
+        map_tx(2),
+        // Conflicts with the first transaction.
+        map_tx(11),
+        map_tx(5),
+        // Transaction without an access set is executed sequentially.
+        Message::sign_transaction(Tx::new(3), TEST_SERVICE_ID, pk, &sec_key),
+        map_tx(25),
+        // Actually conflicts with the previous transaction, so it is re-executed.
+        map_tx(MapTx::MISDECLARED_VALUE),
+        map_tx(7),
+        map_tx(0),
+    ];
+    let tx_hashes = txs.iter().map(Signed::hash).collect::<Vec<_>>();
+
+    let mut block_hashes = Vec::new();
+    for blockchain in vec![sequential, parallel] {
+        let mut fork = blockchain.fork();
+        {
+            let mut schema = Schema::new(&mut fork);
+            for tx in &txs {
+                schema.add_transaction_into_pool(tx.clone());
+            }
+        }
+        blockchain.merge(fork.into_patch()).unwrap();
+
+        let (block_hash, patch) =
+            blockchain.create_patch(ValidatorId::zero(), Height(1), Utc::now(), &tx_hashes);
+        blockchain.merge(patch).unwrap();
+        block_hashes.push(block_hash);
+
+        let snapshot = blockchain.snapshot();
+        let index = MapIndex::new(MAP_IDX_NAME, &snapshot);
+        assert_eq!(
+            index.iter().collect::<Vec<(u64, u64)>>(),
+            vec![(0, 0), (1, 12), (2, 2), (5, 45), (7, 7)]
+        );
+    }
+    // State hashes of the blocks are equal.
+    assert_eq!(block_hashes[0], block_hashes[1]);
+}
+
+fn handling_execution_cache(blockchain: &mut Blockchain) {
+    let (consensus_key, _) = gen_keypair();
+    let genesis = GenesisConfig::new(iter::once(ValidatorKeys {
+        consensus_key,
+        service_key: blockchain.service_keypair.0,
+        voting_power: None,
+    }));
+    blockchain.initialize(genesis).unwrap();
+
+    let (pk, sec_key) = gen_keypair();
+    let txs = vec![
+        Message::sign_transaction(Tx::new(3), TEST_SERVICE_ID, pk, &sec_key),
+        Message::sign_transaction(MapTx::new(1), TEST_SERVICE_ID, pk, &sec_key),
+        // Panicking transaction is rolled back.
+        Message::sign_transaction(Tx::new(0), TEST_SERVICE_ID, pk, &sec_key),
+        Message::sign_transaction(MapTx::new(11), TEST_SERVICE_ID, pk, &sec_key),
+        Message::sign_transaction(Tx::new(4), TEST_SERVICE_ID, pk, &sec_key),
+    ];
+    let mut fork = blockchain.fork();
+    {
+        let mut schema = Schema::new(&mut fork);
+        for tx in &txs {
+            schema.add_transaction_into_pool(tx.clone());
+        }
+    }
+    blockchain.merge(fork.into_patch()).unwrap();
+
+    let hashes = txs.iter().map(Signed::hash).collect::<Vec<_>>();
+    let proposes = vec![
+        (ValidatorId::zero(), hashes[..4].to_vec()),
+        // Shares the prefix of three transactions with the first propose.
+        (
+            ValidatorId::zero(),
+            vec![hashes[0], hashes[1], hashes[2], hashes[4]],
+        ),
+        // Has the same transactions as the first propose, but another proposer.
+        (ValidatorId(1), hashes[..4].to_vec()),
+        (ValidatorId::zero(), hashes[..2].to_vec()),
+    ];
+
+    let time = Utc::now();
+    for &threads in &[1, 4] {
+        blockchain.set_execution_threads(threads);
+        let mut cache = ExecutionCache::new();
+        for (proposer_id, tx_hashes) in &proposes {
+            let (block_hash, patch) = blockchain.create_patch_cached(
+                &mut cache,
+                *proposer_id,
+                Height(1),
+                time,
+                tx_hashes,
+            );
+            let (expected_hash, expected_patch) =
+                blockchain.create_patch(*proposer_id, Height(1), time, tx_hashes);
+            assert_eq!(block_hash, expected_hash);
+            assert_eq!(patch.summary(), expected_patch.summary());
+        }
+        assert_eq!(cache.len(), 5);
+    }
+
+    let mut cache = ExecutionCache::new();
+    let (_, patch) = blockchain.create_patch_cached(
+        &mut cache,
+        ValidatorId::zero(),
+        Height(1),
+        time,
+        &hashes[..2],
+    );
+    blockchain.merge(patch).unwrap();
+    // The cache is cleared once it is used on top of another block.
+    blockchain.create_patch_cached(&mut cache, ValidatorId::zero(), Height(2), time, &[]);
+    assert!(cache.is_empty());
+}
+
+fn handling_block_time(blockchain: &mut Blockchain) {
+    let (consensus_key, _) = gen_keypair();
+    let genesis = GenesisConfig::new(iter::once(ValidatorKeys {
+        consensus_key,
+        service_key: blockchain.service_keypair.0,
+        voting_power: None,
+    }));
+    blockchain.initialize(genesis).unwrap();
+
+    let (pk, sec_key) = gen_keypair();
+    let tx = Message::sign_transaction(Tx::new(3), TEST_SERVICE_ID, pk, &sec_key);
+    let time_tx = Message::sign_transaction(TimeTx::new(1), TEST_SERVICE_ID, pk, &sec_key);
+    let mut fork = blockchain.fork();
+    {
+        let mut schema = Schema::new(&mut fork);
+        schema.add_transaction_into_pool(tx.clone());
+        schema.add_transaction_into_pool(time_tx.clone());
+    }
+    blockchain.merge(fork.into_patch()).unwrap();
+
+    let hashes = [tx.hash(), time_tx.hash()];
+    let times: [DateTime<Utc>; 2] = [Utc.timestamp(1_000, 0), Utc.timestamp(2_000, 0)];
+    let mut cache = ExecutionCache::new();
+    for time in &times {
+        let (block_hash, patch) = blockchain.create_patch_cached(
+            &mut cache,
+            ValidatorId::zero(),
+            Height(1),
+            *time,
+            &hashes,
+        );
+        let (expected_hash, expected_patch) =
+            blockchain.create_patch(ValidatorId::zero(), Height(1), *time, &hashes);
+        assert_eq!(block_hash, expected_hash);
+        assert_eq!(patch.summary(), expected_patch.summary());
+    }
+    // The transaction reading the time is not reused with another time.
+    assert_eq!(cache.len(), 3);
+
+    let (_, patch) = blockchain.create_patch(ValidatorId::zero(), Height(1), times[1], &hashes);
+    blockchain.merge(patch).unwrap();
+    let snapshot = blockchain.snapshot();
+    let index = ListIndex::new(IDX_NAME, &snapshot);
+    assert_eq!(index.iter().collect::<Vec<u64>>(), vec![3, 14, 2_001]);
+    assert_eq!(Schema::new(&snapshot).last_block().time(), times[1]);
+}
+
+fn handling_rollback(blockchain: &mut Blockchain) {
+    let (consensus_key, _) = gen_keypair();
+    let genesis = GenesisConfig::new(iter::once(ValidatorKeys {
+        consensus_key,
+        service_key: blockchain.service_keypair.0,
+        voting_power: None,
+    }));
+    blockchain.set_undo_depth(2);
+    blockchain.initialize(genesis).unwrap();
+
+    let (pk, sec_key) = gen_keypair();
+    let txs: Vec<_> = (1..4)
+        .map(|value| Message::sign_transaction(Tx::new(value), TEST_SERVICE_ID, pk, &sec_key))
+        .collect();
+    for (height, tx) in (1..).zip(&txs) {
+        let mut fork = blockchain.fork();
+        Schema::new(&mut fork).add_transaction_into_pool(tx.clone());
+        blockchain.merge(fork.into_patch()).unwrap();
+
+        let (block_hash, patch) = blockchain.create_patch(
+            ValidatorId::zero(),
+            Height(height),
+            Utc.timestamp(0, 0),
+            &[tx.hash()],
+        );
+        blockchain
+            .commit(&patch, block_hash, iter::empty())
+            .unwrap();
+    }
+
+    // The undo data of the first block is already pruned.
+    assert!(blockchain.rollback(Height(0)).is_err());
+    assert!(blockchain.rollback(Height(4)).is_err());
+
+    blockchain.rollback(Height(1)).unwrap();
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+    assert_eq!(schema.height(), Height(1));
+    assert_eq!(schema.last_block().height(), Height(1));
+    assert!(schema.block_hash_by_height(Height(2)).is_none());
+    assert_eq!(schema.transactions_pool_len(), 2);
+    assert!(schema.transactions_pool().contains(&txs[1].hash()));
+    assert!(schema.transactions_pool().contains(&txs[2].hash()));
+    assert!(!schema.transactions_pool().contains(&txs[0].hash()));
+    let index = ListIndex::new(IDX_NAME, &snapshot);
+    assert_eq!(index.iter().collect::<Vec<u64>>(), vec![1, 42]);
+}
+
 fn handling_tx_panic_storage_error(blockchain: &mut Blockchain) {
     let (pk, sec_key) = gen_keypair();
     let tx_ok1 = Message::sign_transaction(Tx::new(3), TEST_SERVICE_ID, pk, &sec_key);
@@ -157,10 +593,229 @@ fn handling_tx_panic_storage_error(blockchain: &mut Blockchain) {
     blockchain.create_patch(
         ValidatorId::zero(),
         Height::zero(),
+        Utc::now(),
         &[tx_ok1.hash(), tx_storage_error.hash(), tx_ok2.hash()],
     );
 }
 
+fn evicting_pool_transactions(blockchain: &mut Blockchain) {
+    let (pk, sec_key) = gen_keypair();
+    let tx1 = Message::sign_transaction(Tx::new(1), TEST_SERVICE_ID, pk, &sec_key);
+    let tx2 = Message::sign_transaction(Tx::new(2), TEST_SERVICE_ID, pk, &sec_key);
+    let tx3 = Message::sign_transaction(Tx::new(3), TEST_SERVICE_ID, pk, &sec_key);
+
+    // Adds the given transactions to the pool and then moves to the next height.
+    let add_transactions = |blockchain: &mut Blockchain, txs: &[&Signed<RawTransaction>]| {
+        let mut fork = blockchain.fork();
+        {
+            let mut schema = Schema::new(&mut fork);
+            for tx in txs {
+                schema.add_transaction_into_pool((*tx).clone());
+            }
+            schema.block_hashes_by_height_mut().push(Hash::zero());
+        }
+        blockchain.merge(fork.into_patch()).unwrap();
+    };
+    add_transactions(blockchain, &[&tx1, &tx2]);
+    add_transactions(blockchain, &[&tx3]);
+
+    // `tx1` and `tx2` have been waiting for two blocks, `tx3` for one block only.
+    let mut evicted = blockchain.evict_pool_transactions(Some(2), None, None);
+    evicted.sort();
+    let mut expected = vec![tx1.hash(), tx2.hash()];
+    expected.sort();
+    assert_eq!(evicted, expected);
+    {
+        let snapshot = blockchain.snapshot();
+        let schema = Schema::new(&snapshot);
+        assert_eq!(schema.transactions_pool_len(), 1);
+        assert!(schema.transactions_pool().contains(&tx3.hash()));
+        assert!(!schema.transactions().contains(&tx1.hash()));
+        assert_eq!(
+            schema.transactions_evicted().get(&tx1.hash()),
+            Some(Height(2))
+        );
+        assert_eq!(
+            schema.transactions_evicted().get(&tx2.hash()),
+            Some(Height(2))
+        );
+    }
+
+    // The pool is trimmed to the maximum size.
+    add_transactions(blockchain, &[&tx1]);
+    let evicted = blockchain.evict_pool_transactions(None, Some(1), None);
+    assert_eq!(evicted, vec![tx3.hash()]);
+    {
+        let snapshot = blockchain.snapshot();
+        let schema = Schema::new(&snapshot);
+        assert_eq!(schema.transactions_pool_len(), 1);
+        assert!(schema.transactions_pool().contains(&tx1.hash()));
+        assert_eq!(schema.transactions_evicted().get(&tx1.hash()), None);
+        assert_eq!(
+            schema.transactions_evicted().get(&tx3.hash()),
+            Some(Height(3))
+        );
+    }
+
+    // Records about transactions evicted two or more blocks ago are pruned.
+    // `tx1` is evicted again, so its record from height 2 is superseded.
+    add_transactions(blockchain, &[]);
+    let evicted = blockchain.evict_pool_transactions(None, Some(0), Some(2));
+    assert_eq!(evicted, vec![tx1.hash()]);
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+    assert_eq!(schema.transactions_evicted().get(&tx2.hash()), None);
+    assert_eq!(
+        schema.transactions_evicted().get(&tx1.hash()),
+        Some(Height(4))
+    );
+    assert_eq!(
+        schema.transactions_evicted().get(&tx3.hash()),
+        Some(Height(3))
+    );
+}
+
+fn handling_tx_nonces(blockchain: &mut Blockchain) {
+    let (pk, sec_key) = gen_keypair();
+    let sign = |value, nonce| {
+        Message::sign_transaction_with_nonce(Tx::new(value), TEST_SERVICE_ID, nonce, pk, &sec_key)
+    };
+    let txs = [sign(3, 0), sign(4, 0), sign(5, 2), sign(6, 1)];
+
+    let mut fork = blockchain.fork();
+    {
+        let mut schema = Schema::new(&mut fork);
+        for tx in &txs {
+            schema.add_transaction_into_pool(tx.clone());
+        }
+    }
+    blockchain.merge(fork.into_patch()).unwrap();
+
+    let tx_hashes: Vec<_> = txs.iter().map(Signed::hash).collect();
+    let (_, patch) =
+        blockchain.create_patch(ValidatorId::zero(), Height::zero(), Utc::now(), &tx_hashes);
+    blockchain.merge(patch).unwrap();
+
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+    let error_types: Vec<_> = tx_hashes
+        .iter()
+        .map(|hash| {
+            let result = schema.transaction_results().get(hash).unwrap();
+            result.0.map_err(|e| e.error_type())
+        })
+        .collect();
+    assert_eq!(
+        error_types,
+        vec![
+            Ok(()),
+            Err(TransactionErrorType::InvalidNonce),
+            Err(TransactionErrorType::InvalidNonce),
+            Ok(()),
+        ]
+    );
+    assert_eq!(schema.account_nonce(&pk), 2);
+
+    let index = ListIndex::new(IDX_NAME, &snapshot);
+    assert_eq!(index.iter().collect::<Vec<u64>>(), vec![3, 14, 6, 7]);
+}
+
+fn handling_tx_batches(blockchain: &mut Blockchain) {
+    let (pk, sec_key) = gen_keypair();
+    let sign_batch = |values: &[u64]| {
+        let transactions = values
+            .iter()
+            .map(|&value| RawTransaction::new(TEST_SERVICE_ID, Tx::new(value).into()))
+            .collect();
+        Message::sign_transaction(Batch::new(transactions), BATCH_SERVICE_ID, pk, &sec_key)
+    };
+    let batch_ok = sign_batch(&[3, 4]);
+    let batch_failed = sign_batch(&[5, 0, 6]);
+
+    let mut fork = blockchain.fork();
+    {
+        let mut schema = Schema::new(&mut fork);
+        schema.add_transaction_into_pool(batch_ok.clone());
+        schema.add_transaction_into_pool(batch_failed.clone());
+    }
+    blockchain.merge(fork.into_patch()).unwrap();
+
+    let (_, patch) = blockchain.create_patch(
+        ValidatorId::zero(),
+        Height::zero(),
+        Utc::now(),
+        &[batch_ok.hash(), batch_failed.hash()],
+    );
+    blockchain.merge(patch).unwrap();
+
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+    let results = schema.transaction_results();
+    let error_type = |hash: &Hash| results.get(hash).unwrap().0.map_err(|e| e.error_type());
+
+    assert_eq!(error_type(&batch_ok.hash()), Ok(()));
+    assert_eq!(
+        error_type(&Batch::transaction_hash(&batch_ok.hash(), 0)),
+        Ok(())
+    );
+    assert_eq!(
+        error_type(&Batch::transaction_hash(&batch_ok.hash(), 1)),
+        Ok(())
+    );
+
+    let failed_hash = batch_failed.hash();
+    assert_eq!(error_type(&failed_hash), Err(TransactionErrorType::Panic));
+    // The result of the rolled back transaction is not recorded.
+    assert!(results
+        .get(&Batch::transaction_hash(&failed_hash, 0))
+        .is_none());
+    assert_eq!(
+        error_type(&Batch::transaction_hash(&failed_hash, 1)),
+        Err(TransactionErrorType::Panic)
+    );
+    assert!(results
+        .get(&Batch::transaction_hash(&failed_hash, 2))
+        .is_none());
+
+    // Changes of the failed batch are rolled back.
+    let index = ListIndex::new(IDX_NAME, &snapshot);
+    assert_eq!(index.iter().collect::<Vec<u64>>(), vec![3, 14, 4, 10]);
+}
+
+fn handling_multisigned_tx(blockchain: &mut Blockchain) {
+    let (pk, sec_key) = gen_keypair();
+    let keypairs = (0..3).map(|_| gen_keypair()).collect::<Vec<_>>();
+    let keys = keypairs.iter().map(|&(key, _)| key).collect();
+
+    let transaction = RawTransaction::new(TEST_SERVICE_ID, Tx::new(3).into());
+    let mut envelope = MultiSigned::new(2, keys, transaction);
+    envelope.sign(&keypairs[0].0, &keypairs[0].1).unwrap();
+    envelope.sign(&keypairs[2].0, &keypairs[2].1).unwrap();
+    let tx = Message::sign_transaction(envelope, MULTISIG_SERVICE_ID, pk, &sec_key);
+    tx.verify_envelope().unwrap();
+
+    let mut fork = blockchain.fork();
+    Schema::new(&mut fork).add_transaction_into_pool(tx.clone());
+    blockchain.merge(fork.into_patch()).unwrap();
+
+    let (_, patch) = blockchain.create_patch(
+        ValidatorId::zero(),
+        Height::zero(),
+        Utc::now(),
+        &[tx.hash()],
+    );
+    blockchain.merge(patch).unwrap();
+
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+    assert_eq!(
+        schema.transaction_results().get(&tx.hash()).unwrap().0,
+        Ok(())
+    );
+    let index = ListIndex::new(IDX_NAME, &snapshot);
+    assert_eq!(index.iter().collect::<Vec<u64>>(), vec![3, 14]);
+}
+
 mod transactions_tests {
     use super::TEST_SERVICE_ID;
     use crate::blockchain::{ExecutionResult, Transaction, TransactionContext, TransactionSet};
@@ -288,12 +943,42 @@ impl Service for ServiceGood {
         unimplemented!()
     }
 
-    fn before_commit(&self, fork: &mut Fork) {
-        let mut index = ListIndex::new(IDX_NAME, fork);
+    fn before_commit(&self, mut context: BlockContext) {
+        let mut index = ListIndex::new(IDX_NAME, context.fork());
         index.push(1);
     }
 }
 
+const GENESIS_DATA_SERVICE_NAME: &str = "genesis_data_service";
+
+/// Stores the values passed in the genesis data.
+struct ServiceGenesisData;
+
+impl Service for ServiceGenesisData {
+    fn service_id(&self) -> u16 {
+        1
+    }
+
+    fn service_name(&self) -> &'static str {
+        GENESIS_DATA_SERVICE_NAME
+    }
+
+    fn state_hash(&self, _snapshot: &dyn Snapshot) -> Vec<Hash> {
+        vec![]
+    }
+
+    fn tx_from_raw(&self, _raw: RawTransaction) -> Result<Box<dyn Transaction>, failure::Error> {
+        unimplemented!()
+    }
+
+    fn initialize_with(&self, fork: &mut Fork, params: Value) -> Value {
+        let values: Vec<u64> = serde_json::from_value(params).unwrap();
+        let mut index = ListIndex::new(IDX_NAME, fork);
+        index.extend(values.iter().cloned());
+        json!({ "values_count": values.len() })
+    }
+}
+
 struct ServicePanic;
 
 impl Service for ServicePanic {
@@ -313,7 +998,7 @@ impl Service for ServicePanic {
         unimplemented!()
     }
 
-    fn before_commit(&self, _fork: &mut Fork) {
+    fn before_commit(&self, _context: BlockContext) {
         panic!("42");
     }
 }
@@ -337,13 +1022,302 @@ impl Service for ServicePanicStorageError {
         unimplemented!()
     }
 
-    fn before_commit(&self, _fork: &mut Fork) {
+    fn before_commit(&self, _context: BlockContext) {
         panic!(Error::new("42"));
     }
 }
 
+const BLOCK_CONTEXT_IDX_NAME: &str = "block_context_idx_name";
+
+struct ServiceBlockContext;
+
+impl Service for ServiceBlockContext {
+    fn service_id(&self) -> u16 {
+        TEST_SERVICE_ID
+    }
+
+    fn service_name(&self) -> &'static str {
+        "block_context_service"
+    }
+
+    fn state_hash(&self, _snapshot: &dyn Snapshot) -> Vec<Hash> {
+        vec![]
+    }
+
+    fn tx_from_raw(&self, raw: RawTransaction) -> Result<Box<dyn Transaction>, failure::Error> {
+        Ok(TestServiceTxs::tx_from_raw(raw)?.into())
+    }
+
+    fn before_commit(&self, mut context: BlockContext) {
+        let (height, proposer_id) = (context.height(), context.proposer_id());
+        let results = context
+            .transactions()
+            .iter()
+            .map(|(_, result)| result.0.is_ok() as u64)
+            .collect::<Vec<_>>();
+        let validators_count = context.actual_configuration().validator_keys.len() as u64;
+        let timestamp = context.time().timestamp() as u64;
+
+        let mut index = ListIndex::new(BLOCK_CONTEXT_IDX_NAME, context.fork());
+        index.push(height.0);
+        index.push(u64::from(proposer_id.0));
+        index.push(validators_count);
+        index.push(timestamp);
+        index.extend(results);
+        let evidence = context
+            .evidence()
+            .iter()
+            .map(|evidence| u64::from(evidence.validator().0))
+            .collect::<Vec<_>>();
+        index.extend(evidence);
+    }
+}
+
+fn handling_block_context(blockchain: &mut Blockchain) {
+    let (consensus_key, _) = gen_keypair();
+    let genesis = GenesisConfig::new(iter::once(ValidatorKeys {
+        consensus_key,
+        service_key: blockchain.service_keypair.0,
+        voting_power: None,
+    }));
+    blockchain.initialize(genesis).unwrap();
+
+    let (pk, sec_key) = gen_keypair();
+    let tx_ok = Message::sign_transaction(Tx::new(3), TEST_SERVICE_ID, pk, &sec_key);
+    let tx_failed = Message::sign_transaction(Tx::new(0), TEST_SERVICE_ID, pk, &sec_key);
+
+    let mut fork = blockchain.fork();
+    {
+        let mut schema = Schema::new(&mut fork);
+        schema.add_transaction_into_pool(tx_ok.clone());
+        schema.add_transaction_into_pool(tx_failed.clone());
+    }
+    blockchain.merge(fork.into_patch()).unwrap();
+
+    let time = Utc.timestamp(1_000, 0);
+    let (_, patch) = blockchain.create_patch(
+        ValidatorId(2),
+        Height(1),
+        time,
+        &[tx_failed.hash(), tx_ok.hash()],
+    );
+    blockchain.merge(patch).unwrap();
+
+    let snapshot = blockchain.snapshot();
+    let index = ListIndex::new(BLOCK_CONTEXT_IDX_NAME, &snapshot);
+    assert_eq!(
+        index.iter().collect::<Vec<u64>>(),
+        vec![1, 2, 1, 1_000, 0, 1]
+    );
+    let schema = Schema::new(&snapshot);
+    assert_eq!(schema.last_block().time(), time);
+    assert_eq!(schema.block_time(), time);
+}
+
+fn handling_evidence(blockchain: &mut Blockchain) {
+    let (consensus_key, consensus_secret_key) = gen_keypair();
+    let genesis = GenesisConfig::new(iter::once(ValidatorKeys {
+        consensus_key,
+        service_key: blockchain.service_keypair.0,
+        voting_power: None,
+    }));
+    blockchain.initialize(genesis).unwrap();
+
+    let prevote = |propose_hash, (public_key, secret_key): (PublicKey, &SecretKey)| {
+        Message::concrete(
+            Prevote::new(ValidatorId(0), Height(1), Round(1), &propose_hash, Round(0)),
+            public_key,
+            secret_key,
+        )
+    };
+    let validator = (consensus_key, &consensus_secret_key);
+    let evidence = Evidence::prevotes(
+        prevote(hash(&[1]), validator),
+        prevote(hash(&[2]), validator),
+    )
+    .unwrap();
+    // Another pair of conflicting prevotes of the validator at the same round.
+    let same_round_evidence = Evidence::prevotes(
+        prevote(hash(&[1]), validator),
+        prevote(hash(&[3]), validator),
+    )
+    .unwrap();
+    assert_eq!(same_round_evidence.key(), evidence.key());
+    let (other_key, other_secret_key) = gen_keypair();
+    let other = (other_key, &other_secret_key);
+    let other_evidence =
+        Evidence::prevotes(prevote(hash(&[1]), other), prevote(hash(&[2]), other)).unwrap();
+
+    let (pk, sec_key) = gen_keypair();
+    let (another_pk, another_sec_key) = gen_keypair();
+    let txs = [
+        Message::sign_transaction(evidence.clone(), EVIDENCE_SERVICE_ID, pk, &sec_key),
+        Message::sign_transaction(
+            evidence.clone(),
+            EVIDENCE_SERVICE_ID,
+            another_pk,
+            &another_sec_key,
+        ),
+        Message::sign_transaction(other_evidence, EVIDENCE_SERVICE_ID, pk, &sec_key),
+        Message::sign_transaction(same_round_evidence, EVIDENCE_SERVICE_ID, pk, &sec_key),
+    ];
+    let mut fork = blockchain.fork();
+    {
+        let mut schema = Schema::new(&mut fork);
+        for tx in &txs {
+            schema.add_transaction_into_pool(tx.clone());
+        }
+    }
+    blockchain.merge(fork.into_patch()).unwrap();
+
+    let tx_hashes: Vec<_> = txs.iter().map(Signed::hash).collect();
+    let time = Utc.timestamp(1_000, 0);
+    let (_, patch) = blockchain.create_patch(ValidatorId::zero(), Height(1), time, &tx_hashes);
+    blockchain.merge(patch).unwrap();
+
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+    let error_codes: Vec<_> = tx_hashes
+        .iter()
+        .map(|hash| {
+            let result = schema.transaction_results().get(hash).unwrap();
+            result.0.map_err(|e| e.error_type())
+        })
+        .collect();
+    assert_eq!(
+        error_codes,
+        vec![
+            Ok(()),
+            Err(TransactionErrorType::Code(2)),
+            Err(TransactionErrorType::Code(1)),
+            Err(TransactionErrorType::Code(2)),
+        ]
+    );
+    assert_eq!(
+        schema.evidence().get(&evidence.key()),
+        Some(evidence.clone())
+    );
+    assert_eq!(
+        schema.block_evidence(Height(1)).iter().collect::<Vec<_>>(),
+        vec![evidence.key()]
+    );
+
+    // The evidence is passed to the services.
+    let index = ListIndex::new(BLOCK_CONTEXT_IDX_NAME, &snapshot);
+    assert_eq!(
+        index.iter().collect::<Vec<u64>>(),
+        vec![1, 0, 1, 1_000, 1, 0, 0, 0, 0]
+    );
+}
+
+fn handling_key_rotation(blockchain: &mut Blockchain) {
+    let (consensus_key, consensus_secret_key) = gen_keypair();
+    let (service_key, service_secret_key) = blockchain.service_keypair.clone();
+    let genesis = GenesisConfig::new(iter::once(ValidatorKeys {
+        consensus_key,
+        service_key,
+        voting_power: None,
+    }));
+    blockchain.initialize(genesis).unwrap();
+
+    let (new_key, new_secret_key) = gen_keypair();
+    let rotation = |height| {
+        KeyRotation::new(
+            ValidatorKeyKind::Consensus,
+            consensus_key,
+            new_key,
+            &new_secret_key,
+            Height(height),
+        )
+    };
+    let (other_key, other_secret_key) = gen_keypair();
+    let txs = [
+        // Not signed by the old key.
+        Message::sign_transaction(
+            rotation(5),
+            KEY_ROTATION_SERVICE_ID,
+            other_key,
+            &other_secret_key,
+        ),
+        // The height is not in the future.
+        Message::sign_transaction(
+            rotation(1),
+            KEY_ROTATION_SERVICE_ID,
+            consensus_key,
+            &consensus_secret_key,
+        ),
+        // The new key is already used.
+        Message::sign_transaction(
+            KeyRotation::new(
+                ValidatorKeyKind::Service,
+                service_key,
+                consensus_key,
+                &consensus_secret_key,
+                Height(5),
+            ),
+            KEY_ROTATION_SERVICE_ID,
+            service_key,
+            &service_secret_key,
+        ),
+        Message::sign_transaction(
+            rotation(5),
+            KEY_ROTATION_SERVICE_ID,
+            consensus_key,
+            &consensus_secret_key,
+        ),
+        // Another configuration is already scheduled.
+        Message::sign_transaction(
+            rotation(6),
+            KEY_ROTATION_SERVICE_ID,
+            consensus_key,
+            &consensus_secret_key,
+        ),
+    ];
+    let mut fork = blockchain.fork();
+    {
+        let mut schema = Schema::new(&mut fork);
+        for tx in &txs {
+            schema.add_transaction_into_pool(tx.clone());
+        }
+    }
+    blockchain.merge(fork.into_patch()).unwrap();
+
+    let tx_hashes: Vec<_> = txs.iter().map(Signed::hash).collect();
+    let time = Utc.timestamp(1_000, 0);
+    let (_, patch) = blockchain.create_patch(ValidatorId::zero(), Height(1), time, &tx_hashes);
+    blockchain.merge(patch).unwrap();
+
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+    let error_codes: Vec<_> = tx_hashes
+        .iter()
+        .map(|hash| {
+            let result = schema.transaction_results().get(hash).unwrap();
+            result.0.map_err(|e| e.error_type())
+        })
+        .collect();
+    assert_eq!(
+        error_codes,
+        vec![
+            Err(TransactionErrorType::Code(0)),
+            Err(TransactionErrorType::Code(1)),
+            Err(TransactionErrorType::Code(2)),
+            Ok(()),
+            Err(TransactionErrorType::Code(3)),
+        ]
+    );
+
+    let actual = schema.actual_configuration();
+    let following = schema.following_configuration().unwrap();
+    assert_eq!(following.actual_from, Height(5));
+    assert_eq!(following.previous_cfg_hash, actual.hash());
+    assert_eq!(following.validator_keys[0].consensus_key, new_key);
+    assert_eq!(following.validator_keys[0].service_key, service_key);
+    assert_eq!(following.consensus, actual.consensus);
+}
+
 fn assert_service_execute(blockchain: &Blockchain, db: &mut Box<dyn Database>) {
-    let (_, patch) = blockchain.create_patch(ValidatorId::zero(), Height(1), &[]);
+    let (_, patch) = blockchain.create_patch(ValidatorId::zero(), Height(1), Utc::now(), &[]);
     db.merge(patch).unwrap();
     let snapshot = db.snapshot();
     let index = ListIndex::new(IDX_NAME, &snapshot);
@@ -351,8 +1325,50 @@ fn assert_service_execute(blockchain: &Blockchain, db: &mut Box<dyn Database>) {
     assert_eq!(index.get(0), Some(1));
 }
 
+fn handling_genesis_data(blockchain: &mut Blockchain) {
+    let (consensus_key, _) = gen_keypair();
+    let genesis = GenesisConfig::new(iter::once(ValidatorKeys {
+        consensus_key,
+        service_key: blockchain.service_keypair.0,
+        voting_power: None,
+    }))
+    .with_service_data(GENESIS_DATA_SERVICE_NAME, json!([1, 2, 3]));
+    blockchain.initialize(genesis).unwrap();
+
+    let snapshot = blockchain.snapshot();
+    let index = ListIndex::new(IDX_NAME, &snapshot);
+    assert_eq!(index.iter().collect::<Vec<u64>>(), vec![1, 2, 3]);
+    let config = Schema::new(&snapshot).actual_configuration();
+    assert_eq!(
+        config.services[GENESIS_DATA_SERVICE_NAME],
+        json!({ "values_count": 3 })
+    );
+}
+
+fn handling_genesis_data_of_unknown_service(blockchain: &mut Blockchain) {
+    let (consensus_key, _) = gen_keypair();
+    let genesis = GenesisConfig::new(iter::once(ValidatorKeys {
+        consensus_key,
+        service_key: blockchain.service_keypair.0,
+        voting_power: None,
+    }))
+    .with_service_data("unknown_service", json!([1, 2, 3]));
+    blockchain.initialize(genesis).unwrap();
+}
+
+fn handling_genesis_data_of_service_without_support(blockchain: &mut Blockchain) {
+    let (consensus_key, _) = gen_keypair();
+    let genesis = GenesisConfig::new(iter::once(ValidatorKeys {
+        consensus_key,
+        service_key: blockchain.service_keypair.0,
+        voting_power: None,
+    }))
+    .with_service_data("some_service", json!([1, 2, 3]));
+    blockchain.initialize(genesis).unwrap();
+}
+
 fn assert_service_execute_panic(blockchain: &Blockchain, db: &mut Box<dyn Database>) {
-    let (_, patch) = blockchain.create_patch(ValidatorId::zero(), Height(1), &[]);
+    let (_, patch) = blockchain.create_patch(ValidatorId::zero(), Height(1), Utc::now(), &[]);
     db.merge(patch).unwrap();
     let snapshot = db.snapshot();
     let index: ListIndex<_, u32> = ListIndex::new(IDX_NAME, &snapshot);
@@ -367,7 +1383,7 @@ mod memorydb_tests {
     use crate::node::ApiSender;
     use crate::storage::{Database, MemoryDB};
 
-    use super::{ServiceGood, ServicePanic, ServicePanicStorageError};
+    use super::{ServiceBlockContext, ServiceGood, ServicePanic, ServicePanicStorageError};
 
     fn create_database() -> Box<dyn Database> {
         Box::new(MemoryDB::new())
@@ -403,6 +1419,49 @@ mod memorydb_tests {
         super::handling_tx_panic(&mut blockchain);
     }
 
+    #[test]
+    fn handling_tx_simulation() {
+        let mut blockchain = create_blockchain();
+        super::handling_tx_simulation(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_tx_events() {
+        let mut blockchain = create_blockchain();
+        super::handling_tx_events(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_tx_execution_limits() {
+        let mut blockchain = create_blockchain();
+        super::handling_tx_execution_limits(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_parallel_execution() {
+        let mut sequential = create_blockchain();
+        let mut parallel = create_blockchain();
+        super::handling_parallel_execution(&mut sequential, &mut parallel);
+    }
+
+    #[test]
+    fn handling_execution_cache() {
+        let mut blockchain = create_blockchain();
+        super::handling_execution_cache(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_block_time() {
+        let mut blockchain = create_blockchain();
+        super::handling_block_time(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_rollback() {
+        let mut blockchain = create_blockchain();
+        super::handling_rollback(&mut blockchain);
+    }
+
     #[test]
     #[should_panic]
     fn handling_tx_panic_storage_error() {
@@ -410,6 +1469,48 @@ mod memorydb_tests {
         super::handling_tx_panic_storage_error(&mut blockchain);
     }
 
+    #[test]
+    fn handling_tx_nonces() {
+        let mut blockchain = create_blockchain();
+        super::handling_tx_nonces(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_block_context() {
+        let mut blockchain = create_blockchain_with_service(Box::new(ServiceBlockContext));
+        super::handling_block_context(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_evidence() {
+        let mut blockchain = create_blockchain_with_service(Box::new(ServiceBlockContext));
+        super::handling_evidence(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_key_rotation() {
+        let mut blockchain = create_blockchain();
+        super::handling_key_rotation(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_multisigned_tx() {
+        let mut blockchain = create_blockchain();
+        super::handling_multisigned_tx(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_tx_batches() {
+        let mut blockchain = create_blockchain();
+        super::handling_tx_batches(&mut blockchain);
+    }
+
+    #[test]
+    fn evicting_pool_transactions() {
+        let mut blockchain = create_blockchain();
+        super::evicting_pool_transactions(&mut blockchain);
+    }
+
     #[test]
     fn service_execute() {
         let blockchain = create_blockchain_with_service(Box::new(ServiceGood));
@@ -417,6 +1518,26 @@ mod memorydb_tests {
         super::assert_service_execute(&blockchain, &mut db);
     }
 
+    #[test]
+    fn handling_genesis_data() {
+        let mut blockchain = create_blockchain_with_service(Box::new(ServiceGenesisData));
+        super::handling_genesis_data(&mut blockchain);
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown service")]
+    fn handling_genesis_data_of_unknown_service() {
+        let mut blockchain = create_blockchain_with_service(Box::new(ServiceGenesisData));
+        super::handling_genesis_data_of_unknown_service(&mut blockchain);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not accept genesis data")]
+    fn handling_genesis_data_of_service_without_support() {
+        let mut blockchain = create_blockchain_with_service(Box::new(ServiceGood));
+        super::handling_genesis_data_of_service_without_support(&mut blockchain);
+    }
+
     #[test]
     fn service_execute_panic() {
         let blockchain = create_blockchain_with_service(Box::new(ServicePanic));
@@ -444,7 +1565,7 @@ mod rocksdb_tests {
     use crate::node::ApiSender;
     use crate::storage::{Database, DbOptions, RocksDB};
 
-    use super::{ServiceGood, ServicePanic, ServicePanicStorageError};
+    use super::{ServiceBlockContext, ServiceGood, ServicePanic, ServicePanicStorageError};
 
     fn create_database(path: &Path) -> Box<dyn Database> {
         let opts = DbOptions::default();
@@ -488,6 +1609,56 @@ mod rocksdb_tests {
         super::handling_tx_panic(&mut blockchain);
     }
 
+    #[test]
+    fn handling_tx_simulation() {
+        let dir = create_temp_dir();
+        let mut blockchain = create_blockchain(dir.path());
+        super::handling_tx_simulation(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_tx_events() {
+        let dir = create_temp_dir();
+        let mut blockchain = create_blockchain(dir.path());
+        super::handling_tx_events(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_tx_execution_limits() {
+        let dir = create_temp_dir();
+        let mut blockchain = create_blockchain(dir.path());
+        super::handling_tx_execution_limits(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_parallel_execution() {
+        let (dir, parallel_dir) = (create_temp_dir(), create_temp_dir());
+        let mut sequential = create_blockchain(dir.path());
+        let mut parallel = create_blockchain(parallel_dir.path());
+        super::handling_parallel_execution(&mut sequential, &mut parallel);
+    }
+
+    #[test]
+    fn handling_execution_cache() {
+        let dir = create_temp_dir();
+        let mut blockchain = create_blockchain(dir.path());
+        super::handling_execution_cache(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_block_time() {
+        let dir = create_temp_dir();
+        let mut blockchain = create_blockchain(dir.path());
+        super::handling_block_time(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_rollback() {
+        let dir = create_temp_dir();
+        let mut blockchain = create_blockchain(dir.path());
+        super::handling_rollback(&mut blockchain);
+    }
+
     #[test]
     #[should_panic]
     fn handling_tx_panic_storage_error() {
@@ -496,6 +1667,57 @@ mod rocksdb_tests {
         super::handling_tx_panic_storage_error(&mut blockchain);
     }
 
+    #[test]
+    fn handling_tx_nonces() {
+        let dir = create_temp_dir();
+        let mut blockchain = create_blockchain(dir.path());
+        super::handling_tx_nonces(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_block_context() {
+        let dir = create_temp_dir();
+        let mut blockchain =
+            create_blockchain_with_service(dir.path(), Box::new(ServiceBlockContext));
+        super::handling_block_context(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_evidence() {
+        let dir = create_temp_dir();
+        let mut blockchain =
+            create_blockchain_with_service(dir.path(), Box::new(ServiceBlockContext));
+        super::handling_evidence(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_key_rotation() {
+        let dir = create_temp_dir();
+        let mut blockchain = create_blockchain(dir.path());
+        super::handling_key_rotation(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_multisigned_tx() {
+        let dir = create_temp_dir();
+        let mut blockchain = create_blockchain(dir.path());
+        super::handling_multisigned_tx(&mut blockchain);
+    }
+
+    #[test]
+    fn handling_tx_batches() {
+        let dir = create_temp_dir();
+        let mut blockchain = create_blockchain(dir.path());
+        super::handling_tx_batches(&mut blockchain);
+    }
+
+    #[test]
+    fn evicting_pool_transactions() {
+        let dir = create_temp_dir();
+        let mut blockchain = create_blockchain(dir.path());
+        super::evicting_pool_transactions(&mut blockchain);
+    }
+
     #[test]
     fn service_execute() {
         let dir = create_temp_dir();
@@ -505,6 +1727,31 @@ mod rocksdb_tests {
         super::assert_service_execute(&blockchain, &mut db);
     }
 
+    #[test]
+    fn handling_genesis_data() {
+        let dir = create_temp_dir();
+        let mut blockchain =
+            create_blockchain_with_service(dir.path(), Box::new(ServiceGenesisData));
+        super::handling_genesis_data(&mut blockchain);
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown service")]
+    fn handling_genesis_data_of_unknown_service() {
+        let dir = create_temp_dir();
+        let mut blockchain =
+            create_blockchain_with_service(dir.path(), Box::new(ServiceGenesisData));
+        super::handling_genesis_data_of_unknown_service(&mut blockchain);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not accept genesis data")]
+    fn handling_genesis_data_of_service_without_support() {
+        let dir = create_temp_dir();
+        let mut blockchain = create_blockchain_with_service(dir.path(), Box::new(ServiceGood));
+        super::handling_genesis_data_of_service_without_support(&mut blockchain);
+    }
+
     #[test]
     fn service_execute_panic() {
         let dir = create_temp_dir();
@@ -14,28 +14,38 @@
 
 //! `Transaction` related types.
 
+use chrono::{DateTime, Utc};
 use hex::ToHex;
 use protobuf::Message;
 use serde::{de::DeserializeOwned, Serialize};
 
-use std::{any::Any, borrow::Cow, convert::Into, error::Error, fmt, u8};
+use std::{any::Any, borrow::Cow, convert::Into, error::Error, fmt, u16};
 
-use crate::crypto::{CryptoHash, Hash, PublicKey};
-use crate::messages::{HexStringRepresentation, RawTransaction, Signed, SignedMessage};
+use super::{AccessSet, Blockchain, Event, Schema, ServiceEvent, ValidatorKeys};
+use crate::crypto::{self, CryptoHash, Hash, PublicKey};
+use crate::helpers::ValidatorId;
+use crate::messages::{
+    BinaryForm, Evidence, HexStringRepresentation, KeyRotation, MaintenanceNotice, RawTransaction,
+    Signed, SignedMessage, ValidatorKeyKind,
+};
 use crate::proto::{self, ProtobufConvert};
 use crate::storage::{Fork, StorageValue};
 
-//  User-defined error codes (`TransactionErrorType::Code(u8)`) have a `0...255` range.
-#[cfg_attr(feature = "cargo-clippy", allow(clippy::cast_lossless))]
-const MAX_ERROR_CODE: u16 = u8::max_value() as u16;
+//  User-defined error codes (`TransactionErrorType::Code(u16)`) have a `0...65535` range.
+const MAX_ERROR_CODE: u32 = u16::max_value() as u32;
 // Represent `(Ok())` `TransactionResult` value.
-const TRANSACTION_STATUS_OK: u16 = MAX_ERROR_CODE + 1;
+const TRANSACTION_STATUS_OK: u32 = MAX_ERROR_CODE + 1;
 // `Err(TransactionErrorType::Panic)`.
-const TRANSACTION_STATUS_PANIC: u16 = TRANSACTION_STATUS_OK + 1;
+const TRANSACTION_STATUS_PANIC: u32 = TRANSACTION_STATUS_OK + 1;
+// `Err(TransactionErrorType::InvalidNonce)`.
+const TRANSACTION_STATUS_INVALID_NONCE: u32 = TRANSACTION_STATUS_PANIC + 1;
+// `Err(TransactionErrorType::LimitExceeded)`.
+const TRANSACTION_STATUS_LIMIT_EXCEEDED: u32 = TRANSACTION_STATUS_INVALID_NONCE + 1;
 
 /// Returns a result of the `Transaction` `execute` method. This result may be
 /// either an empty unit type, in case of success, or an `ExecutionError`, if execution has
-/// failed. Errors consist of an error code and an optional description.
+/// failed. Errors consist of an error code, an optional description and an optional
+/// structured payload.
 pub type ExecutionResult = Result<(), ExecutionError>;
 /// Extended version of `ExecutionResult` (with additional values set exclusively by Exonum
 /// framework) that can be obtained through `Schema::transaction_results` method.
@@ -171,6 +181,35 @@ pub trait Transaction: ::std::fmt::Debug + Send + 'static + ::erased_serde::Seri
     /// }
     /// # fn main() {}
     fn execute<'a>(&self, context: TransactionContext<'a>) -> ExecutionResult;
+
+    /// Returns the storage access of the transaction if it is known before the execution.
+    ///
+    /// If the node executes transactions in several threads (see
+    /// `Blockchain::set_execution_threads`), consecutive transactions of a block with
+    /// non-conflicting access sets are executed concurrently. Transactions without
+    /// an access set are executed sequentially, which is the default.
+    ///
+    /// The access set only affects the scheduling of the execution. A transaction which
+    /// has actually read keys written by the preceding concurrently executed transactions
+    /// is re-executed, so the results are always the same as for the sequential execution.
+    /// Note that events emitted by the transactions are stored in a common index,
+    /// so transactions emitting events are effectively executed sequentially.
+    ///
+    /// The public key of the transaction author is passed as it is returned
+    /// by `TransactionContext::author` during the execution.
+    fn access_set(&self, _author: &PublicKey) -> Option<AccessSet> {
+        None
+    }
+
+    /// Returns the estimated cost of the transaction execution in the abstract units of gas.
+    ///
+    /// The cost is used by the leader to fit transactions into the optional
+    /// `txs_block_cost_limit` of the consensus configuration, and by the validators to reject
+    /// proposes exceeding it. The cost must be deterministic, i.e., the same on all nodes.
+    /// By default, each transaction costs one unit.
+    fn execution_cost(&self) -> u64 {
+        1
+    }
 }
 
 //TODO: Add doc/examples.
@@ -181,6 +220,7 @@ pub struct TransactionContext<'a> {
     service_id: u16,
     tx_hash: Hash,
     author: PublicKey,
+    signers: Vec<PublicKey>,
 }
 
 impl<'a> TransactionContext<'a> {
@@ -191,6 +231,33 @@ impl<'a> TransactionContext<'a> {
             service_id: raw_message.service_id(),
             tx_hash: raw_message.hash(),
             author: raw_message.author(),
+            signers: vec![raw_message.author()],
+        }
+    }
+
+    /// Creates a context for a transaction which is a part of a batch.
+    pub(crate) fn for_batch_item(
+        fork: &'a mut Fork,
+        service_id: u16,
+        tx_hash: Hash,
+        author: PublicKey,
+    ) -> Self {
+        TransactionContext {
+            fork,
+            service_id,
+            tx_hash,
+            author,
+            signers: vec![author],
+        }
+    }
+
+    /// Converts the context of a multisignature envelope into the context
+    /// of the wrapped transaction.
+    pub(crate) fn for_multisigned(self, service_id: u16, signers: Vec<PublicKey>) -> Self {
+        TransactionContext {
+            service_id,
+            signers,
+            ..self
         }
     }
     /// Returns fork of current blockchain state.
@@ -205,46 +272,360 @@ impl<'a> TransactionContext<'a> {
     pub fn author(&self) -> PublicKey {
         self.author
     }
+    /// Returns public keys of the transaction signers.
+    ///
+    /// For a usual transaction the only signer is its author. For a transaction
+    /// wrapped into a [`MultiSigned`] envelope signers are the policy keys whose
+    /// signatures are present in the envelope; the author is the key which has
+    /// submitted the envelope to the network.
+    ///
+    /// [`MultiSigned`]: ../messages/struct.MultiSigned.html
+    pub fn signers(&self) -> &[PublicKey] {
+        &self.signers
+    }
     /// Returns current transaction message hash.
     /// This hash could be used to link some data in storage for external usage.
     pub fn tx_hash(&self) -> Hash {
         self.tx_hash
     }
+    /// Returns the time of the block containing the transaction. The time is set
+    /// by the proposer of the block, so it is the same on every node.
+    ///
+    /// The time is read from the storage, so the read counts towards
+    /// the execution limits of the transaction.
+    pub fn time(&self) -> DateTime<Utc> {
+        Schema::new(&*self.fork).block_time()
+    }
+
+    /// Emits the event which is appended to the event log of the current block.
+    ///
+    /// Events are a part of the changes made by the transaction, so they are
+    /// discarded if the transaction execution fails.
+    ///
+    /// # Panics
+    ///
+    /// This method can panic on serialization failure.
+    pub fn emit<E: ServiceEvent>(&mut self, event: &E) {
+        let payload = event.encode().expect("Couldn't serialize event.");
+        let event = Event::new(self.service_id, E::EVENT_TYPE, self.tx_hash, payload);
+        let mut schema = Schema::new(&mut *self.fork);
+        let height = schema.next_height();
+        schema.block_events_mut(height).push(event);
+    }
+}
+
+/// Transactions of a [`Batch`] parsed by the corresponding services.
+///
+/// The batch is executed by the blockchain in a special way, see [`Batch`] for details.
+///
+/// [`Batch`]: ../messages/struct.Batch.html
+#[derive(Debug, Serialize)]
+pub(crate) struct BatchTransaction {
+    /// Service identifiers and transactions of the batch.
+    pub(crate) transactions: Vec<(u16, Box<dyn Transaction>)>,
+}
+
+impl Transaction for BatchTransaction {
+    /// Executes the batch in the same way as the blockchain does. An error of the failed
+    /// transaction of the batch is returned as is, and its panic is raised again.
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let (tx_hash, author) = (context.tx_hash(), context.author());
+        let (result, _) = Blockchain::execute_batch(self, author, tx_hash, context.fork());
+        match result.0 {
+            Ok(()) => Ok(()),
+            Err(error) => match error.error_type() {
+                TransactionErrorType::Code(code) => Err(ExecutionError {
+                    code,
+                    description: error.description,
+                    payload: error.payload,
+                }),
+                _ => panic!("{}", error),
+            },
+        }
+    }
+
+    fn execution_cost(&self) -> u64 {
+        self.transactions.iter().fold(0, |cost, (_, transaction)| {
+            cost.saturating_add(transaction.execution_cost())
+        })
+    }
+}
+
+/// Transaction wrapped into a [`MultiSigned`] envelope and parsed by the corresponding service.
+///
+/// [`MultiSigned`]: ../messages/struct.MultiSigned.html
+#[derive(Debug, Serialize)]
+pub(crate) struct MultiSignedTransaction {
+    /// Identifier of the service owning the wrapped transaction.
+    pub(crate) service_id: u16,
+    /// Policy keys which have signed the envelope.
+    pub(crate) signers: Vec<PublicKey>,
+    /// Wrapped transaction.
+    pub(crate) transaction: Box<dyn Transaction>,
+}
+
+impl Transaction for MultiSignedTransaction {
+    fn execute(&self, context: TransactionContext) -> ExecutionResult {
+        let context = context.for_multisigned(self.service_id, self.signers.clone());
+        self.transaction.execute(context)
+    }
+
+    fn access_set(&self, author: &PublicKey) -> Option<AccessSet> {
+        self.transaction.access_set(author)
+    }
+
+    fn execution_cost(&self) -> u64 {
+        self.transaction.execution_cost()
+    }
+}
+
+// Error codes of the evidence transactions.
+const EVIDENCE_FROM_FUTURE: u16 = 0;
+const EVIDENCE_NOT_FROM_VALIDATOR: u16 = 1;
+const EVIDENCE_ALREADY_RECORDED: u16 = 2;
+
+/// Records the evidence of equivocation in the core schema. The messages of the evidence
+/// should be signed by the validator of the configuration actual at their height, and
+/// no evidence with the same key should be recorded before.
+impl Transaction for Evidence {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let evidence_key = self.key();
+        let mut schema = Schema::new(context.fork());
+        let height = schema.height().next();
+        if self.height() > height {
+            return Err(ExecutionError::with_description(
+                EVIDENCE_FROM_FUTURE,
+                format!("Evidence from the future height {}", self.height()),
+            ));
+        }
+
+        let config = schema.configuration_by_height(self.height());
+        let validator_key = config
+            .validator_keys
+            .get(self.validator().0 as usize)
+            .map(|keys| keys.consensus_key);
+        if validator_key != Some(self.author()) {
+            return Err(ExecutionError::with_description(
+                EVIDENCE_NOT_FROM_VALIDATOR,
+                format!(
+                    "Messages are not signed by the validator {}",
+                    self.validator()
+                ),
+            ));
+        }
+
+        if schema.evidence().contains(&evidence_key) {
+            return Err(ExecutionError::with_description(
+                EVIDENCE_ALREADY_RECORDED,
+                "Evidence is already recorded",
+            ));
+        }
+        schema.evidence_mut().put(&evidence_key, self.clone());
+        schema.block_evidence_mut(height).push(evidence_key);
+        Ok(())
+    }
+}
+
+// Error codes of the key rotation transactions.
+const KEY_ROTATION_NOT_FROM_VALIDATOR: u16 = 0;
+const KEY_ROTATION_INVALID_HEIGHT: u16 = 1;
+const KEY_ROTATION_KEY_IN_USE: u16 = 2;
+const KEY_ROTATION_CONFIG_SCHEDULED: u16 = 3;
+
+/// Schedules the configuration with the rotated key of the validator. The transaction
+/// should be signed by the old key, which should be used by a validator
+/// in the actual configuration.
+impl Transaction for KeyRotation {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let author = context.author();
+        let mut schema = Schema::new(context.fork());
+        let mut config = schema.actual_configuration();
+
+        let key = |keys: &ValidatorKeys| match self.kind() {
+            ValidatorKeyKind::Consensus => keys.consensus_key,
+            ValidatorKeyKind::Service => keys.service_key,
+        };
+        let index = config
+            .validator_keys
+            .iter()
+            .position(|keys| key(keys) == *self.old_key())
+            .filter(|_| author == *self.old_key())
+            .ok_or_else(|| {
+                ExecutionError::with_description(
+                    KEY_ROTATION_NOT_FROM_VALIDATOR,
+                    "Key rotation is not signed by the old key of a validator",
+                )
+            })?;
+
+        let height = schema.height().next();
+        if self.height() <= height {
+            return Err(ExecutionError::with_description(
+                KEY_ROTATION_INVALID_HEIGHT,
+                format!(
+                    "Key rotation height {} should be greater than the current height {}",
+                    self.height(),
+                    height
+                ),
+            ));
+        }
+
+        let new_key = *self.new_key();
+        if config
+            .validator_keys
+            .iter()
+            .any(|keys| keys.consensus_key == new_key || keys.service_key == new_key)
+        {
+            return Err(ExecutionError::with_description(
+                KEY_ROTATION_KEY_IN_USE,
+                "New key is already used by a validator",
+            ));
+        }
+
+        if schema.following_configuration().is_some() {
+            return Err(ExecutionError::with_description(
+                KEY_ROTATION_CONFIG_SCHEDULED,
+                "Another configuration is already scheduled",
+            ));
+        }
+
+        config.previous_cfg_hash = config.hash();
+        config.actual_from = self.height();
+        let keys = &mut config.validator_keys[index];
+        match self.kind() {
+            ValidatorKeyKind::Consensus => keys.consensus_key = new_key,
+            ValidatorKeyKind::Service => keys.service_key = new_key,
+        }
+        schema.commit_configuration(config);
+        Ok(())
+    }
+}
+
+// Error codes of the maintenance notices.
+const MAINTENANCE_NOT_FROM_VALIDATOR: u16 = 0;
+const MAINTENANCE_NO_MAJORITY: u16 = 1;
+
+/// Records the maintenance of the validator in the core schema. The notice should be
+/// signed by the service key of a validator in the actual configuration.
+impl Transaction for MaintenanceNotice {
+    fn execute(&self, mut context: TransactionContext) -> ExecutionResult {
+        let author = context.author();
+        let mut schema = Schema::new(context.fork());
+        let config = schema.actual_configuration();
+        let id = config
+            .validator_keys
+            .iter()
+            .position(|keys| keys.service_key == author)
+            .ok_or_else(|| {
+                ExecutionError::with_description(
+                    MAINTENANCE_NOT_FROM_VALIDATOR,
+                    "Maintenance notice is not signed by a validator",
+                )
+            })?;
+
+        if self.until() <= schema.height().next() {
+            schema.validators_maintenance_mut().remove(&author);
+            return Ok(());
+        }
+
+        let in_maintenance = schema.validators_in_maintenance();
+        let active_voting_power: u64 = config
+            .validator_keys
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != id && !in_maintenance.contains_key(&ValidatorId(i as u16)))
+            .map(|(_, keys)| keys.voting_power())
+            .sum();
+        if active_voting_power < config.majority_voting_power() {
+            return Err(ExecutionError::with_description(
+                MAINTENANCE_NO_MAJORITY,
+                "Validators remaining in the consensus would not have the majority",
+            ));
+        }
+
+        schema
+            .validators_maintenance_mut()
+            .put(&author, self.until());
+        Ok(())
+    }
 }
 
 /// Result of unsuccessful transaction execution.
 ///
 /// An execution error consists
-/// of an error code and optional description. The error code affects the blockchain
-/// state hash, while the description does not. Therefore,
+/// of an error code, optional description and optional payload. The error code and
+/// the payload affect the blockchain state hash, while the description does not. Therefore,
 /// descriptions are mostly used for developer purposes, not for interaction of
-/// the system with users.
+/// the system with users. Clients should rely on the error code, its name from
+/// the [`Service::error_name`] registry and the structured payload instead.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate exonum;
+/// # #[macro_use] extern crate exonum_derive;
+/// # #[macro_use] extern crate serde_derive;
+/// use exonum::blockchain::ExecutionError;
+/// use exonum::crypto::PublicKey;
+///
+/// // Details of the error which are available to clients.
+/// #[derive(Debug, Clone, Serialize, Deserialize, ProtobufConvert)]
+/// #[exonum(pb = "exonum::proto::schema::doc_tests::MyTransaction")]
+/// struct WalletNotFound {
+///     public_key: PublicKey,
+/// }
 ///
+/// # fn main() {
+/// # let public_key = PublicKey::zero();
+/// let error = ExecutionError::with_description(1, "Wallet not found")
+///     .with_payload(&WalletNotFound { public_key });
+/// # drop(error);
+/// # }
+/// ```
+///
+/// [`Service::error_name`]: trait.Service.html#method.error_name
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ExecutionError {
     /// User-defined error code. Error codes can have different meanings for different
     /// transactions and services.
-    code: u8,
+    code: u16,
     /// Optional error description.
     description: Option<String>,
+    /// Serialized structured error data. Empty if there is no payload.
+    payload: Vec<u8>,
 }
 
 impl ExecutionError {
     /// Constructs a new `ExecutionError` instance with the given error code.
-    pub fn new(code: u8) -> Self {
+    pub fn new(code: u16) -> Self {
         Self {
             code,
             description: None,
+            payload: Vec::new(),
         }
     }
 
     /// Constructs a new `ExecutionError` instance with the given error code and description.
-    pub fn with_description<T: Into<String>>(code: u8, description: T) -> Self {
+    pub fn with_description<T: Into<String>>(code: u16, description: T) -> Self {
         Self {
             code,
             description: Some(description.into()),
+            payload: Vec::new(),
         }
     }
+
+    /// Attaches structured data to the error. The payload is stored in the
+    /// transaction result in the serialized form and is available to clients via
+    /// [`TransactionError::decode_payload`].
+    ///
+    /// # Panics
+    ///
+    /// This method can panic on serialization failure.
+    ///
+    /// [`TransactionError::decode_payload`]: struct.TransactionError.html#method.decode_payload
+    pub fn with_payload<P: BinaryForm>(mut self, payload: &P) -> Self {
+        self.payload = payload.encode().expect("Couldn't serialize error payload.");
+        self
+    }
 }
 
 /// Type of transaction error.
@@ -252,9 +633,15 @@ impl ExecutionError {
 pub enum TransactionErrorType {
     /// Panic occurred during transaction execution.
     Panic,
+    /// Transaction nonce does not match the next nonce of its author, so the
+    /// transaction has not been executed.
+    InvalidNonce,
+    /// Transaction has exceeded the storage access limits set in the consensus
+    /// configuration, so its execution has been aborted.
+    LimitExceeded,
     /// User-defined error code. Can have different meanings for different transactions and
     /// services.
-    Code(u8),
+    Code(u16),
 }
 
 /// Result of unsuccessful transaction execution encompassing both service and framework-wide error
@@ -265,8 +652,13 @@ pub enum TransactionErrorType {
 ///
 /// - Content of the `description` field is excluded from the hash calculation (see `StorageValue`
 ///   implementation for the details).
+/// - `service_id` is set by the framework to the identifier of the service whose transaction
+///   has returned an error or panicked. For batches, this is the service of the failed
+///   transaction of the batch.
 /// - `TransactionErrorType::Panic` is set by the framework if panic is raised during transaction
 ///   execution.
+/// - `TransactionErrorType::LimitExceeded` is set by the framework if the transaction exceeds
+///   the storage access limits.
 /// - `TransactionError` implements `Display` which can be used for obtaining a simple error
 ///   description.
 ///
@@ -303,6 +695,13 @@ pub struct TransactionError {
     error_type: TransactionErrorType,
     /// Optional error description.
     description: Option<String>,
+    /// Identifier of the service which has failed the execution.
+    service_id: Option<u16>,
+    /// Serialized structured error data. Empty if there is no payload.
+    payload: Vec<u8>,
+    /// Name of the error code from the service error registry. This field
+    /// is not persisted and is filled by the blockchain explorer.
+    name: Option<String>,
 }
 
 impl TransactionError {
@@ -311,11 +710,14 @@ impl TransactionError {
         Self {
             error_type,
             description,
+            service_id: None,
+            payload: Vec::new(),
+            name: None,
         }
     }
 
     /// Creates a new `TransactionError` instance with the specified error code and description.
-    pub(crate) fn code(code: u8, description: Option<String>) -> Self {
+    pub(crate) fn code(code: u16, description: Option<String>) -> Self {
         Self::new(TransactionErrorType::Code(code), description)
     }
 
@@ -324,6 +726,16 @@ impl TransactionError {
         Self::new(TransactionErrorType::Panic, description)
     }
 
+    /// Creates a new `TransactionError` representing a mismatched author nonce.
+    pub(crate) fn invalid_nonce(description: Option<String>) -> Self {
+        Self::new(TransactionErrorType::InvalidNonce, description)
+    }
+
+    /// Creates a new `TransactionError` representing exceeded storage access limits.
+    pub(crate) fn limit_exceeded(description: Option<String>) -> Self {
+        Self::new(TransactionErrorType::LimitExceeded, description)
+    }
+
     /// Creates a new `TransactionError` instance from `std::thread::Result`'s `Err`.
     pub(crate) fn from_panic(panic: &Box<dyn Any + Send>) -> Self {
         Self::panic(panic_description(panic))
@@ -339,6 +751,56 @@ impl TransactionError {
     pub fn description(&self) -> Option<&str> {
         self.description.as_ref().map(String::as_ref)
     }
+
+    /// Returns the identifier of the service which has failed the execution, if known.
+    pub fn service_id(&self) -> Option<u16> {
+        self.service_id
+    }
+
+    /// Returns the serialized error payload. The payload is empty if the service
+    /// has not attached structured data to the error.
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Deserializes the error payload into the given type.
+    ///
+    /// Returns `None` if the error has no payload.
+    pub fn decode_payload<P: BinaryForm>(&self) -> Option<Result<P, failure::Error>> {
+        if self.payload.is_empty() {
+            None
+        } else {
+            Some(P::decode(&self.payload))
+        }
+    }
+
+    /// Returns the name of the error code registered by the service, if any.
+    ///
+    /// Names are resolved by the [`BlockchainExplorer`], so this method returns `None`
+    /// for errors read directly from the storage.
+    ///
+    /// [`BlockchainExplorer`]: ../explorer/struct.BlockchainExplorer.html
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(String::as_ref)
+    }
+
+    /// Sets the identifier of the service which has failed the execution.
+    pub(crate) fn with_service_id(mut self, service_id: u16) -> Self {
+        self.service_id = Some(service_id);
+        self
+    }
+
+    /// Sets the serialized error payload.
+    pub(crate) fn with_payload(mut self, payload: Vec<u8>) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    /// Sets the name of the error code.
+    pub(crate) fn with_name(mut self, name: Option<String>) -> Self {
+        self.name = name;
+        self
+    }
 }
 
 impl<'a, T: Transaction> From<T> for Box<dyn Transaction + 'a> {
@@ -351,9 +813,15 @@ impl fmt::Display for TransactionError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.error_type {
             TransactionErrorType::Panic => write!(f, "Panic during execution")?,
+            TransactionErrorType::InvalidNonce => write!(f, "Invalid nonce")?,
+            TransactionErrorType::LimitExceeded => write!(f, "Execution limit exceeded")?,
             TransactionErrorType::Code(c) => write!(f, "Error code: {}", c)?,
         }
 
+        if let Some(ref name) = self.name {
+            write!(f, " ({})", name)?;
+        }
+
         if let Some(ref description) = self.description {
             write!(f, " description: {}", description)?;
         }
@@ -364,19 +832,21 @@ impl fmt::Display for TransactionError {
 
 // String content (`TransactionError::Description`) is intentionally excluded from the hash
 // calculation because user can be tempted to use error description from a third-party libraries
-// which aren't stable across the versions.
+// which aren't stable across the versions. The error name is not persisted at all.
 impl CryptoHash for TransactionResult {
     fn hash(&self) -> Hash {
-        u16::hash(&status_as_u16(self))
+        let mut proto = self.to_pb();
+        proto.clear_description();
+        let bytes = proto
+            .write_to_bytes()
+            .expect("Failed to serialize TransactionResult to protobuf.");
+        crypto::hash(&bytes)
     }
 }
 
 impl From<ExecutionError> for TransactionError {
     fn from(error: ExecutionError) -> Self {
-        Self {
-            error_type: TransactionErrorType::Code(error.code),
-            description: error.description,
-        }
+        Self::code(error.code, error.description).with_payload(error.payload)
     }
 }
 
@@ -385,27 +855,50 @@ impl ProtobufConvert for TransactionResult {
 
     fn to_pb(&self) -> Self::ProtoStruct {
         let mut proto = <Self as ProtobufConvert>::ProtoStruct::new();
-        proto.set_status(status_as_u16(self).to_pb());
-        if let Some(description) = self.0.as_ref().err().and_then(|e| e.description.clone()) {
-            proto.set_description(description);
+        proto.set_status(status_as_u32(self));
+        if let Err(ref error) = self.0 {
+            if let Some(ref description) = error.description {
+                proto.set_description(description.clone());
+            }
+            if let Some(service_id) = error.service_id {
+                let mut value = protobuf::well_known_types::UInt32Value::new();
+                value.set_value(u32::from(service_id));
+                proto.set_service_id(value);
+            }
+            proto.set_payload(error.payload.clone());
         }
         proto
     }
 
     fn from_pb(mut pb: Self::ProtoStruct) -> Result<Self, failure::Error> {
-        let status_code: u16 = ProtobufConvert::from_pb(pb.get_status())?;
         let description = if pb.get_description() != "" {
             Some(pb.take_description())
         } else {
             None
         };
 
-        Ok(TransactionResult(match status_code {
-            value @ 0..=MAX_ERROR_CODE => Err(TransactionError::code(value as u8, description)),
-            TRANSACTION_STATUS_OK => Ok(()),
-            TRANSACTION_STATUS_PANIC => Err(TransactionError::panic(description)),
+        let error = match pb.get_status() {
+            TRANSACTION_STATUS_OK => return Ok(TransactionResult(Ok(()))),
+            value @ 0..=MAX_ERROR_CODE => TransactionError::code(value as u16, description),
+            TRANSACTION_STATUS_PANIC => TransactionError::panic(description),
+            TRANSACTION_STATUS_INVALID_NONCE => TransactionError::invalid_nonce(description),
+            TRANSACTION_STATUS_LIMIT_EXCEEDED => TransactionError::limit_exceeded(description),
             value => bail!("Invalid TransactionResult value: {}", value),
-        }))
+        };
+        let error = if pb.has_service_id() {
+            let service_id = pb.get_service_id().get_value();
+            ensure!(
+                service_id <= u32::from(u16::max_value()),
+                "Invalid service id in TransactionResult: {}",
+                service_id
+            );
+            error.with_service_id(service_id as u16)
+        } else {
+            error
+        };
+        Ok(TransactionResult(
+            Err(error.with_payload(pb.take_payload())),
+        ))
     }
 }
 
@@ -425,12 +918,14 @@ impl StorageValue for TransactionResult {
     }
 }
 
-fn status_as_u16(status: &TransactionResult) -> u16 {
+fn status_as_u32(status: &TransactionResult) -> u32 {
     match (*status).0 {
         Ok(()) => TRANSACTION_STATUS_OK,
         Err(ref e) => match e.error_type {
             TransactionErrorType::Panic => TRANSACTION_STATUS_PANIC,
-            TransactionErrorType::Code(c) => u16::from(c),
+            TransactionErrorType::InvalidNonce => TRANSACTION_STATUS_INVALID_NONCE,
+            TransactionErrorType::LimitExceeded => TRANSACTION_STATUS_LIMIT_EXCEEDED,
+            TransactionErrorType::Code(c) => u32::from(c),
         },
     }
 }
@@ -503,11 +998,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn execution_error_with_payload() {
+        let error = ExecutionError::new(1000).with_payload(&TxResult { value: 5 });
+        let transaction_error = TransactionError::from(error.clone());
+        assert_eq!(
+            transaction_error.error_type(),
+            TransactionErrorType::Code(1000)
+        );
+        assert_eq!(transaction_error.payload(), error.payload.as_slice());
+
+        let payload: TxResult = transaction_error.decode_payload().unwrap().unwrap();
+        assert_eq!(payload.value, 5);
+        assert!(TransactionError::code(1000, None)
+            .decode_payload::<TxResult>()
+            .is_none());
+    }
+
     #[test]
     fn transaction_error_new() {
         let values = [
             (TransactionErrorType::Panic, None),
             (TransactionErrorType::Panic, Some("panic")),
+            (TransactionErrorType::InvalidNonce, None),
+            (TransactionErrorType::LimitExceeded, None),
             (TransactionErrorType::Code(0), None),
             (TransactionErrorType::Code(1), Some("")),
             (TransactionErrorType::Code(100), None),
@@ -550,6 +1064,10 @@ mod tests {
             Err(TransactionError::panic(Some(
                 "Panic error description".to_owned(),
             ))),
+            Err(TransactionError::invalid_nonce(Some(
+                "Expected nonce 1".to_owned(),
+            ))),
+            Err(TransactionError::limit_exceeded(None).with_service_id(3)),
             Err(TransactionError::code(0, None)),
             Err(TransactionError::code(
                 0,
@@ -565,6 +1083,12 @@ mod tests {
                 255,
                 Some("(Not) really long error description".to_owned()),
             )),
+            Err(TransactionError::code(256, None)),
+            Err(TransactionError::code(u16::max_value(), None).with_service_id(0)),
+            Err(TransactionError::code(3, None)
+                .with_service_id(128)
+                .with_payload(vec![1, 2, 3])),
+            Err(TransactionError::panic(None).with_service_id(u16::max_value())),
         ]
         .iter()
         .map(|res| TransactionResult(res.to_owned()))
@@ -577,6 +1101,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn transaction_result_hash() {
+        let result = |description: Option<&str>, payload: Vec<u8>| {
+            let error = TransactionError::code(3, description.map(str::to_owned))
+                .with_service_id(128)
+                .with_payload(payload);
+            TransactionResult(Err(error))
+        };
+
+        let hash = result(None, vec![1]).hash();
+        assert_eq!(hash, result(Some("description"), vec![1]).hash());
+        assert_ne!(hash, result(None, vec![2]).hash());
+        assert_ne!(
+            hash,
+            TransactionResult(Err(TransactionError::code(3, None).with_payload(vec![1]))).hash()
+        );
+        assert_ne!(hash, TransactionResult(Ok(())).hash());
+    }
+
     #[test]
     fn error_discards_transaction_changes() {
         let statuses = [
@@ -615,10 +1158,17 @@ mod tests {
                 blockchain.merge(fork.into_patch()).unwrap();
             }
 
-            let (_, patch) = blockchain.create_patch(ValidatorId::zero(), Height(index), &[hash]);
+            let (_, patch) =
+                blockchain.create_patch(ValidatorId::zero(), Height(index), Utc::now(), &[hash]);
 
             db.merge(patch).unwrap();
 
+            let snapshot = db.snapshot();
+            let result = Schema::new(&snapshot).transaction_results().get(&hash);
+            if let Err(ref error) = result.unwrap().0 {
+                assert_eq!(error.service_id(), Some(TX_RESULT_SERVICE_ID));
+            }
+
             let mut fork = db.fork();
             let entry = create_entry(&mut fork);
             if status.is_err() {
@@ -713,6 +1263,47 @@ mod tests {
         }
     }
 
+    #[derive(Debug, Serialize)]
+    struct SignersCheck {
+        service_id: u16,
+        signers: Vec<PublicKey>,
+    }
+
+    impl Transaction for SignersCheck {
+        fn execute(&self, context: TransactionContext) -> ExecutionResult {
+            assert_eq!(context.service_id(), self.service_id);
+            assert_eq!(context.signers(), &self.signers[..]);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn multisigned_transaction_context() {
+        let (author, secret_key) = crypto::gen_keypair();
+        let raw = Message::sign_transaction(
+            TxResult { value: 1 },
+            TX_RESULT_SERVICE_ID,
+            author,
+            &secret_key,
+        );
+        let signers = vec![crypto::gen_keypair().0, crypto::gen_keypair().0];
+
+        let db = MemoryDB::new();
+        let mut fork = db.fork();
+        let context = TransactionContext::new(&mut fork, &raw);
+        assert_eq!(context.signers(), &[author]);
+
+        let tx = MultiSignedTransaction {
+            service_id: 3,
+            signers: signers.clone(),
+            transaction: Box::new(SignersCheck {
+                service_id: 3,
+                signers,
+            }),
+        };
+        tx.execute(context).unwrap();
+    }
+
     fn create_entry(fork: &mut Fork) -> Entry<&mut Fork, u64> {
         Entry::new("transaction_status_test", fork)
     }
@@ -0,0 +1,114 @@
+// Copyright 2019 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Undo data of the committed blocks.
+//!
+//! For each committed block, the previous values of all storage keys changed by
+//! the block are saved, so that the storage can be rolled back to an earlier height,
+//! for example, if the local chain has diverged from the network.
+
+use super::Schema;
+use crate::helpers::{Height, Round};
+use crate::proto;
+use crate::storage::{Database, Fork, Patch, Snapshot};
+
+/// Value of a storage key before the block which has changed it.
+#[derive(Debug, Clone, PartialEq, ProtobufConvert)]
+#[exonum(pb = "proto::UndoChange", crate = "crate")]
+pub(crate) struct UndoChange {
+    index_name: String,
+    key: Vec<u8>,
+    /// The key was absent before the block.
+    absent: bool,
+    value: Vec<u8>,
+}
+
+/// Data necessary to revert the changes of a block.
+#[derive(Debug, Clone, PartialEq, ProtobufConvert)]
+#[exonum(pb = "proto::UndoData", crate = "crate")]
+pub(crate) struct UndoData {
+    changes: Vec<UndoChange>,
+}
+
+impl UndoData {
+    /// Collects the values of the keys changed by the patch from the snapshot
+    /// the patch is applied to.
+    pub(crate) fn new(patch: &Patch, snapshot: &dyn Snapshot) -> Self {
+        let changes = patch
+            .iter()
+            .flat_map(|(index_name, changes)| {
+                changes.iter().map(move |(key, _)| {
+                    let value = snapshot.get(index_name, key);
+                    UndoChange {
+                        index_name: index_name.clone(),
+                        key: key.clone(),
+                        absent: value.is_none(),
+                        value: value.unwrap_or_default(),
+                    }
+                })
+            })
+            .collect();
+        Self { changes }
+    }
+
+    /// Restores the saved values in the fork.
+    pub(crate) fn revert(self, fork: &mut Fork) {
+        for change in self.changes {
+            if change.absent {
+                fork.remove(&change.index_name, change.key);
+            } else {
+                fork.put(&change.index_name, change.key, change.value);
+            }
+        }
+    }
+}
+
+/// Reverts the blocks committed after the given height using the saved undo data.
+pub(crate) fn rollback(db: &dyn Database, height: Height) -> Result<(), failure::Error> {
+    let last_height = Schema::new(db.snapshot()).height();
+    if height > last_height {
+        bail!(
+            "Cannot roll back to height {}, the latest block height is {}",
+            height,
+            last_height
+        );
+    }
+
+    let mut fork = db.fork();
+    for block_height in (height.next().0..=last_height.0).rev() {
+        let undo_data = Schema::new(&fork)
+            .undo_data()
+            .get(&block_height)
+            .ok_or_else(|| {
+                format_err!(
+                    "Cannot roll back to height {}, no undo data for the block at height {}",
+                    height,
+                    block_height
+                )
+            })?;
+        undo_data.revert(&mut fork);
+        Schema::new(&mut fork).undo_data_mut().remove(&block_height);
+    }
+
+    {
+        let mut schema = Schema::new(&mut fork);
+        // The pool could be changed after the reverted blocks were committed.
+        let pool_len = schema.transactions_pool().iter().count() as u64;
+        schema.transactions_pool_len_index_mut().set(pool_len);
+        schema.consensus_messages_cache_mut().clear();
+        schema.set_consensus_round(Round::first());
+    }
+    db.merge_sync(fork.into_patch())?;
+    Ok(())
+}
@@ -23,7 +23,7 @@ use tokio_core::reactor::{Handle, Timeout};
 use std::time::{Duration, SystemTime};
 
 use super::{InternalEvent, InternalRequest, TimeoutRequest};
-use crate::messages::{Message, SignedMessage};
+use crate::messages::Message;
 
 #[derive(Debug)]
 pub struct InternalPart {
@@ -50,7 +50,7 @@ impl InternalPart {
         raw: Vec<u8>,
         internal_tx: mpsc::Sender<InternalEvent>,
     ) -> impl Future<Item = (), Error = ()> {
-        future::lazy(|| SignedMessage::from_raw_buffer(raw).and_then(Message::deserialize))
+        future::lazy(|| Message::from_raw_buffer(raw))
             .map_err(drop)
             .and_then(|protocol| {
                 let event = future::ok(InternalEvent::MessageVerified(Box::new(protocol)));
@@ -117,6 +117,7 @@ mod tests {
 
     use super::*;
     use crate::crypto::{gen_keypair, Signature};
+    use crate::messages::SignedMessage;
 
     fn verify_message(msg: Vec<u8>) -> Option<InternalEvent> {
         let (internal_tx, internal_rx) = mpsc::channel(16);
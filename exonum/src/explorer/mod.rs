@@ -21,23 +21,24 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use std::{
     cell::{Ref, RefCell},
-    collections::Bound,
+    collections::{BTreeSet, Bound},
     fmt,
     ops::{Index, Range, RangeFrom, RangeFull, RangeTo},
     slice,
 };
 
 use crate::blockchain::{
-    Block, Blockchain, Schema, TransactionError, TransactionErrorType, TransactionMessage,
-    TransactionResult, TxLocation,
+    Block, BlockProof, Blockchain, Event, Schema, TransactionError, TransactionErrorType,
+    TransactionMessage, TransactionResult, TxLocation,
 };
-use crate::crypto::{CryptoHash, Hash};
+use crate::crypto::{CryptoHash, Hash, PublicKey};
 use crate::helpers::Height;
-use crate::messages::{Precommit, RawTransaction, Signed};
-use crate::storage::{ListProof, Snapshot};
+use crate::messages::{HexStringRepresentation, Precommit, RawTransaction, Signed};
+use crate::storage::{ListProof, PatchSummary, Snapshot};
 
 /// Transaction parsing result.
 type ParseResult = Result<TransactionMessage, failure::Error>;
+type SimulationResult = Result<(TransactionResult, PatchSummary, Vec<String>), failure::Error>;
 
 /// Range of `Height`s.
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -355,19 +356,27 @@ impl<'a> IntoIterator for &'a BlockWithTransactions {
 /// { "type": "success" }
 /// ```
 ///
-/// For transactions that return an [`ExecutionError`], `status` contains the error code
-/// and an optional description, i.e., has the following type in the
-/// [`Flow`] / [`TypeScript`] notation:
+/// For transactions that return an [`ExecutionError`], `status` contains the error code,
+/// an optional description, the identifier of the failed service, the name of the error code
+/// from the [service error registry] and the hex-encoded error payload, i.e., has
+/// the following type in the [`Flow`] / [`TypeScript`] notation:
 ///
 /// ```javascript
-/// { type: 'error', code: number, description?: string }
+/// {
+///   type: 'error',
+///   code: number,
+///   description?: string,
+///   service_id?: number,
+///   name?: string,
+///   payload?: string,
+/// }
 /// ```
 ///
 /// For transactions that have resulted in a panic, `status` contains an optional description
-/// as well:
+/// and the identifier of the failed service as well:
 ///
 /// ```javascript
-/// { type: 'panic', description?: string }
+/// { type: 'panic', description?: string, service_id?: number }
 /// ```
 ///
 /// [`Transaction`]: ../blockchain/trait.Transaction.html
@@ -376,6 +385,7 @@ impl<'a> IntoIterator for &'a BlockWithTransactions {
 /// [`Hash`]: ../../exonum_crypto/struct.Hash.html
 /// [`TransactionResult`]: ../blockchain/struct.TransactionResult.html
 /// [`ExecutionError`]: ../blockchain/struct.ExecutionError.html
+/// [service error registry]: ../blockchain/trait.Service.html#method.error_name
 /// [`Flow`]: https://flow.org/
 /// [`TypeScript`]: https://www.typescriptlang.org/
 ///
@@ -447,8 +457,33 @@ pub struct CommittedTransaction {
 #[derive(Debug, Serialize, Deserialize)]
 enum TxStatus<'a> {
     Success,
-    Panic { description: &'a str },
-    Error { code: u8, description: &'a str },
+    Panic {
+        description: &'a str,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        service_id: Option<u16>,
+    },
+    InvalidNonce {
+        description: &'a str,
+    },
+    LimitExceeded {
+        description: &'a str,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        service_id: Option<u16>,
+    },
+    Error {
+        code: u16,
+        description: &'a str,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        service_id: Option<u16>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        #[serde(
+            with = "HexStringRepresentation",
+            default,
+            skip_serializing_if = "Vec::is_empty"
+        )]
+        payload: Vec<u8>,
+    },
 }
 
 impl<'a> TxStatus<'a> {
@@ -477,9 +512,24 @@ impl<'a> From<&'a TransactionResult> for TxStatus<'a> {
             Ok(()) => TxStatus::Success,
             Err(ref e) => {
                 let description = e.description().unwrap_or_default();
+                let service_id = e.service_id();
                 match e.error_type() {
-                    Panic => TxStatus::Panic { description },
-                    Code(code) => TxStatus::Error { code, description },
+                    Panic => TxStatus::Panic {
+                        description,
+                        service_id,
+                    },
+                    InvalidNonce => TxStatus::InvalidNonce { description },
+                    LimitExceeded => TxStatus::LimitExceeded {
+                        description,
+                        service_id,
+                    },
+                    Code(code) => TxStatus::Error {
+                        code,
+                        description,
+                        service_id,
+                        name: e.name().map(str::to_owned),
+                        payload: e.payload().to_vec(),
+                    },
                 }
             }
         }
@@ -496,11 +546,43 @@ impl<'a> From<TxStatus<'a>> for TransactionResult {
             }
         };
 
+        fn with_service_id(error: TransactionError, service_id: Option<u16>) -> TransactionError {
+            match service_id {
+                Some(service_id) => error.with_service_id(service_id),
+                None => error,
+            }
+        }
+
         TransactionResult(match status {
             TxStatus::Success => Ok(()),
-            TxStatus::Panic { description } => Err(TransactionError::panic(to_option(description))),
-            TxStatus::Error { code, description } => {
-                Err(TransactionError::code(code, to_option(description)))
+            TxStatus::Panic {
+                description,
+                service_id,
+            } => Err(with_service_id(
+                TransactionError::panic(to_option(description)),
+                service_id,
+            )),
+            TxStatus::InvalidNonce { description } => {
+                Err(TransactionError::invalid_nonce(to_option(description)))
+            }
+            TxStatus::LimitExceeded {
+                description,
+                service_id,
+            } => Err(with_service_id(
+                TransactionError::limit_exceeded(to_option(description)),
+                service_id,
+            )),
+            TxStatus::Error {
+                code,
+                description,
+                service_id,
+                name,
+                payload,
+            } => {
+                let error = TransactionError::code(code, to_option(description))
+                    .with_payload(payload)
+                    .with_name(name);
+                Err(with_service_id(error, service_id))
             }
         })
     }
@@ -662,6 +744,8 @@ impl TransactionInfo {
 pub struct BlockchainExplorer<'a> {
     snapshot: Box<dyn Snapshot>,
     transaction_parser: Box<dyn 'a + Fn(Signed<RawTransaction>) -> ParseResult>,
+    error_names: Box<dyn 'a + Fn(u16, u16) -> Option<&'static str>>,
+    simulator: Box<dyn 'a + Fn(&Signed<RawTransaction>) -> SimulationResult>,
 }
 
 impl<'a> fmt::Debug for BlockchainExplorer<'a> {
@@ -679,6 +763,8 @@ impl<'a> BlockchainExplorer<'a> {
                 let tx = blockchain.tx_from_raw(raw.payload().clone())?;
                 Ok(TransactionMessage::new(raw, tx))
             }),
+            error_names: Box::new(move |service_id, code| blockchain.error_name(service_id, code)),
+            simulator: Box::new(move |tx| blockchain.simulate(tx)),
         }
     }
 
@@ -694,6 +780,20 @@ impl<'a> BlockchainExplorer<'a> {
         Some(TransactionInfo::Committed(tx))
     }
 
+    /// Returns the height at which the transaction was evicted from the pool without
+    /// being committed. Returns `None` if the transaction was not evicted or has been
+    /// submitted again after the eviction.
+    pub fn transaction_eviction_height(&self, tx_hash: &Hash) -> Option<Height> {
+        Schema::new(&self.snapshot)
+            .transactions_evicted()
+            .get(tx_hash)
+    }
+
+    /// Returns the next expected nonce of the given transaction author.
+    pub fn account_nonce(&self, author: &PublicKey) -> u64 {
+        Schema::new(&self.snapshot).account_nonce(author)
+    }
+
     /// Returns transaction message without proof.
     pub fn transaction_without_proof(&self, tx_hash: &Hash) -> Option<TransactionMessage> {
         let schema = Schema::new(&self.snapshot);
@@ -724,6 +824,47 @@ impl<'a> BlockchainExplorer<'a> {
         tx_hashes
     }
 
+    /// Fills the name of the error code in the transaction result from the service
+    /// error registry.
+    fn resolve_error_name(&self, result: TransactionResult) -> TransactionResult {
+        TransactionResult(result.0.map_err(|error| {
+            let name = match (error.error_type(), error.service_id()) {
+                (TransactionErrorType::Code(code), Some(service_id)) => {
+                    (self.error_names)(service_id, code)
+                }
+                _ => None,
+            };
+            error.with_name(name.map(str::to_owned))
+        }))
+    }
+
+    /// Executes the transaction against a throwaway fork of the latest blockchain state
+    /// without adding it to the pool, see [`Blockchain::simulate`] for details.
+    ///
+    /// Note that the simulation uses the latest state of the blockchain rather than
+    /// the snapshot wrapped by the explorer.
+    ///
+    /// [`Blockchain::simulate`]: ../blockchain/struct.Blockchain.html#method.simulate
+    pub fn simulate(
+        &self,
+        tx: &Signed<RawTransaction>,
+    ) -> Result<SimulatedTransaction, failure::Error> {
+        let (status, summary, read_indexes) = (self.simulator)(tx)?;
+        let changed_indexes: Vec<String> = summary.touched_indexes().map(str::to_owned).collect();
+        let touched_indexes = read_indexes
+            .into_iter()
+            .chain(changed_indexes.iter().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        Ok(SimulatedTransaction {
+            tx_hash: tx.hash(),
+            status: self.resolve_error_name(status),
+            touched_indexes,
+            changed_indexes,
+        })
+    }
+
     /// Retrieves a transaction that is known to be committed.
     fn committed_transaction(
         &self,
@@ -743,6 +884,7 @@ impl<'a> BlockchainExplorer<'a> {
 
         // Unwrap is OK here, because we already know that transaction is committed.
         let status = schema.transaction_results().get(tx_hash).unwrap();
+        let status = self.resolve_error_name(status);
 
         CommittedTransaction {
             content: maybe_content.unwrap_or_else(|| {
@@ -788,6 +930,37 @@ impl<'a> BlockchainExplorer<'a> {
         })
     }
 
+    /// Returns events emitted by transactions of the block with the specified height.
+    pub fn block_events(&self, height: Height) -> Vec<EventInfo> {
+        let schema = Schema::new(&self.snapshot);
+        let events = schema.block_events(height);
+        events
+            .iter()
+            .enumerate()
+            .map(|(index, event)| EventInfo {
+                height,
+                index: index as u64,
+                event,
+            })
+            .collect()
+    }
+
+    /// Returns the proof of inclusion of the event with the specified index
+    /// in the block with the specified height, or `None` if there is no such event.
+    pub fn event_proof(&self, height: Height, index: u64) -> Option<EventProof> {
+        let schema = Schema::new(&self.snapshot);
+        let block_proof = schema.block_and_precommits(height)?;
+        let events = schema.block_events(height);
+        if index >= events.len() {
+            return None;
+        }
+
+        Some(EventProof {
+            block_proof,
+            proof: events.get_proof(index),
+        })
+    }
+
     /// Iterates over blocks in the blockchain.
     pub fn blocks<R: Into<HeightRange>>(&self, heights: R) -> Blocks {
         use std::cmp::max;
@@ -805,6 +978,77 @@ impl<'a> BlockchainExplorer<'a> {
     }
 }
 
+/// Result of the transaction simulation.
+///
+/// Values of this type are returned by the [`simulate()`] method of the `BlockchainExplorer`.
+///
+/// # JSON presentation
+///
+/// | Name | Equivalent type | Description |
+/// |------|-------|--------|
+/// | `tx_hash` | [`Hash`] | Hash of the simulated transaction |
+/// | `status` | (custom) | Execution status, see [`CommittedTransaction`] |
+/// | `touched_indexes` | `Vec<String>` | Names of the indexes read or changed by the transaction |
+/// | `changed_indexes` | `Vec<String>` | Names of the indexes changed by the transaction |
+///
+/// [`simulate()`]: struct.BlockchainExplorer.html#method.simulate
+/// [`Hash`]: ../../exonum_crypto/struct.Hash.html
+/// [`CommittedTransaction`]: struct.CommittedTransaction.html#status-field
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SimulatedTransaction {
+    tx_hash: Hash,
+    #[serde(with = "TxStatus")]
+    status: TransactionResult,
+    touched_indexes: Vec<String>,
+    changed_indexes: Vec<String>,
+}
+
+impl SimulatedTransaction {
+    /// Returns the hash of the simulated transaction.
+    pub fn tx_hash(&self) -> &Hash {
+        &self.tx_hash
+    }
+
+    /// Returns the status of the transaction execution.
+    pub fn status(&self) -> Result<(), &TransactionError> {
+        self.status.0.as_ref().map(|_| ())
+    }
+
+    /// Returns names of the indexes read or changed by the transaction, including
+    /// the core indexes such as the event log and author nonces.
+    pub fn touched_indexes(&self) -> &[String] {
+        &self.touched_indexes
+    }
+
+    /// Returns names of the indexes changed by the transaction.
+    pub fn changed_indexes(&self) -> &[String] {
+        &self.changed_indexes
+    }
+}
+
+/// Event emitted in a block together with its location in the block event log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventInfo {
+    /// Height of the block in which the event has been emitted.
+    pub height: Height,
+    /// Index of the event in the block event log.
+    pub index: u64,
+    /// The event itself.
+    pub event: Event,
+}
+
+/// Event together with the proof of its inclusion into the blockchain.
+///
+/// The `proof` links the event to the `event_hash` of the block header,
+/// and the block header is authenticated by the `precommits` of the `block_proof`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventProof {
+    /// Block header with the precommits authorizing it.
+    pub block_proof: BlockProof,
+    /// Proof of the event inclusion into the block event log.
+    pub proof: ListProof<Event>,
+}
+
 /// Iterator over blocks in the blockchain.
 pub struct Blocks<'a> {
     explorer: &'a BlockchainExplorer<'a>,
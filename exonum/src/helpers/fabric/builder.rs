@@ -33,6 +33,7 @@ use super::{
 
 use crate::blockchain::Service;
 use crate::node::{ExternalMessage, Node};
+use crate::storage::Database;
 
 /// `NodeBuilder` is a high level object,
 /// usable for fast prototyping and creating app from services list.
@@ -69,8 +70,12 @@ impl NodeBuilder {
         T: Into<OsString> + Clone,
     {
         let feedback = ClapBackend::execute_cmd_string(&self.commands, cmd_line);
-        if let Feedback::RunNode(ref ctx) = feedback {
-            self.node_from_run_context(ctx);
+        match feedback {
+            Feedback::RunNode(ref ctx) => {
+                self.node_from_run_context(ctx);
+            }
+            Feedback::ReplayConsensusLog(ref ctx) => self.replay_consensus_log(ctx),
+            Feedback::None => {}
         }
         feedback != Feedback::None
     }
@@ -82,7 +87,11 @@ impl NodeBuilder {
                 let node = self.node_from_run_context(ctx);
                 Some(node)
             }
-            _ => None,
+            Feedback::ReplayConsensusLog(ref ctx) => {
+                self.replay_consensus_log(ctx);
+                None
+            }
+            Feedback::None => None,
         }
     }
 
@@ -145,13 +154,30 @@ impl NodeBuilder {
     }
 
     fn node_from_run_context(self, ctx: &Context) -> Node {
+        let config = ctx
+            .get(keys::NODE_CONFIG)
+            .expect("could not find node_config");
+        let db = Run::db_helper(ctx, &config.database);
+        self.node_with_database(ctx, db)
+    }
+
+    // Replays the consensus log into the node and prints the report.
+    fn replay_consensus_log(self, ctx: &Context) {
+        let config = ctx
+            .get(keys::NODE_CONFIG)
+            .expect("could not find node_config");
+        let db = Maintenance::replay_database(ctx, &config.database);
+        let report = self.node_with_database(ctx, db).replay_consensus_log();
+        print!("{}", report);
+    }
+
+    fn node_with_database(self, ctx: &Context, db: Box<dyn Database>) -> Node {
         let config_file_path = ctx
             .get(keys::NODE_CONFIG_PATH)
             .expect("Could not find node_config_path");
         let config = ctx
             .get(keys::NODE_CONFIG)
             .expect("could not find node_config");
-        let db = Run::db_helper(ctx, &config.database);
         let services: Vec<Box<dyn Service>> = self
             .service_factories
             .into_iter()
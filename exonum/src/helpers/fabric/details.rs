@@ -43,6 +43,7 @@ use crate::node::{ConnectListConfig, NodeApiConfig, NodeConfig};
 use crate::storage::{Database, DbOptions, RocksDB};
 
 const DATABASE_PATH: &str = "DATABASE_PATH";
+const GENESIS_DATA: &str = "GENESIS_DATA";
 const OUTPUT_DIR: &str = "OUTPUT_DIR";
 const PEER_ADDRESS: &str = "PEER_ADDRESS";
 const LISTEN_ADDRESS: &str = "LISTEN_ADDRESS";
@@ -353,6 +354,15 @@ impl Command for GenerateCommonConfig {
                 "validators-count",
                 false,
             ),
+            Argument::new_named(
+                GENESIS_DATA,
+                false,
+                "Path to JSON file with the initial data of services, \
+                 keyed by the service name.",
+                None,
+                "genesis-data",
+                false,
+            ),
         ]
     }
 
@@ -378,6 +388,15 @@ impl Command for GenerateCommonConfig {
             .arg::<u16>("VALIDATORS_COUNT")
             .expect("VALIDATORS_COUNT not found");
 
+        let genesis_data = context
+            .arg::<String>(GENESIS_DATA)
+            .ok()
+            .map(|path| {
+                let file = fs::File::open(&path).expect("Could not open genesis data file.");
+                serde_json::from_reader(file).expect("Could not parse genesis data file.")
+            })
+            .unwrap_or_default();
+
         context.set(keys::SERVICES_CONFIG, AbstractConfig::default());
         let new_context = exts(context);
         let services_config = new_context.get(keys::SERVICES_CONFIG).unwrap_or_default();
@@ -391,6 +410,7 @@ impl Command for GenerateCommonConfig {
         let template = CommonConfigTemplate {
             services_config,
             general_config,
+            genesis_data,
             ..CommonConfigTemplate::default()
         };
 
@@ -626,6 +646,7 @@ impl Command for GenerateNodeConfig {
         let validator_keys = ValidatorKeys {
             consensus_key: consensus_public_key,
             service_key: service_public_key,
+            voting_power: None,
         };
         let node_pub_config = NodePublicConfig {
             address: addresses.0.clone(),
@@ -666,10 +687,12 @@ impl Finalize {
         template: CommonConfigTemplate,
         configs: &[NodePublicConfig],
     ) -> GenesisConfig {
-        GenesisConfig::new_with_consensus(
+        let mut genesis = GenesisConfig::new_with_consensus(
             template.consensus_config,
             configs.iter().map(|c| c.validator_keys),
-        )
+        );
+        genesis.services_data = template.genesis_data;
+        genesis
     }
 
     fn reduce_configs(
@@ -842,6 +865,10 @@ impl Command for Finalize {
                 database: Default::default(),
                 connect_list,
                 thread_pool_size: Default::default(),
+                execution_threads: Default::default(),
+                undo_depth: Default::default(),
+                rotated_keys: Default::default(),
+                follower: None,
             }
         };
 
@@ -953,6 +980,10 @@ impl Command for GenerateTestnet {
                 database: cfg.database,
                 connect_list: cfg.connect_list,
                 thread_pool_size: cfg.thread_pool_size,
+                execution_threads: cfg.execution_threads,
+                undo_depth: cfg.undo_depth,
+                rotated_keys: Vec::new(),
+                follower: None,
             };
 
             ConfigFile::save(&config, &config_file_path).unwrap();
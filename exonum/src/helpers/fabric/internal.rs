@@ -23,6 +23,8 @@ use super::{Argument, CommandExtension, CommandName, Context};
 pub enum Feedback {
     /// Run node with current context.
     RunNode(Context),
+    /// Replay the consensus log into a node with current context.
+    ReplayConsensusLog(Context),
     /// Do nothing
     None,
 }
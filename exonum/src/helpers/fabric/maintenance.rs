@@ -14,16 +14,22 @@
 
 //! This module implements node maintenance actions.
 
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
 
 use super::{
     internal::{CollectedCommand, Command, Feedback},
+    keys,
+    shared::NodeRunConfig,
     Argument, CommandName, Context,
 };
-use crate::blockchain::Schema;
-use crate::helpers::config::ConfigFile;
-use crate::node::NodeConfig;
-use crate::storage::{Database, DbOptions, RocksDB};
+use crate::blockchain::{self, Schema};
+use crate::helpers::{config::ConfigFile, Height};
+use crate::node::{ConsensusLog, NodeConfig};
+use crate::storage::{self, Database, DbOptions, Patch, RocksDB, Snapshot};
 
 // Context entry for the path to the node config.
 const NODE_CONFIG_PATH: &str = "NODE_CONFIG_PATH";
@@ -31,10 +37,20 @@ const NODE_CONFIG_PATH: &str = "NODE_CONFIG_PATH";
 const DATABASE_PATH: &str = "DATABASE_PATH";
 // Context entry for the type of action to be performed.
 const MAINTENANCE_ACTION_PATH: &str = "MAINTENANCE_ACTION_PATH";
+// Context entry for the height to roll back the database to.
+const ROLLBACK_HEIGHT: &str = "ROLLBACK_HEIGHT";
+// Context entries for the passphrase entry methods of the secret keys.
+const CONSENSUS_KEY_PASS_METHOD: &str = "CONSENSUS_KEY_PASS_METHOD";
+const SERVICE_KEY_PASS_METHOD: &str = "SERVICE_KEY_PASS_METHOD";
 
 /// Maintenance command. Supported actions:
 ///
 /// - `clear-cache` - clear message cache.
+/// - `rollback` - revert the blocks committed after the height specified
+///   with `--height`, so that the node can resync with the network.
+/// - `consensus-log` - print consensus messages saved by the node at the current height.
+/// - `replay-consensus-log` - replay these messages into the node without connecting
+///   to other nodes and print where the consensus stalls. The database is left intact.
 #[derive(Debug)]
 pub struct Maintenance;
 
@@ -67,6 +83,77 @@ impl Maintenance {
 
         info!("Cache cleared successfully");
     }
+
+    fn rollback(context: &Context) {
+        let height = context
+            .arg::<u64>(ROLLBACK_HEIGHT)
+            .unwrap_or_else(|_| panic!("{} not found.", ROLLBACK_HEIGHT));
+        info!("Rolling back the database to height {}", height);
+
+        let config = Self::node_config(context);
+        let db = Self::database(context, &config.database);
+        blockchain::rollback(&*db, Height(height)).expect("Can't roll back the database");
+
+        info!("Database rolled back successfully");
+    }
+
+    fn consensus_log(context: &Context) {
+        let config = Self::node_config(context);
+        let db = Self::database(context, &config.database);
+        print!("{}", ConsensusLog::load(db.snapshot()));
+    }
+
+    fn replay_consensus_log(mut context: Context) -> Feedback {
+        let path = context
+            .arg::<String>(NODE_CONFIG_PATH)
+            .unwrap_or_else(|_| panic!("{} not found.", NODE_CONFIG_PATH));
+        let config: NodeConfig<PathBuf> =
+            ConfigFile::load(path.clone()).expect("Can't load node config file");
+        let run_config = NodeRunConfig {
+            consensus_pass_method: context.arg(CONSENSUS_KEY_PASS_METHOD).unwrap_or_default(),
+            service_pass_method: context.arg(SERVICE_KEY_PASS_METHOD).unwrap_or_default(),
+        };
+
+        context.set(keys::NODE_CONFIG, config);
+        context.set(keys::NODE_CONFIG_PATH, path);
+        context.set(keys::RUN_CONFIG, run_config);
+        Feedback::ReplayConsensusLog(context)
+    }
+
+    /// Opens the database for the replay of the consensus log. The changes made
+    /// during the replay are kept in memory.
+    pub(super) fn replay_database(context: &Context, options: &DbOptions) -> Box<dyn Database> {
+        let db = Self::database(context, options);
+        let changes = Mutex::new(db.fork().into_patch());
+        Box::new(OverlayDB { db, changes })
+    }
+}
+
+// Database keeping the merged changes in memory, so that the underlying database is not changed.
+struct OverlayDB {
+    db: Box<dyn Database>,
+    changes: Mutex<Patch>,
+}
+
+impl Database for OverlayDB {
+    fn snapshot(&self) -> Box<dyn Snapshot> {
+        let mut fork = self.db.fork();
+        fork.merge(self.changes.lock().unwrap().clone());
+        Box::new(fork)
+    }
+
+    fn merge(&self, patch: Patch) -> storage::Result<()> {
+        let mut changes = self.changes.lock().unwrap();
+        let mut fork = self.db.fork();
+        fork.merge(changes.clone());
+        fork.merge(patch);
+        *changes = fork.into_patch();
+        Ok(())
+    }
+
+    fn merge_sync(&self, patch: Patch) -> storage::Result<()> {
+        self.merge(patch)
+    }
 }
 
 impl Command for Maintenance {
@@ -96,6 +183,34 @@ impl Command for Maintenance {
                 "action",
                 false,
             ),
+            Argument::new_named(
+                ROLLBACK_HEIGHT,
+                false,
+                "Height to roll back the database to (for the rollback action).",
+                None,
+                "height",
+                false,
+            ),
+            Argument::new_named(
+                CONSENSUS_KEY_PASS_METHOD,
+                false,
+                "Passphrase entry method for consensus key (for the replay-consensus-log action).\n\
+                 Possible values are: stdin, env{:ENV_VAR_NAME}, pass:PASSWORD (default: stdin)\n\
+                 If ENV_VAR_NAME is not specified $EXONUM_CONSENSUS_PASS is used",
+                None,
+                "consensus-key-pass",
+                false,
+            ),
+            Argument::new_named(
+                SERVICE_KEY_PASS_METHOD,
+                false,
+                "Passphrase entry method for service key (for the replay-consensus-log action).\n\
+                 Possible values are: stdin, env{:ENV_VAR_NAME}, pass:PASSWORD (default: stdin)\n\
+                 If ENV_VAR_NAME is not specified $EXONUM_SERVICE_PASS is used",
+                None,
+                "service-key-pass",
+                false,
+            ),
         ]
     }
 
@@ -104,7 +219,8 @@ impl Command for Maintenance {
     }
 
     fn about(&self) -> &str {
-        "Maintenance module. Available actions: clear-cache."
+        "Maintenance module. Available actions: clear-cache, rollback, consensus-log, \
+         replay-consensus-log."
     }
 
     fn execute(
@@ -119,6 +235,12 @@ impl Command for Maintenance {
 
         if action == "clear-cache" {
             Self::clear_cache(&context);
+        } else if action == "rollback" {
+            Self::rollback(&context);
+        } else if action == "consensus-log" {
+            Self::consensus_log(&context);
+        } else if action == "replay-consensus-log" {
+            return Self::replay_consensus_log(context);
         } else {
             println!("Unsupported maintenance action: {}", action);
         }
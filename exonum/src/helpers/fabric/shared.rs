@@ -14,6 +14,8 @@
 
 //! This module is used to collect structures that is shared into `CommandExtension` from `Command`.
 
+use serde_json::Value;
+
 use std::{collections::BTreeMap, net::SocketAddr, path::PathBuf};
 
 use crate::blockchain::config::{ConsensusConfig, ValidatorKeys};
@@ -65,6 +67,9 @@ pub struct CommonConfigTemplate {
     pub services_config: AbstractConfig,
     /// General configuration.
     pub general_config: AbstractConfig,
+    /// Initial data of services keyed by the service name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub genesis_data: BTreeMap<String, Value>,
 }
 
 /// `NodePrivateConfig` collects all public and secret keys.
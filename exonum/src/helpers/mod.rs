@@ -53,6 +53,7 @@ pub fn generate_testnet_config(count: u16, start_port: u16) -> Vec<NodeConfig> {
                 .map(|x| ValidatorKeys {
                     consensus_key: (x.0).0,
                     service_key: (x.1).0,
+                    voting_power: None,
                 }),
         );
     let peers = (0..validators.len())
@@ -78,6 +79,10 @@ pub fn generate_testnet_config(count: u16, start_port: u16) -> Vec<NodeConfig> {
             services_configs: Default::default(),
             database: Default::default(),
             thread_pool_size: Default::default(),
+            execution_threads: Default::default(),
+            undo_depth: Default::default(),
+            rotated_keys: Default::default(),
+            follower: None,
         })
         .collect::<Vec<_>>()
 }
@@ -83,6 +83,7 @@ pub mod api;
 #[doc(hidden)]
 pub mod events;
 pub mod explorer;
+pub mod light_client;
 pub mod node;
 pub mod storage;
 
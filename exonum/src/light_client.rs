@@ -0,0 +1,539 @@
+// Copyright 2019 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Light client verifying blocks and proofs of the blockchain state without
+//! access to the blockchain database.
+//!
+//! The client starts from a trusted configuration, usually the genesis one, and
+//! tracks the subsequent configurations the same way as the `configs_actual_from`
+//! index of the core schema does: each configuration is applied to the blocks
+//! since its `actual_from` height. A new configuration is accepted only with a proof
+//! of its presence in the blockchain state from a block committed by the validators
+//! of the previous configuration.
+//!
+//! The client operates only on the blocks, `Precommit` messages and proofs returned
+//! by the node API and does not use the storage or the node, so it can be used
+//! in wallets and other applications which do not trust the node they query.
+//!
+//! # Examples
+//!
+//! ```
+//! # use exonum::blockchain::BlockProof;
+//! # use exonum::crypto::Hash;
+//! # use exonum::light_client::{Error, LightClient};
+//! # use exonum::storage::MapProof;
+//! fn wallet_balance(
+//!     client: &LightClient,
+//!     block_proof: &BlockProof,
+//!     to_table: MapProof<Hash, Hash>,
+//!     to_wallet: MapProof<Hash, u64>,
+//! ) -> Result<Option<u64>, Error> {
+//!     // The wallets are stored in the first table of the service with the identifier 128.
+//!     let checked_proof = client.verify_service_proof(block_proof, to_table, 128, 0, to_wallet)?;
+//!     Ok(checked_proof.entries().next().map(|(_, balance)| *balance))
+//! }
+//! ```
+
+use crate::blockchain::{BlockProof, Blockchain, StoredConfiguration, CORE_SERVICE};
+use crate::crypto::{CryptoHash, Hash};
+use crate::helpers::{Height, ValidatorId};
+use crate::storage::{
+    proof_map_index::{CheckedMapProof, MapProofError, ProofMapKey},
+    MapProof, StorageValue,
+};
+
+/// Index of the `configs` table in the core state hash.
+const CONFIGS_TABLE: usize = 0;
+
+/// An error returned when a block or a proof cannot be verified.
+#[derive(Debug, Fail)]
+pub enum Error {
+    /// The block is committed before the first configuration known to the client.
+    #[fail(display = "no configuration is known for the block at height {}", _0)]
+    UnknownConfiguration(Height),
+
+    /// The block does not contain precommits of validators with enough voting power.
+    #[fail(
+        display = "block has precommits with voting power {}, at least {} is required",
+        voting_power, majority_voting_power
+    )]
+    NotEnoughPrecommits {
+        /// Total voting power of the validators which have signed precommits in the block proof.
+        voting_power: u64,
+        /// Voting power required by the validator set.
+        majority_voting_power: u64,
+    },
+
+    /// The precommit is signed by a key different from the consensus key of its validator.
+    #[fail(display = "precommit is not signed by the validator {}", _0)]
+    InvalidPrecommitAuthor(ValidatorId),
+
+    /// The precommit is for another block, height or round.
+    #[fail(display = "precommit of the validator {} is for another block", _0)]
+    InvalidPrecommit(ValidatorId),
+
+    /// Several precommits of the same validator.
+    #[fail(display = "several precommits of the validator {}", _0)]
+    DuplicatePrecommit(ValidatorId),
+
+    /// The map proof is malformed.
+    #[fail(display = "invalid map proof: {}", _0)]
+    InvalidProof(#[cause] MapProofError),
+
+    /// The proof does not lead to the expected root hash.
+    #[fail(
+        display = "proof leads to hash {:?} instead of the expected {:?}",
+        actual, expected
+    )]
+    HashMismatch {
+        /// Root hash of the proof.
+        actual: Hash,
+        /// Expected root hash.
+        expected: Hash,
+    },
+
+    /// The proof to the service table shows that the table is missing.
+    #[fail(
+        display = "table {} of the service {} is missing in the proof",
+        table_idx, service_id
+    )]
+    MissingTable {
+        /// Identifier of the service.
+        service_id: u16,
+        /// Index of the table in the service state hash.
+        table_idx: usize,
+    },
+
+    /// The configuration is not the next one after the latest configuration
+    /// known to the client.
+    #[fail(display = "configuration does not follow the actual one: {}", _0)]
+    UnexpectedConfiguration(String),
+}
+
+impl From<MapProofError> for Error {
+    fn from(error: MapProofError) -> Self {
+        Error::InvalidProof(error)
+    }
+}
+
+/// Client verifying the blocks and the proofs to the blockchain state
+/// against the known validator sets.
+#[derive(Debug, Clone)]
+pub struct LightClient {
+    // Known configurations in the increasing order of their `actual_from` heights.
+    configs: Vec<StoredConfiguration>,
+}
+
+impl LightClient {
+    /// Creates a client trusting the given configuration, for example, the genesis one.
+    pub fn new(config: StoredConfiguration) -> Self {
+        Self {
+            configs: vec![config],
+        }
+    }
+
+    /// Returns the latest configuration known to the client.
+    pub fn actual_configuration(&self) -> &StoredConfiguration {
+        self.configs.last().expect("No configurations")
+    }
+
+    /// Returns the configuration which applies to the block at the given height,
+    /// or `None` if the block precedes the configurations known to the client.
+    pub fn configuration_by_height(&self, height: Height) -> Option<&StoredConfiguration> {
+        self.configs
+            .iter()
+            .rev()
+            .find(|config| config.actual_from <= height)
+    }
+
+    /// Verifies that the block is committed by validators with the majority of the voting power
+    /// in the configuration applied to its height.
+    pub fn verify_block(&self, proof: &BlockProof) -> Result<(), Error> {
+        let height = proof.block.height();
+        let config = self
+            .configuration_by_height(height)
+            .ok_or_else(|| Error::UnknownConfiguration(height))?;
+        let validators = &config.validator_keys;
+
+        let block_hash = proof.block.hash();
+        let round = proof.precommits.first().map(|precommit| precommit.round());
+        let mut voted = vec![false; validators.len()];
+        let mut voting_power = 0;
+        for precommit in &proof.precommits {
+            let validator = precommit.validator();
+            let keys = validators
+                .get(validator.0 as usize)
+                .ok_or_else(|| Error::InvalidPrecommitAuthor(validator))?;
+            if precommit.author() != keys.consensus_key {
+                return Err(Error::InvalidPrecommitAuthor(validator));
+            }
+            if precommit.height() != height
+                || Some(precommit.round()) != round
+                || precommit.block_hash() != &block_hash
+            {
+                return Err(Error::InvalidPrecommit(validator));
+            }
+            if voted[validator.0 as usize] {
+                return Err(Error::DuplicatePrecommit(validator));
+            }
+            voted[validator.0 as usize] = true;
+            voting_power += keys.voting_power();
+        }
+
+        let majority_voting_power = config.majority_voting_power();
+        if voting_power < majority_voting_power {
+            return Err(Error::NotEnoughPrecommits {
+                voting_power,
+                majority_voting_power,
+            });
+        }
+        Ok(())
+    }
+
+    /// Verifies the block and the proof from its state hash to the table of a service,
+    /// returned by `Schema::get_proof_to_service_table`. Returns the root hash
+    /// of the table.
+    pub fn verify_service_table(
+        &self,
+        block_proof: &BlockProof,
+        to_table: MapProof<Hash, Hash>,
+        service_id: u16,
+        table_idx: usize,
+    ) -> Result<Hash, Error> {
+        self.verify_block(block_proof)?;
+
+        let checked_proof = to_table.check()?;
+        check_root(&checked_proof, block_proof.block.state_hash())?;
+        let table_key = Blockchain::service_table_unique_key(service_id, table_idx);
+        checked_proof
+            .entries()
+            .find(|(key, _)| **key == table_key)
+            .map(|(_, table_hash)| *table_hash)
+            .ok_or_else(|| Error::MissingTable {
+                service_id,
+                table_idx,
+            })
+    }
+
+    /// Verifies the block, the proof to the table of a service and the proof of entries
+    /// of this table. Returns the checked proof of the table entries.
+    pub fn verify_service_proof<K, V>(
+        &self,
+        block_proof: &BlockProof,
+        to_table: MapProof<Hash, Hash>,
+        service_id: u16,
+        table_idx: usize,
+        proof: MapProof<K, V>,
+    ) -> Result<CheckedMapProof<K, V>, Error>
+    where
+        K: ProofMapKey,
+        V: StorageValue,
+    {
+        let table_hash = self.verify_service_table(block_proof, to_table, service_id, table_idx)?;
+        let checked_proof = proof.check()?;
+        check_root(&checked_proof, &table_hash)?;
+        Ok(checked_proof)
+    }
+
+    /// Adds the configuration following the actual one. The configuration is verified
+    /// with the proof of the `configs` core table from a block committed by the validators
+    /// of the actual configuration, that is, a block before the `actual_from` height
+    /// of the new configuration.
+    pub fn add_configuration(
+        &mut self,
+        block_proof: &BlockProof,
+        to_table: MapProof<Hash, Hash>,
+        config_proof: MapProof<Hash, StoredConfiguration>,
+    ) -> Result<&StoredConfiguration, Error> {
+        let checked_proof = self.verify_service_proof(
+            block_proof,
+            to_table,
+            CORE_SERVICE,
+            CONFIGS_TABLE,
+            config_proof,
+        )?;
+
+        let actual = self.actual_configuration();
+        let actual_hash = actual.hash();
+        let config = checked_proof
+            .entries()
+            .map(|(_, config)| config)
+            .find(|config| config.previous_cfg_hash == actual_hash)
+            .ok_or_else(|| {
+                Error::UnexpectedConfiguration(format!(
+                    "proof contains no configuration with the previous hash {:?}",
+                    actual_hash
+                ))
+            })?;
+        if config.actual_from <= actual.actual_from {
+            return Err(Error::UnexpectedConfiguration(format!(
+                "configuration is actual from {}, the actual one is actual from {}",
+                config.actual_from, actual.actual_from
+            )));
+        }
+
+        self.configs.push(config.clone());
+        Ok(self.actual_configuration())
+    }
+}
+
+fn check_root<K, V>(proof: &CheckedMapProof<K, V>, expected: &Hash) -> Result<(), Error> {
+    let actual = proof.merkle_root();
+    if actual != *expected {
+        return Err(Error::HashMismatch {
+            actual,
+            expected: *expected,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use futures::sync::mpsc;
+
+    use super::*;
+    use crate::blockchain::{GenesisConfig, Schema, ValidatorKeys};
+    use crate::crypto::{self, gen_keypair, PublicKey, SecretKey};
+    use crate::helpers::Round;
+    use crate::messages::{Message, Precommit, Signed};
+    use crate::node::ApiSender;
+    use crate::storage::MemoryDB;
+
+    fn validator_keys(keys: &[(PublicKey, SecretKey)]) -> Vec<ValidatorKeys> {
+        keys.iter()
+            .map(|(consensus_key, _)| ValidatorKeys {
+                consensus_key: *consensus_key,
+                service_key: *consensus_key,
+                voting_power: None,
+            })
+            .collect()
+    }
+
+    fn create_blockchain(validators: &[(PublicKey, SecretKey)]) -> Blockchain {
+        let service_keypair = gen_keypair();
+        let api_channel = mpsc::channel(1);
+        let mut blockchain = Blockchain::new(
+            MemoryDB::new(),
+            Vec::new(),
+            service_keypair.0,
+            service_keypair.1,
+            ApiSender::new(api_channel.0),
+        );
+        let genesis = GenesisConfig::new(validator_keys(validators).into_iter());
+        blockchain.initialize(genesis).unwrap();
+        blockchain
+    }
+
+    fn create_precommit(
+        validator: u16,
+        height: Height,
+        block_hash: &Hash,
+        keys: &(PublicKey, SecretKey),
+    ) -> Signed<Precommit> {
+        Message::concrete(
+            Precommit::new(
+                ValidatorId(validator),
+                height,
+                Round::first(),
+                &crypto::hash(&[]),
+                block_hash,
+                Utc::now(),
+            ),
+            keys.0,
+            &keys.1,
+        )
+    }
+
+    // Commits an empty block signed by the given validators.
+    fn commit_block(blockchain: &mut Blockchain, validators: &[(PublicKey, SecretKey)]) {
+        let height = Schema::new(&blockchain.snapshot()).height().next();
+        let (block_hash, patch) = blockchain.create_patch(
+            ValidatorId::zero(),
+            height,
+            Utc.timestamp(height.0 as i64, 0),
+            &[],
+        );
+        let precommits = validators
+            .iter()
+            .enumerate()
+            .map(|(id, keys)| create_precommit(id as u16, height, &block_hash, keys))
+            .collect::<Vec<_>>();
+        blockchain
+            .commit(&patch, block_hash, precommits.into_iter())
+            .unwrap();
+    }
+
+    fn block_proof(blockchain: &Blockchain, height: Height) -> BlockProof {
+        Schema::new(&blockchain.snapshot())
+            .block_and_precommits(height)
+            .unwrap()
+    }
+
+    #[test]
+    fn verify_block() {
+        let validators: Vec<_> = (0..4).map(|_| gen_keypair()).collect();
+        let mut blockchain = create_blockchain(&validators);
+        let client = LightClient::new(Schema::new(&blockchain.snapshot()).actual_configuration());
+
+        commit_block(&mut blockchain, &validators[..3]);
+        let proof = block_proof(&blockchain, Height(1));
+        client.verify_block(&proof).unwrap();
+
+        let mut not_enough = proof.clone();
+        not_enough.precommits.pop();
+        match client.verify_block(&not_enough) {
+            Err(Error::NotEnoughPrecommits {
+                voting_power: 2,
+                majority_voting_power: 3,
+            }) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        let mut duplicate = proof.clone();
+        duplicate.precommits[2] = duplicate.precommits[1].clone();
+        match client.verify_block(&duplicate) {
+            Err(Error::DuplicatePrecommit(ValidatorId(1))) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        let mut wrong_author = proof.clone();
+        let block_hash = proof.block.hash();
+        wrong_author.precommits[2] = create_precommit(2, Height(1), &block_hash, &gen_keypair());
+        match client.verify_block(&wrong_author) {
+            Err(Error::InvalidPrecommitAuthor(ValidatorId(2))) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        let mut wrong_block = proof.clone();
+        wrong_block.precommits[2] =
+            create_precommit(2, Height(1), &crypto::hash(&[1]), &validators[2]);
+        match client.verify_block(&wrong_block) {
+            Err(Error::InvalidPrecommit(ValidatorId(2))) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn verify_block_with_voting_power() {
+        let validators: Vec<_> = (0..4).map(|_| gen_keypair()).collect();
+        let mut blockchain = create_blockchain(&validators);
+        let mut config = Schema::new(&blockchain.snapshot()).actual_configuration();
+        config.validator_keys[0].voting_power = Some(5);
+        let client = LightClient::new(config);
+
+        commit_block(&mut blockchain, &validators);
+        let proof = block_proof(&blockchain, Height(1));
+        client.verify_block(&proof).unwrap();
+
+        let mut heavy = proof.clone();
+        heavy.precommits.truncate(2);
+        client.verify_block(&heavy).unwrap();
+
+        let mut light = proof.clone();
+        light.precommits.remove(0);
+        match client.verify_block(&light) {
+            Err(Error::NotEnoughPrecommits {
+                voting_power: 3,
+                majority_voting_power: 6,
+            }) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn verify_service_proof() {
+        let validators: Vec<_> = (0..4).map(|_| gen_keypair()).collect();
+        let mut blockchain = create_blockchain(&validators);
+        let genesis_config = Schema::new(&blockchain.snapshot()).actual_configuration();
+        let client = LightClient::new(genesis_config.clone());
+
+        commit_block(&mut blockchain, &validators);
+        let proof = block_proof(&blockchain, Height(1));
+        let snapshot = blockchain.snapshot();
+        let schema = Schema::new(&snapshot);
+        let to_table = schema.get_proof_to_service_table(CORE_SERVICE, CONFIGS_TABLE);
+        let config_hash = genesis_config.hash();
+        let to_config = schema.configs().get_proof(config_hash);
+
+        let checked_proof = client
+            .verify_service_proof(
+                &proof,
+                to_table.clone(),
+                CORE_SERVICE,
+                CONFIGS_TABLE,
+                to_config.clone(),
+            )
+            .unwrap();
+        assert_eq!(
+            checked_proof.entries().collect::<Vec<_>>(),
+            vec![(&config_hash, &genesis_config)]
+        );
+
+        // The proof to another table.
+        match client.verify_service_proof(&proof, to_table, CORE_SERVICE, 1, to_config) {
+            Err(Error::MissingTable { .. }) | Err(Error::HashMismatch { .. }) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn track_validator_set_changes() {
+        let validators: Vec<_> = (0..4).map(|_| gen_keypair()).collect();
+        let new_validators: Vec<_> = (0..4).map(|_| gen_keypair()).collect();
+        let mut blockchain = create_blockchain(&validators);
+        let genesis_config = Schema::new(&blockchain.snapshot()).actual_configuration();
+        let mut client = LightClient::new(genesis_config.clone());
+
+        let mut new_config = genesis_config.clone();
+        new_config.previous_cfg_hash = genesis_config.hash();
+        new_config.actual_from = Height(3);
+        new_config.validator_keys = validator_keys(&new_validators);
+        let mut fork = blockchain.fork();
+        Schema::new(&mut fork).commit_configuration(new_config.clone());
+        blockchain.merge(fork.into_patch()).unwrap();
+
+        commit_block(&mut blockchain, &validators);
+        commit_block(&mut blockchain, &validators);
+        commit_block(&mut blockchain, &new_validators);
+        let new_block = block_proof(&blockchain, Height(3));
+        assert!(client.verify_block(&new_block).is_err());
+
+        // Empty blocks do not change the state, so the proofs built from the current state
+        // are valid for all the blocks.
+        let snapshot = blockchain.snapshot();
+        let schema = Schema::new(&snapshot);
+        let to_table = schema.get_proof_to_service_table(CORE_SERVICE, CONFIGS_TABLE);
+        let to_config = schema.configs().get_proof(new_config.hash());
+
+        // The block committed by the new validators cannot be verified yet.
+        assert!(client
+            .add_configuration(&new_block, to_table.clone(), to_config.clone())
+            .is_err());
+
+        let old_block = block_proof(&blockchain, Height(2));
+        let added = client
+            .add_configuration(&old_block, to_table, to_config)
+            .unwrap();
+        assert_eq!(*added, new_config);
+
+        client.verify_block(&new_block).unwrap();
+        client.verify_block(&old_block).unwrap();
+        assert_eq!(
+            client.configuration_by_height(Height(2)),
+            Some(&genesis_config)
+        );
+    }
+}
@@ -0,0 +1,309 @@
+// Copyright 2019 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use byteorder::{ByteOrder, LittleEndian};
+use failure::Error;
+
+use std::{borrow::Cow, mem};
+
+use super::{
+    BinaryForm, Message, Precommit, Prevote, ProtocolMessage, RawTransaction, ServiceTransaction,
+    Signed, SignedMessage, EVIDENCE_SERVICE_ID,
+};
+use crate::crypto::{self, CryptoHash, Hash, PublicKey};
+use crate::helpers::{Height, Round, ValidatorId};
+use crate::storage::StorageValue;
+
+const PREVOTES_KIND: u8 = 0;
+const PRECOMMITS_KIND: u8 = 1;
+
+/// Evidence of equivocation: two conflicting consensus messages signed by the same
+/// validator, that is, prevotes for different proposes or precommits for different
+/// blocks at the same height and round.
+///
+/// `Evidence` can be serialized into a byte buffer with the following layout:
+///
+/// | Position  | Stored data                                     |
+/// | - - - - - | - - - - - - - - - - - - - - - - - - - - - - - |
+/// | `0`       | kind of the messages: `0` for prevotes, `1` for precommits |
+/// | `1..5`    | length `L` of the first message                 |
+/// | `5..5+L`  | first message                                   |
+/// | `5+L..`   | second message                                  |
+///
+/// The messages are ordered by their hashes, so the same pair of messages always
+/// produces the same evidence. Deserialization verifies signatures of the messages
+/// and checks that they conflict.
+///
+/// Evidence is submitted as a usual `RawTransaction` with the reserved
+/// [`EVIDENCE_SERVICE_ID`] service identifier. Nodes submit such transactions
+/// automatically when they receive conflicting messages, unless evidence with the same
+/// [`key`] is already recorded or waits in the pool. The committed evidence is recorded
+/// in the [`evidence`] index of the core schema by its key and is passed to services
+/// in [`BlockContext::evidence`], so that services can penalise the validator.
+///
+/// [`EVIDENCE_SERVICE_ID`]: constant.EVIDENCE_SERVICE_ID.html
+/// [`key`]: #method.key
+/// [`evidence`]: ../blockchain/struct.Schema.html#method.evidence
+/// [`BlockContext::evidence`]: ../blockchain/struct.BlockContext.html#method.evidence
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Evidence {
+    /// Prevotes for different proposes.
+    Prevotes(Signed<Prevote>, Signed<Prevote>),
+    /// Precommits for different blocks.
+    Precommits(Signed<Precommit>, Signed<Precommit>),
+}
+
+impl Evidence {
+    /// Creates evidence from the conflicting prevotes.
+    pub fn prevotes(first: Signed<Prevote>, second: Signed<Prevote>) -> Result<Self, Error> {
+        check_conflict(&first, &second)?;
+        let (first, second) = ordered(first, second);
+        Ok(Evidence::Prevotes(first, second))
+    }
+
+    /// Creates evidence from the conflicting precommits.
+    pub fn precommits(first: Signed<Precommit>, second: Signed<Precommit>) -> Result<Self, Error> {
+        check_conflict(&first, &second)?;
+        let (first, second) = ordered(first, second);
+        Ok(Evidence::Precommits(first, second))
+    }
+
+    /// Extracts evidence from the given raw transaction.
+    pub fn from_raw(raw: &RawTransaction) -> Result<Self, Error> {
+        ensure!(
+            raw.service_id == EVIDENCE_SERVICE_ID,
+            "Transaction with service id {} is not an evidence.",
+            raw.service_id
+        );
+        ensure!(
+            raw.service_transaction.transaction_id == 0,
+            "Unknown evidence transaction id {}.",
+            raw.service_transaction.transaction_id
+        );
+        Self::decode(&raw.service_transaction.payload)
+    }
+
+    /// Returns the identifier of the validator which has signed the messages.
+    pub fn validator(&self) -> ValidatorId {
+        match *self {
+            Evidence::Prevotes(ref first, _) => first.validator(),
+            Evidence::Precommits(ref first, _) => first.validator(),
+        }
+    }
+
+    /// Returns the consensus key of the validator which has signed the messages.
+    pub fn author(&self) -> PublicKey {
+        match *self {
+            Evidence::Prevotes(ref first, _) => first.author(),
+            Evidence::Precommits(ref first, _) => first.author(),
+        }
+    }
+
+    /// Returns the height of the messages.
+    pub fn height(&self) -> Height {
+        match *self {
+            Evidence::Prevotes(ref first, _) => first.height(),
+            Evidence::Precommits(ref first, _) => first.height(),
+        }
+    }
+
+    /// Returns the round of the messages.
+    pub fn round(&self) -> Round {
+        match *self {
+            Evidence::Prevotes(ref first, _) => first.round(),
+            Evidence::Precommits(ref first, _) => first.round(),
+        }
+    }
+
+    /// Returns the key identifying the equivocation regardless of the particular
+    /// conflicting messages, that is, the hash of the kind of the messages, the validator,
+    /// the height and the round. More than two conflicting messages of the validator
+    /// produce different evidence with the same key, so the validator is penalised
+    /// only once for them.
+    pub fn key(&self) -> Hash {
+        let kind = match *self {
+            Evidence::Prevotes(..) => PREVOTES_KIND,
+            Evidence::Precommits(..) => PRECOMMITS_KIND,
+        };
+        let mut buffer = [0; 1 + 2 + 8 + 4];
+        buffer[0] = kind;
+        LittleEndian::write_u16(&mut buffer[1..3], self.validator().0);
+        LittleEndian::write_u64(&mut buffer[3..11], self.height().0);
+        LittleEndian::write_u32(&mut buffer[11..], self.round().0);
+        crypto::hash(&buffer)
+    }
+}
+
+/// Consensus messages which can be used as evidence.
+trait Vote: ProtocolMessage {
+    fn validator(&self) -> ValidatorId;
+    fn height(&self) -> Height;
+    fn round(&self) -> Round;
+    /// Hash of the propose or the block the validator has voted for.
+    fn voted_hash(&self) -> &Hash;
+}
+
+impl Vote for Prevote {
+    fn validator(&self) -> ValidatorId {
+        self.validator()
+    }
+
+    fn height(&self) -> Height {
+        self.height()
+    }
+
+    fn round(&self) -> Round {
+        self.round()
+    }
+
+    fn voted_hash(&self) -> &Hash {
+        self.propose_hash()
+    }
+}
+
+impl Vote for Precommit {
+    fn validator(&self) -> ValidatorId {
+        self.validator()
+    }
+
+    fn height(&self) -> Height {
+        self.height()
+    }
+
+    fn round(&self) -> Round {
+        self.round()
+    }
+
+    fn voted_hash(&self) -> &Hash {
+        self.block_hash()
+    }
+}
+
+/// Checks that the messages are signed by the same validator at the same height and round
+/// and vote for different hashes.
+fn check_conflict<T: Vote>(first: &Signed<T>, second: &Signed<T>) -> Result<(), Error> {
+    let (first_vote, second_vote) = (first.payload(), second.payload());
+    ensure!(
+        first_vote.validator() == second_vote.validator() && first.author() == second.author(),
+        "Messages are signed by different validators."
+    );
+    ensure!(
+        first_vote.height() == second_vote.height() && first_vote.round() == second_vote.round(),
+        "Messages are for different heights or rounds."
+    );
+    ensure!(
+        first_vote.voted_hash() != second_vote.voted_hash(),
+        "Messages vote for the same hash."
+    );
+    Ok(())
+}
+
+fn ordered<T: ProtocolMessage>(first: Signed<T>, second: Signed<T>) -> (Signed<T>, Signed<T>) {
+    if first.hash() <= second.hash() {
+        (first, second)
+    } else {
+        (second, first)
+    }
+}
+
+fn decode_message<T: ProtocolMessage>(buffer: &[u8]) -> Result<Signed<T>, Error> {
+    let message = Message::deserialize(SignedMessage::from_raw_buffer(buffer.to_vec())?)?;
+    T::try_from(message).map_err(|_| format_err!("Unexpected message type in evidence."))
+}
+
+impl BinaryForm for Evidence {
+    fn encode(&self) -> Result<Vec<u8>, Error> {
+        let (kind, first, second) = match *self {
+            Evidence::Prevotes(ref first, ref second) => (
+                PREVOTES_KIND,
+                first.signed_message().raw(),
+                second.signed_message().raw(),
+            ),
+            Evidence::Precommits(ref first, ref second) => (
+                PRECOMMITS_KIND,
+                first.signed_message().raw(),
+                second.signed_message().raw(),
+            ),
+        };
+        let mut buffer = vec![kind];
+        let mut len = [0; mem::size_of::<u32>()];
+        LittleEndian::write_u32(&mut len, first.len() as u32);
+        buffer.extend_from_slice(&len);
+        buffer.extend_from_slice(first);
+        buffer.extend_from_slice(second);
+        Ok(buffer)
+    }
+
+    fn decode(buffer: &[u8]) -> Result<Self, Error> {
+        let (kind, first, second) = split_buffer(buffer)?;
+        match kind {
+            PREVOTES_KIND => Self::prevotes(decode_message(first)?, decode_message(second)?),
+            PRECOMMITS_KIND => Self::precommits(decode_message(first)?, decode_message(second)?),
+            kind => bail!("Unknown kind of evidence {}.", kind),
+        }
+    }
+}
+
+/// Splits the serialized evidence into the kind and the buffers of the messages.
+fn split_buffer(buffer: &[u8]) -> Result<(u8, &[u8], &[u8]), Error> {
+    let header = 1 + mem::size_of::<u32>();
+    ensure!(
+        buffer.len() >= header,
+        "Buffer too short in Evidence deserialization."
+    );
+    let len = LittleEndian::read_u32(&buffer[1..header]) as usize;
+    ensure!(
+        buffer.len() - header >= len,
+        "Buffer too short in Evidence deserialization."
+    );
+    let (first, second) = buffer[header..].split_at(len);
+    Ok((buffer[0], first, second))
+}
+
+impl From<Evidence> for ServiceTransaction {
+    fn from(evidence: Evidence) -> Self {
+        let payload = evidence.encode().expect("Couldn't serialize evidence.");
+        ServiceTransaction::from_raw_unchecked(0, payload)
+    }
+}
+
+impl CryptoHash for Evidence {
+    fn hash(&self) -> Hash {
+        crypto::hash(&self.encode().expect("Couldn't serialize evidence."))
+    }
+}
+
+impl StorageValue for Evidence {
+    fn into_bytes(self) -> Vec<u8> {
+        self.encode().unwrap()
+    }
+
+    /// Stored evidence has been checked before the commit, so the messages are parsed
+    /// without verification of their signatures, as other stored messages are.
+    fn from_bytes(value: Cow<[u8]>) -> Self {
+        let (kind, first, second) = split_buffer(&value).unwrap();
+        match kind {
+            PREVOTES_KIND => Evidence::Prevotes(
+                Signed::from_bytes(first.into()),
+                Signed::from_bytes(second.into()),
+            ),
+            PRECOMMITS_KIND => Evidence::Precommits(
+                Signed::from_bytes(first.into()),
+                Signed::from_bytes(second.into()),
+            ),
+            kind => panic!("Unknown kind of evidence {}.", kind),
+        }
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright 2019 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use byteorder::{ByteOrder, LittleEndian};
+use failure::Error;
+
+use std::mem;
+
+use super::{BinaryForm, RawTransaction, ServiceTransaction, KEY_ROTATION_SERVICE_ID};
+use crate::crypto::{self, PublicKey, SecretKey, Signature, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+use crate::helpers::Height;
+
+/// Kind of the validator key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidatorKeyKind {
+    /// Consensus key of the validator.
+    Consensus,
+    /// Service key of the validator.
+    Service,
+}
+
+/// Rotation of a consensus or service key of a validator, which replaces the key
+/// in the configuration starting from the given height. The identity of the validator,
+/// that is, its position in the list of validators, is preserved.
+///
+/// `KeyRotation` can be serialized into a byte buffer with the following layout:
+///
+/// | Position    | Stored data                                   |
+/// | - - - - - - | - - - - - - - - - - - - - - - - - - - - - - - |
+/// | `0`         | kind of the key: `0` for consensus, `1` for service |
+/// | `1..33`     | old public key                                |
+/// | `33..65`    | new public key                                |
+/// | `65..73`    | height from which the new key is used         |
+/// | `73..137`   | signature of the bytes `0..73` by the new key |
+///
+/// The signature proves that the validator owns the new key; it is verified
+/// when the rotation is decoded from bytes. `KeyRotation` is serialized to JSON
+/// for the explorer, but cannot be deserialized from JSON, so that a rotation
+/// with an unchecked signature cannot be obtained.
+///
+/// Key rotation is submitted as a usual `RawTransaction` with the reserved
+/// [`KEY_ROTATION_SERVICE_ID`] service identifier, signed by the old key. The transaction
+/// schedules a new configuration in the core schema, which differs from the actual
+/// configuration only by the rotated key. Thus, the rotation fails if another configuration
+/// is already scheduled. Nodes switch to the new key at the given height if it is listed
+/// in the `rotated_keys` of the node configuration. Other nodes add the new consensus key
+/// to their connect lists with the address of the old key at the same height.
+///
+/// [`KEY_ROTATION_SERVICE_ID`]: constant.KEY_ROTATION_SERVICE_ID.html
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KeyRotation {
+    kind: ValidatorKeyKind,
+    old_key: PublicKey,
+    new_key: PublicKey,
+    height: Height,
+    signature: Signature,
+}
+
+const SIGNED_DATA_LEN: usize = 1 + 2 * PUBLIC_KEY_LENGTH + mem::size_of::<u64>();
+
+impl KeyRotation {
+    /// Creates a rotation of the `old_key` to the `new_key` starting from the given height.
+    /// The rotation is signed by the secret key corresponding to the `new_key`.
+    pub fn new(
+        kind: ValidatorKeyKind,
+        old_key: PublicKey,
+        new_key: PublicKey,
+        new_secret_key: &SecretKey,
+        height: Height,
+    ) -> Self {
+        let data = signed_data(kind, &old_key, &new_key, height);
+        Self {
+            kind,
+            old_key,
+            new_key,
+            height,
+            signature: crypto::sign(&data, new_secret_key),
+        }
+    }
+
+    /// Extracts key rotation from the given raw transaction.
+    pub fn from_raw(raw: &RawTransaction) -> Result<Self, Error> {
+        ensure!(
+            raw.service_id == KEY_ROTATION_SERVICE_ID,
+            "Transaction with service id {} is not a key rotation.",
+            raw.service_id
+        );
+        ensure!(
+            raw.service_transaction.transaction_id == 0,
+            "Unknown key rotation transaction id {}.",
+            raw.service_transaction.transaction_id
+        );
+        Self::decode(&raw.service_transaction.payload)
+    }
+
+    /// Returns the kind of the rotated key.
+    pub fn kind(&self) -> ValidatorKeyKind {
+        self.kind
+    }
+
+    /// Returns the key being replaced.
+    pub fn old_key(&self) -> &PublicKey {
+        &self.old_key
+    }
+
+    /// Returns the key replacing the old one.
+    pub fn new_key(&self) -> &PublicKey {
+        &self.new_key
+    }
+
+    /// Returns the height from which the new key is used.
+    pub fn height(&self) -> Height {
+        self.height
+    }
+}
+
+fn signed_data(
+    kind: ValidatorKeyKind,
+    old_key: &PublicKey,
+    new_key: &PublicKey,
+    height: Height,
+) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(SIGNED_DATA_LEN + SIGNATURE_LENGTH);
+    buffer.push(kind as u8);
+    buffer.extend_from_slice(old_key.as_ref());
+    buffer.extend_from_slice(new_key.as_ref());
+    let mut height_bytes = [0; mem::size_of::<u64>()];
+    LittleEndian::write_u64(&mut height_bytes, height.into());
+    buffer.extend_from_slice(&height_bytes);
+    buffer
+}
+
+impl BinaryForm for KeyRotation {
+    fn encode(&self) -> Result<Vec<u8>, Error> {
+        let mut buffer = signed_data(self.kind, &self.old_key, &self.new_key, self.height);
+        buffer.extend_from_slice(self.signature.as_ref());
+        Ok(buffer)
+    }
+
+    fn decode(buffer: &[u8]) -> Result<Self, Error> {
+        ensure!(
+            buffer.len() == SIGNED_DATA_LEN + SIGNATURE_LENGTH,
+            "Wrong buffer length in KeyRotation deserialization."
+        );
+        let kind = match buffer[0] {
+            0 => ValidatorKeyKind::Consensus,
+            1 => ValidatorKeyKind::Service,
+            kind => bail!("Unknown kind of the validator key {}.", kind),
+        };
+        let keys_end = 1 + 2 * PUBLIC_KEY_LENGTH;
+        let old_key =
+            PublicKey::from_slice(&buffer[1..=PUBLIC_KEY_LENGTH]).expect("Couldn't read PublicKey");
+        let new_key = PublicKey::from_slice(&buffer[1 + PUBLIC_KEY_LENGTH..keys_end])
+            .expect("Couldn't read PublicKey");
+        let height = Height(LittleEndian::read_u64(&buffer[keys_end..SIGNED_DATA_LEN]));
+        let signature =
+            Signature::from_slice(&buffer[SIGNED_DATA_LEN..]).expect("Couldn't read signature");
+        ensure!(
+            crypto::verify(&signature, &buffer[..SIGNED_DATA_LEN], &new_key),
+            "Key rotation is not signed by the new key."
+        );
+        ensure!(old_key != new_key, "Key rotation does not change the key.");
+        Ok(Self {
+            kind,
+            old_key,
+            new_key,
+            height,
+            signature,
+        })
+    }
+}
+
+impl From<KeyRotation> for ServiceTransaction {
+    fn from(rotation: KeyRotation) -> Self {
+        let payload = rotation.encode().expect("Couldn't serialize key rotation.");
+        ServiceTransaction::from_raw_unchecked(0, payload)
+    }
+}
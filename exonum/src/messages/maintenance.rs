@@ -0,0 +1,95 @@
+// Copyright 2019 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use byteorder::{ByteOrder, LittleEndian};
+use failure::Error;
+
+use std::mem;
+
+use super::{BinaryForm, RawTransaction, ServiceTransaction, MAINTENANCE_SERVICE_ID};
+use crate::helpers::Height;
+
+/// Notice of a validator announcing that it does not take part in the consensus
+/// until the given height, e.g., because its node is being upgraded.
+///
+/// `MaintenanceNotice` is serialized as the height in the little-endian byte order.
+///
+/// The notice is submitted as a usual `RawTransaction` with the reserved
+/// [`MAINTENANCE_SERVICE_ID`] service identifier, signed by the service key
+/// of the validator. After the notice is committed, the validator is in maintenance
+/// starting from the next height until the height of the notice. During the maintenance
+/// the validator is skipped by the leader election, so the network does not wait
+/// for its proposes. A notice with the height not greater than the next height finishes
+/// the maintenance of the validator early.
+///
+/// The maintenance is rejected if the validators remaining in the consensus
+/// do not have the majority of the voting power.
+///
+/// [`MAINTENANCE_SERVICE_ID`]: constant.MAINTENANCE_SERVICE_ID.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceNotice {
+    until: Height,
+}
+
+impl MaintenanceNotice {
+    /// Creates a notice of the maintenance lasting until the given height.
+    pub fn new(until: Height) -> Self {
+        Self { until }
+    }
+
+    /// Extracts maintenance notice from the given raw transaction.
+    pub fn from_raw(raw: &RawTransaction) -> Result<Self, Error> {
+        ensure!(
+            raw.service_id == MAINTENANCE_SERVICE_ID,
+            "Transaction with service id {} is not a maintenance notice.",
+            raw.service_id
+        );
+        ensure!(
+            raw.service_transaction.transaction_id == 0,
+            "Unknown maintenance transaction id {}.",
+            raw.service_transaction.transaction_id
+        );
+        Self::decode(&raw.service_transaction.payload)
+    }
+
+    /// Returns the height at which the maintenance ends.
+    pub fn until(&self) -> Height {
+        self.until
+    }
+}
+
+impl BinaryForm for MaintenanceNotice {
+    fn encode(&self) -> Result<Vec<u8>, Error> {
+        let mut buffer = vec![0; mem::size_of::<u64>()];
+        LittleEndian::write_u64(&mut buffer, self.until.into());
+        Ok(buffer)
+    }
+
+    fn decode(buffer: &[u8]) -> Result<Self, Error> {
+        ensure!(
+            buffer.len() == mem::size_of::<u64>(),
+            "Wrong buffer length in MaintenanceNotice deserialization."
+        );
+        Ok(Self::new(Height(LittleEndian::read_u64(buffer))))
+    }
+}
+
+impl From<MaintenanceNotice> for ServiceTransaction {
+    fn from(notice: MaintenanceNotice) -> Self {
+        let payload = notice
+            .encode()
+            .expect("Couldn't serialize maintenance notice.");
+        ServiceTransaction::from_raw_unchecked(0, payload)
+    }
+}
@@ -44,12 +44,20 @@ use crate::storage::StorageValue;
 
 pub(crate) use self::{authorization::SignedMessage, helpers::HexStringRepresentation};
 pub use self::{
+    evidence::Evidence,
     helpers::{to_hex_string, BinaryForm},
+    key_rotation::{KeyRotation, ValidatorKeyKind},
+    maintenance::MaintenanceNotice,
+    multisig::{MultiSigned, MULTISIG_MAX_KEYS},
     protocol::*,
 };
 
 mod authorization;
+mod evidence;
 mod helpers;
+mod key_rotation;
+mod maintenance;
+mod multisig;
 mod protocol;
 #[cfg(test)]
 mod tests;
@@ -57,12 +65,41 @@ mod tests;
 /// Version of the protocol. Different versions are incompatible.
 pub const PROTOCOL_MAJOR_VERSION: u8 = 1;
 pub(crate) const RAW_TRANSACTION_HEADER: usize = mem::size_of::<u16>() * 2;
+/// Value of the service identifier reserved to mark transactions with a nonce.
+///
+/// Transactions without a nonce are serialized as `service_id | transaction_id | payload`.
+/// If a nonce is present, the serialized transaction is prefixed by this marker and
+/// the nonce: `marker | nonce | service_id | transaction_id | payload`. Thus, services
+/// cannot use this value as their identifier.
+pub const RAW_TRANSACTION_NONCE_MARKER: u16 = u16::max_value();
+/// Value of the service identifier reserved for batches of transactions.
+///
+/// See [`Batch`](struct.Batch.html) for details.
+pub const BATCH_SERVICE_ID: u16 = RAW_TRANSACTION_NONCE_MARKER - 1;
+/// Value of the service identifier reserved for multisignature envelopes of transactions.
+///
+/// See [`MultiSigned`](struct.MultiSigned.html) for details.
+pub const MULTISIG_SERVICE_ID: u16 = BATCH_SERVICE_ID - 1;
+/// Value of the service identifier reserved for evidence of equivocation of validators.
+///
+/// See [`Evidence`](enum.Evidence.html) for details.
+pub const EVIDENCE_SERVICE_ID: u16 = MULTISIG_SERVICE_ID - 1;
+/// Value of the service identifier reserved for rotations of validator keys.
+///
+/// See [`KeyRotation`](struct.KeyRotation.html) for details.
+pub const KEY_ROTATION_SERVICE_ID: u16 = EVIDENCE_SERVICE_ID - 1;
+/// Value of the service identifier reserved for maintenance notices of validators.
+///
+/// See [`MaintenanceNotice`](struct.MaintenanceNotice.html) for details.
+pub const MAINTENANCE_SERVICE_ID: u16 = KEY_ROTATION_SERVICE_ID - 1;
 
 /// Transaction raw buffer.
 /// This struct is used to transfer transactions in network.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct RawTransaction {
     service_id: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    nonce: Option<u64>,
     service_transaction: ServiceTransaction,
 }
 
@@ -96,6 +133,26 @@ impl RawTransaction {
     pub fn new(service_id: u16, service_transaction: ServiceTransaction) -> RawTransaction {
         RawTransaction {
             service_id,
+            nonce: None,
+            service_transaction,
+        }
+    }
+
+    /// Creates a new instance of RawTransaction with the author nonce.
+    ///
+    /// Transactions with a nonce are executed only if the nonce is equal to the
+    /// number of transactions with nonces previously executed from the same author.
+    /// See [`Schema::account_nonces`] for details.
+    ///
+    /// [`Schema::account_nonces`]: ../blockchain/struct.Schema.html#method.account_nonces
+    pub fn new_with_nonce(
+        service_id: u16,
+        nonce: u64,
+        service_transaction: ServiceTransaction,
+    ) -> RawTransaction {
+        RawTransaction {
+            service_id,
+            nonce: Some(nonce),
             service_transaction,
         }
     }
@@ -109,27 +166,71 @@ impl RawTransaction {
     pub fn service_id(&self) -> u16 {
         self.service_id
     }
+
+    /// Returns the author nonce specified for current transaction, if any.
+    pub fn nonce(&self) -> Option<u64> {
+        self.nonce
+    }
+
+    /// Verifies signatures of the multisignature envelope contained in the transaction.
+    /// Transactions of other services are always accepted.
+    ///
+    /// See [`MultiSigned`](struct.MultiSigned.html) for details.
+    pub fn verify_envelope(&self) -> Result<(), Error> {
+        if self.service_id == MULTISIG_SERVICE_ID {
+            MultiSigned::from_raw(self)?.verify()?;
+        }
+        Ok(())
+    }
 }
 
 impl BinaryForm for RawTransaction {
     fn encode(&self) -> Result<Vec<u8>, Error> {
-        let mut buffer = vec![0; mem::size_of::<u16>()];
-        LittleEndian::write_u16(&mut buffer[0..2], self.service_id);
+        ensure!(
+            self.service_id != RAW_TRANSACTION_NONCE_MARKER,
+            "Service id {} is reserved.",
+            RAW_TRANSACTION_NONCE_MARKER
+        );
+        let mut buffer = Vec::new();
+        if let Some(nonce) = self.nonce {
+            let mut header = [0; mem::size_of::<u16>() + mem::size_of::<u64>()];
+            LittleEndian::write_u16(&mut header[0..2], RAW_TRANSACTION_NONCE_MARKER);
+            LittleEndian::write_u64(&mut header[2..10], nonce);
+            buffer.extend_from_slice(&header);
+        }
+        let mut service_id = [0; mem::size_of::<u16>()];
+        LittleEndian::write_u16(&mut service_id, self.service_id);
+        buffer.extend_from_slice(&service_id);
         let value = self.service_transaction.encode()?;
         buffer.extend_from_slice(&value);
         Ok(buffer)
     }
 
     /// Converts a serialized byte array into a transaction.
-    fn decode(buffer: &[u8]) -> Result<Self, Error> {
+    fn decode(mut buffer: &[u8]) -> Result<Self, Error> {
         ensure!(
             buffer.len() >= mem::size_of::<u16>(),
             "Buffer too short in RawTransaction deserialization."
         );
+        let mut nonce = None;
+        if LittleEndian::read_u16(&buffer[0..2]) == RAW_TRANSACTION_NONCE_MARKER {
+            ensure!(
+                buffer.len() >= mem::size_of::<u16>() * 2 + mem::size_of::<u64>(),
+                "Buffer too short in RawTransaction deserialization."
+            );
+            nonce = Some(LittleEndian::read_u64(&buffer[2..10]));
+            buffer = &buffer[10..];
+            ensure!(
+                LittleEndian::read_u16(&buffer[0..2]) != RAW_TRANSACTION_NONCE_MARKER,
+                "Service id {} is reserved.",
+                RAW_TRANSACTION_NONCE_MARKER
+            );
+        }
         let service_id = LittleEndian::read_u16(&buffer[0..2]);
         let service_transaction = ServiceTransaction::decode(&buffer[2..])?;
         Ok(RawTransaction {
             service_id,
+            nonce,
             service_transaction,
         })
     }
@@ -157,6 +258,132 @@ impl BinaryForm for ServiceTransaction {
     }
 }
 
+/// Batch of service transactions executed atomically under one signature.
+///
+/// A batch is sent as a usual `RawTransaction` with the reserved [`BATCH_SERVICE_ID`]
+/// service identifier, so it shares the pool, the nonce and the lifecycle of other
+/// transactions. Transactions of the batch are executed in order; if any of them fails,
+/// changes made by all transactions of the batch are rolled back.
+///
+/// Results of the batch transactions are recorded in the `transaction_results` index
+/// under hashes returned by [`Batch::transaction_hash`]. If the batch fails, only
+/// the result of the failed transaction is recorded, since the changes of
+/// the preceding transactions are rolled back.
+///
+/// Batches cannot be nested, and transactions inside a batch cannot have nonces
+/// or be wrapped into multisignature envelopes.
+///
+/// [`BATCH_SERVICE_ID`]: constant.BATCH_SERVICE_ID.html
+/// [`Batch::transaction_hash`]: #method.transaction_hash
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Batch {
+    transactions: Vec<RawTransaction>,
+}
+
+impl Batch {
+    /// Creates a new batch from the given transactions.
+    pub fn new(transactions: Vec<RawTransaction>) -> Self {
+        Batch { transactions }
+    }
+
+    /// Returns transactions of the batch.
+    pub fn transactions(&self) -> &[RawTransaction] {
+        &self.transactions
+    }
+
+    /// Extracts a batch from the given raw transaction.
+    pub fn from_raw(raw: &RawTransaction) -> Result<Self, Error> {
+        ensure!(
+            raw.service_id == BATCH_SERVICE_ID,
+            "Transaction with service id {} is not a batch.",
+            raw.service_id
+        );
+        ensure!(
+            raw.service_transaction.transaction_id == 0,
+            "Unknown batch transaction id {}.",
+            raw.service_transaction.transaction_id
+        );
+        Self::decode(&raw.service_transaction.payload)
+    }
+
+    /// Returns the hash under which the result of the transaction with the given index
+    /// in the batch is recorded.
+    pub fn transaction_hash(batch_hash: &Hash, index: usize) -> Hash {
+        let mut buffer = batch_hash.as_ref().to_vec();
+        let mut index_bytes = [0; mem::size_of::<u32>()];
+        LittleEndian::write_u32(&mut index_bytes, index as u32);
+        buffer.extend_from_slice(&index_bytes);
+        hash(&buffer)
+    }
+}
+
+impl BinaryForm for Batch {
+    fn encode(&self) -> Result<Vec<u8>, Error> {
+        ensure!(!self.transactions.is_empty(), "Batch cannot be empty.");
+        let mut buffer = Vec::new();
+        for transaction in &self.transactions {
+            ensure!(
+                transaction.nonce.is_none(),
+                "Transactions inside a batch cannot have nonces."
+            );
+            ensure!(
+                transaction.service_id != BATCH_SERVICE_ID,
+                "Batches cannot be nested."
+            );
+            ensure!(
+                transaction.service_id != MULTISIG_SERVICE_ID,
+                "Multisignature envelopes cannot be a part of a batch."
+            );
+            let value = transaction.encode()?;
+            let mut len = [0; mem::size_of::<u32>()];
+            LittleEndian::write_u32(&mut len, value.len() as u32);
+            buffer.extend_from_slice(&len);
+            buffer.extend_from_slice(&value);
+        }
+        Ok(buffer)
+    }
+
+    fn decode(mut buffer: &[u8]) -> Result<Self, Error> {
+        let mut transactions = Vec::new();
+        while !buffer.is_empty() {
+            ensure!(
+                buffer.len() >= mem::size_of::<u32>(),
+                "Buffer too short in Batch deserialization."
+            );
+            let len = LittleEndian::read_u32(&buffer[0..4]) as usize;
+            buffer = &buffer[4..];
+            ensure!(
+                buffer.len() >= len,
+                "Buffer too short in Batch deserialization."
+            );
+            let transaction = RawTransaction::decode(&buffer[..len])?;
+            ensure!(
+                transaction.nonce.is_none(),
+                "Transactions inside a batch cannot have nonces."
+            );
+            ensure!(
+                transaction.service_id != BATCH_SERVICE_ID,
+                "Batches cannot be nested."
+            );
+            ensure!(
+                transaction.service_id != MULTISIG_SERVICE_ID,
+                "Multisignature envelopes cannot be a part of a batch."
+            );
+            transactions.push(transaction);
+            buffer = &buffer[len..];
+        }
+        ensure!(!transactions.is_empty(), "Batch cannot be empty.");
+        Ok(Batch { transactions })
+    }
+}
+
+impl From<Batch> for ServiceTransaction {
+    fn from(batch: Batch) -> Self {
+        let payload = batch.encode().expect("Couldn't serialize batch.");
+        ServiceTransaction::from_raw_unchecked(0, payload)
+    }
+}
+
 /// Wraps a `Payload` together with the corresponding `SignedMessage`.
 ///
 /// Usually one wants to work with fully parsed messages (i.e., `Payload`). However, occasionally
@@ -0,0 +1,324 @@
+// Copyright 2019 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use byteorder::{ByteOrder, LittleEndian};
+use failure::Error;
+
+use std::mem;
+
+use super::{
+    BinaryForm, RawTransaction, ServiceTransaction, BATCH_SERVICE_ID, MULTISIG_SERVICE_ID,
+};
+use crate::crypto::{self, PublicKey, SecretKey, Signature, PUBLIC_KEY_LENGTH, SIGNATURE_LENGTH};
+
+/// Maximum number of keys in the policy of a `MultiSigned` envelope.
+pub const MULTISIG_MAX_KEYS: usize = u8::max_value() as usize;
+
+/// Envelope authorizing the payload by signatures of at least `threshold`
+/// of the listed `keys` (M-of-N policy).
+///
+/// `MultiSigned` can be serialized into a byte buffer with the following layout:
+///
+/// | Position             | Stored data                         |
+/// | - - - - - - - - - -  | - - - - - - - - - - - - - - - - - - |
+/// | `0`                  | threshold `M`                       |
+/// | `1`                  | number of keys `N`                  |
+/// | `2..2+32*N`          | public keys of the policy           |
+/// | `K..K+4`             | payload length `L`                  |
+/// | `K+4..K+4+L`         | payload                             |
+/// | `K+4+L..`            | signatures as `(key index, signature)` pairs, 65 bytes each |
+///
+/// Here `K` is `2 + 32 * N`.
+///
+/// Each signature covers the policy and the payload, i.e., all data before
+/// the signatures section. Signatures are ordered by the key index and each key
+/// can sign the envelope only once.
+///
+/// Deserialization checks the structure of the envelope, but not the signatures.
+/// Use [`verify`] to check that the envelope is authorized.
+///
+/// Transactions are wrapped into the envelope by sending `MultiSigned<RawTransaction>`
+/// as a usual `RawTransaction` with the reserved [`MULTISIG_SERVICE_ID`] service
+/// identifier. Signatures of such transactions are verified by the node
+/// in the transaction verification thread pool, and keys of the signers are available
+/// to the wrapped transaction via [`TransactionContext::signers`].
+///
+/// [`verify`]: #method.verify
+/// [`MULTISIG_SERVICE_ID`]: constant.MULTISIG_SERVICE_ID.html
+/// [`TransactionContext::signers`]: ../blockchain/struct.TransactionContext.html#method.signers
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct MultiSigned<T> {
+    threshold: u8,
+    keys: Vec<PublicKey>,
+    payload: T,
+    signatures: Vec<(u8, Signature)>,
+}
+
+impl<T: BinaryForm> MultiSigned<T> {
+    /// Creates a new envelope with the given M-of-N policy and without signatures.
+    pub fn new(threshold: u8, keys: Vec<PublicKey>, payload: T) -> Self {
+        MultiSigned {
+            threshold,
+            keys,
+            payload,
+            signatures: Vec::new(),
+        }
+    }
+
+    /// Returns the minimal number of signatures required to authorize the payload.
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// Returns public keys of the policy.
+    pub fn keys(&self) -> &[PublicKey] {
+        &self.keys
+    }
+
+    /// Returns reference to the payload.
+    pub fn payload(&self) -> &T {
+        &self.payload
+    }
+
+    /// Converts the envelope into the payload.
+    pub fn into_payload(self) -> T {
+        self.payload
+    }
+
+    /// Returns signatures together with indexes of the corresponding policy keys.
+    pub fn signatures(&self) -> &[(u8, Signature)] {
+        &self.signatures
+    }
+
+    /// Returns public keys of the policy which have signed the envelope.
+    ///
+    /// Signatures are not verified by this method.
+    pub fn signers(&self) -> Vec<PublicKey> {
+        self.signatures
+            .iter()
+            .map(|&(index, _)| self.keys[index as usize])
+            .collect()
+    }
+
+    /// Returns the data which should be signed by the policy keys.
+    pub fn signing_data(&self) -> Result<Vec<u8>, Error> {
+        check_policy(self.threshold, &self.keys)?;
+        let mut buffer = Vec::new();
+        buffer.push(self.threshold);
+        buffer.push(self.keys.len() as u8);
+        for key in &self.keys {
+            buffer.extend_from_slice(key.as_ref());
+        }
+        let payload = self.payload.encode()?;
+        let mut len = [0; mem::size_of::<u32>()];
+        LittleEndian::write_u32(&mut len, payload.len() as u32);
+        buffer.extend_from_slice(&len);
+        buffer.extend_from_slice(&payload);
+        Ok(buffer)
+    }
+
+    /// Signs the envelope with the secret key corresponding to the given policy key.
+    /// A previous signature of the same key is replaced.
+    pub fn sign(&mut self, public_key: &PublicKey, secret_key: &SecretKey) -> Result<(), Error> {
+        let index = self
+            .keys
+            .iter()
+            .position(|key| key == public_key)
+            .ok_or_else(|| format_err!("Key {:?} is not a part of the policy.", public_key))?;
+        let signature = crypto::sign(&self.signing_data()?, secret_key);
+        self.add_signature(index as u8, signature)
+    }
+
+    /// Adds the signature of the policy key with the given index, for example,
+    /// a signature produced by a co-signer from the [`signing_data`].
+    /// A previous signature of the same key is replaced.
+    ///
+    /// [`signing_data`]: #method.signing_data
+    pub fn add_signature(&mut self, index: u8, signature: Signature) -> Result<(), Error> {
+        ensure!(
+            (index as usize) < self.keys.len(),
+            "Key index {} is out of the policy bounds.",
+            index
+        );
+        match self.signatures.binary_search_by_key(&index, |&(i, _)| i) {
+            Ok(position) => self.signatures[position].1 = signature,
+            Err(position) => self.signatures.insert(position, (index, signature)),
+        }
+        Ok(())
+    }
+
+    /// Checks that the policy is correct and the envelope is signed by at least
+    /// `threshold` keys of the policy with valid signatures.
+    pub fn verify(&self) -> Result<(), Error> {
+        check_signatures(&self.keys, &self.signatures)?;
+        ensure!(
+            self.signatures.len() >= self.threshold as usize,
+            "Not enough signatures: {} of {} required.",
+            self.signatures.len(),
+            self.threshold
+        );
+        let data = self.signing_data()?;
+        for &(index, ref signature) in &self.signatures {
+            if !crypto::verify(signature, &data, &self.keys[index as usize]) {
+                bail!("Cannot verify signature of the key with index {}.", index);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl MultiSigned<RawTransaction> {
+    /// Extracts an envelope from the given raw transaction.
+    ///
+    /// The wrapped transaction cannot have a nonce and cannot be a batch
+    /// or another envelope.
+    pub fn from_raw(raw: &RawTransaction) -> Result<Self, Error> {
+        ensure!(
+            raw.service_id == MULTISIG_SERVICE_ID,
+            "Transaction with service id {} is not a multisignature envelope.",
+            raw.service_id
+        );
+        ensure!(
+            raw.service_transaction.transaction_id == 0,
+            "Unknown multisignature transaction id {}.",
+            raw.service_transaction.transaction_id
+        );
+        let envelope = Self::decode(&raw.service_transaction.payload)?;
+        let transaction = envelope.payload();
+        ensure!(
+            transaction.nonce.is_none(),
+            "Transactions inside a multisignature envelope cannot have nonces."
+        );
+        ensure!(
+            transaction.service_id != MULTISIG_SERVICE_ID
+                && transaction.service_id != BATCH_SERVICE_ID,
+            "Service id {} cannot be used inside a multisignature envelope.",
+            transaction.service_id
+        );
+        Ok(envelope)
+    }
+}
+
+impl<T: BinaryForm> BinaryForm for MultiSigned<T> {
+    fn encode(&self) -> Result<Vec<u8>, Error> {
+        check_signatures(&self.keys, &self.signatures)?;
+        let mut buffer = self.signing_data()?;
+        for &(index, ref signature) in &self.signatures {
+            buffer.push(index);
+            buffer.extend_from_slice(signature.as_ref());
+        }
+        Ok(buffer)
+    }
+
+    fn decode(buffer: &[u8]) -> Result<Self, Error> {
+        ensure!(
+            buffer.len() >= 2,
+            "Buffer too short in MultiSigned deserialization."
+        );
+        let threshold = buffer[0];
+        let keys_count = buffer[1] as usize;
+        let keys_end = 2 + keys_count * PUBLIC_KEY_LENGTH;
+        ensure!(
+            buffer.len() >= keys_end + mem::size_of::<u32>(),
+            "Buffer too short in MultiSigned deserialization."
+        );
+        let keys = buffer[2..keys_end]
+            .chunks(PUBLIC_KEY_LENGTH)
+            .map(|key| PublicKey::from_slice(key).expect("Couldn't read PublicKey"))
+            .collect::<Vec<_>>();
+        check_policy(threshold, &keys)?;
+
+        let len = LittleEndian::read_u32(&buffer[keys_end..keys_end + 4]) as usize;
+        let payload_start = keys_end + mem::size_of::<u32>();
+        ensure!(
+            buffer.len() - payload_start >= len,
+            "Buffer too short in MultiSigned deserialization."
+        );
+        let payload = T::decode(&buffer[payload_start..payload_start + len])?;
+
+        let signatures_buffer = &buffer[payload_start + len..];
+        ensure!(
+            signatures_buffer.len() % (1 + SIGNATURE_LENGTH) == 0,
+            "Incorrect signatures length in MultiSigned deserialization."
+        );
+        let signatures = signatures_buffer
+            .chunks(1 + SIGNATURE_LENGTH)
+            .map(|chunk| {
+                let signature =
+                    Signature::from_slice(&chunk[1..]).expect("Couldn't read signature");
+                (chunk[0], signature)
+            })
+            .collect::<Vec<_>>();
+        check_signatures(&keys, &signatures)?;
+
+        Ok(MultiSigned {
+            threshold,
+            keys,
+            payload,
+            signatures,
+        })
+    }
+}
+
+impl From<MultiSigned<RawTransaction>> for ServiceTransaction {
+    fn from(envelope: MultiSigned<RawTransaction>) -> Self {
+        let payload = envelope
+            .encode()
+            .expect("Couldn't serialize multisignature envelope.");
+        ServiceTransaction::from_raw_unchecked(0, payload)
+    }
+}
+
+/// Checks that the M-of-N policy is satisfiable and does not contain duplicate keys.
+fn check_policy(threshold: u8, keys: &[PublicKey]) -> Result<(), Error> {
+    ensure!(
+        keys.len() <= MULTISIG_MAX_KEYS,
+        "Too many keys in the policy: {}, maximum is {}.",
+        keys.len(),
+        MULTISIG_MAX_KEYS
+    );
+    ensure!(threshold > 0, "Threshold of the policy cannot be zero.");
+    ensure!(
+        threshold as usize <= keys.len(),
+        "Threshold {} is greater than the number of keys {}.",
+        threshold,
+        keys.len()
+    );
+    let mut sorted_keys = keys.to_vec();
+    sorted_keys.sort();
+    sorted_keys.dedup();
+    ensure!(
+        sorted_keys.len() == keys.len(),
+        "Policy contains duplicate keys."
+    );
+    Ok(())
+}
+
+/// Checks that signatures are ordered by unique key indexes within the policy bounds.
+fn check_signatures(keys: &[PublicKey], signatures: &[(u8, Signature)]) -> Result<(), Error> {
+    let mut previous = None;
+    for &(index, _) in signatures {
+        ensure!(
+            (index as usize) < keys.len(),
+            "Key index {} is out of the policy bounds.",
+            index
+        );
+        ensure!(
+            previous.map_or(true, |previous| previous < index),
+            "Signatures should be ordered by unique key indexes."
+        );
+        previous = Some(index);
+    }
+    Ok(())
+}
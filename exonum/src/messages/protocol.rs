@@ -149,6 +149,7 @@ impl Status {
 /// ### Validation
 /// The message is ignored if it
 ///     * contains incorrect `prev_hash`
+///     * contains `time` earlier than the time of the previous block
 ///     * is sent by non-leader
 ///     * contains already committed transactions
 ///     * is already known
@@ -174,6 +175,8 @@ pub struct Propose {
     prev_hash: Hash,
     /// The list of transactions to include in the next block.
     transactions: Vec<Hash>,
+    /// Time of the next block.
+    time: DateTime<Utc>,
 }
 
 impl Propose {
@@ -184,6 +187,7 @@ impl Propose {
         round: Round,
         prev_hash: &Hash,
         transactions: &[Hash],
+        time: DateTime<Utc>,
     ) -> Self {
         Self {
             validator,
@@ -191,6 +195,7 @@ impl Propose {
             round,
             prev_hash: *prev_hash,
             transactions: transactions.to_vec(),
+            time,
         }
     }
 
@@ -214,6 +219,10 @@ impl Propose {
     pub fn transactions(&self) -> &[Hash] {
         &self.transactions
     }
+    /// Time of the next block.
+    pub fn time(&self) -> DateTime<Utc> {
+        self.time
+    }
 }
 
 /// Pre-vote for a new block.
@@ -912,9 +921,18 @@ impl Message {
     }
 
     /// Checks buffer and return instance of `Message`.
+    ///
+    /// Signatures of multisignature transactions are verified as well,
+    /// see [`RawTransaction::verify_envelope`] for details.
+    ///
+    /// [`RawTransaction::verify_envelope`]: struct.RawTransaction.html#method.verify_envelope
     pub fn from_raw_buffer(buffer: Vec<u8>) -> Result<Message, failure::Error> {
         let signed = SignedMessage::from_raw_buffer(buffer)?;
-        Self::deserialize(signed)
+        let message = Self::deserialize(signed)?;
+        if let Message::Service(Service::RawTransaction(ref transaction)) = message {
+            transaction.verify_envelope()?;
+        }
+        Ok(message)
     }
 
     /// Creates a new raw transaction message.
@@ -935,6 +953,26 @@ impl Message {
         let raw_tx = RawTransaction::new(service_id, set);
         Self::concrete(raw_tx, public_key, secret_key)
     }
+
+    /// Creates a new raw transaction message with the author nonce.
+    ///
+    /// # Panics
+    ///
+    /// This method can panic on serialization failure.
+    pub fn sign_transaction_with_nonce<T>(
+        transaction: T,
+        service_id: u16,
+        nonce: u64,
+        public_key: PublicKey,
+        secret_key: &SecretKey,
+    ) -> Signed<RawTransaction>
+    where
+        T: Into<ServiceTransaction>,
+    {
+        let set: ServiceTransaction = transaction.into();
+        let raw_tx = RawTransaction::new_with_nonce(service_id, nonce, set);
+        Self::concrete(raw_tx, public_key, secret_key)
+    }
 }
 
 impl Requests {
@@ -1,15 +1,19 @@
+use byteorder::{ByteOrder, LittleEndian};
 use chrono::Utc;
 use hex::FromHex;
 
 use super::{
-    BinaryForm, BlockResponse, Message, Precommit, ProtocolMessage, RawTransaction,
-    ServiceTransaction, Signed, SignedMessage, Status, TransactionsResponse,
-    RAW_TRANSACTION_EMPTY_SIZE, TRANSACTION_RESPONSE_EMPTY_SIZE,
+    Batch, BinaryForm, BlockResponse, Evidence, KeyRotation, MaintenanceNotice, Message,
+    MultiSigned, Precommit, Prevote, ProtocolMessage, RawTransaction, ServiceTransaction, Signed,
+    SignedMessage, Status, TransactionsResponse, ValidatorKeyKind, BATCH_SERVICE_ID,
+    EVIDENCE_SERVICE_ID, KEY_ROTATION_SERVICE_ID, MAINTENANCE_SERVICE_ID, MULTISIG_SERVICE_ID,
+    RAW_TRANSACTION_EMPTY_SIZE, RAW_TRANSACTION_NONCE_MARKER, TRANSACTION_RESPONSE_EMPTY_SIZE,
 };
 use crate::blockchain::{Block, BlockProof};
 use crate::crypto::{gen_keypair, hash, PublicKey, SecretKey};
 use crate::helpers::{Height, Round, ValidatorId};
 use crate::proto;
+use crate::storage::StorageValue;
 
 #[test]
 fn test_block_response_empty_size() {
@@ -86,6 +90,8 @@ fn test_block() {
         &hash(&[1]),
         &hash(&txs),
         &hash(&[3]),
+        &hash(&[4]),
+        ts,
     );
 
     let precommits = vec![
@@ -231,3 +237,294 @@ fn test_raw_transaction_small_size() {
     assert_eq!(tx.service_id, 0);
     assert_eq!(tx.service_transaction.transaction_id, 0);
 }
+
+#[test]
+fn test_raw_transaction_with_nonce() {
+    let set = ServiceTransaction::from_raw_unchecked(1, vec![1, 2, 3]);
+    let tx = RawTransaction::new_with_nonce(128, 5, set.clone());
+    let bytes = tx.encode().unwrap();
+    assert_eq!(
+        bytes.len(),
+        RawTransaction::new(128, set).encode().unwrap().len() + 10
+    );
+
+    let decoded = RawTransaction::decode(&bytes).unwrap();
+    assert_eq!(decoded, tx);
+    assert_eq!(decoded.nonce(), Some(5));
+    assert_eq!(decoded.service_id(), 128);
+
+    assert!(RawTransaction::decode(&bytes[..11]).is_err());
+    let reserved = RawTransaction::new(
+        RAW_TRANSACTION_NONCE_MARKER,
+        ServiceTransaction::from_raw_unchecked(0, vec![]),
+    );
+    assert!(reserved.encode().is_err());
+}
+
+#[test]
+fn test_batch_round_trip() {
+    let transactions = vec![
+        RawTransaction::new(1, ServiceTransaction::from_raw_unchecked(0, vec![1, 2])),
+        RawTransaction::new(2, ServiceTransaction::from_raw_unchecked(3, vec![])),
+    ];
+    let batch = Batch::new(transactions.clone());
+    let bytes = batch.encode().unwrap();
+    assert_eq!(Batch::decode(&bytes).unwrap(), batch);
+
+    let (public_key, secret_key) = gen_keypair();
+    let signed =
+        Message::sign_transaction(batch.clone(), BATCH_SERVICE_ID, public_key, &secret_key);
+    assert_eq!(Batch::from_raw(signed.payload()).unwrap(), batch);
+    assert_ne!(
+        Batch::transaction_hash(&signed.hash(), 0),
+        Batch::transaction_hash(&signed.hash(), 1)
+    );
+
+    assert!(Batch::new(vec![]).encode().is_err());
+    assert!(Batch::decode(&[]).is_err());
+    assert!(Batch::decode(&bytes[..bytes.len() - 1]).is_err());
+    assert!(Batch::from_raw(&transactions[0]).is_err());
+
+    let nested = RawTransaction::new(BATCH_SERVICE_ID, batch.into());
+    assert!(Batch::new(vec![nested]).encode().is_err());
+    let with_nonce =
+        RawTransaction::new_with_nonce(1, 0, ServiceTransaction::from_raw_unchecked(0, vec![]));
+    assert!(Batch::new(vec![with_nonce]).encode().is_err());
+}
+
+#[test]
+fn test_multisigned_round_trip() {
+    let keypairs = (0..3).map(|_| gen_keypair()).collect::<Vec<_>>();
+    let keys = keypairs.iter().map(|&(key, _)| key).collect::<Vec<_>>();
+    let transaction =
+        RawTransaction::new(1, ServiceTransaction::from_raw_unchecked(0, vec![1, 2, 3]));
+
+    let mut envelope = MultiSigned::new(2, keys.clone(), transaction.clone());
+    envelope.sign(&keypairs[2].0, &keypairs[2].1).unwrap();
+    assert!(envelope.verify().is_err());
+    envelope.sign(&keypairs[0].0, &keypairs[0].1).unwrap();
+    envelope.verify().unwrap();
+    assert_eq!(envelope.signers(), vec![keys[0], keys[2]]);
+
+    let bytes = envelope.encode().unwrap();
+    let decoded = MultiSigned::<RawTransaction>::decode(&bytes).unwrap();
+    assert_eq!(decoded, envelope);
+    decoded.verify().unwrap();
+
+    // Signature of another key is rejected.
+    let mut forged = envelope.clone();
+    let signature = forged.signatures()[0].1;
+    forged.add_signature(1, signature).unwrap();
+    assert!(forged.verify().is_err());
+
+    // Incorrect policies are rejected.
+    assert!(MultiSigned::new(0, keys.clone(), transaction.clone())
+        .encode()
+        .is_err());
+    assert!(MultiSigned::new(4, keys.clone(), transaction.clone())
+        .encode()
+        .is_err());
+    assert!(
+        MultiSigned::new(1, vec![keys[0], keys[0]], transaction.clone())
+            .encode()
+            .is_err()
+    );
+    assert!(MultiSigned::<RawTransaction>::decode(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn test_multisigned_transaction_verification() {
+    let (author, author_key) = gen_keypair();
+    let keypairs = (0..2).map(|_| gen_keypair()).collect::<Vec<_>>();
+    let keys = keypairs.iter().map(|&(key, _)| key).collect::<Vec<_>>();
+    let transaction = RawTransaction::new(1, ServiceTransaction::from_raw_unchecked(0, vec![]));
+
+    let mut envelope = MultiSigned::new(2, keys, transaction);
+    envelope.sign(&keypairs[0].0, &keypairs[0].1).unwrap();
+    let under_signed =
+        Message::sign_transaction(envelope.clone(), MULTISIG_SERVICE_ID, author, &author_key);
+    assert!(Message::from_raw_buffer(under_signed.serialize()).is_err());
+
+    envelope.sign(&keypairs[1].0, &keypairs[1].1).unwrap();
+    let signed =
+        Message::sign_transaction(envelope.clone(), MULTISIG_SERVICE_ID, author, &author_key);
+    assert_eq!(MultiSigned::from_raw(signed.payload()).unwrap(), envelope);
+    Message::from_raw_buffer(signed.serialize()).unwrap();
+
+    let nested = RawTransaction::new(MULTISIG_SERVICE_ID, envelope.clone().into());
+    let mut nested_envelope = MultiSigned::new(1, vec![author], nested);
+    nested_envelope.sign(&author, &author_key).unwrap();
+    let nested_raw = RawTransaction::new(MULTISIG_SERVICE_ID, nested_envelope.into());
+    assert!(MultiSigned::from_raw(&nested_raw).is_err());
+
+    let in_batch = RawTransaction::new(MULTISIG_SERVICE_ID, envelope.into());
+    assert!(Batch::new(vec![in_batch]).encode().is_err());
+}
+
+#[test]
+fn test_evidence_serialization() {
+    let (public_key, secret_key) = gen_keypair();
+    let prevote = |propose_hash| {
+        Message::concrete(
+            Prevote::new(ValidatorId(1), Height(2), Round(3), &propose_hash, Round(0)),
+            public_key,
+            &secret_key,
+        )
+    };
+    let (first, second) = (prevote(hash(&[1])), prevote(hash(&[2])));
+
+    let evidence = Evidence::prevotes(first.clone(), second.clone()).unwrap();
+    assert_eq!(
+        Evidence::prevotes(second.clone(), first.clone()).unwrap(),
+        evidence
+    );
+    assert_eq!(evidence.validator(), ValidatorId(1));
+    assert_eq!(evidence.author(), public_key);
+    assert_eq!(evidence.height(), Height(2));
+    assert_eq!(evidence.round(), Round(3));
+    assert_eq!(
+        Evidence::decode(&evidence.encode().unwrap()).unwrap(),
+        evidence
+    );
+    assert_eq!(
+        Evidence::from_bytes(evidence.clone().into_bytes().into()),
+        evidence
+    );
+
+    let tx = Message::sign_transaction(
+        evidence.clone(),
+        EVIDENCE_SERVICE_ID,
+        public_key,
+        &secret_key,
+    );
+    assert_eq!(Evidence::from_raw(tx.payload()).unwrap(), evidence);
+    let json = serde_json::to_string(&evidence).unwrap();
+    assert_eq!(serde_json::from_str::<Evidence>(&json).unwrap(), evidence);
+
+    // Messages for the same propose do not conflict.
+    assert!(Evidence::prevotes(first.clone(), first.clone()).is_err());
+    // Messages of different validators do not conflict.
+    let (other_key, other_secret_key) = gen_keypair();
+    let other = Message::concrete(
+        Prevote::new(ValidatorId(1), Height(2), Round(3), &hash(&[2]), Round(0)),
+        other_key,
+        &other_secret_key,
+    );
+    assert!(Evidence::prevotes(first.clone(), other).is_err());
+    // Messages of different rounds do not conflict.
+    let other_round = Message::concrete(
+        Prevote::new(ValidatorId(1), Height(2), Round(4), &hash(&[2]), Round(0)),
+        public_key,
+        &secret_key,
+    );
+    assert!(Evidence::prevotes(first, other_round).is_err());
+}
+
+#[test]
+fn test_evidence_with_precommits() {
+    let (public_key, secret_key) = gen_keypair();
+    let precommit = |block_hash| {
+        Message::concrete(
+            Precommit::new(
+                ValidatorId(0),
+                Height(1),
+                Round(1),
+                &hash(&[0]),
+                &block_hash,
+                Utc::now(),
+            ),
+            public_key,
+            &secret_key,
+        )
+    };
+
+    let evidence = Evidence::precommits(precommit(hash(&[1])), precommit(hash(&[2]))).unwrap();
+    assert_eq!(
+        Evidence::decode(&evidence.encode().unwrap()).unwrap(),
+        evidence
+    );
+    assert_eq!(
+        Evidence::from_bytes(evidence.clone().into_bytes().into()),
+        evidence
+    );
+
+    // Decoding checks that the messages conflict.
+    let mut buffer = evidence.encode().unwrap();
+    buffer[0] = 0;
+    assert!(Evidence::decode(&buffer).is_err());
+    let message = precommit(hash(&[1])).serialize();
+    let mut buffer = vec![1, 0, 0, 0, 0];
+    LittleEndian::write_u32(&mut buffer[1..], message.len() as u32);
+    buffer.extend_from_slice(&message);
+    buffer.extend_from_slice(&message);
+    assert!(Evidence::decode(&buffer).is_err());
+}
+
+#[test]
+fn test_key_rotation() {
+    let (old_key, old_secret_key) = gen_keypair();
+    let (new_key, new_secret_key) = gen_keypair();
+    let rotation = KeyRotation::new(
+        ValidatorKeyKind::Service,
+        old_key,
+        new_key,
+        &new_secret_key,
+        Height(10),
+    );
+    assert_eq!(rotation.kind(), ValidatorKeyKind::Service);
+    assert_eq!(*rotation.old_key(), old_key);
+    assert_eq!(*rotation.new_key(), new_key);
+    assert_eq!(rotation.height(), Height(10));
+    assert_eq!(
+        KeyRotation::decode(&rotation.encode().unwrap()).unwrap(),
+        rotation
+    );
+
+    let tx = Message::sign_transaction(
+        rotation.clone(),
+        KEY_ROTATION_SERVICE_ID,
+        old_key,
+        &old_secret_key,
+    );
+    assert_eq!(KeyRotation::from_raw(tx.payload()).unwrap(), rotation);
+
+    // Decoding checks the signature of the new key.
+    let mut buffer = rotation.encode().unwrap();
+    buffer[0] = 0;
+    assert!(KeyRotation::decode(&buffer).is_err());
+    let forged = KeyRotation::new(
+        ValidatorKeyKind::Service,
+        old_key,
+        new_key,
+        &old_secret_key,
+        Height(10),
+    );
+    assert!(KeyRotation::decode(&forged.encode().unwrap()).is_err());
+    // The key should change.
+    let same = KeyRotation::new(
+        ValidatorKeyKind::Consensus,
+        new_key,
+        new_key,
+        &new_secret_key,
+        Height(10),
+    );
+    assert!(KeyRotation::decode(&same.encode().unwrap()).is_err());
+}
+
+#[test]
+fn test_maintenance_notice() {
+    let (public_key, secret_key) = gen_keypair();
+    let notice = MaintenanceNotice::new(Height(10));
+    assert_eq!(notice.until(), Height(10));
+    assert_eq!(
+        MaintenanceNotice::decode(&notice.encode().unwrap()).unwrap(),
+        notice
+    );
+
+    let tx = Message::sign_transaction(notice, MAINTENANCE_SERVICE_ID, public_key, &secret_key);
+    assert_eq!(MaintenanceNotice::from_raw(tx.payload()).unwrap(), notice);
+
+    let tx = Message::sign_transaction(notice, KEY_ROTATION_SERVICE_ID, public_key, &secret_key);
+    assert!(MaintenanceNotice::from_raw(tx.payload()).is_err());
+    assert!(MaintenanceNotice::decode(&[0; 4]).is_err());
+}
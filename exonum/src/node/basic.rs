@@ -15,9 +15,10 @@
 use rand::Rng;
 
 use super::{NodeHandler, NodeRole, RequestData};
-use crate::crypto::PublicKey;
-use crate::events::error::LogError;
+use crate::blockchain::Schema;
+use crate::crypto::{Hash, PublicKey};
 use crate::events::network::ConnectedPeerAddr;
+use crate::events::{error::LogError, InternalRequest};
 use crate::helpers::Height;
 use crate::messages::{Connect, Message, PeersRequest, Responses, Service, Signed, Status};
 
@@ -157,6 +158,19 @@ impl NodeHandler {
             return;
         }
 
+        if self.is_follower() && !self.trusted_nodes().contains(&msg.author()) {
+            trace!(
+                "Ignoring status message from peer = {:?} which is not trusted.",
+                msg.author()
+            );
+            return;
+        }
+
+        // Check that the last block of the peer is the same as the local one.
+        if msg.height() <= height {
+            self.check_status_fork(msg);
+        }
+
         // Handle message from future height
         if msg.height() > height {
             let peer = msg.author();
@@ -172,6 +186,66 @@ impl NodeHandler {
         }
     }
 
+    /// Checks that the last block reported in the `Status` message is the same as the block
+    /// at the same height in the local blockchain. A conflicting block reported by a single
+    /// validator is not trusted, but enough validators to include an honest one prove that
+    /// the local blockchain has diverged from the network.
+    ///
+    /// The node is shut down if the local blockchain has diverged from the network.
+    fn check_status_fork(&mut self, msg: &Signed<Status>) {
+        let block_height = match msg.height().0.checked_sub(1) {
+            Some(height) => Height(height),
+            None => return,
+        };
+        let local_hash =
+            match Schema::new(&self.blockchain.snapshot()).block_hash_by_height(block_height) {
+                Some(hash) => hash,
+                None => return,
+            };
+        if local_hash == *msg.last_hash() {
+            return;
+        }
+
+        if self.state.find_validator(msg.author()).is_none() {
+            warn!(
+                "Received status with a block conflicting with the local one \
+                 from non-validator peer = {:?}, msg={:?}",
+                msg.author(),
+                msg
+            );
+            return;
+        }
+        warn!(
+            "Received status with a block conflicting with the local one, \
+             local block hash = {:?}, msg={:?}",
+            local_hash, msg
+        );
+        let witnesses_power =
+            self.state
+                .add_fork_witness(block_height, *msg.last_hash(), msg.author());
+        if witnesses_power >= self.state.one_honest_voting_power() {
+            self.halt_on_fork(block_height, &local_hash, msg.last_hash());
+        }
+    }
+
+    /// Stops the node because the local blockchain has diverged from the network.
+    /// The node is disabled at once, so it does not take part in the consensus on top
+    /// of the diverged blockchain, and then shut down through the event loop.
+    pub(crate) fn halt_on_fork(&mut self, height: Height, local_hash: &Hash, network_hash: &Hash) {
+        let message = format!(
+            "Local blockchain has diverged from the network: the block at height {} \
+             is {:?} locally and {:?} in the network. The node is shutting down. Roll back \
+             the database to a height before the divergence with \
+             `maintenance --action rollback --height <N>` and restart the node to resync \
+             with the network.",
+            height, local_hash, network_hash
+        );
+        error!("{}", message);
+        self.is_enabled = false;
+        self.api_state().set_enabled(false);
+        self.execute_later(InternalRequest::Shutdown);
+    }
+
     /// Handles the `PeersRequest` message. Node sends `Connect` messages of other peers as result.
     pub fn handle_request_peers(&mut self, msg: &Signed<PeersRequest>) {
         let peers: Vec<Signed<Connect>> =
@@ -218,6 +292,12 @@ impl NodeHandler {
         }
         self.add_peer_exchange_timeout();
     }
+    /// Handles `NodeTimeout::Follow`. The follower requests the next block from the trusted nodes.
+    pub fn handle_follow_timeout(&mut self) {
+        self.request_next_block();
+        self.add_follow_timeout();
+    }
+
     /// Handles `NodeTimeout::UpdateApiState`.
     /// Node update internal `ApiState` and `NodeRole`.
     pub fn handle_update_api_state_timeout(&mut self) {
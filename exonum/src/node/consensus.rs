@@ -12,19 +12,26 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashSet;
+use chrono::{DateTime, Utc};
+
+use std::{
+    cmp,
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
 use crate::blockchain::Schema;
 use crate::crypto::{CryptoHash, Hash, PublicKey};
 use crate::events::InternalRequest;
 use crate::helpers::{Height, Round, ValidatorId};
 use crate::messages::{
-    BlockRequest, BlockResponse, Consensus as ConsensusMessage, Precommit, Prevote,
-    PrevotesRequest, Propose, ProposeRequest, RawTransaction, Signed, SignedMessage,
-    TransactionsRequest, TransactionsResponse,
+    BlockRequest, BlockResponse, Consensus as ConsensusMessage, Evidence, MaintenanceNotice,
+    Message, Precommit, Prevote, PrevotesRequest, Propose, ProposeRequest, RawTransaction, Signed,
+    SignedMessage, TransactionsRequest, TransactionsResponse, EVIDENCE_SERVICE_ID,
+    MAINTENANCE_SERVICE_ID,
 };
-use crate::node::{NodeHandler, RequestData};
-use crate::storage::Patch;
+use crate::node::{NodeHandler, NodeTimeout, PoolEvictionPolicy, RequestData};
+use crate::storage::{Patch, Snapshot};
 
 // TODO Reduce view invocations. (ECR-171)
 impl NodeHandler {
@@ -38,6 +45,23 @@ impl NodeHandler {
             return;
         }
 
+        if self.is_follower() {
+            trace!(
+                "Ignoring a consensus message {:?} in the follower mode",
+                msg
+            );
+            return;
+        }
+
+        if let Some(until) = self.state.maintenance_until() {
+            trace!(
+                "Ignoring a consensus message {:?} during the maintenance until height {}",
+                msg,
+                until
+            );
+            return;
+        }
+
         // Warning for messages from previous and future height
         if msg.height() < self.state.height().previous()
             || msg.height() > self.state.height().next()
@@ -100,6 +124,15 @@ impl NodeHandler {
             return;
         }
 
+        // Check time
+        if msg.time() < self.blockchain.last_block().time() {
+            error!(
+                "Received propose with time earlier than the last block time msg={:?}",
+                msg
+            );
+            return;
+        }
+
         // Check leader
         if msg.validator() != self.state.leader(msg.round()) {
             error!(
@@ -119,6 +152,7 @@ impl NodeHandler {
             msg.clone(),
             &schema.transactions(),
             &schema.transactions_pool(),
+            self.system_state.current_time(),
         ) {
             Ok(state) => state.has_unknown_txs(),
             Err(err) => {
@@ -161,7 +195,6 @@ impl NodeHandler {
         }
 
         let block = msg.block();
-        let block_hash = block.hash();
 
         // TODO: Add block with greater height to queue. (ECR-171)
         if self.state.height() != block.height() {
@@ -170,6 +203,15 @@ impl NodeHandler {
 
         // Check block content.
         if block.prev_hash() != &self.last_block_hash() {
+            // The block committed by the majority of validators on top of another block
+            // proves that the local blockchain has diverged from the network.
+            if self.verify_block_precommits(msg).is_ok() {
+                self.halt_on_fork(
+                    block.height().previous(),
+                    &self.last_block_hash(),
+                    block.prev_hash(),
+                );
+            }
             bail!(
                 "Received block prev_hash is distinct from the one in db, \
                  block={:?}, block.prev_hash={:?}, db.last_block_hash={:?}",
@@ -186,14 +228,19 @@ impl NodeHandler {
         if !msg.verify_tx_hash() {
             bail!("Received block has invalid tx_hash, msg={:?}", msg);
         }
+        self.verify_block_precommits(msg)
+    }
+
+    /// Checks that the block from the `BlockResponse` is committed by the majority
+    /// of validators.
+    fn verify_block_precommits(&self, msg: &Signed<BlockResponse>) -> Result<(), failure::Error> {
+        let block = msg.block();
         let precommits: Result<Vec<_>, _> = msg
             .precommits()
             .into_iter()
             .map(Precommit::verify_precommit)
             .collect();
-        self.verify_precommits(&precommits?, &block_hash, block.height())?;
-
-        Ok(())
+        self.verify_precommits(&precommits?, &block.hash(), block.height())
     }
 
     /// Handles the `Block` message. For details see the message documentation.
@@ -241,7 +288,16 @@ impl NodeHandler {
         // Send prevote
         if self.state.locked_round() == Round::zero() {
             if self.state.is_validator() && !self.state.have_prevote(propose_round) {
-                self.broadcast_prevote(propose_round, &hash);
+                match self
+                    .check_propose_time(&hash)
+                    .and_then(|()| self.check_propose_budget(&hash))
+                    .and_then(|()| self.check_propose_nonces(&hash))
+                {
+                    Ok(()) => {
+                        self.broadcast_prevote(propose_round, &hash);
+                    }
+                    Err(err) => error!("Refusing to prevote for propose {:?}: {}", hash, err),
+                }
             } else {
                 // TODO: what if we HAVE prevote for the propose round? (ECR-171)
             }
@@ -269,6 +325,7 @@ impl NodeHandler {
             }
 
             let precommits = self.state.precommits(round, our_block_hash).to_vec();
+            self.add_consensus_latency(&hash);
             self.commit(our_block_hash, precommits.into_iter(), Some(propose_round));
         }
     }
@@ -283,8 +340,12 @@ impl NodeHandler {
         let block_hash = block.hash();
 
         if self.state.block(&block_hash).is_none() {
-            let (computed_block_hash, patch) =
-                self.create_block(block.proposer_id(), block.height(), msg.transactions());
+            let (computed_block_hash, patch) = self.create_block(
+                block.proposer_id(),
+                block.height(),
+                block.time(),
+                msg.transactions(),
+            );
             // Verify block_hash.
             assert!(
                 computed_block_hash == block_hash,
@@ -320,6 +381,12 @@ impl NodeHandler {
             self.state.consensus_public_key_of(msg.validator())
         );
 
+        if let Some(other) = self.state.conflicting_prevote(msg) {
+            let evidence = Evidence::prevotes(other, msg.clone())
+                .expect("BUG: Prevotes of the same validator and round do not conflict.");
+            self.report_evidence(evidence);
+        }
+
         // Add prevote
         let has_consensus = self.state.add_prevote(msg.clone());
 
@@ -392,6 +459,7 @@ impl NodeHandler {
 
         // Commit.
         let precommits = self.state.precommits(round, our_block_hash).to_vec();
+        self.add_consensus_latency(propose_hash);
         self.commit(our_block_hash, precommits.into_iter(), Some(round));
     }
 
@@ -416,6 +484,10 @@ impl NodeHandler {
                 self.blockchain.save_messages(round, raw_messages);
 
                 self.state.lock(round, propose_hash);
+                if let Some(propose_state) = self.state.propose(&propose_hash) {
+                    let latency = self.elapsed_time(propose_state.received_at());
+                    metric!("node.prevote_latency", latency);
+                }
                 // Send precommit
                 if self.state.is_validator() && !self.state.have_incompatible_prevotes() {
                     // Execute block and get state hash
@@ -442,6 +514,12 @@ impl NodeHandler {
             self.state.consensus_public_key_of(msg.validator())
         );
 
+        if let Some(other) = self.state.conflicting_precommit(msg) {
+            let evidence = Evidence::precommits(other, msg.clone())
+                .expect("BUG: Precommits of the same validator and round do not conflict.");
+            self.report_evidence(evidence);
+        }
+
         // Add precommit
         let has_consensus = self.state.add_precommit(msg.clone());
 
@@ -466,6 +544,19 @@ impl NodeHandler {
         }
     }
 
+    /// Records the consensus latency of the block of the given propose. The latency is measured
+    /// since the propose was received, so the time the leader has been waiting for transactions
+    /// is not taken into account.
+    fn add_consensus_latency(&mut self, propose_hash: &Hash) {
+        let received_at = match self.state.propose(propose_hash) {
+            Some(propose_state) => propose_state.received_at(),
+            None => return,
+        };
+        let latency = self.elapsed_time(received_at);
+        metric!("node.consensus_latency", latency);
+        self.state.add_consensus_latency(latency);
+    }
+
     /// Commits block, so new height is achieved.
     pub fn commit<I: Iterator<Item = Signed<Precommit>>>(
         &mut self,
@@ -483,22 +574,37 @@ impl NodeHandler {
                 .commit(block_state.patch(), block_hash, precommits)
                 .unwrap();
             // Update node state.
-            self.state
-                .update_config(Schema::new(&self.blockchain.snapshot()).actual_configuration());
+            let snapshot = self.blockchain.snapshot();
+            let config = Schema::new(&snapshot).actual_configuration();
+            self.switch_rotated_keys(&config);
+            self.add_rotated_keys_to_connect_list(&config);
+            self.state.update_config(config);
             // Update state to new height.
             let block_hash = self.blockchain.last_hash();
             self.state
                 .new_height(&block_hash, self.system_state.current_time());
+            // Waiting for transactions does not carry over to the new height.
+            self.is_waiting_for_txs = false;
+            let maintenance_until = self.state.maintenance_until();
+            self.state.update_leader_election(&snapshot);
+            if self.state.maintenance_until() != maintenance_until {
+                self.log_maintenance();
+            }
             (block_state.txs().len(), block_state.proposer_id())
         };
 
         self.api_state.broadcast(&block_hash);
+        self.evict_pool_transactions();
 
         let snapshot = self.blockchain.snapshot();
         let schema = Schema::new(&snapshot);
         let pool_len = schema.transactions_pool_len();
+        let pool = schema.transactions_pool();
+        self.state
+            .retain_pool_evidence(|tx_hash| pool.contains(tx_hash));
 
         metric!("node.mempool", pool_len);
+        metric!("node.round_timeout", self.first_round_timeout());
 
         let height = self.state.height();
         info!(
@@ -511,6 +617,11 @@ impl NodeHandler {
             block_hash.to_hex(),
         );
 
+        // The follower does not take part in the consensus.
+        if self.is_follower() {
+            return;
+        }
+
         self.broadcast_status();
         self.add_status_timeout();
 
@@ -537,9 +648,76 @@ impl NodeHandler {
             bail!("Received already processed transaction, hash {:?}", hash)
         }
 
-        if let Err(e) = self.blockchain.tx_from_raw(msg.payload().clone()) {
-            error!("Received invalid transaction {:?}, result: {}", msg, e);
-            bail!("Received malicious transaction.")
+        let transaction = match self.blockchain.tx_from_raw(msg.payload().clone()) {
+            Ok(transaction) => transaction,
+            Err(e) => {
+                error!("Received invalid transaction {:?}, result: {}", msg, e);
+                bail!("Received malicious transaction.")
+            }
+        };
+
+        // A transaction exceeding the block budget on its own can never be committed.
+        if let Some(limit) = self.txs_block_size_limit() {
+            let size = msg.signed_message().raw().len() as u64;
+            ensure!(
+                size <= limit,
+                "Transaction size ({}) exceeds txs_block_size_limit ({}), hash {:?}",
+                size,
+                limit,
+                hash
+            );
+        }
+        if let Some(limit) = self.txs_block_cost_limit() {
+            let cost = transaction.execution_cost();
+            ensure!(
+                cost <= limit,
+                "Transaction execution cost ({}) exceeds txs_block_cost_limit ({}), hash {:?}",
+                cost,
+                limit,
+                hash
+            );
+        }
+
+        // Only one transaction with the evidence of the same equivocation is accepted
+        // into the pool, unless a propose or a block awaits another one.
+        let evidence_key = if msg.service_id() == EVIDENCE_SERVICE_ID {
+            Some(Evidence::from_raw(msg.payload())?.key())
+        } else {
+            None
+        };
+        if let Some(ref key) = evidence_key {
+            if !self.state.is_tx_awaited(&hash) {
+                ensure!(
+                    !self.is_evidence_known(key),
+                    "Received evidence which is already recorded or in the pool, hash {:?}",
+                    hash
+                );
+            }
+        }
+
+        if let Some(nonce) = msg.nonce() {
+            let expected = Schema::new(&snapshot).account_nonce(&msg.author());
+            if nonce < expected {
+                bail!(
+                    "Received transaction with already used nonce {}, hash {:?}",
+                    nonce,
+                    hash
+                )
+            }
+        }
+
+        if let Some(limit) = self.mempool_config.size_limit {
+            let pool_len = Schema::new(&snapshot).transactions_pool_len();
+            if limit.policy == PoolEvictionPolicy::RejectNew
+                && pool_len >= limit.max_size
+                && !self.state.is_tx_awaited(&hash)
+            {
+                bail!(
+                    "Transactions pool is full ({} transactions), rejected transaction {:?}",
+                    pool_len,
+                    hash
+                )
+            }
         }
 
         let mut fork = self.blockchain.fork();
@@ -550,9 +728,16 @@ impl NodeHandler {
         self.blockchain
             .merge(fork.into_patch())
             .expect("Unable to save transaction to persistent pool.");
+        if let Some(key) = evidence_key {
+            self.state.add_pool_evidence(key, hash);
+        }
 
         if self.state.is_leader() && self.state.round() != Round::zero() {
-            self.maybe_add_propose_timeout();
+            if self.is_waiting_for_txs {
+                self.add_propose_timeout();
+            } else {
+                self.maybe_add_propose_timeout();
+            }
         }
 
         let full_proposes = self.state.check_incomplete_proposes(hash);
@@ -571,6 +756,24 @@ impl NodeHandler {
         Ok(())
     }
 
+    /// Evicts stale transactions from the pool according to the memory pool configuration.
+    fn evict_pool_transactions(&mut self) {
+        let max_size = self
+            .mempool_config
+            .size_limit
+            .filter(|limit| limit.policy == PoolEvictionPolicy::EvictOldest)
+            .map(|limit| limit.max_size);
+        let evicted = self.blockchain.evict_pool_transactions(
+            self.mempool_config.max_tx_age,
+            max_size,
+            self.mempool_config.evicted_tx_retention,
+        );
+        if !evicted.is_empty() {
+            info!("Evicted {} transactions from the pool", evicted.len());
+            trace!("Evicted transactions: {:?}", evicted);
+        }
+    }
+
     /// Handles raw transactions.
     pub fn handle_txs_batch(
         &mut self,
@@ -607,6 +810,69 @@ impl NodeHandler {
         }
     }
 
+    /// Submits the evidence of equivocation of a validator as a transaction signed
+    /// by the service key of the node, unless the evidence with the same key is already
+    /// recorded or waits in the pool.
+    fn report_evidence(&mut self, evidence: Evidence) {
+        if self.is_evidence_known(&evidence.key()) {
+            trace!("Evidence {:?} is already recorded or in the pool", evidence);
+            return;
+        }
+        warn!(
+            "Validator {} has signed conflicting messages at height {}, round {}",
+            evidence.validator(),
+            evidence.height(),
+            evidence.round()
+        );
+        let tx = Message::sign_transaction(
+            evidence,
+            EVIDENCE_SERVICE_ID,
+            *self.state.service_public_key(),
+            self.state.service_secret_key(),
+        );
+        self.handle_incoming_tx(tx);
+    }
+
+    /// Checks whether the evidence with the given key is already recorded in the blockchain
+    /// or waits in the pool.
+    fn is_evidence_known(&self, key: &Hash) -> bool {
+        let snapshot = self.blockchain.snapshot();
+        let schema = Schema::new(&snapshot);
+        schema.evidence().contains(key)
+            || self.state.pool_evidence(key).map_or(false, |tx_hash| {
+                schema.transactions_pool().contains(&tx_hash)
+            })
+    }
+
+    /// Announces the maintenance of the node until the given height with a `MaintenanceNotice`
+    /// signed by the service key of the node.
+    pub(crate) fn announce_maintenance(&mut self, until: Height) {
+        if !self.state.is_validator() {
+            warn!("Ignoring the maintenance request because the node is not a validator");
+            return;
+        }
+
+        info!("Announce the maintenance until height {}", until);
+        let tx = Message::sign_transaction(
+            MaintenanceNotice::new(until),
+            MAINTENANCE_SERVICE_ID,
+            *self.state.service_public_key(),
+            self.state.service_secret_key(),
+        );
+        self.handle_incoming_tx(tx);
+    }
+
+    /// Logs whether the node is in maintenance at the current height.
+    pub(crate) fn log_maintenance(&self) {
+        match self.state.maintenance_until() {
+            Some(until) => info!(
+                "The node is in maintenance until height {}, consensus messages are ignored",
+                until
+            ),
+            None => info!("The node is not in maintenance, it takes part in the consensus"),
+        }
+    }
+
     /// Handle new round, after jump.
     pub fn handle_new_round(&mut self, height: Height, round: Round) {
         trace!("Handle new round");
@@ -626,6 +892,9 @@ impl NodeHandler {
 
     // Try to process consensus messages from the future round.
     fn process_new_round(&mut self) {
+        // Waiting for transactions does not carry over to the new round.
+        self.is_waiting_for_txs = false;
+
         if self.state.is_validator() {
             // Send prevote if we are locked or propose if we are leader
             if let Some(hash) = self.state.locked_propose() {
@@ -684,21 +953,32 @@ impl NodeHandler {
             }
             let snapshot = self.blockchain.snapshot();
             let schema = Schema::new(&snapshot);
-            let pool = schema.transactions_pool();
             let pool_len = schema.transactions_pool_len();
 
+            if pool_len == 0 {
+                if let Some(max_idle_timeout) = self.max_idle_timeout() {
+                    let heartbeat_time =
+                        self.state.height_start_time() + Duration::from_millis(max_idle_timeout);
+                    if self.system_state.current_time() < heartbeat_time {
+                        info!("LEADER: pool is empty, waiting for transactions");
+                        self.add_timeout(NodeTimeout::Propose(height, round), heartbeat_time);
+                        self.is_waiting_for_txs = true;
+                        return;
+                    }
+                }
+            }
+
             info!("LEADER: pool = {}", pool_len);
 
             let round = self.state.round();
-            let max_count = ::std::cmp::min(u64::from(self.txs_block_limit()), pool_len);
-
-            let txs: Vec<Hash> = pool.iter().take(max_count as usize).collect();
+            let txs = self.propose_transactions(&schema);
             let propose = self.sign_message(Propose::new(
                 validator_id,
                 self.state.height(),
                 round,
                 self.state.last_hash(),
                 &txs,
+                self.propose_time(),
             ));
             // Put our propose to the consensus messages cache
             self.blockchain.save_message(round, propose.clone());
@@ -709,7 +989,9 @@ impl NodeHandler {
             self.allow_expedited_propose = true;
 
             // Save our propose into state
-            let hash = self.state.add_self_propose(propose);
+            let hash = self
+                .state
+                .add_self_propose(propose, self.system_state.current_time());
 
             // Send prevote
             let has_majority_prevotes = self.broadcast_prevote(round, &hash);
@@ -775,13 +1057,206 @@ impl NodeHandler {
     }
 
     /// Creates block with given transaction and returns its hash and corresponding changes.
+    /// Transactions already executed at the current height in the same order are not
+    /// re-executed.
     pub fn create_block(
         &mut self,
         proposer_id: ValidatorId,
         height: Height,
+        time: DateTime<Utc>,
         tx_hashes: &[Hash],
     ) -> (Hash, Patch) {
-        self.blockchain.create_patch(proposer_id, height, tx_hashes)
+        self.blockchain.create_patch_cached(
+            self.state.execution_cache_mut(),
+            proposer_id,
+            height,
+            time,
+            tx_hashes,
+        )
+    }
+
+    /// Returns the time for the propose of the current height, that is, the time
+    /// at which the node has started the height. The time is never earlier than the time
+    /// of the last block, even if the local clock is behind.
+    pub(crate) fn propose_time(&self) -> DateTime<Utc> {
+        let height_start_time = self.state.height_start_time().into();
+        cmp::max(height_start_time, self.blockchain.last_block().time())
+    }
+
+    /// Returns hashes of the pool transactions to be included into the `Propose` of the leader.
+    ///
+    /// Transactions are taken in the pool order while they fit into `txs_block_limit`,
+    /// `txs_block_size_limit` and `txs_block_cost_limit`. Transactions with a nonce ahead
+    /// of the next expected nonce of the author are deferred until the preceding transactions
+    /// of the author are included, so they stay in the pool until the gap is filled.
+    fn propose_transactions<T: AsRef<dyn Snapshot>>(&self, schema: &Schema<T>) -> Vec<Hash> {
+        let pool = schema.transactions_pool();
+        let transactions = schema.transactions();
+        let max_count = self.txs_block_limit() as usize;
+        let size_limit = self.txs_block_size_limit().unwrap_or(u64::max_value());
+        let cost_limit = self.txs_block_cost_limit().unwrap_or(u64::max_value());
+
+        let (mut size, mut cost) = (0_u64, 0_u64);
+        let mut nonces = HashMap::new();
+        let mut deferred = HashMap::new();
+        let mut txs = Vec::new();
+        for pool_hash in pool.iter() {
+            let mut next = Some(pool_hash);
+            while let Some(tx_hash) = next.take() {
+                let tx = match transactions.get(&tx_hash) {
+                    Some(tx) => tx,
+                    None => return txs,
+                };
+                if let Some(nonce) = tx.nonce() {
+                    let author = tx.author();
+                    let expected = *nonces
+                        .entry(author)
+                        .or_insert_with(|| schema.account_nonce(&author));
+                    if nonce > expected {
+                        deferred.insert((author, nonce), tx_hash);
+                        continue;
+                    }
+                    if nonce == expected {
+                        nonces.insert(author, nonce + 1);
+                        next = deferred.remove(&(author, nonce + 1));
+                    }
+                }
+
+                let (tx_size, tx_cost) = match self.transaction_budget(&tx) {
+                    Some(budget) => budget,
+                    None => return txs,
+                };
+                size = size.saturating_add(tx_size);
+                cost = cost.saturating_add(tx_cost);
+                if size > size_limit || cost > cost_limit {
+                    return txs;
+                }
+                txs.push(tx_hash);
+                if txs.len() >= max_count {
+                    return txs;
+                }
+            }
+        }
+        txs
+    }
+
+    /// Checks that the time of the known propose is not ahead of the local clock
+    /// by more than `max_propose_time_drift`.
+    fn check_propose_time(&self, propose_hash: &Hash) -> Result<(), failure::Error> {
+        let max_drift = self.max_propose_time_drift();
+        let propose = self
+            .state
+            .propose(propose_hash)
+            .ok_or_else(|| format_err!("Unknown propose {:?}", propose_hash))?;
+        let time = propose.message().time();
+        let now: DateTime<Utc> = self.system_state.current_time().into();
+        ensure!(
+            time <= now + chrono::Duration::milliseconds(max_drift as i64),
+            "Propose time ({}) is ahead of the local time ({}) by more than \
+             max_propose_time_drift ({} ms)",
+            time,
+            now,
+            max_drift
+        );
+        Ok(())
+    }
+
+    /// Checks that the transactions of the known propose fit into `txs_block_size_limit`
+    /// and `txs_block_cost_limit`.
+    fn check_propose_budget(&self, propose_hash: &Hash) -> Result<(), failure::Error> {
+        let size_limit = self.txs_block_size_limit();
+        let cost_limit = self.txs_block_cost_limit();
+        if size_limit.is_none() && cost_limit.is_none() {
+            return Ok(());
+        }
+
+        let propose = self
+            .state
+            .propose(propose_hash)
+            .ok_or_else(|| format_err!("Unknown propose {:?}", propose_hash))?;
+        let snapshot = self.blockchain.snapshot();
+        let schema = Schema::new(&snapshot);
+        let (mut size, mut cost) = (0_u64, 0_u64);
+        for tx_hash in propose.message().transactions() {
+            let (tx_size, tx_cost) = schema
+                .transactions()
+                .get(tx_hash)
+                .and_then(|tx| self.transaction_budget(&tx))
+                .ok_or_else(|| format_err!("Unknown transaction {:?} in the propose", tx_hash))?;
+            size = size.saturating_add(tx_size);
+            cost = cost.saturating_add(tx_cost);
+        }
+
+        if let Some(limit) = size_limit {
+            ensure!(
+                size <= limit,
+                "Transactions size ({}) exceeds txs_block_size_limit ({})",
+                size,
+                limit
+            );
+        }
+        if let Some(limit) = cost_limit {
+            ensure!(
+                cost <= limit,
+                "Transactions execution cost ({}) exceeds txs_block_cost_limit ({})",
+                cost,
+                limit
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks that the transactions of the known propose do not skip author nonces, that is,
+    /// each transaction with a nonce ahead of the next expected nonce of the author is preceded
+    /// in the propose by the transactions with the missing nonces.
+    fn check_propose_nonces(&self, propose_hash: &Hash) -> Result<(), failure::Error> {
+        let propose = self
+            .state
+            .propose(propose_hash)
+            .ok_or_else(|| format_err!("Unknown propose {:?}", propose_hash))?;
+        let snapshot = self.blockchain.snapshot();
+        let schema = Schema::new(&snapshot);
+        let mut nonces = HashMap::new();
+        for tx_hash in propose.message().transactions() {
+            let tx = schema
+                .transactions()
+                .get(tx_hash)
+                .ok_or_else(|| format_err!("Unknown transaction {:?} in the propose", tx_hash))?;
+            let nonce = match tx.nonce() {
+                Some(nonce) => nonce,
+                None => continue,
+            };
+            let author = tx.author();
+            let expected = nonces
+                .entry(author)
+                .or_insert_with(|| schema.account_nonce(&author));
+            ensure!(
+                nonce <= *expected,
+                "Transaction {:?} has nonce {} while the next expected nonce of the author is {}",
+                tx_hash,
+                nonce,
+                expected
+            );
+            if nonce == *expected {
+                *expected += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the size (in bytes) and the execution cost of the transaction.
+    /// The cost is computed only if `txs_block_cost_limit` is set, otherwise it is zero.
+    fn transaction_budget(&self, tx: &Signed<RawTransaction>) -> Option<(u64, u64)> {
+        let size = tx.signed_message().raw().len() as u64;
+        let cost = if self.txs_block_cost_limit().is_some() {
+            self.blockchain
+                .tx_from_raw(tx.payload().clone())
+                .ok()?
+                .execution_cost()
+        } else {
+            0
+        };
+        Some((size, cost))
     }
 
     /// Calls `create_block` with transactions from the corresponding `Propose` and returns the
@@ -795,8 +1270,12 @@ impl NodeHandler {
 
         let tx_hashes = propose.transactions().to_vec();
 
-        let (block_hash, patch) =
-            self.create_block(propose.validator(), propose.height(), tx_hashes.as_slice());
+        let (block_hash, patch) = self.create_block(
+            propose.validator(),
+            propose.height(),
+            propose.time(),
+            tx_hashes.as_slice(),
+        );
         // Save patch
         self.state
             .add_block(block_hash, patch, tx_hashes, propose.validator());
@@ -834,8 +1313,17 @@ impl NodeHandler {
     }
 
     /// Requests a block for the next height from all peers with a bigger height. Called when the
-    /// node tries to catch up with other nodes' height.
+    /// node tries to catch up with other nodes' height. The follower requests the block from
+    /// the trusted nodes regardless of their height.
     pub fn request_next_block(&mut self) {
+        if self.is_follower() {
+            let height = self.state.height();
+            for peer in self.trusted_nodes() {
+                self.request(RequestData::Block(height), peer);
+            }
+            return;
+        }
+
         // TODO: Randomize next peer. (ECR-171)
         let heights: Vec<_> = self
             .state
@@ -910,19 +1398,21 @@ impl NodeHandler {
     }
 
     /// Checks that pre-commits count is correct and calls `verify_precommit` for each of them.
+    /// The pre-commits should be sent by validators with the majority of the voting power.
     fn verify_precommits(
         &self,
         precommits: &[Signed<Precommit>],
         block_hash: &Hash,
         block_height: Height,
     ) -> Result<(), failure::Error> {
-        if precommits.len() < self.state.majority_count() {
+        if precommits.is_empty() {
             bail!("Received block without consensus");
         } else if precommits.len() > self.state.validators().len() {
             bail!("Wrong precommits count in block");
         }
 
         let mut validators = HashSet::with_capacity(precommits.len());
+        let mut voting_power = 0;
         let round = precommits[0].round();
         for precommit in precommits {
             if !validators.insert(precommit.validator()) {
@@ -930,8 +1420,12 @@ impl NodeHandler {
             }
 
             self.verify_precommit(block_hash, block_height, round, precommit)?;
+            voting_power += self.state.voting_power_of(precommit.validator());
         }
 
+        if voting_power < self.state.majority_count() {
+            bail!("Received block without consensus");
+        }
         Ok(())
     }
 
@@ -0,0 +1,235 @@
+// Copyright 2019 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Inspection of the consensus messages saved by the node.
+//!
+//! The node saves its own and received consensus messages of the current height
+//! in order to recover its state after a restart. The log of these messages allows
+//! to find out what the node did in a stuck round. Replaying the log into a node
+//! with [`Node::replay_consensus_log`] reproduces the issue offline and reports
+//! where the consensus stalls.
+//!
+//! [`Node::replay_consensus_log`]: ../struct.Node.html#method.replay_consensus_log
+
+use std::{collections::BTreeMap, fmt};
+
+use super::{RequestData, State};
+use crate::blockchain::Schema;
+use crate::crypto::{CryptoHash, Hash};
+use crate::helpers::{Height, Round};
+use crate::messages::{Consensus, Message};
+use crate::storage::Snapshot;
+
+/// Consensus messages saved by the node at the current height.
+#[derive(Debug, Clone)]
+pub struct ConsensusLog {
+    height: Height,
+    round: Round,
+    messages: Vec<Message>,
+}
+
+impl ConsensusLog {
+    /// Loads the log from the given database snapshot.
+    pub fn load<T: AsRef<dyn Snapshot>>(snapshot: T) -> Self {
+        let schema = Schema::new(snapshot);
+        let messages = schema.consensus_messages_cache().iter().collect();
+        Self {
+            height: schema.height().next(),
+            round: schema.consensus_round(),
+            messages,
+        }
+    }
+
+    /// Returns the height the messages are saved at.
+    pub fn height(&self) -> Height {
+        self.height
+    }
+
+    /// Returns the last round the node has saved messages for.
+    pub fn round(&self) -> Round {
+        self.round
+    }
+
+    /// Returns the saved messages in the order they have been saved.
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+}
+
+/// Writes the height and the saved round followed by the messages, one per line.
+impl fmt::Display for ConsensusLog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "height: {}", self.height)?;
+        writeln!(f, "round: {}", self.round)?;
+        for message in &self.messages {
+            let consensus = match *message {
+                Message::Consensus(ref consensus) => consensus,
+                ref other => {
+                    writeln!(f, "{:?}", other)?;
+                    continue;
+                }
+            };
+            write!(
+                f,
+                "height={} round={} validator={} author={} ",
+                consensus.height(),
+                consensus.round(),
+                consensus.validator(),
+                consensus.author().to_hex()
+            )?;
+            match *consensus {
+                Consensus::Propose(ref msg) => writeln!(
+                    f,
+                    "Propose hash={} prev_hash={} txs={}",
+                    msg.hash().to_hex(),
+                    msg.prev_hash().to_hex(),
+                    msg.transactions().len()
+                )?,
+                Consensus::Prevote(ref msg) => writeln!(
+                    f,
+                    "Prevote propose={} locked_round={}",
+                    msg.propose_hash().to_hex(),
+                    msg.locked_round()
+                )?,
+                Consensus::Precommit(ref msg) => writeln!(
+                    f,
+                    "Precommit propose={} block={} time={}",
+                    msg.propose_hash().to_hex(),
+                    msg.block_hash().to_hex(),
+                    msg.time()
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Consensus state of the node after replaying a consensus log.
+///
+/// Unless the node has committed the block, the report shows where the consensus
+/// stalls: the votes collected in each round and the data requested from other nodes.
+#[derive(Debug, Clone)]
+pub struct ReplayReport {
+    log_height: Height,
+    height: Height,
+    round: Round,
+    locked_round: Round,
+    locked_propose: Option<Hash>,
+    majority_count: u64,
+    prevotes: BTreeMap<(Round, Hash), u64>,
+    precommits: BTreeMap<(Round, Hash), u64>,
+    requests: Vec<RequestData>,
+}
+
+impl ReplayReport {
+    /// Creates the report from the state of the node the log has been replayed into.
+    pub fn new(log: &ConsensusLog, state: &State) -> Self {
+        let mut requests: Vec<_> = state.requests().cloned().collect();
+        requests.sort();
+        Self {
+            log_height: log.height(),
+            height: state.height(),
+            round: state.round(),
+            locked_round: state.locked_round(),
+            locked_propose: state.locked_propose(),
+            majority_count: state.majority_count(),
+            prevotes: state.prevotes_voting_power(),
+            precommits: state.precommits_voting_power(),
+            requests,
+        }
+    }
+
+    /// Returns `true` if the block at the height of the log has been committed.
+    pub fn is_committed(&self) -> bool {
+        self.height > self.log_height
+    }
+
+    /// Returns the round the node has ended up in.
+    pub fn round(&self) -> Round {
+        self.round
+    }
+
+    /// Returns the voting power of the pre-votes by rounds and propose hashes.
+    pub fn prevotes(&self) -> &BTreeMap<(Round, Hash), u64> {
+        &self.prevotes
+    }
+
+    /// Returns the voting power of the pre-commits by rounds and block hashes.
+    pub fn precommits(&self) -> &BTreeMap<(Round, Hash), u64> {
+        &self.precommits
+    }
+
+    /// Returns the data the node waits for from other nodes.
+    pub fn requests(&self) -> &[RequestData] {
+        &self.requests
+    }
+}
+
+/// Writes the collected votes and the requested data, one per line.
+impl fmt::Display for ReplayReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_committed() {
+            return writeln!(f, "the block at height {} is committed", self.log_height);
+        }
+        writeln!(f, "stalled at height {} round {}", self.height, self.round)?;
+        match self.locked_propose {
+            Some(hash) => writeln!(
+                f,
+                "locked on propose={} in round {}",
+                hash.to_hex(),
+                self.locked_round
+            )?,
+            None => writeln!(f, "not locked")?,
+        }
+        for (&(round, hash), voting_power) in &self.prevotes {
+            writeln!(
+                f,
+                "round={} Prevotes propose={} voting_power={}/{}",
+                round,
+                hash.to_hex(),
+                voting_power,
+                self.majority_count
+            )?;
+        }
+        for (&(round, hash), voting_power) in &self.precommits {
+            writeln!(
+                f,
+                "round={} Precommits block={} voting_power={}/{}",
+                round,
+                hash.to_hex(),
+                voting_power,
+                self.majority_count
+            )?;
+        }
+        for request in &self.requests {
+            match *request {
+                RequestData::Propose(hash) => writeln!(f, "waiting for propose={}", hash.to_hex())?,
+                RequestData::ProposeTransactions(hash) => {
+                    writeln!(f, "waiting for transactions of propose={}", hash.to_hex())?
+                }
+                RequestData::BlockTransactions => {
+                    writeln!(f, "waiting for transactions of the block")?
+                }
+                RequestData::Prevotes(round, hash) => writeln!(
+                    f,
+                    "waiting for prevotes of round={} propose={}",
+                    round,
+                    hash.to_hex()
+                )?,
+                RequestData::Block(height) => writeln!(f, "waiting for block={}", height)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -86,6 +86,7 @@ impl NodeHandler {
                     }
                 }
             }
+            ExternalMessage::Maintenance(until) => self.announce_maintenance(until),
             ExternalMessage::Shutdown => self.execute_later(InternalRequest::Shutdown),
             ExternalMessage::Rebroadcast => self.handle_rebroadcast(),
         }
@@ -106,6 +107,7 @@ impl NodeHandler {
             NodeTimeout::PeerExchange => self.handle_peer_exchange_timeout(),
             NodeTimeout::UpdateApiState => self.handle_update_api_state_timeout(),
             NodeTimeout::Propose(height, round) => self.handle_propose_timeout(height, round),
+            NodeTimeout::Follow => self.handle_follow_timeout(),
         }
     }
 
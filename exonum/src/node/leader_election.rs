@@ -0,0 +1,423 @@
+// Copyright 2019 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Strategies of choosing the leader of a consensus round.
+//!
+//! The strategy used by the network is selected by the `leader_election` field
+//! of [`ConsensusConfig`]. Leaders must be the same on all nodes, so the strategies
+//! only depend on the actual configuration and the committed blocks. Regardless
+//! of the strategy, validators in maintenance are not chosen as leaders
+//! (see [`SkipMaintenance`]).
+//!
+//! [`ConsensusConfig`]: ../../blockchain/struct.ConsensusConfig.html
+//! [`SkipMaintenance`]: struct.SkipMaintenance.html
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use std::fmt;
+
+use crate::blockchain::{LeaderElectionStrategy, Schema, StoredConfiguration, ValidatorKeys};
+use crate::crypto;
+use crate::helpers::{Height, Round, ValidatorId};
+use crate::storage::Snapshot;
+
+/// Chooses the leader of a consensus round at a certain height.
+///
+/// Instances are created for each height with [`create`], so implementations may
+/// precompute the data they need from the blockchain state.
+///
+/// [`create`]: fn.create.html
+pub trait LeaderElection: Send + fmt::Debug {
+    /// Returns the leader of the given round at the given height.
+    fn leader(&self, height: Height, round: Round) -> ValidatorId;
+}
+
+/// Creates the leader election selected in the configuration for the height following
+/// the last block in the snapshot.
+pub fn create(config: &StoredConfiguration, snapshot: &dyn Snapshot) -> Box<dyn LeaderElection> {
+    let election: Box<dyn LeaderElection> = match config.consensus.leader_election {
+        LeaderElectionStrategy::RoundRobin => {
+            Box::new(RoundRobin::new(config.validator_keys.len()))
+        }
+        LeaderElectionStrategy::Weighted => Box::new(Weighted::new(&config.validator_keys)),
+        LeaderElectionStrategy::Reputation { missed_proposes } => {
+            Box::new(Reputation::new(config, snapshot, missed_proposes))
+        }
+    };
+
+    let in_maintenance = Schema::new(snapshot).validators_in_maintenance();
+    if in_maintenance.is_empty() {
+        election
+    } else {
+        Box::new(SkipMaintenance::new(
+            election,
+            config.validator_keys.len(),
+            in_maintenance.keys().cloned(),
+        ))
+    }
+}
+
+/// Validators become leaders in turn: the leader of the first round at the height `h`
+/// is the validator `h + 1`, the next validator leads the second round and so on.
+#[derive(Debug)]
+pub struct RoundRobin {
+    validators_count: u64,
+}
+
+impl RoundRobin {
+    /// Creates the round-robin election among the given number of validators.
+    pub fn new(validators_count: usize) -> Self {
+        Self {
+            validators_count: validators_count as u64,
+        }
+    }
+}
+
+impl LeaderElection for RoundRobin {
+    fn leader(&self, height: Height, round: Round) -> ValidatorId {
+        let height: u64 = height.into();
+        let round: u64 = round.into();
+        ValidatorId(((height + round) % self.validators_count) as u16)
+    }
+}
+
+/// Leaders are chosen pseudo-randomly with the probability proportional to the voting power
+/// of validators. The random value is the hash of the height and the round.
+#[derive(Debug)]
+pub struct Weighted {
+    voting_powers: Vec<u64>,
+    total_voting_power: u64,
+}
+
+impl Weighted {
+    /// Creates the weighted election among the given validators.
+    pub fn new(validators: &[ValidatorKeys]) -> Self {
+        let voting_powers = validators
+            .iter()
+            .map(ValidatorKeys::voting_power)
+            .collect::<Vec<_>>();
+        Self {
+            total_voting_power: voting_powers.iter().sum(),
+            voting_powers,
+        }
+    }
+}
+
+impl LeaderElection for Weighted {
+    fn leader(&self, height: Height, round: Round) -> ValidatorId {
+        let mut seed = [0; 12];
+        LittleEndian::write_u64(&mut seed[..8], height.into());
+        LittleEndian::write_u32(&mut seed[8..], round.into());
+        let hash = crypto::hash(&seed);
+        let mut value = LittleEndian::read_u64(&hash.as_ref()[..8]) % self.total_voting_power;
+
+        for (id, &voting_power) in self.voting_powers.iter().enumerate() {
+            if value < voting_power {
+                return ValidatorId(id as u16);
+            }
+            value -= voting_power;
+        }
+        unreachable!("Value is less than the total voting power");
+    }
+}
+
+/// Validators become leaders in the round-robin order, but the validators which have
+/// not proposed the block in each of their last `missed_proposes` turns are moved to
+/// the end of the order. Here, the turn of the validator is the height at which it leads
+/// the first round in the round-robin order.
+///
+/// Every `missed_proposes + 1`-th turn of a skipped validator is a probation turn,
+/// in which the validator is not skipped. If the validator proposes the block,
+/// it returns to the usual order.
+#[derive(Debug)]
+pub struct Reputation {
+    order: Vec<ValidatorId>,
+}
+
+impl Reputation {
+    /// Creates the reputation-based election for the height following the last block
+    /// in the snapshot.
+    pub fn new(
+        config: &StoredConfiguration,
+        snapshot: &dyn Snapshot,
+        missed_proposes: u32,
+    ) -> Self {
+        let schema = Schema::new(snapshot);
+        let height = schema.height().next();
+        let validators_count = config.validator_keys.len() as u64;
+        let first_leader = (height.0 + 1) % validators_count;
+
+        let missed_proposes = u64::from(missed_proposes);
+        let is_skipped = |id: u64| {
+            // Distance to the last height at which the validator has led the first round.
+            let distance = match (height.0 + 1 + validators_count - id) % validators_count {
+                0 => validators_count,
+                distance => distance,
+            };
+            let probation = distance == validators_count
+                && (height.0 / validators_count) % (missed_proposes + 1) == 0;
+            let missed_all = (0..missed_proposes).all(|i| {
+                match height.0.checked_sub(distance + i * validators_count) {
+                    Some(turn) if turn > 0 => proposer(&schema, Height(turn)) != Some(id),
+                    // There is not enough history.
+                    _ => false,
+                }
+            });
+            missed_all && !probation
+        };
+
+        let (mut order, skipped): (Vec<_>, Vec<_>) = (0..validators_count)
+            .map(|i| (first_leader + i) % validators_count)
+            .partition(|&id| !is_skipped(id));
+        order.extend(skipped);
+
+        Self {
+            order: order.into_iter().map(|id| ValidatorId(id as u16)).collect(),
+        }
+    }
+}
+
+fn proposer<T: AsRef<dyn Snapshot>>(schema: &Schema<T>, height: Height) -> Option<u64> {
+    let block_hash = schema.block_hash_by_height(height)?;
+    let block = schema.blocks().get(&block_hash)?;
+    Some(u64::from(block.proposer_id().0))
+}
+
+impl LeaderElection for Reputation {
+    fn leader(&self, _height: Height, round: Round) -> ValidatorId {
+        let validators_count = self.order.len() as u64;
+        let round: u64 = round.into();
+        self.order[((round + validators_count - 1) % validators_count) as usize]
+    }
+}
+
+/// Wrapper of a leader election which skips the validators in maintenance. If the leader
+/// chosen by the wrapped election is in maintenance, the round is led by the next validator
+/// (in the order of identifiers) which is not in maintenance.
+#[derive(Debug)]
+pub struct SkipMaintenance {
+    election: Box<dyn LeaderElection>,
+    in_maintenance: Vec<bool>,
+}
+
+impl SkipMaintenance {
+    /// Wraps the given election skipping the given validators.
+    pub fn new<I>(election: Box<dyn LeaderElection>, validators_count: usize, skipped: I) -> Self
+    where
+        I: IntoIterator<Item = ValidatorId>,
+    {
+        let mut in_maintenance = vec![false; validators_count];
+        for id in skipped {
+            in_maintenance[id.0 as usize] = true;
+        }
+        Self {
+            election,
+            in_maintenance,
+        }
+    }
+}
+
+impl LeaderElection for SkipMaintenance {
+    fn leader(&self, height: Height, round: Round) -> ValidatorId {
+        let leader = self.election.leader(height, round);
+        let validators_count = self.in_maintenance.len();
+        (0..validators_count)
+            .map(|i| (leader.0 as usize + i) % validators_count)
+            .find(|&id| !self.in_maintenance[id])
+            .map_or(leader, |id| ValidatorId(id as u16))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use futures::sync::mpsc;
+
+    use super::*;
+    use crate::blockchain::{Blockchain, GenesisConfig};
+    use crate::crypto::{gen_keypair_from_seed, CryptoHash, Hash, Seed, SEED_LENGTH};
+    use crate::messages::{MaintenanceNotice, Message, MAINTENANCE_SERVICE_ID};
+    use crate::node::ApiSender;
+    use crate::storage::MemoryDB;
+
+    fn create_blockchain(voting_powers: &[u64], strategy: LeaderElectionStrategy) -> Blockchain {
+        let validators = voting_powers.iter().enumerate().map(|(i, &voting_power)| {
+            let keys = gen_keypair_from_seed(&Seed::new([i as u8; SEED_LENGTH]));
+            ValidatorKeys {
+                consensus_key: keys.0,
+                service_key: keys.0,
+                voting_power: Some(voting_power),
+            }
+        });
+        let mut genesis = GenesisConfig::new(validators);
+        genesis.consensus.leader_election = strategy;
+
+        let service_keys = gen_keypair_from_seed(&Seed::new([255; SEED_LENGTH]));
+        let api_channel = mpsc::channel(1);
+        let mut blockchain = Blockchain::new(
+            MemoryDB::new(),
+            Vec::new(),
+            service_keys.0,
+            service_keys.1,
+            ApiSender::new(api_channel.0),
+        );
+        blockchain.initialize(genesis).unwrap();
+        blockchain
+    }
+
+    fn commit_block(blockchain: &mut Blockchain, proposer: u16) {
+        commit_block_with_txs(blockchain, proposer, &[]);
+    }
+
+    fn commit_block_with_txs(blockchain: &mut Blockchain, proposer: u16, tx_hashes: &[Hash]) {
+        let height = Schema::new(&blockchain.snapshot()).height().next();
+        let (block_hash, patch) = blockchain.create_patch(
+            ValidatorId(proposer),
+            height,
+            Utc.timestamp(height.0 as i64, 0),
+            tx_hashes,
+        );
+        blockchain
+            .commit(&patch, block_hash, Vec::new().into_iter())
+            .unwrap();
+    }
+
+    fn commit_maintenance(blockchain: &mut Blockchain, validator: u8, until: Height) {
+        let keys = gen_keypair_from_seed(&Seed::new([validator; SEED_LENGTH]));
+        let tx = Message::sign_transaction(
+            MaintenanceNotice::new(until),
+            MAINTENANCE_SERVICE_ID,
+            keys.0,
+            &keys.1,
+        );
+        let tx_hash = tx.hash();
+        let mut fork = blockchain.fork();
+        Schema::new(&mut fork).add_transaction_into_pool(tx);
+        blockchain.merge(fork.into_patch()).unwrap();
+        commit_block_with_txs(blockchain, 0, &[tx_hash]);
+    }
+
+    fn leader_election(blockchain: &Blockchain) -> Box<dyn LeaderElection> {
+        let snapshot = blockchain.snapshot();
+        let config = Schema::new(&snapshot).actual_configuration();
+        create(&config, &snapshot)
+    }
+
+    #[test]
+    fn round_robin() {
+        let election = RoundRobin::new(4);
+        assert_eq!(election.leader(Height(1), Round(1)), ValidatorId(2));
+        assert_eq!(election.leader(Height(1), Round(2)), ValidatorId(3));
+        assert_eq!(election.leader(Height(1), Round(3)), ValidatorId(0));
+        assert_eq!(election.leader(Height(6), Round(1)), ValidatorId(3));
+    }
+
+    #[test]
+    fn weighted() {
+        let blockchain = create_blockchain(&[1, 3, 6], LeaderElectionStrategy::Weighted);
+        let election = leader_election(&blockchain);
+
+        let mut counts = [0; 3];
+        for height in 0..100 {
+            for round in 1..=10 {
+                let leader = election.leader(Height(height), Round(round));
+                assert_eq!(leader, election.leader(Height(height), Round(round)));
+                counts[leader.0 as usize] += 1;
+            }
+        }
+        assert!(counts[0] < counts[1] && counts[1] < counts[2]);
+    }
+
+    #[test]
+    fn reputation() {
+        let strategy = LeaderElectionStrategy::Reputation { missed_proposes: 2 };
+        let mut blockchain = create_blockchain(&[1, 1, 1, 1], strategy);
+
+        // Validator 2 is the first leader at heights 1, 5, 9, 13 and 17,
+        // but does not propose at heights 1 and 5.
+        for height in 1..9 {
+            let election = leader_election(&blockchain);
+            let proposer = election.leader(Height(height), Round(1));
+            let proposer = if proposer == ValidatorId(2) {
+                election.leader(Height(height), Round(2))
+            } else {
+                proposer
+            };
+            commit_block(&mut blockchain, proposer.0);
+        }
+
+        // After two missed turns, validator 2 is skipped.
+        let election = leader_election(&blockchain);
+        assert_eq!(election.leader(Height(9), Round(1)), ValidatorId(3));
+        assert_eq!(election.leader(Height(9), Round(4)), ValidatorId(2));
+
+        // Every third turn is a probation turn.
+        for height in 9..13 {
+            let election = leader_election(&blockchain);
+            commit_block(&mut blockchain, election.leader(Height(height), Round(1)).0);
+        }
+        let election = leader_election(&blockchain);
+        assert_eq!(election.leader(Height(13), Round(1)), ValidatorId(2));
+        commit_block(&mut blockchain, 2);
+
+        // The validator has proposed the block, so it is not skipped anymore.
+        for height in 14..17 {
+            let election = leader_election(&blockchain);
+            commit_block(&mut blockchain, election.leader(Height(height), Round(1)).0);
+        }
+        let election = leader_election(&blockchain);
+        assert_eq!(election.leader(Height(17), Round(1)), ValidatorId(2));
+    }
+
+    #[test]
+    fn maintenance() {
+        let mut blockchain = create_blockchain(&[1, 1, 1, 1], LeaderElectionStrategy::RoundRobin);
+
+        // Validator 2 is in maintenance at heights 2, 3 and 4.
+        commit_maintenance(&mut blockchain, 2, Height(5));
+        let election = leader_election(&blockchain);
+        assert_eq!(election.leader(Height(2), Round(1)), ValidatorId(3));
+        assert_eq!(election.leader(Height(2), Round(4)), ValidatorId(3));
+
+        // The maintenance of validator 3 would leave less than the majority of validators.
+        commit_maintenance(&mut blockchain, 3, Height(5));
+        let in_maintenance = Schema::new(&blockchain.snapshot()).validators_in_maintenance();
+        assert_eq!(
+            in_maintenance.into_iter().collect::<Vec<_>>(),
+            vec![(ValidatorId(2), Height(5))]
+        );
+        let election = leader_election(&blockchain);
+        assert_eq!(election.leader(Height(3), Round(3)), ValidatorId(3));
+
+        // The validator is back after the maintenance.
+        commit_block(&mut blockchain, 0);
+        let election = leader_election(&blockchain);
+        assert_eq!(election.leader(Height(4), Round(2)), ValidatorId(3));
+        commit_block(&mut blockchain, 0);
+        let election = leader_election(&blockchain);
+        assert_eq!(election.leader(Height(5), Round(1)), ValidatorId(2));
+
+        // The maintenance can be finished early.
+        commit_maintenance(&mut blockchain, 2, Height(10));
+        assert_eq!(
+            leader_election(&blockchain).leader(Height(6), Round(4)),
+            ValidatorId(3)
+        );
+        commit_maintenance(&mut blockchain, 2, Height(0));
+        assert_eq!(
+            leader_election(&blockchain).leader(Height(7), Round(3)),
+            ValidatorId(2)
+        );
+    }
+}
@@ -19,14 +19,18 @@
 
 pub use self::{
     connect_list::{ConnectList, PeerAddress},
+    consensus_log::{ConsensusLog, ReplayReport},
+    leader_election::LeaderElection,
     state::{RequestData, State, ValidatorState},
 };
 
+pub mod consensus_log;
+pub mod leader_election;
 // TODO: Temporary solution to get access to WAIT constants. (ECR-167)
 pub mod state;
 
 use failure::Error;
-use futures::{sync::mpsc, Future, Sink};
+use futures::{sync::mpsc, Future, Sink, Stream};
 use tokio_core::reactor::Core;
 use tokio_threadpool::Builder as ThreadPoolBuilder;
 use toml::Value;
@@ -46,7 +50,8 @@ use crate::api::{
     ApiAccess, ApiAggregator,
 };
 use crate::blockchain::{
-    Blockchain, ConsensusConfig, GenesisConfig, Schema, Service, SharedNodeState, ValidatorKeys,
+    Blockchain, ConsensusConfig, GenesisConfig, Schema, Service, SharedNodeState,
+    StoredConfiguration, ValidatorKeys,
 };
 use crate::crypto::{self, read_keys_from_file, CryptoHash, Hash, PublicKey, SecretKey};
 use crate::events::{
@@ -60,7 +65,9 @@ use crate::helpers::{
     fabric::{NodePrivateConfig, NodePublicConfig},
     user_agent, Height, Milliseconds, Round, ValidatorId,
 };
-use crate::messages::{Connect, Message, ProtocolMessage, RawTransaction, Signed, SignedMessage};
+use crate::messages::{
+    Connect, Message, ProtocolMessage, RawTransaction, Signed, SignedMessage, ValidatorKeyKind,
+};
 use crate::node::state::SharedConnectList;
 use crate::storage::{Database, DbOptions};
 
@@ -79,6 +86,8 @@ pub enum ExternalMessage {
     Transaction(Signed<RawTransaction>),
     /// Enable or disable the node.
     Enable(bool),
+    /// Announce the maintenance of the validator until the given height.
+    Maintenance(Height),
     /// Shutdown the node.
     Shutdown,
     /// Rebroadcast transactions from the pool.
@@ -100,6 +109,8 @@ pub enum NodeTimeout {
     UpdateApiState,
     /// Exchange peers timeout.
     PeerExchange,
+    /// Request of new blocks from the trusted nodes in the follower mode.
+    Follow,
 }
 
 /// A helper trait that provides the node with information about the state of the system such
@@ -137,6 +148,14 @@ pub struct NodeHandler {
     config_manager: Option<ConfigManager>,
     /// Can we speed up Propose with transaction pressure?
     allow_expedited_propose: bool,
+    /// Does the leader wait for transactions instead of proposing an empty block?
+    is_waiting_for_txs: bool,
+    /// Memory pool configuration.
+    mempool_config: MemoryPoolConfig,
+    /// Keys the node switches to after the rotation of its validator keys.
+    rotated_keys: Vec<RotatedKey>,
+    /// Follower mode configuration.
+    follower: Option<FollowerConfig>,
 }
 
 /// Service configuration.
@@ -148,6 +167,30 @@ pub struct ServiceConfig {
     pub service_secret_key: SecretKey,
 }
 
+/// Key of the validator, which the node switches to when the rotation of its key
+/// becomes actual. See [`KeyRotation`] for details.
+///
+/// [`KeyRotation`]: ../messages/struct.KeyRotation.html
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RotatedKey<T = SecretKey> {
+    /// Kind of the key.
+    pub kind: ValidatorKeyKind,
+    /// New public key.
+    pub public_key: PublicKey,
+    /// New secret key.
+    pub secret_key: T,
+}
+
+impl<T> RotatedKey<T> {
+    /// Checks if the key is used by a validator in the given configuration.
+    pub fn is_used_by(&self, config: &StoredConfiguration) -> bool {
+        config.validator_keys.iter().any(|keys| match self.kind {
+            ValidatorKeyKind::Consensus => keys.consensus_key == self.public_key,
+            ValidatorKeyKind::Service => keys.service_key == self.public_key,
+        })
+    }
+}
+
 /// Listener config.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ListenerConfig {
@@ -161,6 +204,26 @@ pub struct ListenerConfig {
     pub address: SocketAddr,
 }
 
+/// Configuration of the follower mode.
+///
+/// The follower does not take part in the consensus and does not maintain connections
+/// with the whole network. Instead, it connects only to the trusted nodes and fetches
+/// committed blocks together with their precommits with `BlockRequest` messages.
+/// Blocks are verified against the validator set as usual, so the trusted nodes cannot
+/// make the follower commit a block not approved by the majority of validators.
+///
+/// The trusted nodes should have the follower in their connect lists. In this case,
+/// they notify the follower about new blocks with `Status` messages; otherwise,
+/// the follower learns about new blocks only by polling.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FollowerConfig {
+    /// Nodes the follower fetches blocks from. The nodes are added to the connect list
+    /// of the follower.
+    pub trusted_nodes: Vec<ConnectInfo>,
+    /// Interval between the requests of new blocks from the trusted nodes.
+    pub poll_interval: Milliseconds,
+}
+
 /// An api configuration options.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct NodeApiConfig {
@@ -218,18 +281,55 @@ impl Default for EventsPoolCapacity {
     }
 }
 
+/// Policy applied to the transactions pool when it reaches its maximum size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolEvictionPolicy {
+    /// The oldest transactions are evicted from the pool after each committed block
+    /// until the pool fits the limit.
+    EvictOldest,
+    /// New transactions are rejected while the pool is full. Transactions required
+    /// to process proposes or blocks of the current height are accepted anyway.
+    RejectNew,
+}
+
+/// Maximum size of the transactions pool.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PoolSizeLimit {
+    /// Maximum number of uncommitted transactions in the pool.
+    pub max_size: u64,
+    /// Policy applied when the pool is full.
+    pub policy: PoolEvictionPolicy,
+}
+
 /// Memory pool configuration parameters.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MemoryPoolConfig {
+    /// Maximum number of blocks an uncommitted transaction may stay in the pool.
+    /// Transactions that were not committed in time are evicted from the pool.
+    /// If not specified, transactions are never evicted by age.
+    #[serde(default)]
+    pub max_tx_age: Option<u64>,
     /// Sets the maximum number of messages that can be buffered on the event loop's
     /// notification channel before a send will fail.
     pub events_pool_capacity: EventsPoolCapacity,
+    /// Maximum size of the transactions pool. If not specified, the pool size is
+    /// not limited.
+    #[serde(default)]
+    pub size_limit: Option<PoolSizeLimit>,
+    /// Number of blocks during which hashes of evicted transactions are kept
+    /// in the `transactions_evicted` table. If not specified, the hashes are kept forever.
+    #[serde(default)]
+    pub evicted_tx_retention: Option<u64>,
 }
 
 impl Default for MemoryPoolConfig {
     fn default() -> Self {
         Self {
+            max_tx_age: None,
             events_pool_capacity: EventsPoolCapacity::default(),
+            size_limit: None,
+            evicted_tx_retention: None,
         }
     }
 }
@@ -267,6 +367,22 @@ pub struct NodeConfig<T = SecretKey> {
     pub connect_list: ConnectListConfig,
     /// Transaction Verification Thread Pool size.
     pub thread_pool_size: Option<u8>,
+    /// Number of threads used to execute transactions of a block. Transactions
+    /// are executed sequentially if not set.
+    #[serde(default)]
+    pub execution_threads: Option<u8>,
+    /// Number of the latest blocks which can be rolled back with the `maintenance` command.
+    /// The default value is used if not set.
+    #[serde(default)]
+    pub undo_depth: Option<u64>,
+    /// Keys which the node switches to after the rotation of its validator keys.
+    /// Secret keys are read with the passphrase of the key of the same kind.
+    #[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+    pub rotated_keys: Vec<RotatedKey<T>>,
+    /// Follower mode configuration. If set, the node does not take part in the consensus
+    /// and fetches committed blocks from the trusted nodes instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub follower: Option<FollowerConfig>,
 }
 
 impl NodeConfig<PathBuf> {
@@ -278,16 +394,15 @@ impl NodeConfig<PathBuf> {
         service_passphrase: &[u8],
     ) -> NodeConfig {
         let config_folder = config_file_path.as_ref().parent().unwrap();
-        let consensus_key_path = if self.consensus_secret_key.is_absolute() {
-            self.consensus_secret_key
-        } else {
-            config_folder.join(&self.consensus_secret_key)
-        };
-        let service_key_path = if self.service_secret_key.is_absolute() {
-            self.service_secret_key
-        } else {
-            config_folder.join(&self.service_secret_key)
+        let key_path = |path: PathBuf| {
+            if path.is_absolute() {
+                path
+            } else {
+                config_folder.join(&path)
+            }
         };
+        let consensus_key_path = key_path(self.consensus_secret_key);
+        let service_key_path = key_path(self.service_secret_key);
 
         let consensus_secret_key = read_keys_from_file(&consensus_key_path, consensus_passphrase)
             .expect("Could not read consensus_secret_key from file")
@@ -295,6 +410,24 @@ impl NodeConfig<PathBuf> {
         let service_secret_key = read_keys_from_file(&service_key_path, service_passphrase)
             .expect("Could not read service_secret_key from file")
             .1;
+        let rotated_keys = self
+            .rotated_keys
+            .into_iter()
+            .map(|key| {
+                let passphrase = match key.kind {
+                    ValidatorKeyKind::Consensus => consensus_passphrase,
+                    ValidatorKeyKind::Service => service_passphrase,
+                };
+                let secret_key = read_keys_from_file(&key_path(key.secret_key), passphrase)
+                    .expect("Could not read rotated secret key from file")
+                    .1;
+                RotatedKey {
+                    kind: key.kind,
+                    public_key: key.public_key,
+                    secret_key,
+                }
+            })
+            .collect();
         NodeConfig {
             consensus_secret_key,
             service_secret_key,
@@ -310,6 +443,10 @@ impl NodeConfig<PathBuf> {
             database: self.database,
             connect_list: self.connect_list,
             thread_pool_size: self.thread_pool_size,
+            execution_threads: self.execution_threads,
+            undo_depth: self.undo_depth,
+            rotated_keys,
+            follower: self.follower,
         }
     }
 }
@@ -327,6 +464,10 @@ pub struct Configuration {
     pub peer_discovery: Vec<String>,
     /// Memory pool configuration.
     pub mempool: MemoryPoolConfig,
+    /// Keys the node switches to after the rotation of its validator keys.
+    pub rotated_keys: Vec<RotatedKey>,
+    /// Follower mode configuration.
+    pub follower: Option<FollowerConfig>,
 }
 
 /// Channel for messages, timeouts and api requests.
@@ -433,11 +574,11 @@ impl ConnectListConfig {
 impl NodeHandler {
     /// Creates `NodeHandler` using specified `Configuration`.
     pub fn new(
-        blockchain: Blockchain,
+        mut blockchain: Blockchain,
         external_address: &str,
         sender: NodeSender,
         system_state: Box<dyn SystemStateProvider>,
-        config: Configuration,
+        mut config: Configuration,
         api_state: SharedNodeState,
         config_file_path: Option<String>,
     ) -> Self {
@@ -451,12 +592,36 @@ impl NodeHandler {
         let stored = Schema::new(&snapshot).actual_configuration();
         info!("Creating a node with config: {:#?}", stored);
 
+        // Use the rotated keys which have become actual while the node was stopped.
+        for key in &config.rotated_keys {
+            if !key.is_used_by(&stored) {
+                continue;
+            }
+            match key.kind {
+                ValidatorKeyKind::Consensus => {
+                    config.listener.consensus_public_key = key.public_key;
+                    config.listener.consensus_secret_key = key.secret_key.clone();
+                }
+                ValidatorKeyKind::Service => {
+                    config.service.service_public_key = key.public_key;
+                    config.service.service_secret_key = key.secret_key.clone();
+                    blockchain.service_keypair = (key.public_key, key.secret_key.clone());
+                }
+            }
+        }
+
         let validator_id = stored
             .validator_keys
             .iter()
             .position(|pk| pk.consensus_key == config.listener.consensus_public_key)
             .map(|id| ValidatorId(id as u16));
         info!("Validator id = '{:?}'", validator_id);
+        if config.follower.is_some() {
+            info!("Running in the follower mode");
+            if validator_id.is_some() {
+                warn!("The node is a validator, but it does not take part in the consensus");
+            }
+        }
         let connect = Message::concrete(
             Connect::new(
                 external_address,
@@ -468,7 +633,7 @@ impl NodeHandler {
         );
 
         let connect_list = config.listener.connect_list;
-        let state = State::new(
+        let mut state = State::new(
             validator_id,
             config.listener.consensus_public_key,
             config.listener.consensus_secret_key,
@@ -482,6 +647,7 @@ impl NodeHandler {
             last_height,
             system_state.current_time(),
         );
+        state.update_leader_election(&snapshot);
 
         let node_role = NodeRole::new(validator_id);
         let is_enabled = api_state.is_enabled();
@@ -503,6 +669,10 @@ impl NodeHandler {
             node_role,
             config_manager,
             allow_expedited_propose: true,
+            is_waiting_for_txs: false,
+            mempool_config: config.mempool,
+            rotated_keys: config.rotated_keys,
+            follower: config.follower,
         }
     }
 
@@ -519,16 +689,17 @@ impl NodeHandler {
         &self.api_state
     }
 
-    /// Returns value of the `first_round_timeout` field from the current `ConsensusConfig`.
+    /// Returns value of the `first_round_timeout` field from the current `ConsensusConfig`
+    /// or the timeout adapted to the network latency if the adaptive timeouts are enabled.
     pub fn first_round_timeout(&self) -> Milliseconds {
-        self.state().consensus_config().first_round_timeout
+        self.state()
+            .adaptive_round_timeout()
+            .unwrap_or_else(|| self.state().consensus_config().first_round_timeout)
     }
 
     /// Returns value of the `round_timeout_increase` field from the current `ConsensusConfig`.
     pub fn round_timeout_increase(&self) -> Milliseconds {
-        (self.state().consensus_config().first_round_timeout
-            * ConsensusConfig::TIMEOUT_LINEAR_INCREASE_PERCENT)
-            / 100
+        (self.first_round_timeout() * ConsensusConfig::TIMEOUT_LINEAR_INCREASE_PERCENT) / 100
     }
 
     /// Returns value of the `status_timeout` field from the current `ConsensusConfig`.
@@ -546,6 +717,21 @@ impl NodeHandler {
         self.state().consensus_config().txs_block_limit
     }
 
+    /// Returns value of the `txs_block_size_limit` field from the current `ConsensusConfig`.
+    pub fn txs_block_size_limit(&self) -> Option<u64> {
+        self.state().consensus_config().txs_block_size_limit
+    }
+
+    /// Returns value of the `txs_block_cost_limit` field from the current `ConsensusConfig`.
+    pub fn txs_block_cost_limit(&self) -> Option<u64> {
+        self.state().consensus_config().txs_block_cost_limit
+    }
+
+    /// Returns value of the `max_propose_time_drift` field from the current `ConsensusConfig`.
+    pub fn max_propose_time_drift(&self) -> Milliseconds {
+        self.state().consensus_config().max_propose_time_drift
+    }
+
     /// Returns value of the minimal propose timeout.
     pub fn min_propose_timeout(&self) -> Milliseconds {
         self.state().consensus_config().min_propose_timeout
@@ -561,6 +747,111 @@ impl NodeHandler {
         self.state().consensus_config().propose_timeout_threshold
     }
 
+    /// Returns maximal interval between blocks if there are no transactions to commit.
+    pub fn max_idle_timeout(&self) -> Option<Milliseconds> {
+        self.state().consensus_config().max_idle_timeout
+    }
+
+    /// Returns the follower mode configuration if the node runs in this mode.
+    pub fn follower(&self) -> Option<&FollowerConfig> {
+        self.follower.as_ref()
+    }
+
+    /// Checks if the node runs in the follower mode.
+    pub fn is_follower(&self) -> bool {
+        self.follower.is_some()
+    }
+
+    /// Returns the keys of the trusted nodes of the follower.
+    fn trusted_nodes(&self) -> Vec<PublicKey> {
+        self.follower.as_ref().map_or_else(Vec::new, |follower| {
+            follower
+                .trusted_nodes
+                .iter()
+                .map(|node| node.public_key)
+                .collect()
+        })
+    }
+
+    /// Switches the node to the rotated keys which are used by the given configuration
+    /// instead of the current keys of the node.
+    ///
+    /// Network connections of the node are still authenticated with the previous consensus key
+    /// until the node is restarted. Other nodes accept connections with the new key after
+    /// the restart, since they add it to their connect lists when the rotation becomes actual,
+    /// see [`add_rotated_keys_to_connect_list`].
+    ///
+    /// [`add_rotated_keys_to_connect_list`]: #method.add_rotated_keys_to_connect_list
+    pub(crate) fn switch_rotated_keys(&mut self, config: &StoredConfiguration) {
+        for key in &self.rotated_keys {
+            if !key.is_used_by(config) {
+                continue;
+            }
+            match key.kind {
+                ValidatorKeyKind::Consensus => {
+                    if *self.state.consensus_public_key() != key.public_key {
+                        info!("Switching to the consensus key {:?}", key.public_key);
+                        self.state
+                            .set_consensus_keys(key.public_key, key.secret_key.clone());
+                    }
+                }
+                ValidatorKeyKind::Service => {
+                    if *self.state.service_public_key() != key.public_key {
+                        info!("Switching to the service key {:?}", key.public_key);
+                        self.state
+                            .set_service_keys(key.public_key, key.secret_key.clone());
+                        self.blockchain.service_keypair = (key.public_key, key.secret_key.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds the rotated consensus keys of the validators in the given configuration
+    /// to the connect list with the addresses of their previous keys, so that the validators
+    /// are able to connect with the new keys after a restart. The previous keys are kept
+    /// in the connect list, since the validators use them for connections until they restart.
+    ///
+    /// A key is considered rotated if the validator at the same position in the actual
+    /// configuration has the same service key and another consensus key.
+    pub(crate) fn add_rotated_keys_to_connect_list(&mut self, config: &StoredConfiguration) {
+        let connect_list = self.state.connect_list();
+        let rotated: Vec<_> = self
+            .state
+            .config()
+            .validator_keys
+            .iter()
+            .zip(&config.validator_keys)
+            .filter(|(old, new)| {
+                old.service_key == new.service_key && old.consensus_key != new.consensus_key
+            })
+            .filter(|(_, new)| !connect_list.is_peer_allowed(&new.consensus_key))
+            .filter_map(|(old, new)| {
+                connect_list
+                    .find_address_by_key(&old.consensus_key)
+                    .map(|address| ConnectInfo {
+                        address: address.address,
+                        public_key: new.consensus_key,
+                    })
+            })
+            .collect();
+        if rotated.is_empty() {
+            return;
+        }
+
+        for peer in rotated {
+            info!(
+                "Adding the rotated consensus key {:?} to the connect list",
+                peer.public_key
+            );
+            self.state.add_peer_to_connect_list(peer);
+        }
+        if let Some(ref config_manager) = self.config_manager {
+            let connect_list_config = ConnectListConfig::from_connect_list(&connect_list);
+            config_manager.store_connect_list(connect_list_config);
+        }
+    }
+
     /// Returns `State` of the node.
     pub fn state(&self) -> &State {
         &self.state
@@ -571,7 +862,10 @@ impl NodeHandler {
         let listen_address = self.system_state.listen_address();
         info!("Start listening address={}", listen_address);
 
-        let peers: HashSet<_> = {
+        let peers: HashSet<_> = if self.is_follower() {
+            // The follower communicates only with the trusted nodes.
+            self.trusted_nodes().into_iter().collect()
+        } else {
             let it = self.state.peers().values().map(|p| p.author());
             let it = it.chain(
                 self.state()
@@ -589,11 +883,20 @@ impl NodeHandler {
             info!("Trying to connect with peer {}", key);
         }
 
-        let snapshot = self.blockchain.snapshot();
-        let schema = Schema::new(&snapshot);
+        if self.is_follower() {
+            self.add_timeouts();
+            self.request_next_block();
+            return;
+        }
+
+        if self.state.maintenance_until().is_some() {
+            self.log_maintenance();
+        }
+
+        let log = ConsensusLog::load(self.blockchain.snapshot());
 
         // Recover previous saved round if any
-        let round = schema.consensus_round();
+        let round = log.round();
         self.state.jump_round(round);
         info!("Jump to round {}", round);
 
@@ -601,17 +904,31 @@ impl NodeHandler {
 
         // Recover cached consensus messages if any. We do this after main initialization and before
         // the start of event processing.
-        let messages = schema.consensus_messages_cache();
-        for msg in messages.iter() {
-            self.handle_message(msg);
+        self.replay_consensus_log(&log);
+    }
+
+    /// Handles the messages from the consensus log as if they were received from the network.
+    /// If the log is saved at a later round, the node jumps to this round first.
+    pub fn replay_consensus_log(&mut self, log: &ConsensusLog) {
+        if log.round() > self.state.round() {
+            self.state.jump_round(log.round());
+            info!("Jump to round {}", log.round());
+            self.add_round_timeout();
+        }
+        for msg in log.messages() {
+            self.handle_message(msg.clone());
         }
     }
 
     /// Runs the node's basic timers.
     fn add_timeouts(&mut self) {
-        self.add_round_timeout();
-        self.add_status_timeout();
-        self.add_peer_exchange_timeout();
+        if self.is_follower() {
+            self.add_follow_timeout();
+        } else {
+            self.add_round_timeout();
+            self.add_status_timeout();
+            self.add_peer_exchange_timeout();
+        }
         self.add_update_api_state_timeout();
     }
 
@@ -696,6 +1013,7 @@ impl NodeHandler {
         );
         let timeout = NodeTimeout::Propose(self.state.height(), self.state.round());
         self.add_timeout(timeout, time);
+        self.is_waiting_for_txs = false;
     }
 
     fn maybe_add_propose_timeout(&mut self) {
@@ -733,6 +1051,14 @@ impl NodeHandler {
         self.add_timeout(NodeTimeout::PeerExchange, time);
     }
 
+    /// Adds `NodeTimeout::Follow` timeout to the channel if the node runs in the follower mode.
+    pub fn add_follow_timeout(&mut self) {
+        if let Some(poll_interval) = self.follower.as_ref().map(|f| f.poll_interval) {
+            let time = self.system_state.current_time() + Duration::from_millis(poll_interval);
+            self.add_timeout(NodeTimeout::Follow, time);
+        }
+    }
+
     /// Adds `NodeTimeout::UpdateApiState` timeout to the channel.
     pub fn add_update_api_state_timeout(&mut self) {
         let time = self.system_state.current_time()
@@ -740,6 +1066,16 @@ impl NodeHandler {
         self.add_timeout(NodeTimeout::UpdateApiState, time);
     }
 
+    /// Returns the time passed since the given moment.
+    pub fn elapsed_time(&self, since: SystemTime) -> Milliseconds {
+        let elapsed = self
+            .system_state
+            .current_time()
+            .duration_since(since)
+            .unwrap_or_default();
+        elapsed.as_secs() * 1000 + u64::from(elapsed.subsec_millis())
+    }
+
     /// Returns hash of the last block.
     pub fn last_block_hash(&self) -> Hash {
         self.blockchain.last_block().hash()
@@ -905,15 +1241,29 @@ impl Node {
             node_cfg.service_secret_key.clone(),
             ApiSender::new(channel.api_requests.0.clone()),
         );
+        if let Some(threads) = node_cfg.execution_threads {
+            blockchain.set_execution_threads(threads.into());
+        }
+        if let Some(depth) = node_cfg.undo_depth {
+            blockchain.set_undo_depth(depth);
+        }
         blockchain.initialize(node_cfg.genesis.clone()).unwrap();
 
-        let peers = node_cfg.connect_list.addresses();
+        let mut connect_list = node_cfg.connect_list;
+        if let Some(ref follower) = node_cfg.follower {
+            for node in &follower.trusted_nodes {
+                if !connect_list.peers.contains(node) {
+                    connect_list.peers.push(node.clone());
+                }
+            }
+        }
+        let peers = connect_list.addresses();
 
         let config = Configuration {
             listener: ListenerConfig {
                 consensus_public_key: node_cfg.consensus_public_key,
                 consensus_secret_key: node_cfg.consensus_secret_key,
-                connect_list: ConnectList::from_config(node_cfg.connect_list),
+                connect_list: ConnectList::from_config(connect_list),
                 address: node_cfg.listen_address,
             },
             service: ServiceConfig {
@@ -923,6 +1273,8 @@ impl Node {
             mempool: node_cfg.mempool,
             network: node_cfg.network,
             peer_discovery: peers,
+            rotated_keys: node_cfg.rotated_keys,
+            follower: node_cfg.follower,
         };
 
         let api_state = SharedNodeState::new(node_cfg.api.state_update_timeout as u64);
@@ -1084,6 +1436,35 @@ impl Node {
         (handler_part, network_part, internal_part)
     }
 
+    /// Replays the consensus log saved in the database without connecting to other nodes
+    /// and reports the consensus state the node ends up in.
+    ///
+    /// The replayed messages are saved to the database and the block may be committed,
+    /// so the node should be created over a database which may be changed.
+    pub fn replay_consensus_log(self) -> ReplayReport {
+        let Self {
+            mut handler,
+            channel,
+            ..
+        } = self;
+        // Requests of the handler are not processed, but the channels are drained
+        // so that the handler does not block on them.
+        let (network_tx, network_rx) = channel.network_requests;
+        let (internal_tx, internal_rx) = channel.internal_requests;
+        drop((network_tx, internal_tx));
+        let network_requests = thread::spawn(move || network_rx.wait().count());
+        let internal_requests = thread::spawn(move || internal_rx.wait().count());
+
+        let log = ConsensusLog::load(handler.blockchain.snapshot());
+        handler.replay_consensus_log(&log);
+        let report = ReplayReport::new(&log, handler.state());
+
+        drop(handler);
+        network_requests.join().unwrap();
+        internal_requests.join().unwrap();
+        report
+    }
+
     /// Returns `Blockchain` instance.
     pub fn blockchain(&self) -> Blockchain {
         self.handler.blockchain.clone()
@@ -18,13 +18,15 @@ use bit_vec::BitVec;
 use serde_json::Value;
 
 use std::{
-    collections::{hash_map::Entry, BTreeMap, HashMap, HashSet},
+    collections::{hash_map::Entry, BTreeMap, HashMap, HashSet, VecDeque},
     ops::Deref,
     sync::{Arc, RwLock},
     time::{Duration, SystemTime},
 };
 
-use crate::blockchain::{ConsensusConfig, StoredConfiguration, ValidatorKeys};
+use crate::blockchain::{
+    ConsensusConfig, ExecutionCache, Schema, StoredConfiguration, ValidatorKeys,
+};
 use crate::crypto::{Hash, PublicKey, SecretKey};
 use crate::events::network::ConnectedPeerAddr;
 use crate::helpers::{Height, Milliseconds, Round, ValidatorId};
@@ -34,6 +36,7 @@ use crate::messages::{
 };
 use crate::node::{
     connect_list::{ConnectList, PeerAddress},
+    leader_election::{self, LeaderElection, RoundRobin},
     ConnectInfo,
 };
 use crate::storage::{KeySetIndex, MapIndex, Patch, Snapshot};
@@ -61,6 +64,8 @@ pub struct State {
     service_secret_key: SecretKey,
 
     config: StoredConfiguration,
+    leader_election: Box<dyn LeaderElection>,
+    validators_in_maintenance: BTreeMap<ValidatorId, Height>,
     connect_list: SharedConnectList,
 
     peers: HashMap<PublicKey, Signed<Connect>>,
@@ -76,6 +81,7 @@ pub struct State {
     // Messages.
     proposes: HashMap<Hash, ProposeState>,
     blocks: HashMap<Hash, BlockState>,
+    execution_cache: ExecutionCache,
     prevotes: HashMap<(Round, Hash), Votes<Signed<Prevote>>>,
     precommits: HashMap<(Round, Hash), Votes<Signed<Precommit>>>,
 
@@ -92,6 +98,16 @@ pub struct State {
 
     validators_rounds: BTreeMap<ValidatorId, Round>,
 
+    // Validators which have reported committed blocks conflicting with the local ones,
+    // grouped by the height and the hash of the conflicting block.
+    fork_witnesses: HashMap<(Height, Hash), HashSet<PublicKey>>,
+
+    // Hashes of the evidence transactions in the pool by the keys of their evidence.
+    pool_evidence: HashMap<Hash, Hash>,
+
+    // Consensus latencies of the recent heights for the adaptive round timeouts.
+    consensus_latencies: VecDeque<Milliseconds>,
+
     incomplete_block: Option<IncompleteBlock>,
 }
 
@@ -136,6 +152,8 @@ pub struct ProposeState {
     block_hash: Option<Hash>,
     // Whether the message has been saved to the consensus messages' cache or not.
     is_saved: bool,
+    // Time when the propose was received or created by the node.
+    received_at: SystemTime,
 }
 
 /// State of a block.
@@ -179,6 +197,7 @@ pub struct Votes<T: VoteMessage> {
     messages: Vec<T>,
     validators: BitVec,
     count: usize,
+    voting_power: u64,
 }
 
 impl ValidatorState {
@@ -223,14 +242,17 @@ where
             messages: Vec::new(),
             validators: BitVec::from_elem(validators_len, false),
             count: 0,
+            voting_power: 0,
         }
     }
 
-    /// Inserts a new message if it hasn't been inserted yet.
-    pub fn insert(&mut self, message: T) {
+    /// Inserts a new message with the given voting power of its author
+    /// if it hasn't been inserted yet.
+    pub fn insert(&mut self, message: T, voting_power: u64) {
         let voter: usize = message.validator().into();
         if !self.validators[voter] {
             self.count += 1;
+            self.voting_power += voting_power;
             self.validators.set(voter, true);
             self.messages.push(message);
         }
@@ -246,6 +268,11 @@ where
         self.count
     }
 
+    /// Returns the total voting power of validators which have sent the messages.
+    pub fn voting_power(&self) -> u64 {
+        self.voting_power
+    }
+
     /// Returns messages.
     pub fn messages(&self) -> &Vec<T> {
         &self.messages
@@ -333,6 +360,11 @@ impl ProposeState {
     pub fn set_saved(&mut self, saved: bool) {
         self.is_saved = saved;
     }
+
+    /// Returns the time when the propose was received or created by the node.
+    pub fn received_at(&self) -> SystemTime {
+        self.received_at
+    }
 }
 
 impl BlockState {
@@ -466,6 +498,7 @@ impl State {
 
             proposes: HashMap::new(),
             blocks: HashMap::new(),
+            execution_cache: ExecutionCache::new(),
             prevotes: HashMap::new(),
             precommits: HashMap::new(),
 
@@ -477,10 +510,18 @@ impl State {
             nodes_max_height: BTreeMap::new(),
             validators_rounds: BTreeMap::new(),
 
+            fork_witnesses: HashMap::new(),
+
+            pool_evidence: HashMap::new(),
+
+            consensus_latencies: VecDeque::new(),
+
             our_connect_message: connect,
 
             requests: HashMap::new(),
 
+            leader_election: Box::new(RoundRobin::new(stored.validator_keys.len())),
+            validators_in_maintenance: BTreeMap::new(),
             config: stored,
 
             incomplete_block: None,
@@ -550,6 +591,33 @@ impl State {
         &self.config.consensus
     }
 
+    /// Records the consensus latency of the current height, that is, the time passed since
+    /// the committed propose was received till the majority of precommits. The latencies
+    /// are kept only if the adaptive round timeouts are enabled.
+    pub fn add_consensus_latency(&mut self, latency: Milliseconds) {
+        let window = match self.consensus_config().adaptive_timeouts {
+            Some(adaptive) => adaptive.window as usize,
+            None => return,
+        };
+        self.consensus_latencies.push_back(latency);
+        while self.consensus_latencies.len() > window {
+            self.consensus_latencies.pop_front();
+        }
+    }
+
+    /// Returns the timeout of the first round adapted to the consensus latency of the recent
+    /// heights, or `None` if the adaptive round timeouts are disabled. The `first_round_timeout`
+    /// within the bounds is used until the latency is measured.
+    pub fn adaptive_round_timeout(&self) -> Option<Milliseconds> {
+        let adaptive = self.consensus_config().adaptive_timeouts?;
+        if self.consensus_latencies.is_empty() {
+            return Some(adaptive.bound(self.consensus_config().first_round_timeout));
+        }
+        let total: Milliseconds = self.consensus_latencies.iter().sum();
+        let average = total / self.consensus_latencies.len() as Milliseconds;
+        Some(adaptive.round_timeout(average))
+    }
+
     /// Returns `BTreeMap` with service configs identified by name.
     pub fn services_config(&self) -> &BTreeMap<String, Value> {
         &self.config.services
@@ -574,6 +642,7 @@ impl State {
         self.renew_validator_id(validator_id);
         trace!("Validator={:#?}", self.validator_state());
 
+        self.fork_witnesses.clear();
         self.config = config;
     }
 
@@ -647,16 +716,47 @@ impl State {
         &self.service_secret_key
     }
 
+    /// Replaces the consensus keys of the current node after the key rotation.
+    pub(crate) fn set_consensus_keys(&mut self, public_key: PublicKey, secret_key: SecretKey) {
+        self.consensus_public_key = public_key;
+        self.consensus_secret_key = secret_key;
+    }
+
+    /// Replaces the service keys of the current node after the key rotation.
+    pub(crate) fn set_service_keys(&mut self, public_key: PublicKey, secret_key: SecretKey) {
+        self.service_public_key = public_key;
+        self.service_secret_key = secret_key;
+    }
+
     /// Returns the leader id for the specified round and current height.
     pub fn leader(&self, round: Round) -> ValidatorId {
-        let height: u64 = self.height().into();
-        let round: u64 = round.into();
-        ValidatorId(((height + round) % (self.validators().len() as u64)) as u16)
+        self.leader_election.leader(self.height, round)
+    }
+
+    /// Updates the leader election and the validators in maintenance for the current height,
+    /// according to the actual configuration and the blocks committed to the blockchain.
+    pub fn update_leader_election(&mut self, snapshot: &dyn Snapshot) {
+        self.leader_election = leader_election::create(&self.config, snapshot);
+        self.validators_in_maintenance = Schema::new(snapshot).validators_in_maintenance();
+    }
+
+    /// Returns the validators in maintenance at the current height, along with the heights
+    /// at which their maintenance ends.
+    pub fn validators_in_maintenance(&self) -> &BTreeMap<ValidatorId, Height> {
+        &self.validators_in_maintenance
+    }
+
+    /// Returns the height at which the maintenance of the current node ends if the node
+    /// is a validator in maintenance. Returns `None` otherwise.
+    pub fn maintenance_until(&self) -> Option<Height> {
+        self.validator_id()
+            .and_then(|id| self.validators_in_maintenance.get(&id).cloned())
     }
 
     /// Updates known round for a validator and returns
-    /// a new actual round if at least one non byzantine validators is guaranteed to be on a higher round.
-    /// Otherwise returns None.
+    /// a new actual round if at least one non byzantine validator is guaranteed to be
+    /// on a higher round, that is, validators with more than 1/3 of the total voting power
+    /// are on this or higher rounds. Otherwise returns None.
     pub fn update_validator_round(&mut self, id: ValidatorId, round: Round) -> Option<Round> {
         // Update known round.
         {
@@ -675,23 +775,25 @@ impl State {
             *known_round = round;
         }
 
-        // Find highest non-byzantine round.
-        // At max we can have (N - 1) / 3 byzantine nodes.
-        // It is calculated via rounded up integer division.
-        let max_byzantine_count = (self.validators().len() + 2) / 3 - 1;
-        if self.validators_rounds.len() <= max_byzantine_count {
-            trace!("Count of validators, lower then max byzantine count.");
-            return None;
-        }
-
-        let mut rounds: Vec<_> = self.validators_rounds.iter().map(|(_, v)| v).collect();
-        rounds.sort_unstable_by(|a, b| b.cmp(a));
-
-        if *rounds[max_byzantine_count] > self.round {
-            Some(*rounds[max_byzantine_count])
-        } else {
-            None
+        // Find the highest round reached by validators with the voting power
+        // which includes at least one honest validator.
+        let threshold = self.one_honest_voting_power();
+        let mut rounds: Vec<_> = self
+            .validators_rounds
+            .iter()
+            .map(|(id, round)| (*round, self.voting_power_of(*id)))
+            .collect();
+        rounds.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+
+        let mut voting_power = 0;
+        for (round, power) in rounds {
+            voting_power += power;
+            if voting_power >= threshold {
+                return Some(round).filter(|&round| round > self.round);
+            }
         }
+        trace!("Voting power of validators on known rounds is lower than byzantine one.");
+        None
     }
 
     /// Returns the height for a validator identified by the public key.
@@ -716,9 +818,60 @@ impl State {
             .collect()
     }
 
-    /// Returns sufficient number of votes for current validators number.
-    pub fn majority_count(&self) -> usize {
-        Self::byzantine_majority_count(self.validators().len())
+    /// Adds the validator which has reported a committed block at the given height
+    /// conflicting with the local one. Returns the total voting power of the validators
+    /// which have reported the same conflicting block.
+    pub fn add_fork_witness(&mut self, height: Height, block_hash: Hash, key: PublicKey) -> u64 {
+        let witnesses = self
+            .fork_witnesses
+            .entry((height, block_hash))
+            .or_insert_with(HashSet::new);
+        witnesses.insert(key);
+        self.config
+            .validator_keys
+            .iter()
+            .filter(|v| witnesses.contains(&v.consensus_key))
+            .map(ValidatorKeys::voting_power)
+            .sum()
+    }
+
+    /// Returns the hash of the transaction in the pool with the evidence of the given key.
+    pub fn pool_evidence(&self, key: &Hash) -> Option<Hash> {
+        self.pool_evidence.get(key).cloned()
+    }
+
+    /// Remembers the transaction in the pool with the evidence of the given key.
+    pub fn add_pool_evidence(&mut self, key: Hash, tx_hash: Hash) {
+        self.pool_evidence.insert(key, tx_hash);
+    }
+
+    /// Forgets the evidence transactions which are no longer in the pool.
+    pub fn retain_pool_evidence<F>(&mut self, mut in_pool: F)
+    where
+        F: FnMut(&Hash) -> bool,
+    {
+        self.pool_evidence.retain(|_, tx_hash| in_pool(tx_hash));
+    }
+
+    /// Returns the voting power of validators which guarantees that there is
+    /// at least one honest validator among them, that is, more than 1/3 of the total
+    /// voting power of the current validators.
+    pub fn one_honest_voting_power(&self) -> u64 {
+        self.config.total_voting_power() - self.majority_count() + 1
+    }
+
+    /// Returns the voting power of the validator with the given id.
+    pub fn voting_power_of(&self, id: ValidatorId) -> u64 {
+        let id: usize = id.into();
+        self.validators()
+            .get(id)
+            .map_or(0, ValidatorKeys::voting_power)
+    }
+
+    /// Returns the voting power of validators sufficient to reach the consensus,
+    /// that is, more than 2/3 of the total voting power of the current validators.
+    pub fn majority_count(&self) -> u64 {
+        self.config.majority_voting_power()
     }
 
     /// Returns sufficient number of votes for the given validators number.
@@ -778,6 +931,11 @@ impl State {
         self.proposes.get(hash)
     }
 
+    /// Returns the cache of the transactions executed at the current height.
+    pub fn execution_cache_mut(&mut self) -> &mut ExecutionCache {
+        &mut self.execution_cache
+    }
+
     /// Returns a block with the specified hash.
     pub fn block(&self, hash: &Hash) -> Option<&BlockState> {
         self.blocks.get(hash)
@@ -808,11 +966,13 @@ impl State {
         self.last_hash = *block_hash;
         // TODO: Destruct/construct structure HeightState instead of call clear. (ECR-171)
         self.blocks.clear();
+        self.execution_cache.clear();
         self.proposes.clear();
         self.unknown_proposes_with_precommits.clear();
         self.prevotes.clear();
         self.precommits.clear();
         self.validators_rounds.clear();
+        self.fork_witnesses.clear();
         if let Some(ref mut validator_state) = self.validator_state {
             validator_state.clear();
         }
@@ -868,6 +1028,18 @@ impl State {
         None
     }
 
+    /// Checks if some propose or the incomplete block of the current height
+    /// waits for this transaction.
+    pub fn is_tx_awaited(&self, tx_hash: &Hash) -> bool {
+        self.proposes
+            .values()
+            .any(|propose_state| propose_state.unknown_txs.contains(tx_hash))
+            || self
+                .incomplete_block
+                .as_ref()
+                .map_or(false, |block| block.unknown_txs.contains(tx_hash))
+    }
+
     /// Returns pre-votes for the specified round and propose hash.
     pub fn prevotes(&self, round: Round, propose_hash: Hash) -> &[Signed<Prevote>] {
         self.prevotes
@@ -882,6 +1054,27 @@ impl State {
             .map_or_else(|| [].as_ref(), |votes| votes.messages().as_slice())
     }
 
+    /// Returns the voting power of the collected pre-votes by rounds and propose hashes.
+    pub fn prevotes_voting_power(&self) -> BTreeMap<(Round, Hash), u64> {
+        self.prevotes
+            .iter()
+            .map(|(key, votes)| (*key, votes.voting_power()))
+            .collect()
+    }
+
+    /// Returns the voting power of the collected pre-commits by rounds and block hashes.
+    pub fn precommits_voting_power(&self) -> BTreeMap<(Round, Hash), u64> {
+        self.precommits
+            .iter()
+            .map(|(key, votes)| (*key, votes.voting_power()))
+            .collect()
+    }
+
+    /// Returns the data requested from other nodes and not received yet.
+    pub fn requests(&self) -> impl Iterator<Item = &RequestData> {
+        self.requests.keys()
+    }
+
     /// Returns `true` if this node has pre-vote for the specified round.
     ///
     /// # Panics
@@ -897,7 +1090,7 @@ impl State {
 
     /// Adds propose from this node to the proposes list for the current height. Such propose
     /// cannot contain unknown transactions. Returns hash of the propose.
-    pub fn add_self_propose(&mut self, msg: Signed<Propose>) -> Hash {
+    pub fn add_self_propose(&mut self, msg: Signed<Propose>, received_at: SystemTime) -> Hash {
         debug_assert!(self.validator_state().is_some());
         let propose_hash = msg.hash();
         self.proposes.insert(
@@ -910,6 +1103,7 @@ impl State {
                 // saving a propose to the cache. Think about making this approach less error-prone.
                 // (ECR-1635)
                 is_saved: true,
+                received_at,
             },
         );
 
@@ -922,6 +1116,7 @@ impl State {
         msg: Signed<Propose>,
         transactions: &MapIndex<S, Hash, Signed<RawTransaction>>,
         transaction_pool: &KeySetIndex<S, Hash>,
+        received_at: SystemTime,
     ) -> Result<&ProposeState, failure::Error> {
         let propose_hash = msg.hash();
         match self.proposes.entry(propose_hash) {
@@ -953,6 +1148,7 @@ impl State {
                     unknown_txs,
                     block_hash: None,
                     is_saved: false,
+                    received_at,
                 }))
             }
         }
@@ -1040,23 +1236,46 @@ impl State {
         }
 
         let key = (msg.round(), *msg.propose_hash());
+        let voting_power = self.voting_power_of(msg.validator());
         let validators_len = self.validators().len();
         let votes = self
             .prevotes
             .entry(key)
             .or_insert_with(|| Votes::new(validators_len));
-        votes.insert(msg);
-        votes.count() >= majority_count
+        votes.insert(msg, voting_power);
+        votes.voting_power() >= majority_count
     }
 
     /// Returns `true` if there are +2/3 pre-votes for the specified round and hash.
     pub fn has_majority_prevotes(&self, round: Round, propose_hash: Hash) -> bool {
         match self.prevotes.get(&(round, propose_hash)) {
-            Some(votes) => votes.count() >= self.majority_count(),
+            Some(votes) => votes.voting_power() >= self.majority_count(),
             None => false,
         }
     }
 
+    /// Returns the pre-vote of the same validator for another propose in the same round,
+    /// if it has been added.
+    pub fn conflicting_prevote(&self, msg: &Signed<Prevote>) -> Option<Signed<Prevote>> {
+        find_conflicting_vote(
+            &self.prevotes,
+            msg.round(),
+            msg.propose_hash(),
+            msg.validator(),
+        )
+    }
+
+    /// Returns the pre-commit of the same validator for another block in the same round,
+    /// if it has been added.
+    pub fn conflicting_precommit(&self, msg: &Signed<Precommit>) -> Option<Signed<Precommit>> {
+        find_conflicting_vote(
+            &self.precommits,
+            msg.round(),
+            msg.block_hash(),
+            msg.validator(),
+        )
+    }
+
     /// Returns ids of validators that that sent pre-votes for the specified propose.
     pub fn known_prevotes(&self, round: Round, propose_hash: &Hash) -> BitVec {
         let len = self.validators().len();
@@ -1098,13 +1317,14 @@ impl State {
         }
 
         let key = (msg.round(), *msg.block_hash());
+        let voting_power = self.voting_power_of(msg.validator());
         let validators_len = self.validators().len();
         let votes = self
             .precommits
             .entry(key)
             .or_insert_with(|| Votes::new(validators_len));
-        votes.insert(msg);
-        votes.count() >= majority_count
+        votes.insert(msg, voting_power);
+        votes.voting_power() >= majority_count
     }
 
     /// Adds unknown (for this node) propose.
@@ -1133,7 +1353,7 @@ impl State {
     /// Returns true if the node has +2/3 pre-commits for the specified round and block hash.
     pub fn has_majority_precommits(&self, round: Round, block_hash: Hash) -> bool {
         match self.precommits.get(&(round, block_hash)) {
-            Some(votes) => votes.count() >= self.majority_count(),
+            Some(votes) => votes.voting_power() >= self.majority_count(),
             None => false,
         }
     }
@@ -1211,3 +1431,23 @@ impl State {
         list.add(peer);
     }
 }
+
+/// Finds the vote of the validator in the given round for another hash.
+fn find_conflicting_vote<T: VoteMessage>(
+    votes: &HashMap<(Round, Hash), Votes<T>>,
+    round: Round,
+    hash: &Hash,
+    validator: ValidatorId,
+) -> Option<T> {
+    votes
+        .iter()
+        .filter(|((vote_round, vote_hash), _)| *vote_round == round && vote_hash != hash)
+        .filter_map(|(_, votes)| {
+            votes
+                .messages()
+                .iter()
+                .find(|message| message.validator() == validator)
+        })
+        .next()
+        .cloned()
+}
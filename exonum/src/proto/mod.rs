@@ -59,7 +59,9 @@
 //! }
 //! ```
 
-pub use self::schema::blockchain::{Block, ConfigReference, TransactionResult, TxLocation};
+pub use self::schema::blockchain::{
+    Block, ConfigReference, Event, TransactionResult, TxLocation, UndoChange, UndoData,
+};
 pub use self::schema::helpers::{BitVec, Hash, PublicKey, Signature};
 pub use self::schema::protocol::{
     BlockRequest, BlockResponse, Connect, PeersRequest, Precommit, Prevote, PrevotesRequest,
@@ -279,3 +279,104 @@ fn test_store_txs_positions() {
         assert_eq!(committed_height, location.block_height());
     }
 }
+
+/// idea of the test is to verify that the majority of prevotes and precommits is computed
+/// with the voting power of validators rather than with their number
+#[test]
+fn test_majority_of_voting_power() {
+    // Total voting power is 8, so the majority is 6.
+    let sandbox = sandbox::timestamping_sandbox_builder()
+        .with_voting_powers(vec![1, 1, 1, 5])
+        .build();
+
+    let propose = ProposeBuilder::new(&sandbox).build();
+    let block = BlockBuilder::new(&sandbox).build();
+
+    sandbox.recv(&propose);
+    sandbox.broadcast(&sandbox.create_prevote(
+        ValidatorId(0),
+        Height(1),
+        Round(1),
+        &propose.hash(),
+        NOT_LOCKED,
+        sandbox.s(ValidatorId(0)),
+    ));
+    for i in 1..3 {
+        sandbox.recv(&sandbox.create_prevote(
+            ValidatorId(i),
+            Height(1),
+            Round(1),
+            &propose.hash(),
+            NOT_LOCKED,
+            sandbox.s(ValidatorId(i)),
+        ));
+    }
+    // Three of four validators have not enough voting power.
+    sandbox.assert_lock(NOT_LOCKED, None);
+
+    sandbox.recv(&sandbox.create_prevote(
+        ValidatorId(3),
+        Height(1),
+        Round(1),
+        &propose.hash(),
+        NOT_LOCKED,
+        sandbox.s(ValidatorId(3)),
+    ));
+    sandbox.broadcast(&sandbox.create_precommit(
+        ValidatorId(0),
+        Height(1),
+        Round(1),
+        &propose.hash(),
+        &block.hash(),
+        sandbox.time().into(),
+        sandbox.s(ValidatorId(0)),
+    ));
+    sandbox.assert_lock(Round(1), Some(propose.hash()));
+
+    // Precommits of two validators have enough voting power.
+    sandbox.recv(&sandbox.create_precommit(
+        ValidatorId(3),
+        Height(1),
+        Round(1),
+        &propose.hash(),
+        &block.hash(),
+        sandbox.time().into(),
+        sandbox.s(ValidatorId(3)),
+    ));
+    sandbox.assert_state(Height(2), Round(1));
+    sandbox.broadcast(&sandbox.create_status(
+        &sandbox.p(ValidatorId(0)),
+        Height(2),
+        &block.hash(),
+        sandbox.s(ValidatorId(0)),
+    ));
+}
+
+/// idea of the test is to verify that the node jumps to a higher round only if validators
+/// with more than 1/3 of the total voting power are on this round
+#[test]
+fn test_jump_to_round_by_voting_power() {
+    // Total voting power is 8, so more than 1/3 of it is 3.
+    let sandbox = sandbox::timestamping_sandbox_builder()
+        .with_voting_powers(vec![1, 1, 1, 5])
+        .build();
+
+    let prevote = |validator| {
+        sandbox.create_prevote(
+            ValidatorId(validator),
+            Height(1),
+            Round(3),
+            &empty_hash(),
+            NOT_LOCKED,
+            sandbox.s(ValidatorId(validator)),
+        )
+    };
+
+    // Two of four validators have not enough voting power.
+    sandbox.recv(&prevote(1));
+    sandbox.recv(&prevote(2));
+    sandbox.assert_state(Height(1), Round(1));
+
+    sandbox.recv(&prevote(3));
+    sandbox.assert_state(Height(1), Round(3));
+}
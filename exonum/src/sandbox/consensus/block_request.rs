@@ -16,10 +16,13 @@
 
 use std::time::Duration;
 
-use crate::crypto::CryptoHash;
+use crate::crypto::{hash, CryptoHash};
 use crate::helpers::{Height, Round, ValidatorId};
 use crate::node::state::{BLOCK_REQUEST_TIMEOUT, TRANSACTIONS_REQUEST_TIMEOUT};
-use crate::sandbox::{sandbox::timestamping_sandbox, sandbox_tests_helper::*};
+use crate::sandbox::{
+    sandbox::{timestamping_sandbox, timestamping_sandbox_builder},
+    sandbox_tests_helper::*,
+};
 
 /// HANDLE block response
 
@@ -943,3 +946,218 @@ fn transactions_request_to_multiple_nodes() {
         sandbox.s(ValidatorId(0)),
     ));
 }
+
+/// HANDLE chain fork
+
+/// - should ignore a conflicting last block reported by a single validator
+#[test]
+fn ignore_status_with_conflicting_block_from_one_validator() {
+    let sandbox = timestamping_sandbox();
+
+    sandbox.recv(&sandbox.create_status(
+        &sandbox.p(ValidatorId(1)),
+        Height(1),
+        &empty_hash(),
+        sandbox.s(ValidatorId(1)),
+    ));
+
+    sandbox.assert_state(Height(1), Round(1));
+}
+
+/// - should stop the node if a conflicting last block is reported by f + 1 validators
+#[test]
+fn halt_on_status_with_conflicting_block_from_validators() {
+    let sandbox = timestamping_sandbox();
+
+    sandbox.recv(&sandbox.create_status(
+        &sandbox.p(ValidatorId(1)),
+        Height(1),
+        &empty_hash(),
+        sandbox.s(ValidatorId(1)),
+    ));
+    sandbox.recv(&sandbox.create_status(
+        &sandbox.p(ValidatorId(2)),
+        Height(1),
+        &empty_hash(),
+        sandbox.s(ValidatorId(2)),
+    ));
+
+    sandbox.assert_shutdown();
+}
+
+/// - should not stop the node if the validators report different conflicting blocks
+#[test]
+fn ignore_status_with_different_conflicting_blocks() {
+    let sandbox = timestamping_sandbox();
+
+    sandbox.recv(&sandbox.create_status(
+        &sandbox.p(ValidatorId(1)),
+        Height(1),
+        &empty_hash(),
+        sandbox.s(ValidatorId(1)),
+    ));
+    sandbox.recv(&sandbox.create_status(
+        &sandbox.p(ValidatorId(2)),
+        Height(1),
+        &hash(&[1]),
+        sandbox.s(ValidatorId(2)),
+    ));
+
+    sandbox.assert_state(Height(1), Round(1));
+}
+
+/// - should stop the node if a block on top of another previous block is committed
+/// by the majority of validators
+#[test]
+fn halt_on_block_response_with_conflicting_prev_hash() {
+    let sandbox = timestamping_sandbox();
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_prev_hash(&empty_hash())
+        .build();
+    let block = BlockBuilder::new(&sandbox)
+        .with_prev_hash(&empty_hash())
+        .build();
+
+    let precommits = (1..4).map(|id| {
+        sandbox.create_precommit(
+            ValidatorId(id),
+            Height(1),
+            Round(1),
+            &propose.hash(),
+            &block.hash(),
+            sandbox.time().into(),
+            sandbox.s(ValidatorId(id)),
+        )
+    });
+
+    sandbox.recv(&sandbox.create_block_response(
+        &sandbox.p(ValidatorId(3)),
+        &sandbox.p(ValidatorId(0)),
+        block.clone(),
+        precommits,
+        &[],
+        sandbox.s(ValidatorId(3)),
+    ));
+
+    sandbox.assert_shutdown();
+}
+
+/// - should ignore a block on top of another previous block without the majority of precommits
+#[test]
+fn ignore_block_response_with_conflicting_prev_hash_without_consensus() {
+    let sandbox = timestamping_sandbox();
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_prev_hash(&empty_hash())
+        .build();
+    let block = BlockBuilder::new(&sandbox)
+        .with_prev_hash(&empty_hash())
+        .build();
+
+    let precommit = sandbox.create_precommit(
+        ValidatorId(1),
+        Height(1),
+        Round(1),
+        &propose.hash(),
+        &block.hash(),
+        sandbox.time().into(),
+        sandbox.s(ValidatorId(1)),
+    );
+
+    sandbox.recv(&sandbox.create_block_response(
+        &sandbox.p(ValidatorId(3)),
+        &sandbox.p(ValidatorId(0)),
+        block.clone(),
+        vec![precommit],
+        &[],
+        sandbox.s(ValidatorId(3)),
+    ));
+
+    sandbox.assert_state(Height(1), Round(1));
+}
+
+/// HANDLE block response in the follower mode
+
+/// - follower should fetch blocks from the trusted node and ignore consensus messages
+/// idea of test is:
+/// - send BlockRequest to the trusted node without waiting for its Status
+/// - receive BlockResponse, block should be committed without broadcasting Status
+/// - receive Prevote for the next height, it should not trigger any requests
+/// - request the next block from the trusted node right after the commit and by the poll timeout
+#[test]
+fn follower_fetches_blocks_from_trusted_node() {
+    let sandbox = timestamping_sandbox_builder().as_follower(1000).build();
+
+    let tx = gen_timestamping_tx();
+
+    let propose = ProposeBuilder::new(&sandbox).build();
+
+    let block = BlockBuilder::new(&sandbox)
+        .with_tx_hash(&tx.hash())
+        .with_state_hash(&sandbox.compute_state_hash(&[tx.clone()]))
+        .build();
+
+    let precommits = (1..4)
+        .map(|i| {
+            sandbox.create_precommit(
+                ValidatorId(i),
+                Height(1),
+                Round(1),
+                &propose.hash(),
+                &block.hash(),
+                sandbox.time().into(),
+                sandbox.s(ValidatorId(i)),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    sandbox.add_time(Duration::from_millis(BLOCK_REQUEST_TIMEOUT));
+    sandbox.send(
+        sandbox.p(ValidatorId(1)),
+        &sandbox.create_block_request(
+            &sandbox.p(ValidatorId(0)),
+            &sandbox.p(ValidatorId(1)),
+            Height(1),
+            sandbox.s(ValidatorId(0)),
+        ),
+    );
+    sandbox.recv(&tx);
+
+    sandbox.recv(&sandbox.create_block_response(
+        &sandbox.p(ValidatorId(1)),
+        &sandbox.p(ValidatorId(0)),
+        block.clone(),
+        precommits,
+        &[tx.hash()],
+        sandbox.s(ValidatorId(1)),
+    ));
+    sandbox.assert_state(Height(2), Round(1));
+
+    let propose = ProposeBuilder::new(&sandbox).build();
+    sandbox.recv(&sandbox.create_prevote(
+        ValidatorId(2),
+        Height(2),
+        Round(1),
+        &propose.hash(),
+        NOT_LOCKED,
+        sandbox.s(ValidatorId(2)),
+    ));
+
+    let block_request = sandbox.create_block_request(
+        &sandbox.p(ValidatorId(0)),
+        &sandbox.p(ValidatorId(1)),
+        Height(2),
+        sandbox.s(ValidatorId(0)),
+    );
+    sandbox.add_time(Duration::from_millis(BLOCK_REQUEST_TIMEOUT));
+    sandbox.send(sandbox.p(ValidatorId(1)), &block_request);
+
+    // The trusted node has no block for the next height yet.
+    sandbox.add_time(Duration::from_millis(BLOCK_REQUEST_TIMEOUT));
+    sandbox.add_time(Duration::from_millis(1000 - 3 * BLOCK_REQUEST_TIMEOUT));
+
+    sandbox.add_time(Duration::from_millis(BLOCK_REQUEST_TIMEOUT));
+    sandbox.send(sandbox.p(ValidatorId(1)), &block_request);
+    sandbox.assert_state(Height(2), Round(1));
+}
@@ -15,10 +15,14 @@
 //! Tests in this module are designed to test configuration change protocol.
 
 use crate::blockchain::Schema;
-use crate::crypto::CryptoHash;
+use crate::crypto::{gen_keypair, CryptoHash};
 use crate::helpers::{Height, ValidatorId};
+use crate::messages::{KeyRotation, Message, ValidatorKeyKind, KEY_ROTATION_SERVICE_ID};
+use crate::node::RotatedKey;
 use crate::sandbox::{
-    config_updater::TxConfig, sandbox::timestamping_sandbox, sandbox_tests_helper::*,
+    config_updater::TxConfig,
+    sandbox::{timestamping_sandbox, timestamping_sandbox_builder},
+    sandbox_tests_helper::*,
 };
 use crate::storage::StorageValue;
 
@@ -121,3 +125,82 @@ fn test_schema_config_changes() {
         following_cfg
     );
 }
+
+/// - switch to the rotated consensus key
+/// - idea of the test is to rotate the consensus key of the sandbox validator
+/// - node signs consensus messages with the new key since the rotation height
+#[test]
+fn test_switch_to_rotated_consensus_key() {
+    let (new_key, new_secret_key) = gen_keypair();
+    let sandbox = timestamping_sandbox_builder()
+        .with_rotated_keys(vec![RotatedKey {
+            kind: ValidatorKeyKind::Consensus,
+            public_key: new_key,
+            secret_key: new_secret_key.clone(),
+        }])
+        .build();
+    let sandbox_state = SandboxState::new();
+
+    let old_key = sandbox.p(ValidatorId(0));
+    let rotation_height = sandbox.current_height().next().next();
+    let tx_rotation = Message::sign_transaction(
+        KeyRotation::new(
+            ValidatorKeyKind::Consensus,
+            old_key,
+            new_key,
+            &new_secret_key,
+            rotation_height,
+        ),
+        KEY_ROTATION_SERVICE_ID,
+        old_key,
+        sandbox.s(ValidatorId(0)),
+    );
+
+    add_one_height_with_transactions(&sandbox, &sandbox_state, &[tx_rotation]);
+    assert_eq!(*sandbox.node_state().consensus_public_key(), old_key);
+    add_one_height(&sandbox, &sandbox_state);
+    assert_eq!(sandbox.current_height(), rotation_height);
+    assert_eq!(sandbox.p(ValidatorId(0)), new_key);
+    assert_eq!(*sandbox.node_state().consensus_public_key(), new_key);
+
+    // Prevotes and precommits of the node are expected to be signed with the new key.
+    add_one_height(&sandbox, &sandbox_state);
+}
+
+/// - add the rotated consensus key of another validator to the connect list
+/// - idea of the test is to rotate the consensus key of another validator
+/// - node accepts connections with the new key after the rotation height
+#[test]
+fn test_add_rotated_consensus_key_to_connect_list() {
+    let sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+
+    let (new_key, new_secret_key) = gen_keypair();
+    let old_key = sandbox.p(ValidatorId(1));
+    let tx_rotation = Message::sign_transaction(
+        KeyRotation::new(
+            ValidatorKeyKind::Consensus,
+            old_key,
+            new_key,
+            &new_secret_key,
+            sandbox.current_height().next().next(),
+        ),
+        KEY_ROTATION_SERVICE_ID,
+        old_key,
+        sandbox.s(ValidatorId(1)),
+    );
+
+    add_one_height_with_transactions(&sandbox, &sandbox_state, &[tx_rotation]);
+    assert!(!sandbox.node_state().peer_in_connect_list(&new_key));
+    add_one_height(&sandbox, &sandbox_state);
+
+    let connect_list = sandbox.node_state().connect_list();
+    assert_eq!(
+        connect_list
+            .find_address_by_key(&new_key)
+            .map(|a| a.address),
+        Some(sandbox.a(ValidatorId(1)))
+    );
+    // The validator uses the old key for connections until it restarts.
+    assert!(connect_list.is_peer_allowed(&old_key));
+}
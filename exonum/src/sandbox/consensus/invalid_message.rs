@@ -15,9 +15,15 @@
 //! Tests in this module are designed to test ability of the node to handle
 //! incorrect messages.
 
+use chrono::{DateTime, Duration, Utc};
+
+use crate::crypto;
 use crate::helpers::{Height, Round, ValidatorId};
-use crate::messages::{Message, Propose};
-use crate::sandbox::{sandbox::timestamping_sandbox, sandbox_tests_helper::*};
+use crate::messages::{Evidence, Message, Propose, EVIDENCE_SERVICE_ID};
+use crate::sandbox::{
+    sandbox::{timestamping_sandbox, timestamping_sandbox_builder},
+    sandbox_tests_helper::*,
+};
 
 /// HANDLE message
 /// - verify signature
@@ -53,6 +59,7 @@ fn test_ignore_message_with_incorrect_validator_id() {
             Round(1),
             &sandbox.last_hash(),
             &[],
+            sandbox.time().into(),
         ),
         sandbox.p(ValidatorId(1)),
         sandbox.s(ValidatorId(1)),
@@ -74,6 +81,111 @@ fn ignore_propose_with_incorrect_prev_hash() {
     sandbox.recv(&propose);
 }
 
+#[test]
+fn ignore_propose_with_time_before_last_block() {
+    let sandbox = timestamping_sandbox();
+    add_one_height(&sandbox, &SandboxState::new());
+
+    let leader = sandbox.current_leader();
+    let propose = Message::concrete(
+        Propose::new(
+            leader,
+            sandbox.current_height(),
+            sandbox.current_round(),
+            &sandbox.last_hash(),
+            &[],
+            sandbox.last_block().time() - Duration::seconds(1),
+        ),
+        sandbox.p(leader),
+        sandbox.s(leader),
+    );
+
+    sandbox.recv(&propose);
+}
+
+/// HANDLE propose with the time ahead of the local clock
+/// - the propose is accepted, but the node does not vote for it
+#[test]
+fn ignore_propose_with_time_ahead_of_local_clock() {
+    let sandbox = timestamping_sandbox_builder()
+        .with_consensus(|config| config.max_propose_time_drift = 1000)
+        .build();
+
+    let leader = sandbox.current_leader();
+    let propose = Message::concrete(
+        Propose::new(
+            leader,
+            sandbox.current_height(),
+            sandbox.current_round(),
+            &sandbox.last_hash(),
+            &[],
+            DateTime::<Utc>::from(sandbox.time()) + Duration::seconds(2),
+        ),
+        sandbox.p(leader),
+        sandbox.s(leader),
+    );
+
+    sandbox.recv(&propose);
+    // Prevote is not broadcast.
+}
+
+/// HANDLE propose with the time far ahead of the local clock under the default config
+/// - the propose is accepted, but the node does not vote for it
+#[test]
+fn ignore_propose_with_far_future_time_by_default() {
+    let sandbox = timestamping_sandbox();
+
+    let leader = sandbox.current_leader();
+    let propose = Message::concrete(
+        Propose::new(
+            leader,
+            sandbox.current_height(),
+            sandbox.current_round(),
+            &sandbox.last_hash(),
+            &[],
+            DateTime::<Utc>::from(sandbox.time()) + Duration::days(365),
+        ),
+        sandbox.p(leader),
+        sandbox.s(leader),
+    );
+
+    sandbox.recv(&propose);
+    // Prevote is not broadcast.
+}
+
+/// HANDLE propose with the time ahead of the local clock within `max_propose_time_drift`
+/// - the node votes for the propose as usual
+#[test]
+fn handle_propose_with_time_within_drift() {
+    let sandbox = timestamping_sandbox_builder()
+        .with_consensus(|config| config.max_propose_time_drift = 1000)
+        .build();
+
+    let leader = sandbox.current_leader();
+    let propose = Message::concrete(
+        Propose::new(
+            leader,
+            sandbox.current_height(),
+            sandbox.current_round(),
+            &sandbox.last_hash(),
+            &[],
+            DateTime::<Utc>::from(sandbox.time()) + Duration::milliseconds(500),
+        ),
+        sandbox.p(leader),
+        sandbox.s(leader),
+    );
+
+    sandbox.recv(&propose);
+    sandbox.broadcast(&sandbox.create_prevote(
+        ValidatorId(0),
+        sandbox.current_height(),
+        sandbox.current_round(),
+        &propose.hash(),
+        NOT_LOCKED,
+        sandbox.s(ValidatorId(0)),
+    ));
+}
+
 #[test]
 fn ignore_propose_from_non_leader() {
     let sandbox = timestamping_sandbox();
@@ -139,3 +251,135 @@ fn handle_propose_that_sends_before_than_propose_timeout_exceeded() {
         sandbox.s(ValidatorId(0)),
     ));
 }
+
+/// HANDLE conflicting messages
+/// - node should broadcast evidence transaction signed with its service key
+
+#[test]
+fn broadcast_evidence_on_conflicting_prevotes() {
+    let sandbox = timestamping_sandbox();
+
+    let first = sandbox.create_prevote(
+        ValidatorId(1),
+        Height(1),
+        Round(1),
+        &crypto::hash(&[1]),
+        NOT_LOCKED,
+        sandbox.s(ValidatorId(1)),
+    );
+    let second = sandbox.create_prevote(
+        ValidatorId(1),
+        Height(1),
+        Round(1),
+        &crypto::hash(&[2]),
+        NOT_LOCKED,
+        sandbox.s(ValidatorId(1)),
+    );
+
+    sandbox.recv(&first);
+    // The same prevote is not a conflict.
+    sandbox.recv(&first);
+    sandbox.recv(&second);
+
+    let evidence = Evidence::prevotes(first, second).unwrap();
+    let tx = {
+        let state = sandbox.node_state();
+        Message::sign_transaction(
+            evidence,
+            EVIDENCE_SERVICE_ID,
+            *state.service_public_key(),
+            state.service_secret_key(),
+        )
+    };
+    sandbox.broadcast(&tx);
+    sandbox.assert_pool_len(1);
+}
+
+/// - node should submit the evidence of the same equivocation only once
+#[test]
+fn ignore_evidence_of_known_equivocation() {
+    let sandbox = timestamping_sandbox();
+
+    let prevote = |propose_hash| {
+        sandbox.create_prevote(
+            ValidatorId(1),
+            Height(1),
+            Round(1),
+            &propose_hash,
+            NOT_LOCKED,
+            sandbox.s(ValidatorId(1)),
+        )
+    };
+    let (first, second, third) = (
+        prevote(crypto::hash(&[1])),
+        prevote(crypto::hash(&[2])),
+        prevote(crypto::hash(&[3])),
+    );
+
+    sandbox.recv(&first);
+    sandbox.recv(&second);
+
+    let evidence = Evidence::prevotes(first.clone(), second).unwrap();
+    let tx = {
+        let state = sandbox.node_state();
+        Message::sign_transaction(
+            evidence,
+            EVIDENCE_SERVICE_ID,
+            *state.service_public_key(),
+            state.service_secret_key(),
+        )
+    };
+    sandbox.broadcast(&tx);
+
+    // Another conflicting prevote of the validator at the same round is not reported.
+    sandbox.recv(&third);
+    // Evidence of the same equivocation submitted by another node is not accepted.
+    let (public_key, secret_key) = crypto::gen_keypair();
+    sandbox.recv(&Message::sign_transaction(
+        Evidence::prevotes(first, third).unwrap(),
+        EVIDENCE_SERVICE_ID,
+        public_key,
+        &secret_key,
+    ));
+    sandbox.assert_pool_len(1);
+}
+
+#[test]
+fn broadcast_evidence_on_conflicting_precommits() {
+    let sandbox = timestamping_sandbox();
+
+    let propose_hash = crypto::hash(&[1]);
+    let first = sandbox.create_precommit(
+        ValidatorId(2),
+        Height(1),
+        Round(1),
+        &propose_hash,
+        &crypto::hash(&[2]),
+        sandbox.time().into(),
+        sandbox.s(ValidatorId(2)),
+    );
+    let second = sandbox.create_precommit(
+        ValidatorId(2),
+        Height(1),
+        Round(1),
+        &propose_hash,
+        &crypto::hash(&[3]),
+        sandbox.time().into(),
+        sandbox.s(ValidatorId(2)),
+    );
+
+    sandbox.recv(&first);
+    sandbox.recv(&second);
+
+    let evidence = Evidence::precommits(second, first).unwrap();
+    let tx = {
+        let state = sandbox.node_state();
+        Message::sign_transaction(
+            evidence,
+            EVIDENCE_SERVICE_ID,
+            *state.service_public_key(),
+            state.service_secret_key(),
+        )
+    };
+    sandbox.broadcast(&tx);
+}
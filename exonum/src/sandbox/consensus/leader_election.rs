@@ -0,0 +1,242 @@
+// Copyright 2019 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests in this module are designed to test the leader election strategies
+//! selected in the consensus configuration and the maintenance of validators.
+
+use std::time::Duration;
+
+use crate::blockchain::LeaderElectionStrategy;
+use crate::crypto::CryptoHash;
+use crate::helpers::{Height, Round, ValidatorId};
+use crate::messages::{MaintenanceNotice, Message, RawTransaction, Signed, MAINTENANCE_SERVICE_ID};
+use crate::node::{state::BLOCK_REQUEST_TIMEOUT, ExternalMessage};
+use crate::sandbox::{sandbox::timestamping_sandbox_builder, sandbox_tests_helper::*};
+
+fn maintenance_notice(
+    sandbox: &TimestampingSandbox,
+    validator: ValidatorId,
+    until: Height,
+) -> Signed<RawTransaction> {
+    Message::sign_transaction(
+        MaintenanceNotice::new(until),
+        MAINTENANCE_SERVICE_ID,
+        sandbox.service_p(validator),
+        sandbox.service_s(validator),
+    )
+}
+
+/// HANDLE propose with the round-robin leader election
+/// - the leader of the round is `(height + round) % validators_count`
+
+#[test]
+fn round_robin_leaders() {
+    let sandbox = timestamping_sandbox_builder().build();
+    let sandbox_state = SandboxState::new();
+
+    for round in 1..=4 {
+        assert_eq!(
+            sandbox.leader(Round(round)),
+            ValidatorId(((1 + round) % 4) as u16)
+        );
+    }
+    add_one_height(&sandbox, &sandbox_state);
+    assert_eq!(sandbox.leader(Round(1)), ValidatorId(3));
+}
+
+/// HANDLE propose with the weighted leader election
+/// - validator with the overwhelming voting power is the leader
+/// - propose of the round-robin leader is ignored
+
+#[test]
+fn weighted_leader() {
+    let sandbox = timestamping_sandbox_builder()
+        .with_voting_powers(vec![1, 1, 1, 1_000_000])
+        .with_consensus(|config| config.leader_election = LeaderElectionStrategy::Weighted)
+        .build();
+    assert_eq!(sandbox.leader(Round(1)), ValidatorId(3));
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_validator(ValidatorId(2))
+        .build();
+    sandbox.recv(&propose);
+
+    let propose = ProposeBuilder::new(&sandbox).build();
+    sandbox.recv(&propose);
+    sandbox.broadcast(&sandbox.create_prevote(
+        ValidatorId(0),
+        Height(1),
+        Round(1),
+        &propose.hash(),
+        NOT_LOCKED,
+        sandbox.s(ValidatorId(0)),
+    ));
+}
+
+/// HANDLE propose with the reputation-based leader election
+/// - validators which have missed their turns are moved to the end of the order
+/// - propose of the round-robin leader is ignored
+
+#[test]
+fn reputation_leader() {
+    let sandbox = timestamping_sandbox_builder()
+        .with_consensus(|config| {
+            config.leader_election = LeaderElectionStrategy::Reputation { missed_proposes: 1 }
+        })
+        .build();
+    let sandbox_state = SandboxState::new();
+
+    // Validator 1 proposes all the blocks, so validators 2, 3 and 0 miss their turns
+    // at heights 1, 2 and 3.
+    for _ in 1..5 {
+        add_one_height_with_transactions_from_other_validator(&sandbox, &sandbox_state, &[]);
+    }
+    sandbox.assert_state(Height(5), Round(1));
+    assert_eq!(sandbox.leader(Round(1)), ValidatorId(1));
+    assert_eq!(sandbox.leader(Round(2)), ValidatorId(2));
+    assert_eq!(sandbox.leader(Round(4)), ValidatorId(0));
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_validator(ValidatorId(2))
+        .build();
+    sandbox.recv(&propose);
+
+    let propose = ProposeBuilder::new(&sandbox).build();
+    sandbox.recv(&propose);
+    sandbox.broadcast(&sandbox.create_prevote(
+        ValidatorId(0),
+        Height(5),
+        Round(1),
+        &propose.hash(),
+        NOT_LOCKED,
+        sandbox.s(ValidatorId(0)),
+    ));
+}
+
+/// HANDLE maintenance request
+/// - node broadcasts the maintenance notice signed by its service key
+
+#[test]
+fn announce_maintenance() {
+    let sandbox = timestamping_sandbox_builder().build();
+
+    sandbox
+        .node_handler_mut()
+        .channel
+        .api_requests
+        .send(ExternalMessage::Maintenance(Height(10)))
+        .unwrap();
+    sandbox.process_events();
+
+    sandbox.broadcast(&maintenance_notice(&sandbox, ValidatorId(0), Height(10)));
+    sandbox.assert_pool_len(1);
+}
+
+/// HANDLE propose with a validator in maintenance
+/// - leader slots of the validator in maintenance are taken by the next validator
+/// - the validator leads the rounds again at the deadline height
+
+#[test]
+fn validator_in_maintenance_is_skipped() {
+    let sandbox = timestamping_sandbox_builder().build();
+    let sandbox_state = SandboxState::new();
+
+    let notice = maintenance_notice(&sandbox, ValidatorId(2), Height(3));
+    add_one_height_with_transactions(&sandbox, &sandbox_state, &[notice]);
+    sandbox.assert_state(Height(2), Round(1));
+    assert_eq!(sandbox.leader(Round(1)), ValidatorId(3));
+    assert_eq!(sandbox.leader(Round(4)), ValidatorId(3));
+
+    add_one_height(&sandbox, &sandbox_state);
+    sandbox.assert_state(Height(3), Round(1));
+    assert_eq!(sandbox.leader(Round(3)), ValidatorId(2));
+}
+
+/// HANDLE consensus messages in maintenance
+/// - node in maintenance does not vote for proposes
+/// - node fetches committed blocks with block requests
+/// - node takes part in the consensus again at the deadline height
+
+#[test]
+fn node_in_maintenance() {
+    let sandbox = timestamping_sandbox_builder().build();
+    let sandbox_state = SandboxState::new();
+
+    let notice = maintenance_notice(&sandbox, ValidatorId(0), Height(3));
+    add_one_height_with_transactions(&sandbox, &sandbox_state, &[notice]);
+    sandbox.assert_state(Height(2), Round(1));
+
+    let propose = ProposeBuilder::new(&sandbox).build();
+    let block = BlockBuilder::new(&sandbox).build();
+    let precommits = (1..4)
+        .map(|id| {
+            let id = ValidatorId(id);
+            sandbox.create_precommit(
+                id,
+                Height(2),
+                Round(1),
+                &propose.hash(),
+                &block.hash(),
+                sandbox.time().into(),
+                sandbox.s(id),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    // Consensus messages are ignored.
+    sandbox.recv(&propose);
+    for precommit in &precommits {
+        sandbox.recv(precommit);
+    }
+    sandbox.assert_state(Height(2), Round(1));
+
+    sandbox.recv(&sandbox.create_status(
+        &sandbox.p(ValidatorId(3)),
+        Height(3),
+        &block.hash(),
+        sandbox.s(ValidatorId(3)),
+    ));
+    sandbox.add_time(Duration::from_millis(BLOCK_REQUEST_TIMEOUT));
+    sandbox.send(
+        sandbox.p(ValidatorId(3)),
+        &sandbox.create_block_request(
+            &sandbox.p(ValidatorId(0)),
+            &sandbox.p(ValidatorId(3)),
+            Height(2),
+            sandbox.s(ValidatorId(0)),
+        ),
+    );
+    sandbox.recv(&sandbox.create_block_response(
+        &sandbox.p(ValidatorId(3)),
+        &sandbox.p(ValidatorId(0)),
+        block.clone(),
+        precommits,
+        &[],
+        sandbox.s(ValidatorId(3)),
+    ));
+    sandbox.assert_state(Height(3), Round(1));
+    sandbox.broadcast(&sandbox.create_status(
+        &sandbox.p(ValidatorId(0)),
+        Height(3),
+        &block.hash(),
+        sandbox.s(ValidatorId(0)),
+    ));
+
+    // The maintenance is over, and the node is the leader of the first round.
+    assert_eq!(sandbox.leader(Round(1)), ValidatorId(0));
+    sandbox.add_time(Duration::from_millis(PROPOSE_TIMEOUT));
+    let propose = ProposeBuilder::new(&sandbox).build();
+    sandbox.broadcast(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+}
@@ -18,6 +18,7 @@ mod basic;
 mod block_request;
 mod config;
 mod invalid_message;
+mod leader_election;
 mod recovery;
 mod round_details;
 mod timeouts;
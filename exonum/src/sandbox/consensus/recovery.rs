@@ -19,7 +19,7 @@ use std::time::Duration;
 
 use crate::crypto::CryptoHash;
 use crate::helpers::{user_agent, Height, Round, ValidatorId};
-use crate::node;
+use crate::node::{self, ConsensusLog, ReplayReport};
 use crate::sandbox::{
     sandbox::{timestamping_sandbox, SandboxBuilder},
     sandbox_tests_helper::*,
@@ -269,6 +269,121 @@ fn should_save_precommit_to_consensus_cache() {
     sandbox_restarted.check_broadcast_status(Height(2), &block.hash());
 }
 
+/// Idea:
+/// - Node locks on some Propose and broadcasts `Precommit`.
+/// - Consensus log of the node is decoded and replayed into another node.
+/// - Another node locks on the same Propose and broadcasts the same `Prevote` and `Precommit`.
+#[test]
+fn should_replay_consensus_log() {
+    let sandbox = timestamping_sandbox();
+
+    let propose = ProposeBuilder::new(&sandbox).build();
+    let prevote = make_prevote_from_propose(&sandbox, &propose);
+    let block = BlockBuilder::new(&sandbox).build();
+
+    sandbox.recv(&propose);
+    sandbox.broadcast(&prevote);
+    for i in 1..3 {
+        sandbox.recv(&sandbox.create_prevote(
+            ValidatorId(i),
+            Height(1),
+            Round(1),
+            &propose.hash(),
+            NOT_LOCKED,
+            sandbox.s(ValidatorId(i)),
+        ));
+    }
+    let precommit = sandbox.create_precommit(
+        ValidatorId(0),
+        Height(1),
+        Round(1),
+        &propose.hash(),
+        &block.hash(),
+        sandbox.time().into(),
+        sandbox.s(ValidatorId(0)),
+    );
+    sandbox.broadcast(&precommit);
+
+    let log = ConsensusLog::load(sandbox.blockchain_ref().snapshot());
+    assert_eq!(log.round(), Round(1));
+    let decoded = log.to_string();
+    assert!(decoded.contains(&format!("Propose hash={}", propose.hash().to_hex())));
+    assert!(decoded.contains(&format!(
+        "validator=0 author={} Precommit propose={}",
+        sandbox.p(ValidatorId(0)).to_hex(),
+        propose.hash().to_hex()
+    )));
+
+    let sandbox_replayed = timestamping_sandbox();
+    sandbox_replayed.replay_consensus_log(&log);
+    sandbox_replayed.assert_lock(Round(1), Some(propose.hash()));
+    sandbox_replayed.broadcast(&prevote);
+    sandbox_replayed.broadcast(&precommit);
+}
+
+/// Idea:
+/// - Node locks on some Propose and broadcasts `Precommit`.
+/// - Consensus log of the node is replayed into another node.
+/// - The replay report shows that the consensus stalls in the first round
+///   with the majority of prevotes and a single precommit.
+#[test]
+fn should_report_stalled_consensus_after_replay() {
+    let sandbox = timestamping_sandbox();
+
+    let propose = ProposeBuilder::new(&sandbox).build();
+    let prevote = make_prevote_from_propose(&sandbox, &propose);
+    let block = BlockBuilder::new(&sandbox).build();
+
+    sandbox.recv(&propose);
+    sandbox.broadcast(&prevote);
+    for i in 1..3 {
+        sandbox.recv(&sandbox.create_prevote(
+            ValidatorId(i),
+            Height(1),
+            Round(1),
+            &propose.hash(),
+            NOT_LOCKED,
+            sandbox.s(ValidatorId(i)),
+        ));
+    }
+    let precommit = sandbox.create_precommit(
+        ValidatorId(0),
+        Height(1),
+        Round(1),
+        &propose.hash(),
+        &block.hash(),
+        sandbox.time().into(),
+        sandbox.s(ValidatorId(0)),
+    );
+    sandbox.broadcast(&precommit);
+
+    let log = ConsensusLog::load(sandbox.blockchain_ref().snapshot());
+    let sandbox_replayed = timestamping_sandbox();
+    sandbox_replayed.replay_consensus_log(&log);
+    sandbox_replayed.broadcast(&prevote);
+    sandbox_replayed.broadcast(&precommit);
+
+    let report = ReplayReport::new(&log, &sandbox_replayed.node_state());
+    assert!(!report.is_committed());
+    assert_eq!(report.round(), Round(1));
+    assert_eq!(
+        report.prevotes().iter().collect::<Vec<_>>(),
+        vec![(&(Round(1), propose.hash()), &3)]
+    );
+    assert_eq!(
+        report.precommits().iter().collect::<Vec<_>>(),
+        vec![(&(Round(1), block.hash()), &1)]
+    );
+    assert!(report.requests().is_empty());
+
+    let report = report.to_string();
+    assert!(report.starts_with("stalled at height 1 round 1\n"));
+    assert!(report.contains(&format!(
+        "round=1 Precommits block={} voting_power=1/3",
+        block.hash().to_hex()
+    )));
+}
+
 /// Idea:
 /// - Node locks on some Propose.
 /// - Node doesn't get any precommits within the round.
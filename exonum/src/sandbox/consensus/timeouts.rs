@@ -16,10 +16,14 @@
 
 use std::time::Duration;
 
+use crate::blockchain::AdaptiveTimeouts;
 use crate::crypto::CryptoHash;
 use crate::helpers::{Height, Round, ValidatorId};
 use crate::node::state::PROPOSE_REQUEST_TIMEOUT;
-use crate::sandbox::{sandbox::timestamping_sandbox, sandbox_tests_helper::*};
+use crate::sandbox::{
+    sandbox::{timestamping_sandbox, timestamping_sandbox_builder},
+    sandbox_tests_helper::*,
+};
 
 /// HANDLE ROUND TIMEOUT:
 /// - Ignore if height and round are not the same
@@ -156,6 +160,146 @@ fn test_send_propose_and_prevote_when_we_are_leader() {
     sandbox.add_time(Duration::from_millis(0));
 }
 
+/// HANDLE PROPOSE TIMEOUT with the `max_idle_timeout` option:
+/// - leader does not propose an empty block
+/// - propose is sent as soon as a transaction is received
+#[test]
+fn test_wait_for_transactions_when_we_are_leader() {
+    let sandbox = timestamping_sandbox_builder()
+        .with_consensus(|config| config.max_idle_timeout = Some(10_000))
+        .build();
+
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    sandbox.add_time(Duration::from_millis(
+        sandbox.current_round_timeout() + PROPOSE_TIMEOUT,
+    ));
+    sandbox.assert_state(Height(1), Round(3));
+    sandbox.add_time(Duration::from_millis(PROPOSE_TIMEOUT));
+
+    // we are leader, but the pool is empty
+    let tx = gen_timestamping_tx();
+    sandbox.recv(&tx);
+    sandbox.add_time(Duration::from_millis(0));
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_tx_hashes(&[tx.hash()])
+        .build();
+    sandbox.broadcast(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+    sandbox.add_time(Duration::from_millis(0));
+}
+
+/// HANDLE PROPOSE TIMEOUT with the `max_idle_timeout` option:
+/// - leader proposes an empty block when `max_idle_timeout` passes since the height start
+#[test]
+fn test_send_heartbeat_propose_when_we_are_leader() {
+    let sandbox = timestamping_sandbox_builder()
+        .with_consensus(|config| config.max_idle_timeout = Some(3000))
+        .build();
+
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    sandbox.add_time(Duration::from_millis(
+        sandbox.current_round_timeout() + PROPOSE_TIMEOUT,
+    ));
+    sandbox.assert_state(Height(1), Round(3));
+
+    // propose timeout has fired at 2300 ms since the height start, the heartbeat is due at 3000 ms
+    sandbox.add_time(Duration::from_millis(699));
+    sandbox.add_time(Duration::from_millis(1));
+    sandbox.assert_state(Height(1), Round(3));
+
+    let propose = ProposeBuilder::new(&sandbox).build();
+    sandbox.broadcast(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+    sandbox.add_time(Duration::from_millis(0));
+}
+
+/// HANDLE PROPOSE TIMEOUT with the `max_idle_timeout` option:
+/// - leader waits for transactions, but the round ends before `max_idle_timeout` passes
+/// - leader proposes an empty block in its next round
+#[test]
+fn test_send_heartbeat_propose_in_next_leader_round() {
+    let sandbox = timestamping_sandbox_builder()
+        .with_consensus(|config| config.max_idle_timeout = Some(5000))
+        .build();
+
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    sandbox.add_time(Duration::from_millis(
+        sandbox.current_round_timeout() + PROPOSE_TIMEOUT,
+    ));
+    sandbox.assert_state(Height(1), Round(3));
+    // we are leader, but the pool is empty
+    sandbox.add_time(Duration::from_millis(0));
+
+    // the heartbeat is due in a round of another leader
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    while !sandbox.is_leader() {
+        sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    }
+    sandbox.assert_state(Height(1), Round(7));
+
+    let propose = ProposeBuilder::new(&sandbox).build();
+    sandbox.broadcast(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+    sandbox.add_time(Duration::from_millis(0));
+}
+
+/// HANDLE PROPOSE TIMEOUT with the `max_idle_timeout` option:
+/// - leader waits for transactions, but the block of another validator is committed
+/// - leader waits for transactions at the next height as well
+/// - propose is sent as soon as a transaction is received
+#[test]
+fn test_wait_for_transactions_at_next_height() {
+    let sandbox = timestamping_sandbox_builder()
+        .with_consensus(|config| config.max_idle_timeout = Some(10_000))
+        .build();
+
+    let propose = ProposeBuilder::new(&sandbox).build();
+    let block = BlockBuilder::new(&sandbox).build();
+    sandbox.recv(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    sandbox.add_time(Duration::from_millis(
+        sandbox.current_round_timeout() + PROPOSE_TIMEOUT,
+    ));
+    sandbox.assert_state(Height(1), Round(3));
+    // we are leader, but the pool is empty
+    sandbox.add_time(Duration::from_millis(0));
+
+    for validator in 1..4 {
+        let validator = ValidatorId(validator);
+        sandbox.recv(&sandbox.create_precommit(
+            validator,
+            Height(1),
+            Round(1),
+            &propose.hash(),
+            &block.hash(),
+            sandbox.time().into(),
+            sandbox.s(validator),
+        ));
+    }
+    sandbox.assert_state(Height(2), Round(1));
+    sandbox.check_broadcast_status(Height(2), &block.hash());
+
+    while !sandbox.is_leader() {
+        sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    }
+    sandbox.add_time(Duration::from_millis(PROPOSE_TIMEOUT));
+    // we are leader at the new height, but the pool is empty
+
+    let tx = gen_timestamping_tx();
+    sandbox.recv(&tx);
+    sandbox.add_time(Duration::from_millis(0));
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_tx_hashes(&[tx.hash()])
+        .build();
+    sandbox.broadcast(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+    sandbox.add_time(Duration::from_millis(0));
+}
+
 /// HANDLE ROUND TIMEOUT:
 /// - send prevote if locked to propose
 /// idea:
@@ -284,3 +428,120 @@ fn test_round_timeout_increase() {
     sandbox.add_time(Duration::from_millis(1));
     sandbox.assert_state(Height(1), Round(5));
 }
+
+/// Commits an empty block at the first round of the current height. The propose is received
+/// after `propose_delay` since the start of the round, and the block is committed after
+/// `latency` since the propose is received.
+fn commit_empty_block_after(sandbox: &TimestampingSandbox, propose_delay: u64, latency: u64) {
+    sandbox.add_time(Duration::from_millis(propose_delay));
+
+    let height = sandbox.current_height();
+    let propose = ProposeBuilder::new(&sandbox).build();
+    let block = BlockBuilder::new(&sandbox).build();
+
+    sandbox.recv(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+    sandbox.add_time(Duration::from_millis(latency));
+    for i in 1..3 {
+        sandbox.recv(&sandbox.create_prevote(
+            ValidatorId(i),
+            height,
+            Round(1),
+            &propose.hash(),
+            NOT_LOCKED,
+            sandbox.s(ValidatorId(i)),
+        ));
+    }
+
+    sandbox.broadcast(&sandbox.create_precommit(
+        ValidatorId(0),
+        height,
+        Round(1),
+        &propose.hash(),
+        &block.hash(),
+        sandbox.time().into(),
+        sandbox.s(ValidatorId(0)),
+    ));
+    for i in 1..3 {
+        sandbox.recv(&sandbox.create_precommit(
+            ValidatorId(i),
+            height,
+            Round(1),
+            &propose.hash(),
+            &block.hash(),
+            sandbox.time().into(),
+            sandbox.s(ValidatorId(i)),
+        ));
+    }
+
+    sandbox.assert_state(height.next(), Round(1));
+    sandbox.check_broadcast_status(height.next(), &block.hash());
+}
+
+/// HANDLE ROUND TIMEOUT with the `adaptive_timeouts` option:
+/// - round timeout is shortened if the consensus is reached quickly
+#[test]
+fn test_adaptive_round_timeout_decreases_on_fast_consensus() {
+    let sandbox = timestamping_sandbox_builder()
+        .with_consensus(|config| {
+            config.adaptive_timeouts = Some(AdaptiveTimeouts {
+                min_round_timeout: 300,
+                max_round_timeout: 3000,
+                window: 3,
+            })
+        })
+        .build();
+
+    // The latency is not measured yet, so the first round timeout is used.
+    assert_eq!(sandbox.node_handler_mut().first_round_timeout(), 1000);
+
+    // Timeout is twice the latency, but not less than `min_round_timeout`.
+    commit_empty_block_after(&sandbox, 0, 100);
+    sandbox.add_time(Duration::from_millis(299));
+    sandbox.assert_state(Height(2), Round(1));
+    sandbox.add_time(Duration::from_millis(1));
+    sandbox.assert_state(Height(2), Round(2));
+}
+
+/// HANDLE ROUND TIMEOUT with the `adaptive_timeouts` option:
+/// - round timeout is extended if the consensus is reached slowly
+#[test]
+fn test_adaptive_round_timeout_increases_on_slow_consensus() {
+    let sandbox = timestamping_sandbox_builder()
+        .with_consensus(|config| {
+            config.adaptive_timeouts = Some(AdaptiveTimeouts {
+                min_round_timeout: 300,
+                max_round_timeout: 3000,
+                window: 3,
+            })
+        })
+        .build();
+
+    commit_empty_block_after(&sandbox, 0, 900);
+    sandbox.add_time(Duration::from_millis(1799));
+    sandbox.assert_state(Height(2), Round(1));
+    sandbox.add_time(Duration::from_millis(1));
+    sandbox.assert_state(Height(2), Round(2));
+}
+
+/// HANDLE ROUND TIMEOUT with the `adaptive_timeouts` option:
+/// - time passed before the propose is received, e.g., while the leader waits
+///   for transactions, is not counted as the consensus latency
+#[test]
+fn test_adaptive_round_timeout_ignores_propose_delay() {
+    let sandbox = timestamping_sandbox_builder()
+        .with_consensus(|config| {
+            config.adaptive_timeouts = Some(AdaptiveTimeouts {
+                min_round_timeout: 300,
+                max_round_timeout: 3000,
+                window: 3,
+            })
+        })
+        .build();
+
+    commit_empty_block_after(&sandbox, 800, 100);
+    sandbox.add_time(Duration::from_millis(299));
+    sandbox.assert_state(Height(2), Round(1));
+    sandbox.add_time(Duration::from_millis(1));
+    sandbox.assert_state(Height(2), Round(2));
+}
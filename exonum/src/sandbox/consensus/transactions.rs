@@ -18,15 +18,15 @@ use bit_vec::BitVec;
 
 use std::time::Duration;
 
-use crate::crypto::{gen_keypair, CryptoHash, Hash};
+use crate::crypto::{gen_keypair, CryptoHash, Hash, PublicKey, SecretKey};
 use crate::helpers::{Height, Milliseconds, Round, ValidatorId};
-use crate::messages::{RawTransaction, Signed};
+use crate::messages::{Message, RawTransaction, Signed};
 use crate::node::state::TRANSACTIONS_REQUEST_TIMEOUT;
 use crate::sandbox::{
     config_updater::TxConfig,
     sandbox::{timestamping_sandbox, timestamping_sandbox_builder, Sandbox},
     sandbox_tests_helper::*,
-    timestamping::{TimestampingTxGenerator, DATA_SIZE},
+    timestamping::{TimestampTx, TimestampingTxGenerator, DATA_SIZE, TIMESTAMPING_SERVICE},
 };
 
 const MAX_PROPOSE_TIMEOUT: Milliseconds = 200;
@@ -54,6 +54,15 @@ fn tx_hashes(transactions: &[Signed<RawTransaction>]) -> Vec<Hash> {
     hashes
 }
 
+fn gen_timestamping_tx_with_nonce(
+    (public_key, secret_key): &(PublicKey, SecretKey),
+    nonce: u64,
+) -> Signed<RawTransaction> {
+    let mut tx = TimestampTx::new();
+    tx.set_data(vec![nonce as u8; DATA_SIZE]);
+    Message::sign_transaction_with_nonce(tx, TIMESTAMPING_SERVICE, nonce, *public_key, secret_key)
+}
+
 /// sends transactions into pool and returns this transactions in processing order
 fn send_txs_into_pool(
     sandbox: &Sandbox,
@@ -727,3 +736,112 @@ fn expedited_propose_on_transaction_pressure() {
     sandbox.broadcast(&propose);
     sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
 }
+
+/// HANDLE PROPOSE TIMEOUT with `txs_block_cost_limit`:
+/// - leader includes into the propose only the pool transactions fitting into the limit
+#[test]
+fn propose_respects_txs_block_cost_limit() {
+    let sandbox = timestamping_sandbox_builder()
+        .with_consensus(|config| config.txs_block_cost_limit = Some(2))
+        .build();
+
+    let transactions =
+        send_txs_into_pool(&sandbox, TimestampingTxGenerator::new(64).take(3).collect());
+    sandbox.assert_pool_len(3);
+
+    // Wait for us to become the leader.
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    sandbox.add_time(Duration::from_millis(
+        sandbox.current_round_timeout() + PROPOSE_TIMEOUT,
+    ));
+    sandbox.assert_state(Height(1), Round(3));
+
+    // Each transaction costs one unit, so only the first two transactions fit.
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_tx_hashes(&tx_hashes(&transactions[..2]))
+        .build();
+
+    sandbox.broadcast(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+}
+
+/// HANDLE PROPOSE with `txs_block_size_limit`:
+/// - transaction exceeding the limit on its own is not added to the pool
+/// - validator does not vote for the propose exceeding the limit
+#[test]
+fn reject_propose_exceeding_txs_block_size_limit() {
+    let transactions = TimestampingTxGenerator::new(64).take(2).collect::<Vec<_>>();
+    let tx_size = transactions
+        .iter()
+        .map(|tx| tx.signed_message().raw().len() as u64)
+        .max()
+        .unwrap();
+
+    let sandbox = timestamping_sandbox_builder()
+        .with_consensus(|config| config.txs_block_size_limit = Some(tx_size))
+        .build();
+
+    for tx in &transactions {
+        sandbox.recv(tx);
+    }
+    let large_tx = TimestampingTxGenerator::new(128).next().unwrap();
+    sandbox.recv(&large_tx);
+    sandbox.assert_pool_len(2);
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_validator(ValidatorId(2))
+        .with_tx_hashes(&tx_hashes(&transactions))
+        .build();
+    sandbox.recv(&propose);
+
+    // No prevote is broadcast.
+    sandbox.add_time(Duration::from_millis(0));
+}
+
+/// HANDLE PROPOSE TIMEOUT with transactions with nonces:
+/// - leader includes transactions of the author in the order of their nonces
+/// - transaction with a nonce gap stays in the pool
+#[test]
+fn propose_defers_transactions_with_nonce_gaps() {
+    let sandbox = timestamping_sandbox();
+    let keypair = gen_keypair();
+    let transactions = [0, 1, 3]
+        .iter()
+        .map(|&nonce| gen_timestamping_tx_with_nonce(&keypair, nonce))
+        .collect::<Vec<_>>();
+    send_txs_into_pool(&sandbox, transactions.clone());
+    sandbox.assert_pool_len(3);
+
+    // Wait for us to become the leader.
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    sandbox.add_time(Duration::from_millis(
+        sandbox.current_round_timeout() + PROPOSE_TIMEOUT,
+    ));
+    sandbox.assert_state(Height(1), Round(3));
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_tx_hashes(&[transactions[0].hash(), transactions[1].hash()])
+        .build();
+    sandbox.broadcast(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+}
+
+/// HANDLE PROPOSE with transactions with nonces:
+/// - validator does not vote for the propose skipping a nonce of the author
+#[test]
+fn reject_propose_with_nonce_gap() {
+    let sandbox = timestamping_sandbox();
+    let keypair = gen_keypair();
+    let tx = gen_timestamping_tx_with_nonce(&keypair, 1);
+    sandbox.recv(&tx);
+    sandbox.assert_pool_len(1);
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_validator(ValidatorId(2))
+        .with_tx_hashes(&[tx.hash()])
+        .build();
+    sandbox.recv(&propose);
+
+    // No prevote is broadcast.
+    sandbox.add_time(Duration::from_millis(0));
+}
@@ -101,6 +101,8 @@ fn test_get_lock_and_send_precommit() {
         &sandbox.last_hash(),
         &Hash::zero(),
         &sandbox.last_state_hash(),
+        &Hash::zero(),
+        propose.time(),
     );
 
     sandbox.recv(&propose);
@@ -161,6 +163,8 @@ fn test_commit() {
         &sandbox.last_hash(),
         &Hash::zero(),
         &sandbox.last_state_hash(),
+        &Hash::zero(),
+        propose.time(),
     );
 
     sandbox.recv(&propose);
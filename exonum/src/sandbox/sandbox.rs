@@ -33,8 +33,9 @@ use super::{
 };
 use crate::{
     blockchain::{
-        Block, BlockProof, Blockchain, ConsensusConfig, GenesisConfig, Schema, Service,
-        SharedNodeState, StoredConfiguration, Transaction, ValidatorKeys,
+        Block, BlockProof, Blockchain, ConsensusConfig, ExecutionLimits, GenesisConfig,
+        LeaderElectionStrategy, Schema, Service, SharedNodeState, StoredConfiguration, Transaction,
+        ValidatorKeys,
     },
     crypto::{gen_keypair, gen_keypair_from_seed, Hash, PublicKey, SecretKey, Seed, SEED_LENGTH},
     events::{
@@ -44,13 +45,13 @@ use crate::{
     helpers::{user_agent, Height, Milliseconds, Round, ValidatorId},
     messages::{
         BlockRequest, BlockResponse, Connect, Message, PeersRequest, Precommit, Prevote,
-        PrevotesRequest, Propose, ProposeRequest, ProtocolMessage, RawTransaction, Signed,
-        SignedMessage, Status, TransactionsRequest, TransactionsResponse,
+        PrevotesRequest, Propose, ProposeRequest, ProtocolMessage, RawTransaction, Signed, Status,
+        TransactionsRequest, TransactionsResponse, ValidatorKeyKind,
     },
     node::{
-        ApiSender, Configuration, ConnectInfo, ConnectList, ConnectListConfig, ExternalMessage,
-        ListenerConfig, NodeHandler, NodeSender, PeerAddress, ServiceConfig, State,
-        SystemStateProvider,
+        ApiSender, Configuration, ConnectInfo, ConnectList, ConnectListConfig, ConsensusLog,
+        ExternalMessage, FollowerConfig, ListenerConfig, NodeHandler, NodeSender, PeerAddress,
+        RotatedKey, ServiceConfig, State, SystemStateProvider,
     },
     storage::{MapProof, MemoryDB},
 };
@@ -85,6 +86,7 @@ pub struct SandboxInner {
     pub network_requests_rx: mpsc::Receiver<NetworkRequest>,
     pub internal_requests_rx: mpsc::Receiver<InternalRequest>,
     pub api_requests_rx: mpsc::Receiver<ExternalMessage>,
+    pub shutdown: bool,
 }
 
 impl SandboxInner {
@@ -125,11 +127,9 @@ impl SandboxInner {
                     InternalRequest::JumpToRound(height, round) => self
                         .handler
                         .handle_event(InternalEvent::JumpToRound(height, round).into()),
-                    InternalRequest::Shutdown => unimplemented!(),
+                    InternalRequest::Shutdown => self.shutdown = true,
                     InternalRequest::VerifyMessage(message) => {
-                        let protocol =
-                            Message::deserialize(SignedMessage::from_raw_buffer(message).unwrap())
-                                .unwrap();
+                        let protocol = Message::from_raw_buffer(message).unwrap();
                         self.handler.handle_event(
                             InternalEvent::MessageVerified(Box::new(protocol)).into(),
                         );
@@ -206,6 +206,16 @@ impl Sandbox {
         &self.validators_map[&p]
     }
 
+    /// Returns the service public key of the validator.
+    pub fn service_p(&self, id: ValidatorId) -> PublicKey {
+        self.cfg().validator_keys[id.0 as usize].service_key
+    }
+
+    /// Returns the service secret key of the validator.
+    pub fn service_s(&self, id: ValidatorId) -> &SecretKey {
+        &self.services_map[&self.service_p(id)]
+    }
+
     pub fn a(&self, id: ValidatorId) -> String {
         let id: usize = id.into();
         self.addresses[id].address.clone()
@@ -292,7 +302,14 @@ impl Sandbox {
         secret_key: &SecretKey,
     ) -> Signed<Propose> {
         Message::concrete(
-            Propose::new(validator_id, height, round, last_hash, tx_hashes),
+            Propose::new(
+                validator_id,
+                height,
+                round,
+                last_hash,
+                tx_hashes,
+                self.propose_time(),
+            ),
             self.p(validator_id),
             secret_key,
         )
@@ -432,6 +449,11 @@ impl Sandbox {
         *inner.time.lock().unwrap() = new_time;
     }
 
+    /// Returns the time of proposes at the current height.
+    pub fn propose_time(&self) -> chrono::DateTime<chrono::Utc> {
+        self.inner.borrow().handler.propose_time()
+    }
+
     pub fn node_handler_mut(&self) -> RefMut<NodeHandler> {
         RefMut::map(self.inner.borrow_mut(), |inner| &mut inner.handler)
     }
@@ -461,6 +483,13 @@ impl Sandbox {
         self.inner.borrow_mut().handle_event(event);
     }
 
+    pub fn replay_consensus_log(&self, log: &ConsensusLog) {
+        self.check_unexpected_message();
+        let mut inner = self.inner.borrow_mut();
+        inner.handler.replay_consensus_log(log);
+        inner.process_events();
+    }
+
     pub fn recv_rebroadcast(&self) {
         self.check_unexpected_message();
         self.inner
@@ -678,7 +707,9 @@ impl Sandbox {
 
         let fork = {
             let mut fork = blockchain.fork();
-            let (_, patch) = blockchain.create_patch(ValidatorId(0), height, &hashes);
+            // The time of the block does not affect the state hash.
+            let time = blockchain.last_block().time();
+            let (_, patch) = blockchain.create_patch(ValidatorId(0), height, time, &hashes);
             fork.merge(patch);
             fork
         };
@@ -778,6 +809,15 @@ impl Sandbox {
         assert_eq!(expected, schema.transactions_pool_len());
     }
 
+    pub fn assert_shutdown(&self) {
+        let inner = self.inner.borrow();
+        assert!(inner.shutdown, "Expected the node to be shut down");
+        assert!(
+            !inner.handler.api_state().is_enabled(),
+            "Expected the node to be disabled"
+        );
+    }
+
     pub fn assert_lock(&self, expected_round: Round, expected_hash: Option<Hash>) {
         let state = self.node_state();
 
@@ -857,6 +897,8 @@ impl Sandbox {
             network: NetworkConfiguration::default(),
             peer_discovery: Vec::new(),
             mempool: Default::default(),
+            rotated_keys: Vec::new(),
+            follower: inner.handler.follower().cloned(),
         };
 
         let system_state = SandboxSystemStateProvider {
@@ -884,6 +926,7 @@ impl Sandbox {
             api_requests_rx: api_channel.1,
             handler,
             time: Arc::clone(&inner.time),
+            shutdown: false,
         };
         let sandbox = Sandbox {
             inner: RefCell::new(inner),
@@ -955,7 +998,10 @@ pub struct SandboxBuilder {
     initialize: bool,
     services: Vec<Box<dyn Service>>,
     validators_count: u8,
+    voting_powers: Vec<u64>,
     consensus_config: ConsensusConfig,
+    follower_poll_interval: Option<Milliseconds>,
+    rotated_keys: Vec<RotatedKey>,
 }
 
 impl SandboxBuilder {
@@ -964,6 +1010,7 @@ impl SandboxBuilder {
             initialize: true,
             services: Vec::new(),
             validators_count: 4,
+            voting_powers: Vec::new(),
             consensus_config: ConsensusConfig {
                 first_round_timeout: 1000,
                 status_timeout: 600_000,
@@ -973,7 +1020,16 @@ impl SandboxBuilder {
                 min_propose_timeout: PROPOSE_TIMEOUT,
                 max_propose_timeout: PROPOSE_TIMEOUT,
                 propose_timeout_threshold: std::u32::MAX,
+                execution_limits: ExecutionLimits::default(),
+                leader_election: LeaderElectionStrategy::default(),
+                max_idle_timeout: None,
+                adaptive_timeouts: None,
+                txs_block_size_limit: None,
+                txs_block_cost_limit: None,
+                max_propose_time_drift: ConsensusConfig::DEFAULT_MAX_PROPOSE_TIME_DRIFT,
             },
+            follower_poll_interval: None,
+            rotated_keys: Vec::new(),
         }
     }
 
@@ -997,6 +1053,23 @@ impl SandboxBuilder {
         self
     }
 
+    pub fn with_voting_powers(mut self, voting_powers: Vec<u64>) -> Self {
+        self.voting_powers = voting_powers;
+        self
+    }
+
+    /// Lists the keys the node switches to when their rotation becomes actual.
+    pub fn with_rotated_keys(mut self, rotated_keys: Vec<RotatedKey>) -> Self {
+        self.rotated_keys = rotated_keys;
+        self
+    }
+
+    /// Runs the node in the follower mode with validator 1 as the only trusted node.
+    pub fn as_follower(mut self, poll_interval: Milliseconds) -> Self {
+        self.follower_poll_interval = Some(poll_interval);
+        self
+    }
+
     pub fn build(self) -> Sandbox {
         let _ = env_logger::Builder::from_default_env()
             .target(env_logger::Target::Stdout)
@@ -1006,6 +1079,9 @@ impl SandboxBuilder {
             self.services,
             self.consensus_config,
             self.validators_count,
+            &self.voting_powers,
+            self.follower_poll_interval,
+            self.rotated_keys,
         );
 
         sandbox.inner.borrow_mut().sent.clear(); // To clear initial connect messages.
@@ -1028,6 +1104,9 @@ fn sandbox_with_services_uninitialized(
     services: Vec<Box<dyn Service>>,
     consensus: ConsensusConfig,
     validators_count: u8,
+    voting_powers: &[u64],
+    follower_poll_interval: Option<Milliseconds>,
+    rotated_keys: Vec<RotatedKey>,
 ) -> Sandbox {
     let validators = (0..validators_count)
         .map(|i| gen_keypair_from_seed(&Seed::new([i; SEED_LENGTH])))
@@ -1068,9 +1147,11 @@ fn sandbox_with_services_uninitialized(
         validators
             .iter()
             .zip(service_keys.iter())
-            .map(|x| ValidatorKeys {
+            .enumerate()
+            .map(|(i, x)| ValidatorKeys {
                 consensus_key: (x.0).0,
                 service_key: (x.1).0,
+                voting_power: voting_powers.get(i).cloned(),
             }),
     );
 
@@ -1093,6 +1174,11 @@ fn sandbox_with_services_uninitialized(
         network: NetworkConfiguration::default(),
         peer_discovery: Vec::new(),
         mempool: Default::default(),
+        rotated_keys: rotated_keys.clone(),
+        follower: follower_poll_interval.map(|poll_interval| FollowerConfig {
+            trusted_nodes: vec![connect_infos[1].clone()],
+            poll_interval,
+        }),
     };
 
     let system_state = SandboxSystemStateProvider {
@@ -1131,11 +1217,23 @@ fn sandbox_with_services_uninitialized(
         internal_requests_rx: internal_channel.1,
         handler,
         time: shared_time,
+        shutdown: false,
     };
+    let (rotated_consensus_keys, rotated_service_keys): (Vec<_>, Vec<_>) = rotated_keys
+        .into_iter()
+        .map(|key| (key.kind, (key.public_key, key.secret_key)))
+        .partition(|(kind, _)| *kind == ValidatorKeyKind::Consensus);
     let sandbox = Sandbox {
         inner: RefCell::new(inner),
-        validators_map: HashMap::from_iter(validators.clone()),
-        services_map: HashMap::from_iter(service_keys),
+        validators_map: validators
+            .iter()
+            .cloned()
+            .chain(rotated_consensus_keys.into_iter().map(|(_, keys)| keys))
+            .collect(),
+        services_map: service_keys
+            .into_iter()
+            .chain(rotated_service_keys.into_iter().map(|(_, keys)| keys))
+            .collect(),
         addresses: connect_infos,
         connect: None,
     };
@@ -1232,6 +1330,7 @@ mod tests {
         let validator_keys = ValidatorKeys {
             consensus_key: public,
             service_key: service,
+            voting_power: None,
         };
 
         let new_peer_addr = gen_primitive_socket_addr(2);
@@ -1294,6 +1393,7 @@ mod tests {
         let validator_keys = ValidatorKeys {
             consensus_key: public,
             service_key: service,
+            voting_power: None,
         };
         s.add_peer_to_connect_list(gen_primitive_socket_addr(1), validator_keys);
         s.recv(&s.create_connect(
@@ -1325,6 +1425,7 @@ mod tests {
         let validator_keys = ValidatorKeys {
             consensus_key: public,
             service_key: service,
+            voting_power: None,
         };
         s.add_peer_to_connect_list(gen_primitive_socket_addr(1), validator_keys);
         s.recv(&s.create_connect(
@@ -1346,6 +1447,7 @@ mod tests {
         let validator_keys = ValidatorKeys {
             consensus_key: public,
             service_key: service,
+            voting_power: None,
         };
         s.add_peer_to_connect_list(gen_primitive_socket_addr(1), validator_keys);
         s.recv(&s.create_connect(
@@ -1375,6 +1477,7 @@ mod tests {
         let validator_keys = ValidatorKeys {
             consensus_key: public,
             service_key: service,
+            voting_power: None,
         };
         s.add_peer_to_connect_list(gen_primitive_socket_addr(1), validator_keys);
         s.recv(&s.create_connect(
@@ -14,6 +14,7 @@
 
 /// purpose of this module is to keep functions with reusable code used for sandbox tests
 use bit_vec::BitVec;
+use chrono::{DateTime, Utc};
 
 use std::{cell::RefCell, collections::BTreeMap, time::Duration};
 
@@ -41,6 +42,7 @@ pub struct BlockBuilder<'a> {
     tx_hash: Option<Hash>,
     state_hash: Option<Hash>,
     tx_count: Option<u32>,
+    time: Option<DateTime<Utc>>,
 
     sandbox: &'a TimestampingSandbox,
 }
@@ -54,6 +56,7 @@ impl<'a> BlockBuilder<'a> {
             tx_hash: None,
             state_hash: None,
             tx_count: None,
+            time: None,
 
             sandbox,
         }
@@ -93,6 +96,11 @@ impl<'a> BlockBuilder<'a> {
         self
     }
 
+    pub fn with_time(mut self, time: DateTime<Utc>) -> Self {
+        self.time = Some(time);
+        self
+    }
+
     pub fn build(&self) -> Block {
         Block::new(
             self.proposer_id
@@ -104,6 +112,9 @@ impl<'a> BlockBuilder<'a> {
             &self
                 .state_hash
                 .unwrap_or_else(|| self.sandbox.last_state_hash()),
+            // Sandbox services do not emit events.
+            &Hash::zero(),
+            self.time.unwrap_or_else(|| self.sandbox.propose_time()),
         )
     }
 }
@@ -0,0 +1,87 @@
+// Copyright 2019 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Logging of the keys accessed through a `Fork`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Set of keys grouped by the index name. An index without the set of keys
+/// stands for all keys of the index.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct IndexKeys {
+    indexes: BTreeMap<String, Option<BTreeSet<Vec<u8>>>>,
+}
+
+impl IndexKeys {
+    /// Adds a single key of the index.
+    pub(crate) fn insert_key(&mut self, name: &str, key: &[u8]) {
+        let keys = self
+            .indexes
+            .entry(name.to_owned())
+            .or_insert_with(|| Some(BTreeSet::new()));
+        if let Some(keys) = keys {
+            keys.insert(key.to_vec());
+        }
+    }
+
+    /// Adds all keys of the index.
+    pub(crate) fn insert_index(&mut self, name: &str) {
+        self.indexes.insert(name.to_owned(), None);
+    }
+
+    /// Adds all keys of the other set.
+    pub(crate) fn union(&mut self, other: &Self) {
+        for (name, other_keys) in &other.indexes {
+            match other_keys {
+                None => self.insert_index(name),
+                Some(other_keys) => {
+                    for key in other_keys {
+                        self.insert_key(name, key);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns an iterator over the index names and their keys (`None` if the set contains
+    /// all keys of the index).
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&str, Option<&BTreeSet<Vec<u8>>>)> {
+        self.indexes
+            .iter()
+            .map(|(name, keys)| (name.as_str(), keys.as_ref()))
+    }
+
+    /// Returns `true` if the set contains at least one key of the index.
+    pub(crate) fn contains_index(&self, name: &str) -> bool {
+        self.indexes.contains_key(name)
+    }
+
+    /// Returns `true` if the sets have at least one common key.
+    pub(crate) fn intersects(&self, other: &Self) -> bool {
+        self.indexes
+            .iter()
+            .any(|(name, keys)| match (keys, other.indexes.get(name)) {
+                (_, None) => false,
+                (None, Some(_)) | (Some(_), Some(None)) => true,
+                (Some(keys), Some(Some(other_keys))) => !keys.is_disjoint(other_keys),
+            })
+    }
+}
+
+/// Keys read and written through a `Fork` while the log is active.
+#[derive(Debug, Default)]
+pub(crate) struct AccessLog {
+    pub(crate) reads: IndexKeys,
+    pub(crate) writes: IndexKeys,
+}
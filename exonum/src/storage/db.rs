@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::{
+    cell::{Ref, RefCell},
     cmp::Ordering::{Equal, Greater, Less},
     collections::{
         btree_map::{BTreeMap, IntoIter as BtmIntoIter, Iter as BtmIter, Range},
@@ -23,7 +24,7 @@ use std::{
     iter::{Iterator as StdIterator, Peekable},
 };
 
-use super::Result;
+use super::{access_log::AccessLog, meter::Meter, AccessCounters, AccessLimits, IndexKeys, Result};
 
 /// Map containing changes with a corresponding key.
 #[derive(Debug, Clone)]
@@ -124,6 +125,52 @@ impl Patch {
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Returns the summary of the patch, i.e., the names of the touched indexes
+    /// together with the number of changes in each of them.
+    pub fn summary(&self) -> PatchSummary {
+        let indexes = self
+            .changes
+            .iter()
+            .filter(|(_, changes)| !changes.data.is_empty())
+            .map(|(name, changes)| (name.clone(), changes.data.len()))
+            .collect();
+        PatchSummary { indexes }
+    }
+}
+
+/// Changes of a certain set of keys made in a fork. Unlike `Patch`, records the keys
+/// of the set which are not changed relative to the snapshot as well.
+#[derive(Debug, Clone)]
+pub(crate) struct KeyChanges {
+    keys: IndexKeys,
+    patch: Patch,
+}
+
+/// Summary of the changes contained in a `Patch`.
+///
+/// The summary is ordered by index names and does not contain indexes
+/// without changes.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct PatchSummary {
+    indexes: BTreeMap<String, usize>,
+}
+
+impl PatchSummary {
+    /// Returns an iterator over the names of the touched indexes.
+    pub fn touched_indexes(&self) -> impl StdIterator<Item = &str> {
+        self.indexes.keys().map(String::as_str)
+    }
+
+    /// Returns the number of changes in the index with the given name.
+    pub fn changes_count(&self, name: &str) -> usize {
+        self.indexes.get(name).cloned().unwrap_or_default()
+    }
+
+    /// Returns `true` if the summary contains no touched indexes.
+    pub fn is_empty(&self) -> bool {
+        self.indexes.is_empty()
+    }
 }
 
 /// Iterator over the `Patch` data.
@@ -204,11 +251,14 @@ pub struct Fork {
     patch: Patch,
     changelog: Vec<(String, Vec<u8>, Option<Change>)>,
     logged: bool,
+    meter: Option<Meter>,
+    access_log: Option<RefCell<AccessLog>>,
 }
 
 struct ForkIter<'a> {
     snapshot: Iter<'a>,
     changes: Option<Peekable<Range<'a, Vec<u8>, Change>>>,
+    meter: Option<&'a Meter>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -254,12 +304,7 @@ pub trait Database: Send + Sync + 'static {
 
     /// Creates a new fork of the database from its current state.
     fn fork(&self) -> Fork {
-        Fork {
-            snapshot: self.snapshot(),
-            patch: Patch::new(),
-            changelog: Vec::new(),
-            logged: false,
-        }
+        Fork::new(self.snapshot())
     }
 
     /// Atomically applies a sequence of patch changes to the database.
@@ -325,18 +370,23 @@ pub trait Iterator {
 
 impl Snapshot for Fork {
     fn get(&self, name: &str, key: &[u8]) -> Option<Vec<u8>> {
-        if let Some(changes) = self.patch.changes(name) {
-            if let Some(change) = changes.data.get(key) {
-                match *change {
-                    Change::Put(ref v) => return Some(v.clone()),
-                    Change::Delete => return None,
-                }
-            }
+        let value = self.get_unmetered(name, key);
+        if let Some(ref meter) = self.meter {
+            meter.read(key.len() + value.as_ref().map_or(0, Vec::len));
         }
-        self.snapshot.get(name, key)
+        if let Some(ref log) = self.access_log {
+            log.borrow_mut().reads.insert_key(name, key);
+        }
+        value
     }
 
     fn contains(&self, name: &str, key: &[u8]) -> bool {
+        if let Some(ref meter) = self.meter {
+            meter.read(key.len());
+        }
+        if let Some(ref log) = self.access_log {
+            log.borrow_mut().reads.insert_key(name, key);
+        }
         if let Some(changes) = self.patch.changes(name) {
             if let Some(change) = changes.data.get(key) {
                 match *change {
@@ -354,15 +404,94 @@ impl Snapshot for Fork {
             Some(changes) => Some(changes.data.range::<[u8], _>(range).peekable()),
             None => None,
         };
+        // Iterated entries depend on all keys of the index, including the absent ones.
+        if let Some(ref log) = self.access_log {
+            log.borrow_mut().reads.insert_index(name);
+        }
 
         Box::new(ForkIter {
             snapshot: self.snapshot.iter(name, from),
             changes,
+            meter: self.meter.as_ref(),
         })
     }
 }
 
 impl Fork {
+    /// Creates a fork over the given snapshot.
+    pub(crate) fn new(snapshot: Box<dyn Snapshot>) -> Self {
+        Self {
+            snapshot,
+            patch: Patch::new(),
+            changelog: Vec::new(),
+            logged: false,
+            meter: None,
+            access_log: None,
+        }
+    }
+
+    fn get_unmetered(&self, name: &str, key: &[u8]) -> Option<Vec<u8>> {
+        if let Some(changes) = self.patch.changes(name) {
+            if let Some(change) = changes.data.get(key) {
+                match *change {
+                    Change::Put(ref v) => return Some(v.clone()),
+                    Change::Delete => return None,
+                }
+            }
+        }
+        self.snapshot.get(name, key)
+    }
+
+    /// Starts metering of the storage access with the given limits. Once a limit
+    /// is exceeded, the access panics with a [`LimitExceeded`] payload.
+    ///
+    /// # Panics
+    ///
+    /// Panics if metering is already active.
+    ///
+    /// [`LimitExceeded`]: struct.LimitExceeded.html
+    pub(crate) fn start_metering(&mut self, limits: AccessLimits) {
+        assert!(
+            self.meter.is_none(),
+            "call start_metering before stop_metering"
+        );
+        self.meter = Some(Meter::new(limits));
+    }
+
+    /// Stops metering of the storage access and returns the amount of the performed access.
+    pub(crate) fn stop_metering(&mut self) -> AccessCounters {
+        self.meter
+            .take()
+            .map(|meter| meter.counters())
+            .unwrap_or_default()
+    }
+
+    /// Starts logging of the keys read and written through the fork.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the log is already active.
+    pub(crate) fn start_access_log(&mut self) {
+        assert!(
+            self.access_log.is_none(),
+            "call start_access_log before stop_access_log"
+        );
+        self.access_log = Some(RefCell::new(AccessLog::default()));
+    }
+
+    /// Returns the keys accessed since the start of the log, if the log is active.
+    pub(crate) fn access_log(&self) -> Option<Ref<AccessLog>> {
+        self.access_log.as_ref().map(RefCell::borrow)
+    }
+
+    /// Stops logging of the accessed keys and returns the log.
+    pub(crate) fn stop_access_log(&mut self) -> AccessLog {
+        self.access_log
+            .take()
+            .map(RefCell::into_inner)
+            .unwrap_or_default()
+    }
+
     /// Creates a new checkpoint.
     ///
     /// In Exonum checkpoints are created before applying each transaction to
@@ -415,6 +544,12 @@ impl Fork {
 
     /// Inserts a key-value pair into the fork.
     pub fn put(&mut self, name: &str, key: Vec<u8>, value: Vec<u8>) {
+        if let Some(ref meter) = self.meter {
+            meter.write(key.len() + value.len());
+        }
+        if let Some(ref log) = self.access_log {
+            log.borrow_mut().writes.insert_key(name, &key);
+        }
         let changes = self
             .patch
             .changes_entry(name.to_string())
@@ -432,6 +567,12 @@ impl Fork {
 
     /// Removes a key from the fork.
     pub fn remove(&mut self, name: &str, key: Vec<u8>) {
+        if let Some(ref meter) = self.meter {
+            meter.write(key.len());
+        }
+        if let Some(ref log) = self.access_log {
+            log.borrow_mut().writes.insert_key(name, &key);
+        }
         let changes = self
             .patch
             .changes_entry(name.to_string())
@@ -450,6 +591,9 @@ impl Fork {
     /// Removes all keys starting with the specified prefix from the column family
     /// with the given `name`.
     pub fn remove_by_prefix(&mut self, name: &str, prefix: Option<&[u8]>) {
+        if let Some(ref log) = self.access_log {
+            log.borrow_mut().writes.insert_index(name);
+        }
         let changes = self
             .patch
             .changes_entry(name.to_string())
@@ -476,6 +620,9 @@ impl Fork {
             if !k.starts_with(prefix_or_empty_slice) {
                 break;
             }
+            if let Some(ref meter) = self.meter {
+                meter.write(k.len());
+            }
 
             let change = changes.data.insert(k.to_vec(), Change::Delete);
             if self.logged {
@@ -509,6 +656,12 @@ impl Fork {
         }
 
         for (name, changes) in patch {
+            if let Some(ref log) = self.access_log {
+                let mut log = log.borrow_mut();
+                for key in changes.data.keys() {
+                    log.writes.insert_key(&name, key);
+                }
+            }
             if let Some(in_changes) = self.patch.changes_mut(&name) {
                 in_changes.data.extend(changes.into_iter());
                 continue;
@@ -518,6 +671,83 @@ impl Fork {
             }
         }
     }
+
+    /// Returns the changes of the given keys made in this fork.
+    pub(crate) fn changes_of(&self, keys: &IndexKeys) -> KeyChanges {
+        let mut patch = Patch::new();
+        for (name, index_keys) in keys.iter() {
+            let changes = match self.patch.changes(name) {
+                Some(changes) => changes,
+                None => continue,
+            };
+            let changes = match index_keys {
+                None => changes.clone(),
+                Some(index_keys) => Changes {
+                    data: index_keys
+                        .iter()
+                        .filter_map(|key| {
+                            changes
+                                .data
+                                .get(key)
+                                .map(|change| (key.clone(), change.clone()))
+                        })
+                        .collect(),
+                },
+            };
+            patch.insert_changes(name.to_owned(), changes);
+        }
+        KeyChanges {
+            keys: keys.clone(),
+            patch,
+        }
+    }
+
+    /// Applies the changes of the keys made in another fork of the same snapshot.
+    /// Unlike `merge`, the keys not changed in the other fork are reverted
+    /// to their state in the snapshot.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a checkpoint has been created before and has not been committed
+    /// or rolled back yet.
+    pub(crate) fn apply_changes(&mut self, changes: KeyChanges) {
+        if self.logged {
+            panic!("call apply_changes before commit or rollback");
+        }
+
+        let KeyChanges { keys, mut patch } = changes;
+        if let Some(ref log) = self.access_log {
+            log.borrow_mut().writes.union(&keys);
+        }
+        for (name, index_keys) in keys.iter() {
+            let applied = patch.changes.remove(name);
+            match index_keys {
+                None => match applied {
+                    Some(applied) => self.patch.insert_changes(name.to_owned(), applied),
+                    None => {
+                        self.patch.changes.remove(name);
+                    }
+                },
+                Some(index_keys) => {
+                    let mut applied = applied.map(|applied| applied.data).unwrap_or_default();
+                    let changes = self
+                        .patch
+                        .changes_entry(name.to_owned())
+                        .or_insert_with(Changes::new);
+                    for key in index_keys {
+                        match applied.remove(key) {
+                            Some(change) => {
+                                changes.data.insert(key.clone(), change);
+                            }
+                            None => {
+                                changes.data.remove(key);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
 }
 
 impl AsRef<dyn Snapshot> for dyn Snapshot + 'static {
@@ -572,10 +802,8 @@ impl<'a> ForkIter<'a> {
             }
         }
     }
-}
 
-impl<'a> Iterator for ForkIter<'a> {
-    fn next(&mut self) -> Option<(&[u8], &[u8])> {
+    fn next_unmetered(&mut self) -> Option<(&[u8], &[u8])> {
         loop {
             match self.step() {
                 NextIterValue::Stored => return self.snapshot.next(),
@@ -614,7 +842,7 @@ impl<'a> Iterator for ForkIter<'a> {
         }
     }
 
-    fn peek(&mut self) -> Option<(&[u8], &[u8])> {
+    fn peek_unmetered(&mut self) -> Option<(&[u8], &[u8])> {
         loop {
             match self.step() {
                 NextIterValue::Stored => return self.snapshot.peek(),
@@ -642,6 +870,21 @@ impl<'a> Iterator for ForkIter<'a> {
     }
 }
 
+impl<'a> Iterator for ForkIter<'a> {
+    fn next(&mut self) -> Option<(&[u8], &[u8])> {
+        let meter = self.meter;
+        let entry = self.next_unmetered();
+        if let (Some(meter), Some((key, value))) = (meter, entry) {
+            meter.read(key.len() + value.len());
+        }
+        entry
+    }
+
+    fn peek(&mut self) -> Option<(&[u8], &[u8])> {
+        self.peek_unmetered()
+    }
+}
+
 impl<T: Database> From<T> for Box<dyn Database> {
     fn from(db: T) -> Self {
         Box::new(db) as Self
@@ -0,0 +1,184 @@
+// Copyright 2019 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Metering of the storage access.
+
+use std::{cell::Cell, fmt, panic};
+
+/// Limits of the storage access. `None` means that the corresponding resource
+/// is not limited.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AccessLimits {
+    /// Maximum number of reads. Each read value and each iterated entry
+    /// counts as a separate read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reads: Option<u64>,
+    /// Maximum number of writes. Each put or removed key counts as a separate write.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub writes: Option<u64>,
+    /// Maximum total length of keys and values read or written (in bytes).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+}
+
+impl AccessLimits {
+    /// Returns limits which are left after consuming the given amount of resources.
+    pub fn remaining(&self, used: &AccessCounters) -> Self {
+        let remaining = |limit: Option<u64>, used| limit.map(|limit| limit.saturating_sub(used));
+        Self {
+            reads: remaining(self.reads, used.reads),
+            writes: remaining(self.writes, used.writes),
+            bytes: remaining(self.bytes, used.bytes),
+        }
+    }
+
+    /// Returns `true` if the given amount of resources does not exceed the limits.
+    pub fn allows(&self, used: &AccessCounters) -> bool {
+        let allows = |limit: Option<u64>, used| limit.map_or(true, |limit| used <= limit);
+        allows(self.reads, used.reads)
+            && allows(self.writes, used.writes)
+            && allows(self.bytes, used.bytes)
+    }
+
+    /// Returns the strictest combination of two limits.
+    pub fn min(&self, other: &Self) -> Self {
+        let min = |a: Option<u64>, b: Option<u64>| match (a, b) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        Self {
+            reads: min(self.reads, other.reads),
+            writes: min(self.writes, other.writes),
+            bytes: min(self.bytes, other.bytes),
+        }
+    }
+}
+
+/// Amount of the storage access performed while metering is active.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AccessCounters {
+    /// Number of reads.
+    pub reads: u64,
+    /// Number of writes.
+    pub writes: u64,
+    /// Total length of keys and values read or written (in bytes).
+    pub bytes: u64,
+}
+
+impl AccessCounters {
+    /// Adds the given counters to these ones.
+    pub fn add(&mut self, other: &Self) {
+        self.reads += other.reads;
+        self.writes += other.writes;
+        self.bytes += other.bytes;
+    }
+}
+
+/// Storage resource limited by `AccessLimits`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessResource {
+    /// Number of reads.
+    Reads,
+    /// Number of writes.
+    Writes,
+    /// Number of bytes.
+    Bytes,
+}
+
+impl fmt::Display for AccessResource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AccessResource::Reads => f.write_str("reads"),
+            AccessResource::Writes => f.write_str("writes"),
+            AccessResource::Bytes => f.write_str("bytes"),
+        }
+    }
+}
+
+/// Storage access limit has been exceeded.
+///
+/// This error is used as a panic payload by a metered `Fork`, so the execution
+/// of a transaction is aborted on the first access exceeding the limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Fail)]
+#[fail(
+    display = "Storage access limit exceeded: {} limit is {}",
+    resource, limit
+)]
+pub struct LimitExceeded {
+    /// Resource whose limit has been exceeded.
+    pub resource: AccessResource,
+    /// Value of the exceeded limit.
+    pub limit: u64,
+}
+
+/// Meter of the storage access attached to a `Fork`.
+#[derive(Debug)]
+pub(crate) struct Meter {
+    limits: AccessLimits,
+    counters: Cell<AccessCounters>,
+}
+
+impl Meter {
+    pub(crate) fn new(limits: AccessLimits) -> Self {
+        Self {
+            limits,
+            counters: Cell::new(AccessCounters::default()),
+        }
+    }
+
+    pub(crate) fn counters(&self) -> AccessCounters {
+        self.counters.get()
+    }
+
+    /// Accounts a read of `len` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics with `LimitExceeded` if the read exceeds the limits.
+    pub(crate) fn read(&self, len: usize) {
+        self.charge(1, 0, len);
+    }
+
+    /// Accounts a write of `len` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics with `LimitExceeded` if the write exceeds the limits.
+    pub(crate) fn write(&self, len: usize) {
+        self.charge(0, 1, len);
+    }
+
+    fn charge(&self, reads: u64, writes: u64, bytes: usize) {
+        let mut counters = self.counters.get();
+        counters.reads += reads;
+        counters.writes += writes;
+        counters.bytes += bytes as u64;
+        self.counters.set(counters);
+
+        let checks = [
+            (AccessResource::Reads, self.limits.reads, counters.reads),
+            (AccessResource::Writes, self.limits.writes, counters.writes),
+            (AccessResource::Bytes, self.limits.bytes, counters.bytes),
+        ];
+        for &(resource, limit, value) in &checks {
+            if let Some(limit) = limit {
+                if value > limit {
+                    // Unwinding bypasses the panic hook, so the exceeded limit is reported
+                    // by the transaction executor rather than printed as a panic.
+                    panic::resume_unwind(Box::new(LimitExceeded { resource, limit }));
+                }
+            }
+        }
+    }
+}
@@ -106,14 +106,18 @@
 //! [`BTreeSet`]: https://doc.rust-lang.org/std/collections/struct.BTreeSet.html
 //! [`HashSet`]: https://doc.rust-lang.org/std/collections/struct.HashSet.html
 
-pub(crate) use self::indexes_metadata::StorageMetadata;
+pub(crate) use self::{
+    access_log::{AccessLog, IndexKeys},
+    db::KeyChanges,
+    indexes_metadata::StorageMetadata,
+};
 
 #[doc(no_inline)]
 pub use self::proof_map_index::{HashedKey, MapProof, ProofMapIndex};
 pub use self::{
     db::{
         Change, Changes, ChangesIterator, Database, Fork, Iter, Iterator, Patch, PatchIterator,
-        Snapshot,
+        PatchSummary, Snapshot,
     },
     entry::Entry,
     error::Error,
@@ -123,6 +127,7 @@ pub use self::{
     list_index::ListIndex,
     map_index::MapIndex,
     memorydb::MemoryDB,
+    meter::{AccessCounters, AccessLimits, AccessResource, LimitExceeded},
     options::DbOptions,
     proof_list_index::{ListProof, ProofListIndex},
     rocksdb::RocksDB,
@@ -134,6 +139,7 @@ pub use self::{
 /// A specialized `Result` type for I/O operations with storage.
 pub type Result<T> = ::std::result::Result<T, Error>;
 
+mod access_log;
 mod base_index;
 mod db;
 mod entry;
@@ -142,6 +148,7 @@ mod hash;
 mod indexes_metadata;
 mod keys;
 mod memorydb;
+mod meter;
 mod options;
 mod rocksdb;
 mod values;
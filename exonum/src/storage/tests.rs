@@ -183,6 +183,159 @@ fn changelog<T: Database>(db: T) {
     assert_eq!(fork.get(IDX_NAME, &[4]), None);
 }
 
+#[test]
+fn patch_summary() {
+    use super::MemoryDB;
+
+    let db = MemoryDB::new();
+    let mut fork = db.fork();
+    fork.put(IDX_NAME, vec![1], vec![1]);
+    fork.put(IDX_NAME, vec![2], vec![2]);
+    fork.remove("other_idx", vec![1]);
+
+    // Indexes without changes after the rollback are not included into the summary.
+    fork.checkpoint();
+    fork.put("rolled_back_idx", vec![1], vec![1]);
+    fork.rollback();
+
+    let summary = fork.into_patch().summary();
+    assert_eq!(
+        summary.touched_indexes().collect::<Vec<_>>(),
+        vec![IDX_NAME, "other_idx"]
+    );
+    assert_eq!(summary.changes_count(IDX_NAME), 2);
+    assert_eq!(summary.changes_count("other_idx"), 1);
+    assert_eq!(summary.changes_count("rolled_back_idx"), 0);
+    assert!(db.fork().into_patch().summary().is_empty());
+}
+
+#[test]
+fn fork_metering() {
+    use super::{AccessCounters, AccessLimits, MemoryDB};
+
+    let db = MemoryDB::new();
+    let mut fork = db.fork();
+    fork.put(IDX_NAME, vec![1], vec![1, 2]);
+    fork.put(IDX_NAME, vec![2], vec![3]);
+    db.merge(fork.into_patch()).unwrap();
+
+    let mut fork = db.fork();
+    fork.start_metering(AccessLimits::default());
+    assert_eq!(fork.get(IDX_NAME, &[1]), Some(vec![1, 2]));
+    assert!(fork.contains(IDX_NAME, &[2]));
+    fork.put(IDX_NAME, vec![3], vec![4, 5, 6]);
+    fork.remove(IDX_NAME, vec![1]);
+    {
+        let mut iter = fork.iter(IDX_NAME, &[]);
+        assert_eq!(iter.next(), Some((&[2][..], &[3][..])));
+        assert_eq!(iter.next(), Some((&[3][..], &[4, 5, 6][..])));
+        assert_eq!(iter.next(), None);
+    }
+    assert_eq!(
+        fork.stop_metering(),
+        AccessCounters {
+            reads: 4,
+            writes: 2,
+            bytes: 3 + 1 + 4 + 1 + 2 + 4,
+        }
+    );
+
+    // Access is not metered after `stop_metering`.
+    fork.put(IDX_NAME, vec![4], vec![4]);
+    assert_eq!(fork.stop_metering(), AccessCounters::default());
+}
+
+#[test]
+fn fork_metering_limit_exceeded() {
+    use super::{AccessLimits, AccessResource, LimitExceeded, MemoryDB};
+    use std::panic;
+
+    let db = MemoryDB::new();
+    let mut fork = db.fork();
+    fork.start_metering(AccessLimits {
+        writes: Some(1),
+        ..AccessLimits::default()
+    });
+    fork.put(IDX_NAME, vec![1], vec![1]);
+
+    let err = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        fork.put(IDX_NAME, vec![2], vec![2]);
+    }))
+    .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<LimitExceeded>(),
+        Some(&LimitExceeded {
+            resource: AccessResource::Writes,
+            limit: 1,
+        })
+    );
+    // The write exceeding the limit is not applied.
+    fork.stop_metering();
+    assert_eq!(fork.get(IDX_NAME, &[2]), None);
+}
+
+#[test]
+fn fork_access_log() {
+    use super::{IndexKeys, MemoryDB};
+
+    let db = MemoryDB::new();
+    let mut fork = db.fork();
+    fork.start_access_log();
+    fork.get(IDX_NAME, &[1]);
+    fork.put(IDX_NAME, vec![2], vec![2]);
+    fork.iter("other_idx", &[]);
+    let log = fork.stop_access_log();
+
+    let mut keys = IndexKeys::default();
+    keys.insert_key(IDX_NAME, &[1]);
+    assert!(log.reads.intersects(&keys));
+    assert!(!log.writes.intersects(&keys));
+    keys.insert_key(IDX_NAME, &[2]);
+    assert!(log.writes.intersects(&keys));
+    // Iterated index is read as a whole.
+    let mut keys = IndexKeys::default();
+    keys.insert_key("other_idx", &[42]);
+    assert!(log.reads.intersects(&keys));
+    assert!(fork.access_log().is_none());
+}
+
+#[test]
+fn fork_apply_key_changes() {
+    use super::MemoryDB;
+
+    let db = MemoryDB::new();
+    let mut fork = db.fork();
+    fork.put(IDX_NAME, vec![1], vec![1]);
+    fork.put(IDX_NAME, vec![2], vec![2]);
+    fork.put("other_idx", vec![1], vec![1]);
+    db.merge(fork.into_patch()).unwrap();
+
+    let mut source = db.fork();
+    source.put(IDX_NAME, vec![3], vec![3]);
+    source.put("other_idx", vec![2], vec![2]);
+    source.start_access_log();
+    source.put(IDX_NAME, vec![1], vec![10]);
+    source.remove(IDX_NAME, vec![2]);
+    source.checkpoint();
+    source.put(IDX_NAME, vec![4], vec![4]);
+    source.rollback();
+    source.remove_by_prefix("other_idx", None);
+    let changes = source.changes_of(&source.stop_access_log().writes);
+
+    let mut target = db.fork();
+    target.put(IDX_NAME, vec![3], vec![3]);
+    target.put(IDX_NAME, vec![4], vec![4]);
+    target.put("other_idx", vec![2], vec![2]);
+    target.apply_changes(changes);
+
+    assert_eq!(target.get(IDX_NAME, &[1]), Some(vec![10]));
+    assert_eq!(target.get(IDX_NAME, &[2]), None);
+    assert_eq!(target.get(IDX_NAME, &[3]), Some(vec![3]));
+    // Keys not changed in the source fork are reverted.
+    assert_eq!(target.get(IDX_NAME, &[4]), None);
+    assert_eq!(target.iter("other_idx", &[]).next(), None);
+}
+
 mod memorydb_tests {
     use super::super::MemoryDB;
 
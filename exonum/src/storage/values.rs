@@ -23,7 +23,7 @@ use std::{borrow::Cow, mem};
 
 use super::UniqueHash;
 use crate::crypto::{Hash, PublicKey};
-use crate::helpers::Round;
+use crate::helpers::{Height, Round};
 
 /// A type that can be (de)serialized as a value in the blockchain storage.
 ///
@@ -302,6 +302,16 @@ impl StorageValue for Round {
     }
 }
 
+impl StorageValue for Height {
+    fn into_bytes(self) -> Vec<u8> {
+        self.0.into_bytes()
+    }
+
+    fn from_bytes(value: Cow<[u8]>) -> Self {
+        Height(<u64 as StorageValue>::from_bytes(value))
+    }
+}
+
 impl StorageValue for Uuid {
     fn into_bytes(self) -> Vec<u8> {
         self.as_bytes().to_vec()
@@ -452,6 +462,18 @@ mod tests {
         assert_round_trip_eq(&values);
     }
 
+    #[test]
+    fn height_round_trip() {
+        let values = [
+            Height::zero(),
+            Height(1),
+            Height(100),
+            Height(u64::max_value()),
+        ];
+
+        assert_round_trip_eq(&values);
+    }
+
     #[test]
     fn uuid_round_trip() {
         let values = [
@@ -49,6 +49,8 @@ const CONFIG_TMP_FOLDER: &str = "/tmp/";
 const CONFIG_TESTDATA_FOLDER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/testdata/config/");
 
 const GENERATED_TEMPLATE: &str = "template.toml";
+const GENERATED_TEMPLATE_GENESIS_DATA: &str = "template_genesis_data.toml";
+const GENESIS_DATA: &str = "genesis_data.json";
 
 const SEC_CONFIG: [&str; 4] = [
     "config0_sec.toml",
@@ -122,6 +124,18 @@ fn generate_template(folder: &str) {
     ]));
 }
 
+fn generate_template_with_genesis_data(folder: &str) {
+    assert!(!default_run_with_matches(vec![
+        "exonum-config-test",
+        "generate-template",
+        &full_tmp_name(GENERATED_TEMPLATE_GENESIS_DATA, folder),
+        "--validators-count",
+        "1",
+        "--genesis-data",
+        &full_testdata_name(GENESIS_DATA),
+    ]));
+}
+
 #[derive(Debug, Clone, Copy)]
 enum IpMode {
     V4,
@@ -258,6 +272,22 @@ fn test_generate_template() {
     }
 }
 
+#[test]
+fn test_generate_template_with_genesis_data() {
+    let command = "generate-template-genesis-data";
+
+    let result = panic::catch_unwind(|| {
+        generate_template_with_genesis_data(command);
+        compare_configs(GENERATED_TEMPLATE_GENESIS_DATA, command);
+    });
+
+    fs::remove_dir_all(full_tmp_folder(command)).unwrap();
+
+    if let Err(err) = result {
+        panic::resume_unwind(err);
+    }
+}
+
 fn test_generate_config(mode: IpMode) {
     // Important because tests run in parallel, folder names should be different.
     let command = match mode {
@@ -108,6 +108,13 @@ impl Service for MyService {
     fn tx_from_raw(&self, raw: RawTransaction) -> Result<Box<dyn Transaction>, failure::Error> {
         ExplorerTransactions::tx_from_raw(raw).map(ExplorerTransactions::into)
     }
+
+    fn error_name(&self, code: u16) -> Option<&'static str> {
+        match code {
+            1 => Some("NotAllowed"),
+            _ => None,
+        }
+    }
 }
 
 /// Generates a keypair from a fixed passphrase.
@@ -136,6 +143,7 @@ pub fn create_blockchain() -> Blockchain {
     let keys = ValidatorKeys {
         consensus_key,
         service_key: service_keys.0,
+        voting_power: None,
     };
     blockchain
         .initialize(GenesisConfig::new(vec![keys].into_iter()))
@@ -162,7 +170,8 @@ pub fn create_block(blockchain: &mut Blockchain, transactions: Vec<Signed<RawTra
     }
     blockchain.merge(fork.into_patch()).unwrap();
 
-    let (block_hash, patch) = blockchain.create_patch(ValidatorId(0), height, &tx_hashes);
+    let time = SystemTime::now().into();
+    let (block_hash, patch) = blockchain.create_patch(ValidatorId(0), height, time, &tx_hashes);
     let (consensus_public_key, consensus_secret_key) = consensus_keys();
 
     let propose = Message::concrete(
@@ -172,6 +181,7 @@ pub fn create_block(blockchain: &mut Blockchain, transactions: Vec<Signed<RawTra
             Round::first(),
             &blockchain.last_hash(),
             &tx_hashes,
+            time,
         ),
         consensus_public_key,
         &consensus_secret_key,
@@ -125,6 +125,8 @@ fn test_explorer_basics() {
     let err = tx_info.status().unwrap_err();
     assert_eq!(err.error_type(), TransactionErrorType::Code(1));
     assert_eq!(err.description(), Some("Not allowed"));
+    assert_eq!(err.service_id(), Some(SERVICE_ID));
+    assert_eq!(err.name(), Some("NotAllowed"));
     assert_eq!(
         serde_json::to_value(&tx_info).unwrap(),
         json!({
@@ -141,6 +143,8 @@ fn test_explorer_basics() {
                 "type": "error",
                 "code": 1,
                 "description": "Not allowed",
+                "service_id": 0,
+                "name": "NotAllowed",
             },
         })
     );
@@ -164,6 +168,7 @@ fn test_explorer_basics() {
             "status": {
                 "type": "panic",
                 "description": "oops",
+                "service_id": 0,
             },
         })
     );
@@ -201,6 +206,42 @@ fn test_explorer_pool_transaction() {
     assert_eq!(tx_info.content().signed_message(), &tx_alice);
 }
 
+#[test]
+fn test_explorer_simulate_transaction() {
+    let blockchain = create_blockchain();
+    let (pk_bob, key_bob) = crypto::gen_keypair();
+    let tx_bob = Message::sign_transaction(
+        CreateWallet::new(&pk_bob, "Bob"),
+        SERVICE_ID,
+        pk_bob,
+        &key_bob,
+    );
+
+    let explorer = BlockchainExplorer::new(&blockchain);
+    let simulated = explorer.simulate(&tx_bob).unwrap();
+    assert_eq!(simulated.tx_hash(), &tx_bob.hash());
+    assert!(simulated.touched_indexes().is_empty());
+    assert!(simulated.changed_indexes().is_empty());
+    assert_eq!(
+        serde_json::to_value(&simulated).unwrap(),
+        json!({
+            "tx_hash": tx_bob.hash(),
+            "status": {
+                "type": "error",
+                "code": 1,
+                "description": "Not allowed",
+                "service_id": 0,
+                "name": "NotAllowed",
+            },
+            "touched_indexes": [],
+            "changed_indexes": [],
+        })
+    );
+
+    // The simulated transaction is neither committed nor added to the pool.
+    assert!(explorer.transaction(&tx_bob.hash()).is_none());
+}
+
 fn tx_generator() -> Box<dyn Iterator<Item = Signed<RawTransaction>>> {
     Box::new((0..).map(|i| {
         let (pk, key) = crypto::gen_keypair();
@@ -150,9 +150,9 @@ fn validate_majority_count(
     if let Some(majority_count) = majority_count {
         if majority_count > validators_count || majority_count < byzantine_majority_count {
             return Err(InvalidMajorityCount {
-                min: byzantine_majority_count as usize,
-                max: validators_count as usize,
-                proposed: majority_count as usize,
+                min: u64::from(byzantine_majority_count),
+                max: u64::from(validators_count),
+                proposed: u64::from(majority_count),
             })?;
         }
     }
@@ -176,6 +176,7 @@ pub fn generate_testnet_config(
                 .map(|x| ValidatorKeys {
                     consensus_key: (x.0).0,
                     service_key: (x.1).0,
+                    voting_power: None,
                 }),
         );
 
@@ -210,6 +211,10 @@ pub fn generate_testnet_config(
             services_configs: service_config.clone(),
             database: Default::default(),
             thread_pool_size: Default::default(),
+            execution_threads: Default::default(),
+            undo_depth: Default::default(),
+            rotated_keys: Default::default(),
+            follower: None,
         })
         .collect::<Vec<_>>()
 }
@@ -15,9 +15,10 @@
 /// Config for Configuration service.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfigurationServiceConfig {
-    /// Number of votes required to commit the new configuration.
+    /// Voting power of validators required to commit the new configuration.
     /// This value should be greater than 2/3 and less or equal to the
-    /// validators count.
+    /// total voting power of the validators. If the validators have no explicit
+    /// voting power, it is equal to the number of votes.
     pub majority_count: Option<u16>,
 }
 
@@ -99,11 +99,7 @@ pub(crate) enum Error {
         display = "Invalid majority count: {}, it should be >= {} and <= {}",
         proposed, min, max
     )]
-    InvalidMajorityCount {
-        min: usize,
-        max: usize,
-        proposed: usize,
-    },
+    InvalidMajorityCount { min: u64, max: u64, proposed: u64 },
 
     #[fail(display = "Does not reference known config with hash {:?}", _0)]
     UnknownConfigRef(Hash),
@@ -132,6 +128,6 @@ impl Error {
 
 impl From<Error> for ExecutionError {
     fn from(value: Error) -> ExecutionError {
-        ExecutionError::with_description(value.code() as u8, value.to_string())
+        ExecutionError::with_description(value.code() as u16, value.to_string())
     }
 }
@@ -90,7 +90,7 @@ use serde_json::{to_value, Value};
 
 use exonum::{
     api::ServiceApiBuilder,
-    blockchain::{self, Transaction, TransactionSet},
+    blockchain::{self, Transaction, TransactionSet, ValidatorKeys},
     crypto::Hash,
     helpers::fabric::{self, keys, Command, CommandExtension, CommandName, Context},
     messages::RawTransaction,
@@ -178,19 +178,21 @@ impl fabric::ServiceFactory for ServiceFactory {
                 .try_into()
                 .unwrap();
 
-        if let Some(majority_count) = service_config.majority_count {
-            let validators_count = context
+        if let Some(majority_count) = service_config.majority_count.map(u64::from) {
+            let total_voting_power: u64 = context
                 .get(keys::NODE_CONFIG)
                 .unwrap()
                 .genesis
                 .validator_keys
-                .len() as u16;
+                .iter()
+                .map(ValidatorKeys::voting_power)
+                .sum();
             let byzantine_majority_count =
-                State::byzantine_majority_count(validators_count as usize) as u16;
-            if majority_count > validators_count || majority_count < byzantine_majority_count {
+                State::byzantine_majority_count(total_voting_power as usize) as u64;
+            if majority_count > total_voting_power || majority_count < byzantine_majority_count {
                 panic!(
                     "Invalid majority count: {}, it should be >= {} and <= {}",
-                    majority_count, byzantine_majority_count, validators_count
+                    majority_count, byzantine_majority_count, total_voting_power
                 );
             }
         }
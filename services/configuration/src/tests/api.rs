@@ -278,7 +278,7 @@ fn test_votes_for_propose() {
     let response = api
         .votes_for_propose(new_cfg.hash())
         .expect("Votes for config is absent");
-    for entry in response.into_iter().take(testkit.majority_count()) {
+    for entry in response.into_iter().take(testkit.majority_count() as usize) {
         let tx = entry.expect("Vote for config is absent");
         assert!(
             Schema::new(&testkit.snapshot())
@@ -322,7 +322,7 @@ fn test_dissenting_votes_for_propose() {
     let response = api
         .votes_for_propose(new_cfg.hash())
         .expect("Dissenting votes for config is absent");
-    for entry in response.into_iter().take(testkit.majority_count()) {
+    for entry in response.into_iter().take(testkit.majority_count() as usize) {
         let tx = entry.expect("VoteAgainst for config is absent");
         assert_matches!(
             tx,
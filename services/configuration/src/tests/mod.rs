@@ -332,6 +332,55 @@ fn test_discard_proposes_with_too_small_majority_count() {
     assert!(testkit.find_propose(new_cfg.hash()).is_none());
 }
 
+#[test]
+fn test_apply_with_weighted_votes() {
+    let mut testkit: TestKit = TestKit::configuration_default();
+
+    // Applying the first configuration with the voting power of validators 5, 1, 1, 1.
+    let new_cfg = {
+        let mut cfg = testkit.configuration_change_proposal();
+        cfg.set_service_config("dummy", "First cfg");
+        cfg.set_actual_from(Height(5));
+        let mut cfg = cfg.stored_configuration().clone();
+        cfg.validator_keys[0].voting_power = Some(5);
+        cfg
+    };
+    testkit.apply_configuration(ValidatorId(0), new_cfg);
+
+    let actual_cfg = Schema::new(&testkit.snapshot()).actual_configuration();
+    assert_eq!(actual_cfg.majority_voting_power(), 6);
+    let new_cfg = {
+        let mut cfg = actual_cfg.clone();
+        cfg.previous_cfg_hash = actual_cfg.hash();
+        cfg.actual_from = Height(10);
+        cfg.services.insert("dummy".to_owned(), "Second cfg".into());
+        cfg
+    };
+    let validators = testkit.network().validators().to_vec();
+    let tx_propose = new_tx_config_propose(&validators[1], new_cfg.clone());
+    testkit.create_block_with_transactions(txvec![tx_propose]);
+
+    // Three validators out of four have not enough voting power.
+    let tx_votes = validators[1..]
+        .iter()
+        .map(|validator| new_tx_config_vote(validator, new_cfg.hash()))
+        .collect::<Vec<_>>();
+    testkit.create_block_with_transactions(tx_votes);
+    testkit.create_blocks_until(Height(8));
+    assert!(Schema::new(&testkit.snapshot())
+        .following_configuration()
+        .is_none());
+
+    // Together with the validator having the most voting power, the votes are enough.
+    let tx_vote = new_tx_config_vote(&validators[0], new_cfg.hash());
+    testkit.create_block_with_transactions(txvec![tx_vote]);
+    testkit.create_blocks_until(Height(10));
+    assert_eq!(
+        Schema::new(&testkit.snapshot()).actual_configuration(),
+        new_cfg
+    );
+}
+
 #[test]
 fn test_discard_propose_for_same_cfg() {
     let mut testkit: TestKit = TestKit::configuration_default();
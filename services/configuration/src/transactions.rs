@@ -20,7 +20,6 @@ use exonum::{
     },
     crypto::{CryptoHash, Hash, PublicKey, SecretKey},
     messages::{Message, RawTransaction, Signed},
-    node::State,
     storage::{Fork, Snapshot},
 };
 
@@ -152,22 +151,28 @@ fn validator_index(snapshot: &dyn Snapshot, key: &PublicKey) -> Option<usize> {
     keys.iter().position(|k| k.service_key == *key)
 }
 
-/// Checks if there is enough votes for a particular configuration hash.
+/// Checks if there is enough votes for a particular configuration hash, that is,
+/// whether the validators which have voted for it have enough voting power.
 fn enough_votes_to_commit(snapshot: &dyn Snapshot, cfg_hash: &Hash) -> bool {
     let actual_config = CoreSchema::new(snapshot).actual_configuration();
 
     let schema = Schema::new(snapshot);
     let votes = schema.votes_by_config_hash(cfg_hash);
-    let votes_count = votes.iter().filter(|vote| vote.is_consent()).count();
+    let votes_power: u64 = votes
+        .iter()
+        .zip(&actual_config.validator_keys)
+        .filter(|(vote, _)| vote.is_consent())
+        .map(|(_, keys)| keys.voting_power())
+        .sum();
 
     let config: ConfigurationServiceConfig = get_service_config(&actual_config);
 
     let majority_count = match config.majority_count {
-        Some(majority_count) => majority_count as usize,
-        _ => State::byzantine_majority_count(actual_config.validator_keys.len()),
+        Some(majority_count) => u64::from(majority_count),
+        _ => actual_config.majority_voting_power(),
     };
 
-    votes_count >= majority_count
+    votes_power >= majority_count
 }
 
 fn get_service_config(config: &StoredConfiguration) -> ConfigurationServiceConfig {
@@ -233,15 +238,16 @@ impl Propose {
 
         let config: ConfigurationServiceConfig = get_service_config(candidate);
 
-        if let Some(proposed_majority_count) = config.majority_count.map(|count| count as usize) {
-            let validators_num = candidate.validator_keys.len();
-            let min_votes_count = State::byzantine_majority_count(validators_num);
+        if let Some(proposed_majority_count) = config.majority_count.map(u64::from) {
+            let total_voting_power = candidate.total_voting_power();
+            let min_votes_count = candidate.majority_voting_power();
 
-            if proposed_majority_count < min_votes_count || proposed_majority_count > validators_num
+            if proposed_majority_count < min_votes_count
+                || proposed_majority_count > total_voting_power
             {
                 return Err(InvalidMajorityCount {
                     min: min_votes_count,
-                    max: validators_num,
+                    max: total_voting_power,
                     proposed: proposed_majority_count,
                 });
             }
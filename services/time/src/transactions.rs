@@ -43,7 +43,7 @@ pub enum Error {
 impl From<Error> for ExecutionError {
     fn from(value: Error) -> ExecutionError {
         let description = value.to_string();
-        ExecutionError::with_description(value as u8, description)
+        ExecutionError::with_description(value as u16, description)
     }
 }
 
@@ -57,7 +57,7 @@ fn assert_storage_times_eq<T: AsRef<dyn Snapshot>>(
 fn assert_transaction_result<S: AsRef<dyn Snapshot>>(
     snapshot: S,
     transaction: &Signed<RawTransaction>,
-    expected_code: u8,
+    expected_code: u16,
 ) -> Option<String> {
     let result = Schema::new(snapshot)
         .transaction_results()
@@ -407,7 +407,7 @@ fn test_creating_transaction_is_not_validator() {
     let (pub_key, sec_key) = gen_keypair();
     let tx = TxTime::sign(Utc::now(), &pub_key, &sec_key);
     testkit.create_block_with_transactions(txvec![tx.clone()]);
-    assert_transaction_result(testkit.snapshot(), &tx, Error::UnknownSender as u8);
+    assert_transaction_result(testkit.snapshot(), &tx, Error::UnknownSender as u16);
 
     let snapshot = testkit.snapshot();
     let schema = TimeSchema::new(snapshot);
@@ -449,7 +449,7 @@ fn test_transaction_time_less_than_validator_time_in_storage() {
     assert_transaction_result(
         testkit.snapshot(),
         &tx1,
-        Error::ValidatorTimeIsGreater as u8,
+        Error::ValidatorTimeIsGreater as u16,
     );
 
     let snapshot = testkit.snapshot();
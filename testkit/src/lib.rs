@@ -165,11 +165,13 @@ pub use crate::network::{TestNetwork, TestNetworkConfiguration, TestNode};
 pub mod compare;
 pub mod proto;
 
+use chrono::Utc;
 use futures::{sync::mpsc, Future, Stream};
+use serde_json::Value;
 use tokio_core::reactor::Core;
 
 use std::sync::{Arc, RwLock};
-use std::{fmt, net::SocketAddr};
+use std::{cmp, collections::BTreeMap, fmt, net::SocketAddr};
 
 use exonum::{
     api::{
@@ -181,7 +183,7 @@ use exonum::{
     explorer::{BlockWithTransactions, BlockchainExplorer},
     helpers::{Height, ValidatorId},
     messages::{RawTransaction, Signed},
-    node::{ApiSender, ExternalMessage, State as NodeState},
+    node::{ApiSender, ExternalMessage},
     storage::{MemoryDB, Patch, Snapshot},
 };
 
@@ -282,6 +284,7 @@ pub struct TestKitBuilder {
     our_validator_id: Option<ValidatorId>,
     validator_count: Option<u16>,
     services: Vec<Box<dyn Service>>,
+    services_data: BTreeMap<String, Value>,
     logger: bool,
 }
 
@@ -303,6 +306,7 @@ impl fmt::Debug for TestKitBuilder {
                     .map(|x| x.service_name())
                     .collect::<Vec<_>>(),
             )
+            .field("services_data", &self.services_data)
             .field("logger", &self.logger)
             .finish()
     }
@@ -315,6 +319,7 @@ impl TestKitBuilder {
             validator_count: None,
             our_validator_id: Some(ValidatorId(0)),
             services: Vec::new(),
+            services_data: BTreeMap::new(),
             logger: false,
         }
     }
@@ -325,6 +330,7 @@ impl TestKitBuilder {
             validator_count: None,
             our_validator_id: None,
             services: Vec::new(),
+            services_data: BTreeMap::new(),
             logger: false,
         }
     }
@@ -348,6 +354,13 @@ impl TestKitBuilder {
         self
     }
 
+    /// Sets the genesis data of the service with the given name. The data is passed
+    /// to `Service::initialize_with` during the creation of the genesis block.
+    pub fn with_service_data(mut self, service_name: &str, data: Value) -> Self {
+        self.services_data.insert(service_name.to_owned(), data);
+        self
+    }
+
     /// Enables a logger inside the testkit.
     pub fn with_logger(mut self) -> Self {
         self.logger = true;
@@ -362,6 +375,7 @@ impl TestKitBuilder {
         crypto::init();
         TestKit::assemble(
             self.services,
+            self.services_data,
             TestNetwork::with_our_role(self.our_validator_id, self.validator_count.unwrap_or(1)),
         )
     }
@@ -409,7 +423,11 @@ impl TestKit {
         TestKitBuilder::validator().with_service(service).create()
     }
 
-    fn assemble(services: Vec<Box<dyn Service>>, network: TestNetwork) -> Self {
+    fn assemble(
+        services: Vec<Box<dyn Service>>,
+        services_data: BTreeMap<String, Value>,
+        network: TestNetwork,
+    ) -> Self {
         let api_channel = mpsc::channel(1_000);
         let api_sender = ApiSender::new(api_channel.0.clone());
 
@@ -424,8 +442,9 @@ impl TestKit {
             api_sender.clone(),
         );
 
-        let genesis = network.genesis_config();
-        blockchain.initialize(genesis.clone()).unwrap();
+        let mut genesis = network.genesis_config();
+        genesis.services_data = services_data;
+        blockchain.initialize(genesis).unwrap();
 
         let events_stream: Box<dyn Stream<Item = (), Error = ()> + Send + Sync> = {
             let mut blockchain = blockchain.clone();
@@ -442,6 +461,7 @@ impl TestKit {
                         }
                         ExternalMessage::PeerAdd(_)
                         | ExternalMessage::Enable(_)
+                        | ExternalMessage::Maintenance(_)
                         | ExternalMessage::Rebroadcast
                         | ExternalMessage::Shutdown => { /* Ignored */ }
                     }
@@ -614,12 +634,14 @@ impl TestKit {
     fn do_create_block(&mut self, tx_hashes: &[crypto::Hash]) -> BlockWithTransactions {
         let new_block_height = self.height().next();
         let last_hash = self.last_block_hash();
+        // The time of the block cannot be earlier than the time of the previous block.
+        let time = cmp::max(Utc::now(), self.blockchain.last_block().time());
 
         let config_patch = self.update_configuration(new_block_height);
         let (block_hash, patch) = {
             let validator_id = self.leader().validator_id().unwrap();
             self.blockchain
-                .create_patch(validator_id, new_block_height, tx_hashes)
+                .create_patch(validator_id, new_block_height, time, tx_hashes)
         };
 
         let patch = if let Some(config_patch) = config_patch {
@@ -633,7 +655,7 @@ impl TestKit {
 
         let propose = self
             .leader()
-            .create_propose(new_block_height, &last_hash, tx_hashes);
+            .create_propose(new_block_height, &last_hash, tx_hashes, time);
         let precommits: Vec<_> = self
             .network()
             .validators()
@@ -865,9 +887,11 @@ impl TestKit {
         &self.network.validators()[id.0 as usize]
     }
 
-    /// Returns sufficient number of validators for the Byzantine Fault Tolerance consensus.
-    pub fn majority_count(&self) -> usize {
-        NodeState::byzantine_majority_count(self.network().validators().len())
+    /// Returns the voting power of validators sufficient for the Byzantine Fault Tolerance
+    /// consensus, that is, more than 2/3 of the total voting power of the validators
+    /// in the actual configuration.
+    pub fn majority_count(&self) -> u64 {
+        self.actual_configuration().majority_voting_power()
     }
 
     /// Returns the leader on the current height. At the moment first validator.
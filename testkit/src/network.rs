@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use exonum::{
@@ -169,6 +170,7 @@ impl TestNode {
         height: Height,
         last_hash: &crypto::Hash,
         tx_hashes: &[crypto::Hash],
+        time: DateTime<Utc>,
     ) -> Signed<Propose> {
         Message::concrete(
             Propose::new(
@@ -178,6 +180,7 @@ impl TestNode {
                 Round::first(),
                 last_hash,
                 tx_hashes,
+                time,
             ),
             self.consensus_public_key,
             &self.consensus_secret_key,
@@ -212,6 +215,7 @@ impl TestNode {
         ValidatorKeys {
             consensus_key: self.consensus_public_key,
             service_key: self.service_public_key,
+            voting_power: None,
         }
     }
 
@@ -602,7 +602,7 @@ fn test_explorer_single_block() {
             assert_eq!(pk, &precommit.author())
         }
 
-        assert!(validators.len() >= testkit.majority_count());
+        assert!(validators.len() as u64 >= testkit.majority_count());
     }
 }
 
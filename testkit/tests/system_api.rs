@@ -14,14 +14,16 @@
 
 #[macro_use]
 extern crate pretty_assertions;
+#[macro_use]
+extern crate serde_json;
 
 use exonum::{
     api::node::{
         private::NodeInfo,
-        public::system::{ConsensusStatus, HealthCheckInfo},
+        public::system::{ConsensusStatus, HealthCheckInfo, MaintenanceInfo},
     },
-    helpers::user_agent,
-    messages::PROTOCOL_MAJOR_VERSION,
+    helpers::{user_agent, Height, ValidatorId},
+    messages::{MaintenanceNotice, Message, MAINTENANCE_SERVICE_ID, PROTOCOL_MAJOR_VERSION},
 };
 use exonum_testkit::{ApiKind, TestKitBuilder};
 
@@ -89,3 +91,47 @@ fn rebroadcast() {
         ()
     )
 }
+
+#[test]
+fn maintenance() {
+    let mut testkit = TestKitBuilder::validator().with_validators(4).create();
+
+    let info: Vec<MaintenanceInfo> = testkit
+        .api()
+        .public(ApiKind::System)
+        .get("v1/maintenance")
+        .unwrap();
+    assert!(info.is_empty());
+
+    let (public_key, secret_key) = testkit.network().validators()[1].service_keypair();
+    let tx = Message::sign_transaction(
+        MaintenanceNotice::new(Height(10)),
+        MAINTENANCE_SERVICE_ID,
+        *public_key,
+        secret_key,
+    );
+    testkit.create_block_with_transaction(tx);
+
+    let info: Vec<MaintenanceInfo> = testkit
+        .api()
+        .public(ApiKind::System)
+        .get("v1/maintenance")
+        .unwrap();
+    assert_eq!(
+        info,
+        vec![MaintenanceInfo {
+            validator: ValidatorId(1),
+            until: Height(10),
+        }]
+    );
+
+    assert_eq!(
+        testkit
+            .api()
+            .private(ApiKind::System)
+            .query(&json!({ "until": 10 }))
+            .post::<()>("v1/maintenance")
+            .unwrap(),
+        ()
+    );
+}